@@ -0,0 +1,340 @@
+use std::{error, fmt, io};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{
+    data_base::ConfigError,
+    relation::{parse::ParseError, table::TableLoadError},
+    sql::{redact::redact, SQLError},
+};
+
+/// The error the server returns for an insert or update that would duplicate a primary or unique key.
+pub const ER_DUP_ENTRY: u16 = 1062;
+/// The error the server returns for a delete or update of a row that another row references.
+pub const ER_ROW_IS_REFERENCED: u16 = 1451;
+/// The error the server returns for an insert or update referencing a row that does not exist.
+pub const ER_NO_REFERENCED_ROW: u16 = 1452;
+/// The error the server returns when a transaction is rolled back to resolve a deadlock.
+pub const ER_LOCK_DEADLOCK: u16 = 1213;
+/// The error the server returns for a table that does not exist.
+pub const ER_NO_SUCH_TABLE: u16 = 1146;
+/// The error MySQL returns for a connection without TLS to a server that has `require_secure_transport` on.
+pub const ER_SECURE_TRANSPORT_REQUIRED: u16 = 3159;
+/// The error MySQL returns for a select stopped by `max_execution_time`.
+pub const ER_QUERY_TIMEOUT: u16 = 3024;
+/// The error MariaDB returns for a statement stopped by `max_statement_time`.
+pub const ER_STATEMENT_TIMEOUT: u16 = 1969;
+
+/// Represents the errors of running statements on a `DataBase`.
+///
+/// Failures callers commonly recover from have variants of their own, so they can be matched without reading error
+/// codes:
+///
+/// ```ignore
+/// match db.execute_write(&insert) {
+///     Err(Error::DuplicateKey{..}) => update(),
+///     Err(Error::Deadlock{..}) => retry(),
+///     result => result.map(|_| ()),
+/// }
+/// ```
+#[derive(Debug)]
+pub enum Error {
+    /// No connection to the database could be opened, or the connection was lost.
+    Connection(mysql::Error),
+    /// The connection information is missing or invalid.
+    Config(ConfigError),
+    /// The statement `sql` failed for a reason without a variant of its own.
+    Query{sql: String, source: Box<mysql::Error>},
+    /// The statement `sql` would duplicate the `value` of the primary or unique key `key_name` (see `ER_DUP_ENTRY`).
+    ///
+    /// `key_name` is the name of the index, without the table newer servers prefix it with. Both are read from the
+    /// message of the server, and are empty if it could not be parsed.
+    DuplicateKey{key_name: String, value: String, sql: String, source: Box<mysql::Error>},
+    /// The statement `sql` references a row that does not exist, or changes a row that another row references, breaking
+    /// the foreign key `constraint` (see `ER_NO_REFERENCED_ROW` and `ER_ROW_IS_REFERENCED`).
+    ///
+    /// `constraint` is read from the message of the server, and is empty if the message does not name it, as for users
+    /// without access to the parent table.
+    ForeignKeyViolation{constraint: String, sql: String, source: Box<mysql::Error>},
+    /// The transaction running `sql` was rolled back to resolve a deadlock, and can be retried (see
+    /// `ER_LOCK_DEADLOCK`).
+    Deadlock{sql: String, source: Box<mysql::Error>},
+    /// The statement `sql` ran longer than its timeout (see `DataBase::execute_with_timeout`), or the connection
+    /// outran the read or write timeout of `DataBaseConfig`.
+    Timeout{sql: String, source: Box<mysql::Error>},
+    /// Statement `index` of a script run by `DataBase::execute_batch` failed with `source`. The statements before it
+    /// were applied.
+    Batch{index: usize, source: Box<Error>},
+    /// A statement or table definition could not be parsed.
+    Parse(String),
+    /// The named table does not exist.
+    NotFound(String),
+    /// The arguments were rejected before a statement was sent, such as a column the table does not have.
+    Invalid(String),
+    /// The statement was not started, as the `DataBase` is shutting down (see `DataBase::shutdown`).
+    ShuttingDown,
+}
+
+impl Error {
+    /// Returns the error of running `sql`, with a variant of its own for the server errors callers branch on.
+    ///
+    /// Errors from the connection rather than the statement, such as a lost connection, become `Error::Connection`.
+    /// The error of the driver is boxed in the other variants, so results carrying an `Error` stay small.
+    pub fn query(sql: &str, source: mysql::Error) -> Error {
+        let sql = sql.to_string();
+
+        match &source {
+            mysql::Error::MySqlError(err) => match err.code {
+                ER_DUP_ENTRY => {
+                    let (key_name, value) = duplicate_entry(&err.message).unwrap_or_default();
+
+                    Error::DuplicateKey{key_name, value, sql, source: Box::new(source)}
+                },
+                ER_ROW_IS_REFERENCED | ER_NO_REFERENCED_ROW => {
+                    let constraint = foreign_key_constraint(&err.message).unwrap_or_default();
+
+                    Error::ForeignKeyViolation{constraint, sql, source: Box::new(source)}
+                },
+                ER_LOCK_DEADLOCK => Error::Deadlock{sql, source: Box::new(source)},
+                ER_QUERY_TIMEOUT | ER_STATEMENT_TIMEOUT => Error::Timeout{sql, source: Box::new(source)},
+                _ => Error::Query{sql, source: Box::new(source)},
+            },
+            mysql::Error::IoError(err) if matches!(err.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock) => {
+                Error::Timeout{sql, source: Box::new(source)}
+            },
+            mysql::Error::IoError(_) | mysql::Error::DriverError(_) => Error::Connection(source),
+            _ => Error::Query{sql, source: Box::new(source)},
+        }
+    }
+
+    /// Returns the error code of the server, if the server failed the statement.
+    pub fn code(&self) -> Option<u16> {
+        match self.mysql_error() {
+            Some(mysql::Error::MySqlError(err)) => Some(err.code),
+            _ => None,
+        }
+    }
+
+    /// Returns the statement that failed, if the error came from one.
+    pub fn sql(&self) -> Option<&str> {
+        match self {
+            Error::Query{sql, ..} |
+            Error::DuplicateKey{sql, ..} |
+            Error::ForeignKeyViolation{sql, ..} |
+            Error::Deadlock{sql, ..} |
+            Error::Timeout{sql, ..} => Some(sql),
+            Error::Batch{source, ..} => source.sql(),
+            _ => None,
+        }
+    }
+
+    /// Returns the error of the driver, if there is one.
+    pub fn mysql_error(&self) -> Option<&mysql::Error> {
+        match self {
+            Error::Connection(source) => Some(source),
+            Error::Query{source, ..} |
+            Error::DuplicateKey{source, ..} |
+            Error::ForeignKeyViolation{source, ..} |
+            Error::Deadlock{source, ..} |
+            Error::Timeout{source, ..} => Some(source.as_ref()),
+            Error::Config(ConfigError::ConnectionFailed(source)) => Some(source),
+            Error::Batch{source, ..} => source.mysql_error(),
+            _ => None,
+        }
+    }
+
+    /// Returns the error of the driver, if there is one, for the errors that keep it.
+    pub fn into_mysql_error(self) -> Result<mysql::Error, Error> {
+        match self {
+            Error::Connection(source) => Ok(source),
+            Error::Query{source, ..} |
+            Error::DuplicateKey{source, ..} |
+            Error::ForeignKeyViolation{source, ..} |
+            Error::Deadlock{source, ..} |
+            Error::Timeout{source, ..} => Ok(*source),
+            Error::Batch{source, ..} => source.into_mysql_error(),
+            err => Err(err),
+        }
+    }
+}
+
+/// Returns the key and the value of a duplicate entry message, such as
+/// `Duplicate entry 'a@b.com' for key 'user.email'`.
+fn duplicate_entry(message: &str) -> Option<(String, String)> {
+    lazy_static! {
+        static ref DUPLICATE_ENTRY : Regex = Regex::new(r"(?s)^Duplicate entry '(.*)' for key '(.*)'$").unwrap();
+    };
+
+    let captures = DUPLICATE_ENTRY.captures(message)?;
+    let key_name = &captures[2];
+    let key_name = key_name.rsplit_once('.').map_or(key_name, |(_, key_name)| key_name);
+
+    Some((key_name.to_string(), captures[1].to_string()))
+}
+
+/// Returns the constraint named by a foreign key violation message, such as
+/// ``Cannot add or update a child row: a foreign key constraint fails (`shop`.`order`, CONSTRAINT `order_user` ...``.
+fn foreign_key_constraint(message: &str) -> Option<String> {
+    lazy_static! {
+        static ref CONSTRAINT : Regex = Regex::new(r"CONSTRAINT `((?:[^`]|``)+)`").unwrap();
+    };
+
+    CONSTRAINT.captures(message).map(|captures| captures[1].replace("``", "`"))
+}
+
+impl fmt::Display for Error {
+    /// Statements are redacted (see `sql::redact::redact`), so the error can be logged.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Connection(err) => write!(f, "database connection failed: {}", err),
+            Error::Config(err) => write!(f, "{}", err),
+            Error::Query{sql, source} => write!(f, "{} in statement: {}", source, redact(sql)),
+            Error::DuplicateKey{sql, source, ..} => write!(f, "duplicate key: {} in statement: {}", source, redact(sql)),
+            Error::ForeignKeyViolation{sql, source, ..} => {
+                write!(f, "foreign key violation: {} in statement: {}", source, redact(sql))
+            },
+            Error::Deadlock{sql, source} => write!(f, "deadlock: {} in statement: {}", source, redact(sql)),
+            Error::Timeout{sql, source} => write!(f, "timed out: {} in statement: {}", source, redact(sql)),
+            Error::Batch{index, source} => write!(f, "statement {} of the script failed: {}", index, source),
+            Error::Parse(err) => write!(f, "failed to parse: {}", err),
+            Error::NotFound(name) => write!(f, "table {} does not exist", name),
+            Error::Invalid(err) => write!(f, "invalid arguments: {}", err),
+            Error::ShuttingDown => write!(f, "the database is shutting down"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Config(err) => Some(err),
+            Error::Batch{source, ..} => Some(source.as_ref()),
+            err => err.mysql_error().map(|err| err as &(dyn error::Error + 'static)),
+        }
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(err: ConfigError) -> Self {
+        Error::Config(err)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err.to_string())
+    }
+}
+
+impl From<TableLoadError> for Error {
+    fn from(err: TableLoadError) -> Self {
+        match err {
+            TableLoadError::ConnectionFailed(err) => Error::Config(err),
+            TableLoadError::TableNotFound(name) => Error::NotFound(name),
+            TableLoadError::Execution(err) => err,
+        }
+    }
+}
+
+impl From<Error> for SQLError {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Config(err) => SQLError::FailedToConnect(err.into()),
+            Error::Parse(_) => SQLError::NotValidCMD,
+            err => match err.into_mysql_error() {
+                Ok(err) => SQLError::Execution(err),
+                Err(err) => SQLError::Err(err.to_string()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mysql::{MySqlError, Value};
+
+    use super::{Error, ER_DUP_ENTRY, ER_LOCK_DEADLOCK, ER_NO_REFERENCED_ROW, ER_ROW_IS_REFERENCED, ER_QUERY_TIMEOUT};
+
+    fn server_error(code: u16) -> mysql::Error {
+        server_message(code, "failed")
+    }
+
+    fn server_message(code: u16, message: &str) -> mysql::Error {
+        mysql::Error::MySqlError(MySqlError{state: String::from("23000"), message: String::from(message), code})
+    }
+
+    #[test]
+    fn query_test() {
+        let sql = "INSERT INTO user(id) VALUES (1)";
+
+        assert!(matches!(Error::query(sql, server_error(ER_DUP_ENTRY)), Error::DuplicateKey{..}));
+        assert!(matches!(Error::query(sql, server_error(ER_NO_REFERENCED_ROW)), Error::ForeignKeyViolation{..}));
+        assert!(matches!(Error::query(sql, server_error(ER_ROW_IS_REFERENCED)), Error::ForeignKeyViolation{..}));
+        assert!(matches!(Error::query(sql, server_error(ER_LOCK_DEADLOCK)), Error::Deadlock{..}));
+        assert!(matches!(Error::query(sql, server_error(1064)), Error::Query{..}));
+        assert!(matches!(
+            Error::query(sql, mysql::Error::IoError(std::io::Error::other("reset"))),
+            Error::Connection(_)
+        ));
+        assert!(matches!(Error::query(sql, mysql::Error::FromValueError(Value::NULL)), Error::Query{..}));
+        assert!(matches!(Error::query(sql, server_error(ER_QUERY_TIMEOUT)), Error::Timeout{..}));
+        assert!(matches!(
+            Error::query(sql, mysql::Error::IoError(std::io::Error::from(std::io::ErrorKind::WouldBlock))),
+            Error::Timeout{..}
+        ));
+
+        let err = Error::query(sql, server_error(ER_DUP_ENTRY));
+
+        assert_eq!(err.code(), Some(ER_DUP_ENTRY));
+        assert_eq!(err.sql(), Some(sql));
+        assert_eq!(err.to_string(), format!("duplicate key: MySqlError {{ ERROR 1062 (23000): failed }} in statement: {}", sql));
+        assert_eq!(Error::NotFound(String::from("user")).code(), None);
+    }
+
+    #[test]
+    fn violation_test() {
+        let sql = "INSERT INTO user(email) VALUES ('a@b.com')";
+
+        let duplicate = |message: &str| match Error::query(sql, server_message(ER_DUP_ENTRY, message)) {
+            Error::DuplicateKey{key_name, value, ..} => (key_name, value),
+            err => panic!("{:?}", err),
+        };
+
+        assert_eq!(
+            duplicate("Duplicate entry 'a@b.com' for key 'user.email'"),
+            (String::from("email"), String::from("a@b.com"))
+        );
+        assert_eq!(
+            duplicate("Duplicate entry 'it's' for key 'PRIMARY'"),
+            (String::from("PRIMARY"), String::from("it's"))
+        );
+        assert_eq!(duplicate("failed"), (String::new(), String::new()));
+
+        let constraint = |code: u16, message: &str| match Error::query(sql, server_message(code, message)) {
+            Error::ForeignKeyViolation{constraint, ..} => constraint,
+            err => panic!("{:?}", err),
+        };
+
+        assert_eq!(
+            constraint(
+                ER_NO_REFERENCED_ROW,
+                "Cannot add or update a child row: a foreign key constraint fails (`shop`.`order`, CONSTRAINT \
+                `order_user` FOREIGN KEY (`user_id`) REFERENCES `user` (`id`))"
+            ),
+            "order_user"
+        );
+        assert_eq!(
+            constraint(
+                ER_ROW_IS_REFERENCED,
+                "Cannot delete or update a parent row: a foreign key constraint fails (`shop`.`order`, CONSTRAINT \
+                `order``s user` FOREIGN KEY (`user_id`) REFERENCES `user` (`id`))"
+            ),
+            "order`s user"
+        );
+        assert_eq!(
+            constraint(ER_ROW_IS_REFERENCED, "Cannot delete or update a parent row: a foreign key constraint fails"),
+            ""
+        );
+    }
+}
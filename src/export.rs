@@ -0,0 +1,384 @@
+use std::{fmt, io::{self, Write}};
+
+use mysql::{prelude::Queryable, Conn, Error, Row};
+
+use crate::{data_base::{DataBase, InFlight}, sql::{QDL, result_set::{ResultSet, ColumnInfo}, value::Value}};
+
+/// Represents possible errors that can occur while exporting data.
+#[derive(Debug)]
+pub enum ExportError {
+    /// No connection could be opened, or the database is shutting down.
+    Connection(crate::Error),
+    /// A query of the export failed.
+    Execution(Error),
+    /// The output could not be written.
+    Io(io::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Connection(err) => write!(f, "no connection could be opened: {}", err),
+            ExportError::Execution(err) => write!(f, "export query failed: {}", err),
+            ExportError::Io(err) => write!(f, "failed to write export: {}", err),
+        }
+    }
+}
+
+/// The statements an `ExportSession` runs on its pinned connection.
+pub trait ExportExecutor {
+    fn execute(&mut self, statement: &str) -> Result<(), Error>;
+    fn query(&mut self, query: &QDL) -> Result<ResultSet, Error>;
+}
+
+impl ExportExecutor for Conn {
+    fn execute(&mut self, statement: &str) -> Result<(), Error> {
+        self.query_drop(statement)
+    }
+    fn query(&mut self, query: &QDL) -> Result<ResultSet, Error> {
+        let result = self.query_iter(query.as_str())?;
+        let columns: Vec<ColumnInfo> = result.columns().as_ref().iter().map(ColumnInfo::from).collect();
+        let rows = result.collect::<Result<Vec<Row>, Error>>()?;
+
+        Ok(ResultSet::with_columns(&columns, rows))
+    }
+}
+
+/// A read only view of the database at a single point in time, from which several exports can be made.
+///
+/// The session runs `START TRANSACTION WITH CONSISTENT SNAPSHOT` at the `REPEATABLE READ` isolation level on a pinned
+/// connection, so every export sees the data as it was when the session began, even while other connections write to
+/// it. The snapshot only covers transactional engines such as InnoDB; tables using MyISAM or MEMORY are read as they
+/// are at the time of each export.
+///
+/// The transaction is committed by `ExportSession::finish`, or rolled back if the session is dropped.
+pub struct ExportSession<E: ExportExecutor = Conn> {
+    executor: E,
+    finished: bool,
+    /// Keeps the session in flight on the `DataBase` it was begun on, so a shutdown waits for it.
+    in_flight: Option<InFlight>,
+}
+
+impl ExportSession<Conn> {
+    /// Opens a connection to `db` and starts a consistent snapshot on it.
+    ///
+    /// The session is in flight on `db` until it is finished or dropped, so `DataBase::shutdown` waits for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportError::Connection` if no connection could be opened or `db` is shutting down, and
+    /// `ExportError::Execution` if the transaction could not be started.
+    pub fn begin(db: &DataBase) -> Result<ExportSession<Conn>, ExportError> {
+        let (conn, in_flight) = db.start().map_err(ExportError::Connection)?;
+
+        let mut session = ExportSession::with_executor(conn).map_err(ExportError::Execution)?;
+        session.in_flight = Some(in_flight);
+
+        Ok(session)
+    }
+}
+
+impl<E: ExportExecutor> ExportSession<E> {
+    /// Starts a consistent snapshot on `executor`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the transaction could not be started.
+    pub fn with_executor(mut executor: E) -> Result<ExportSession<E>, Error> {
+        executor.execute("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")?;
+        executor.execute("START TRANSACTION WITH CONSISTENT SNAPSHOT")?;
+
+        Ok(ExportSession { executor, finished: false, in_flight: None })
+    }
+
+    /// Returns the rows of `query`, as seen by the snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the query fails.
+    pub fn query(&mut self, query: &QDL) -> Result<ResultSet, Error> {
+        self.executor.query(query)
+    }
+
+    /// Writes the rows of `query` to `out` as CSV, and returns the number of rows written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportError::Execution` if the query fails and `ExportError::Io` if `out` could not be written to.
+    pub fn export_csv<W: Write>(&mut self, query: &QDL, out: W) -> Result<usize, ExportError> {
+        let rows = self.query(query).map_err(ExportError::Execution)?;
+
+        write_csv(&rows, out).map_err(ExportError::Io)?;
+
+        Ok(rows.rows.len())
+    }
+
+    /// Writes the rows of `query` to `out` as JSON lines, one object per row, and returns the number of rows written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportError::Execution` if the query fails and `ExportError::Io` if `out` could not be written to.
+    pub fn export_jsonl<W: Write>(&mut self, query: &QDL, out: W) -> Result<usize, ExportError> {
+        let rows = self.query(query).map_err(ExportError::Execution)?;
+
+        write_jsonl(&rows, out).map_err(ExportError::Io)?;
+
+        Ok(rows.rows.len())
+    }
+
+    /// Ends the snapshot by committing the transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if the commit fails.
+    pub fn finish(mut self) -> Result<(), Error> {
+        self.finished = true;
+        self.executor.execute("COMMIT")
+    }
+}
+
+impl<E: ExportExecutor> Drop for ExportSession<E> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        if let Err(err) = self.executor.execute("ROLLBACK") {
+            log::error!("Failed to roll back export session - Err:{:?}", err);
+        }
+    }
+}
+
+/// Returns `bytes` as lowercase hex digits.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Returns the text of a value, without SQL quoting.
+fn plain_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Text(val) => val.clone(),
+        Value::Bytes(val) => hex(val),
+        Value::Date{..} | Value::DateTime{..} => value.to_string().trim_matches('\'').to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+/// Writes `rows` to `out` as CSV with a header line. `NULL` is written as an empty field and binary data as hex.
+///
+/// The header is written even when there are no rows, if `rows` knows its columns (see `ResultSet::with_columns`).
+pub fn write_csv<W: Write>(rows: &ResultSet, mut out: W) -> io::Result<()> {
+    let header: Vec<String> = rows.columns.iter().map(|column| csv_field(column)).collect();
+    writeln!(out, "{}", header.join(","))?;
+
+    for row in &rows.rows {
+        let fields: Vec<String> = row.iter().map(|value| csv_field(&plain_text(value))).collect();
+        writeln!(out, "{}", fields.join(","))?;
+    }
+
+    Ok(())
+}
+
+/// Returns `val` as a JSON string literal.
+fn json_string(val: &str) -> String {
+    let mut escaped = String::with_capacity(val.len() + 2);
+
+    escaped.push('"');
+    for ch in val.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped.push('"');
+
+    escaped
+}
+
+/// Returns a value as JSON. Binary data is written as a hex string and non finite floats as `null`.
+fn json_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("null"),
+        Value::Bool(val) => val.to_string(),
+        Value::Int(val) => val.to_string(),
+        Value::UInt(val) => val.to_string(),
+        Value::Float(val) if val.is_finite() => val.to_string(),
+        Value::Float(_) => String::from("null"),
+        _ => json_string(&plain_text(value)),
+    }
+}
+
+/// Writes `rows` to `out` as JSON lines, one object per row keyed by column name.
+///
+/// Duplicate column names are made unique as described in `ResultSet::unique_columns`. Values read over the text
+/// protocol arrive as strings and are written as JSON strings.
+pub fn write_jsonl<W: Write>(rows: &ResultSet, mut out: W) -> io::Result<()> {
+    let columns: Vec<String> = rows.unique_columns()
+        .iter()
+        .map(|column| json_string(column))
+        .collect();
+
+    for row in rows.iter() {
+        let fields: Vec<String> = columns
+            .iter()
+            .zip(row.values())
+            .map(|(column, value)| format!("{}:{}", column, json_value(value)))
+            .collect();
+
+        writeln!(out, "{{{}}}", fields.join(","))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use mysql::Error;
+
+    use crate::{data_base::DataBase, sql::{SQL, QDL, result_set::ResultSet, value::Value}};
+
+    use super::{ExportExecutor, ExportSession, write_csv, write_jsonl};
+
+    #[derive(Default)]
+    struct MockExecutor {
+        log: Vec<String>,
+    }
+
+    impl ExportExecutor for &mut MockExecutor {
+        fn execute(&mut self, statement: &str) -> Result<(), Error> {
+            self.log.push(statement.to_string());
+            Ok(())
+        }
+        fn query(&mut self, query: &QDL) -> Result<ResultSet, Error> {
+            self.log.push(query.to_string());
+            Ok(rows())
+        }
+    }
+
+    fn rows() -> ResultSet {
+        ResultSet::new(
+            vec![String::from("id"), String::from("name"), String::from("avatar")],
+            vec![
+                vec![Value::Int(1), Value::Text(String::from("Doe, \"John\"")), Value::Bytes(vec![0x00, 0xff])],
+                vec![Value::Int(2), Value::Null, Value::Null],
+            ]
+        )
+    }
+
+    #[test]
+    fn session_test_1() {
+        let mut executor = MockExecutor::default();
+
+        let mut session = ExportSession::with_executor(&mut executor).unwrap();
+
+        assert_eq!(session.export_csv(&QDL(String::from("SELECT * FROM a")), Vec::new()).unwrap(), 2);
+        assert_eq!(session.export_jsonl(&QDL(String::from("SELECT * FROM b")), Vec::new()).unwrap(), 2);
+        session.finish().unwrap();
+
+        assert_eq!(
+            executor.log,
+            vec![
+                "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ",
+                "START TRANSACTION WITH CONSISTENT SNAPSHOT",
+                "SELECT * FROM a",
+                "SELECT * FROM b",
+                "COMMIT",
+            ]
+        );
+    }
+
+    #[test]
+    fn session_test_2() {
+        let mut executor = MockExecutor::default();
+
+        {
+            let mut session = ExportSession::with_executor(&mut executor).unwrap();
+            session.query(&QDL(String::from("SELECT * FROM a"))).unwrap();
+        }
+
+        assert_eq!(executor.log.last().map(|statement| statement.as_str()), Some("ROLLBACK"));
+    }
+
+    #[test]
+    fn write_csv_test() {
+        let mut out: Vec<u8> = Vec::new();
+
+        write_csv(&rows(), &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "id,name,avatar\n1,\"Doe, \"\"John\"\"\",00ff\n2,,\n"
+        );
+    }
+
+    #[test]
+    fn write_jsonl_test() {
+        let mut out: Vec<u8> = Vec::new();
+
+        write_jsonl(&rows(), &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"id\":1,\"name\":\"Doe, \\\"John\\\"\",\"avatar\":\"00ff\"}\n{\"id\":2,\"name\":null,\"avatar\":null}\n"
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn session_test_3() {
+        let db = DataBase::from_env().unwrap();
+
+        db.execute_multiple(&vec![
+            SQL::new("CREATE TABLE export_session_test (col1 INT) ENGINE=InnoDB").unwrap(),
+            SQL::new("INSERT INTO export_session_test (col1) VALUES (1)").unwrap(),
+        ]).unwrap();
+
+        let mut session = ExportSession::begin(&db).unwrap();
+        let query = QDL(String::from("SELECT * FROM export_session_test"));
+
+        assert_eq!(session.export_csv(&query, Vec::new()).unwrap(), 1);
+
+        //written after the snapshot began, so not seen by it
+        db.execute_multiple(&vec![
+            SQL::new("INSERT INTO export_session_test (col1) VALUES (2)").unwrap(),
+        ]).unwrap();
+
+        assert_eq!(session.export_csv(&query, Vec::new()).unwrap(), 1);
+
+        let mut out: Vec<u8> = Vec::new();
+        let empty = QDL(String::from("SELECT col1 AS total FROM export_session_test WHERE col1 > 2"));
+        assert_eq!(session.export_csv(&empty, &mut out).unwrap(), 0);
+        assert_eq!(String::from_utf8(out).unwrap(), "total\n");
+
+        session.finish().unwrap();
+
+        db.execute_multiple(&vec![SQL::new("DROP TABLE export_session_test").unwrap()]).unwrap();
+    }
+
+    #[test]
+    fn write_jsonl_duplicate_test() {
+        let mut out: Vec<u8> = Vec::new();
+
+        let rows = ResultSet::new(
+            vec![String::from("id"), String::from("id")],
+            vec![vec![Value::Int(1), Value::Int(2)]]
+        );
+        write_jsonl(&rows, &mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "{\"id\":1,\"id_2\":2}\n");
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+
+use crate::{
+    data_base::DataBase,
+    error::Error,
+    relation::{Registry, registry::ForeignKey, table::Table},
+    sql::{SQL, QML, SQLError, value::Value}
+};
+
+/// A row of a `Fixture`.
+#[derive(Clone, Debug)]
+struct FixtureRow {
+    /// The name other rows use to reference the row.
+    name: Option<String>,
+    values: HashMap<String, Value>,
+    /// Columns set to the primary key of another named row, as (column, row name).
+    references: Vec<(String, String)>,
+}
+
+/// A set of rows to insert into a table for a test.
+///
+/// Rows can be named, and a column of a row can reference the primary key of a named row, including keys generated
+/// by the server:
+///
+/// ```ignore
+/// let customers = Fixture::new(&customer)
+///     .row(HashMap::from([(String::from("name"), Value::Text(String::from("Alice")))]))
+///     .named("alice");
+/// let orders = Fixture::new(&order)
+///     .row(HashMap::from([(String::from("total"), Value::Int(10))]))
+///     .reference("customer_id", "alice");
+///
+/// let mut fixtures = FixtureSet::new().with(orders).with(customers);
+/// fixtures.load(&db)?;
+/// ...
+/// fixtures.unload(&db)?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Fixture {
+    table: Table,
+    rows: Vec<FixtureRow>,
+}
+
+impl Fixture {
+    pub fn new(table: &Table) -> Fixture {
+        Fixture { table: table.clone(), rows: Vec::new() }
+    }
+
+    /// Returns an empty fixture of the table of `registry` named `table`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SQLError::Err` if there is no table `table`, or an `SQLError` if it could not be loaded.
+    pub fn of(registry: &mut Registry, table: &str) -> Result<Fixture, SQLError> {
+        match registry.table(table)? {
+            Some(table) => Ok(Fixture::new(table)),
+            None => Err(SQLError::Err(format!("Unknown table {}", table))),
+        }
+    }
+
+    /// Adds a row.
+    pub fn row(mut self, values: HashMap<String, Value>) -> Fixture {
+        self.rows.push(FixtureRow { name: None, values, references: Vec::new() });
+        self
+    }
+
+    /// Names the last added row, so other rows can reference it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no row has been added.
+    pub fn named(mut self, name: &str) -> Fixture {
+        self.last_row().name = Some(name.to_string());
+        self
+    }
+
+    /// Sets `column` of the last added row to the primary key of the row named `row_name`, once it is loaded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no row has been added.
+    pub fn reference(mut self, column: &str, row_name: &str) -> Fixture {
+        self.last_row().references.push((column.to_string(), row_name.to_string()));
+        self
+    }
+
+    fn last_row(&mut self) -> &mut FixtureRow {
+        self.rows.last_mut().expect("a row has to be added first")
+    }
+
+    /// Returns the name of the primary key column of the table, if it has a key over a single column. Rows of tables
+    /// with a composite key are deleted by every value set.
+    fn primary_key(&self) -> Option<&str> {
+        match self.table.primary_key.as_slice() {
+            [index] => Some(self.table.attributes[*index].name.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the fixture has to be loaded after `other`.
+    fn depends_on(&self, other: &Fixture) -> bool {
+        if self.table.name == other.table.name {
+            return false;
+        }
+
+        let foreign_key = ForeignKey::of(&self.table)
+            .iter()
+            .any(|key| key.referenced_table.eq_ignore_ascii_case(&other.table.name));
+
+        let reference = self.rows
+            .iter()
+            .flat_map(|row| row.references.iter())
+            .any(|(_, name)| other.rows.iter().any(|row| row.name.as_deref() == Some(name)));
+
+        foreign_key || reference
+    }
+}
+
+/// A row inserted by `FixtureSet::load`.
+#[derive(Clone, Debug)]
+struct LoadedRow {
+    fixture: usize,
+    values: HashMap<String, Value>,
+    /// The primary key of the row, if the table has one.
+    id: Option<Value>,
+}
+
+/// A group of fixtures loaded and unloaded together.
+#[derive(Clone, Debug, Default)]
+pub struct FixtureSet {
+    fixtures: Vec<Fixture>,
+    loaded: Vec<LoadedRow>,
+    ids: HashMap<String, Value>,
+}
+
+impl FixtureSet {
+    pub fn new() -> FixtureSet {
+        FixtureSet::default()
+    }
+
+    /// Adds a fixture to the set.
+    pub fn with(mut self, fixture: Fixture) -> FixtureSet {
+        self.fixtures.push(fixture);
+        self
+    }
+
+    /// Returns the primary key of the loaded row named `row_name`.
+    pub fn id(&self, row_name: &str) -> Option<&Value> {
+        self.ids.get(row_name)
+    }
+
+    /// Returns the indices of the fixtures, ordered so every fixture comes after the fixtures it depends on.
+    fn load_order(&self) -> Result<Vec<usize>, Error> {
+        let mut order: Vec<usize> = Vec::with_capacity(self.fixtures.len());
+
+        while order.len() < self.fixtures.len() {
+            let next = (0..self.fixtures.len())
+                .filter(|i| !order.contains(i))
+                .find(|i| {
+                    (0..self.fixtures.len())
+                        .filter(|j| !order.contains(j))
+                        .all(|j| !self.fixtures[*i].depends_on(&self.fixtures[j]))
+                });
+
+            match next {
+                Some(i) => order.push(i),
+                None => return Err(Error::Invalid(String::from("the fixtures depend on each other in a cycle"))),
+            }
+        }
+
+        Ok(order)
+    }
+
+    /// Inserts the rows of every fixture, in foreign key dependency order.
+    ///
+    /// Rows that do not set their table's primary key get the id generated by the server
+    /// (see `Table::insert_and_get_id`). If a row fails to load, the rows loaded so far are removed again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Invalid` if the fixtures depend on each other in a cycle, a reference names an unknown row or a
+    /// value cannot be written to its column, and an `Error` if an insert fails.
+    pub fn load(&mut self, db: &DataBase) -> Result<(), Error> {
+        for i in self.load_order()? {
+            for row in self.fixtures[i].rows.clone() {
+                if let Err(err) = self.load_row(db, i, row) {
+                    if let Err(cleanup) = self.unload(db) {
+                        log::error!("Failed to remove loaded fixtures - Err:{:?}", cleanup);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_row(&mut self, db: &DataBase, fixture: usize, row: FixtureRow) -> Result<(), Error> {
+        let mut values = row.values;
+
+        for (column, name) in &row.references {
+            match self.ids.get(name) {
+                Some(id) => values.insert(column.clone(), id.clone()),
+                None => return Err(Error::Invalid(format!("fixture row {} is not loaded", name))),
+            };
+        }
+
+        let table = &self.fixtures[fixture].table;
+        let generated = table.insert_and_get_id(db, &values)?;
+
+        let id = self.fixtures[fixture]
+            .primary_key()
+            .map(|primary_key| values.get(primary_key).cloned().unwrap_or(Value::UInt(generated)));
+
+        if let (Some(name), Some(id)) = (row.name, &id) {
+            self.ids.insert(name, id.clone());
+        }
+
+        self.loaded.push(LoadedRow { fixture, values, id });
+
+        Ok(())
+    }
+
+    /// Deletes every loaded row, in the reverse order they were inserted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if a delete fails. The rows that could not be deleted stay loaded.
+    pub fn unload(&mut self, db: &DataBase) -> Result<(), Error> {
+        while let Some(row) = self.loaded.last() {
+            let fixture = &self.fixtures[row.fixture];
+            let delete = delete_statement(&fixture.table.name, fixture.primary_key(), row);
+
+            db.execute(&SQL::Delete(delete), |_| ())?;
+
+            self.loaded.pop();
+        }
+
+        self.ids.clear();
+
+        Ok(())
+    }
+}
+
+/// Returns the statement deleting a loaded row: by primary key if the table has one, otherwise by every value set.
+fn delete_statement(table: &str, primary_key: Option<&str>, row: &LoadedRow) -> QML {
+    let condition = match (primary_key, &row.id) {
+        (Some(primary_key), Some(id)) => format!("{} = {}", primary_key, id),
+        _ => {
+            let mut columns: Vec<(&String, &Value)> = row.values.iter().collect();
+            columns.sort_by_key(|(column, _)| *column);
+
+            columns.iter()
+                .map(|(column, value)| format!("{} <=> {}", column, value))
+                .collect::<Vec<String>>()
+                .join(" AND ")
+        },
+    };
+
+    QML(format!("DELETE FROM {} WHERE {} LIMIT 1", table, condition))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use serial_test::serial;
+
+    use crate::{
+        data_base::DataBase,
+        relation::{Registry, table::{Table, Attribute, AttributeType, Constraint}},
+        sql::{SQL, QML, value::Value},
+    };
+
+    use super::{Fixture, FixtureSet, LoadedRow, delete_statement};
+
+    fn customer() -> Table {
+        Table{
+            name: String::from("fixture_customer"),
+            attributes: vec![
+                Attribute{
+                    name: String::from("id"),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
+                    constraint: HashSet::from([Constraint::NotNull, Constraint::AutoIncrement])
+                },
+                Attribute{
+                    name: String::from("name"),
+                    data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        }
+    }
+
+    fn order() -> Table {
+        Table{
+            name: String::from("fixture_order"),
+            attributes: vec![
+                Attribute{
+                    name: String::from("id"),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
+                    constraint: HashSet::from([Constraint::NotNull, Constraint::AutoIncrement])
+                },
+                Attribute{
+                    name: String::from("customer_id"),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
+                    constraint: HashSet::from([
+                        Constraint::ForeignKey{
+                            table_name: String::from("fixture_customer"),
+                            attribute_name: String::from("id"),
+                            on_delete: None,
+                            on_update: None
+                        }
+                    ])
+                },
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        }
+    }
+
+    fn fixtures() -> FixtureSet {
+        let customers = Fixture::new(&customer())
+            .row(HashMap::from([(String::from("name"), Value::Text(String::from("Alice")))]))
+            .named("alice")
+            .row(HashMap::from([(String::from("name"), Value::Text(String::from("Bob")))]))
+            .named("bob");
+
+        let orders = Fixture::new(&order())
+            .row(HashMap::new())
+            .reference("customer_id", "alice")
+            .row(HashMap::new())
+            .reference("customer_id", "bob");
+
+        FixtureSet::new()
+            .with(orders)
+            .with(customers)
+    }
+
+    #[test]
+    fn load_order_test() {
+        assert_eq!(fixtures().load_order().unwrap(), vec![1, 0]);
+
+        //references alone also order the fixtures
+        let plain_customer = Table{ name: String::from("plain"), ..customer() };
+        let set = FixtureSet::new()
+            .with(Fixture::new(&plain_customer).row(HashMap::new()).reference("ref", "first"))
+            .with(Fixture::new(&customer()).row(HashMap::new()).named("first"));
+
+        assert_eq!(set.load_order().unwrap(), vec![1, 0]);
+
+        //fixtures of registry tables, named in any case
+        let mut registry = Registry::new(vec![customer(), order()]);
+        let set = FixtureSet::new()
+            .with(Fixture::of(&mut registry, "FIXTURE_ORDER").unwrap())
+            .with(Fixture::of(&mut registry, "fixture_customer").unwrap());
+
+        assert_eq!(set.load_order().unwrap(), vec![1, 0]);
+        assert!(Fixture::of(&mut registry, "missing").is_err());
+    }
+
+    #[test]
+    fn delete_statement_test() {
+        let row = LoadedRow{
+            fixture: 0,
+            values: HashMap::from([
+                (String::from("name"), Value::Text(String::from("Alice"))),
+                (String::from("age"), Value::Null),
+            ]),
+            id: Some(Value::UInt(7)),
+        };
+
+        assert_eq!(
+            delete_statement("customer", Some("id"), &row),
+            QML(String::from("DELETE FROM customer WHERE id = 7 LIMIT 1"))
+        );
+        assert_eq!(
+            delete_statement("customer", None, &row),
+            QML(String::from("DELETE FROM customer WHERE age <=> NULL AND name <=> 'Alice' LIMIT 1"))
+        );
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn load_test() {
+        let db = DataBase::from_env().unwrap();
+
+        db.execute_multiple(&vec![
+            SQL::new("CREATE TABLE fixture_customer (id INT NOT NULL AUTO_INCREMENT PRIMARY KEY, name VARCHAR(255))").unwrap(),
+            SQL::new("CREATE TABLE fixture_order (id INT NOT NULL AUTO_INCREMENT PRIMARY KEY, customer_id INT, FOREIGN KEY(customer_id) REFERENCES fixture_customer(id))").unwrap(),
+        ]).unwrap();
+
+        let count = |table: &str| db.execute(
+            &SQL::new(&format!("SELECT COUNT(*) FROM {}", table)).unwrap(),
+            |row| row.unwrap().get::<u64, usize>(0).unwrap()
+        ).unwrap();
+
+        let mut fixtures = fixtures();
+        fixtures.load(&db).unwrap();
+
+        assert_eq!(count("fixture_customer"), vec![2]);
+        assert_eq!(count("fixture_order"), vec![2]);
+
+        let alice = fixtures.id("alice").unwrap().to_string();
+        let alice_orders = db.execute(
+            &SQL::new(&format!("SELECT COUNT(*) FROM fixture_order WHERE customer_id = {}", alice)).unwrap(),
+            |row| row.unwrap().get::<u64, usize>(0).unwrap()
+        ).unwrap();
+        assert_eq!(alice_orders, vec![1]);
+
+        fixtures.unload(&db).unwrap();
+
+        assert_eq!(count("fixture_customer"), vec![0]);
+        assert_eq!(count("fixture_order"), vec![0]);
+
+        db.execute_multiple(&vec![
+            SQL::new("DROP TABLE fixture_order").unwrap(),
+            SQL::new("DROP TABLE fixture_customer").unwrap(),
+        ]).unwrap();
+    }
+}
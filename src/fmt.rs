@@ -0,0 +1,254 @@
+use std::{fmt, time::Duration};
+
+/// The binary units used by `bytes`, after bytes themselves.
+const BYTE_UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+/// Returns `bytes` in the largest binary unit it reaches, with one decimal: `512 B`, `1.5 KiB`, `12.0 MiB`.
+pub fn bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut unit: u128 = 1;
+
+    for (i, name) in BYTE_UNITS.iter().enumerate() {
+        unit *= 1024;
+
+        //rounded in integers, so the output does not depend on float formatting
+        let tenths = (bytes as u128 * 10 + unit / 2) / unit;
+
+        if tenths < 10_240 || i == BYTE_UNITS.len() - 1 {
+            return format!("{}.{} {}", tenths / 10, tenths % 10, name);
+        }
+    }
+
+    unreachable!()
+}
+
+/// Returns `duration` in the largest unit it reaches: `850us`, `12.3ms`, `4.20s`, `3m 07s`.
+pub fn duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+
+    if micros < 1_000 {
+        return format!("{}us", micros);
+    }
+
+    let tenths_of_ms = (micros + 50) / 100;
+    if tenths_of_ms < 10_000 {
+        return format!("{}.{}ms", tenths_of_ms / 10, tenths_of_ms % 10);
+    }
+
+    let hundredths_of_s = (micros + 5_000) / 10_000;
+    if hundredths_of_s < 6_000 {
+        return format!("{}.{:02}s", hundredths_of_s / 100, hundredths_of_s % 100);
+    }
+
+    let seconds = (micros + 500_000) / 1_000_000;
+    format!("{}m {:02}s", seconds / 60, seconds % 60)
+}
+
+/// Returns `count` with its digits grouped in threes by `,`, whatever the locale: `1,234,567`.
+pub fn count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    grouped
+}
+
+/// How a column of a `Grid` aligns its cells.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// The narrowest a column is truncated to by `Grid::render`.
+const MIN_COLUMN_WIDTH: usize = 4;
+
+/// A table of text with padded columns, used by the `Display` of the report types.
+///
+/// Columns are separated by two spaces and padded to their widest cell, header included. A grid displayed with a
+/// width, as in `format!("{:60}", report)`, is rendered to fit in that many characters (see `Grid::render`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid {
+    columns: Vec<(String, Align)>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Grid {
+    /// Returns an empty grid with the given column headers.
+    pub fn new(columns: &[(&str, Align)]) -> Grid {
+        Grid {
+            columns: columns.iter().map(|(header, align)| (header.to_string(), *align)).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row. Missing cells are left empty and extra cells are ignored.
+    pub fn push(&mut self, mut cells: Vec<String>) {
+        cells.resize(self.columns.len(), String::new());
+        self.rows.push(cells);
+    }
+
+    /// Returns the grid as lines of text, without a trailing newline or trailing spaces.
+    ///
+    /// If `width` is given and the grid is wider, the widest left aligned columns are narrowed, down to
+    /// `MIN_COLUMN_WIDTH`, until it fits. Cells that no longer fit end with `~`. Right aligned columns hold numbers and
+    /// are never cut.
+    pub fn render(&self, width: Option<usize>) -> String {
+        let mut widths: Vec<usize> = self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, (header, _))| {
+                self.rows
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([header.chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        if let Some(width) = width {
+            self.fit(&mut widths, width);
+        }
+
+        let lines: Vec<String> = [self.columns.iter().map(|(header, _)| header.clone()).collect()]
+            .iter()
+            .chain(self.rows.iter())
+            .map(|row| {
+                let cells: Vec<String> = row.iter()
+                    .zip(&self.columns)
+                    .zip(&widths)
+                    .map(|((cell, (_, align)), width)| {
+                        let cell = truncate(cell, *width);
+
+                        match align {
+                            Align::Left => format!("{:<width$}", cell, width = width),
+                            Align::Right => format!("{:>width$}", cell, width = width),
+                        }
+                    })
+                    .collect();
+
+                cells.join("  ").trim_end().to_string()
+            })
+            .collect();
+
+        lines.join("\n")
+    }
+
+    /// Narrows the widest left aligned columns of `widths` until the grid fits in `width`, or none can be narrowed.
+    fn fit(&self, widths: &mut [usize], width: usize) {
+        let total = |widths: &[usize]| widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+
+        while total(widths) > width {
+            let widest = (0..widths.len())
+                .filter(|i| self.columns[*i].1 == Align::Left && widths[*i] > MIN_COLUMN_WIDTH)
+                .max_by_key(|i| (widths[*i], usize::MAX - i));
+
+            match widest {
+                Some(i) => {
+                    let excess = total(widths) - width;
+                    widths[i] = widths[i].saturating_sub(excess).max(MIN_COLUMN_WIDTH);
+                },
+                None => break,
+            }
+        }
+    }
+}
+
+/// Returns `cell` cut to `width` characters, ending with `~` if it was cut.
+fn truncate(cell: &str, width: usize) -> String {
+    match cell.chars().count() > width {
+        true => cell.chars().take(width.saturating_sub(1)).chain(['~']).collect(),
+        false => cell.to_string(),
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(f.width()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Align, Grid, bytes, count, duration};
+
+    #[test]
+    fn bytes_test() {
+        assert_eq!(bytes(0), "0 B");
+        assert_eq!(bytes(1023), "1023 B");
+        assert_eq!(bytes(1024), "1.0 KiB");
+        assert_eq!(bytes(1536), "1.5 KiB");
+        assert_eq!(bytes(1024 * 1024 - 1), "1.0 MiB");
+        assert_eq!(bytes(12 * 1024 * 1024 + 100), "12.0 MiB");
+        assert_eq!(bytes(5 * 1024 * 1024 * 1024), "5.0 GiB");
+        assert_eq!(bytes(u64::MAX), "16777216.0 TiB");
+    }
+
+    #[test]
+    fn duration_test() {
+        assert_eq!(duration(Duration::ZERO), "0us");
+        assert_eq!(duration(Duration::from_micros(850)), "850us");
+        assert_eq!(duration(Duration::from_micros(1561)), "1.6ms");
+        assert_eq!(duration(Duration::from_micros(999_960)), "1.00s");
+        assert_eq!(duration(Duration::from_millis(4200)), "4.20s");
+        assert_eq!(duration(Duration::from_secs(187)), "3m 07s");
+        assert_eq!(duration(Duration::from_secs(7200)), "120m 00s");
+    }
+
+    #[test]
+    fn count_test() {
+        assert_eq!(count(0), "0");
+        assert_eq!(count(999), "999");
+        assert_eq!(count(1000), "1,000");
+        assert_eq!(count(1_234_567), "1,234,567");
+    }
+
+    fn grid() -> Grid {
+        let mut grid = Grid::new(&[("table", Align::Left), ("rows", Align::Right), ("note", Align::Left)]);
+
+        grid.push(vec![String::from("user"), count(1200), String::from("created by the fixture")]);
+        grid.push(vec![String::from("invoice_line"), count(7)]);
+
+        grid
+    }
+
+    #[test]
+    fn grid_test() {
+        assert_eq!(
+            grid().to_string(),
+            "table          rows  note\n\
+            user          1,200  created by the fixture\n\
+            invoice_line      7"
+        );
+    }
+
+    #[test]
+    fn grid_width_test() {
+        assert_eq!(
+            format!("{:32}", grid()),
+            "table          rows  note\n\
+            user          1,200  created by~\n\
+            invoice_line      7"
+        );
+
+        //narrowed down to the minimum, it still overflows
+        assert_eq!(
+            format!("{:10}", grid()),
+            "tab~   rows  note\n\
+            user  1,200  cre~\n\
+            inv~      7"
+        );
+    }
+}
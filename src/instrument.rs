@@ -0,0 +1,249 @@
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::sql::{SQL, tag::skip_leading_comments};
+
+lazy_static! {
+    static ref TABLE_NAME: Regex = Regex::new(r"(?i)\b(?:FROM|INTO|UPDATE|TABLE|VIEW)\s+`?(\w+)`?").unwrap();
+}
+
+/// Returns the kind of a statement, as the keyword it starts with.
+fn statement_kind(sql: &SQL) -> &'static str {
+    match sql {
+        SQL::Create(_) => "CREATE",
+        SQL::Alter(_) => "ALTER",
+        SQL::Drop(_) => "DROP",
+        SQL::Truncate(_) => "TRUNCATE",
+        SQL::Show(_) => "SHOW",
+        SQL::Select(_) => "SELECT",
+        SQL::Insert(_) => "INSERT",
+        SQL::Update(_) => "UPDATE",
+        SQL::Delete(_) => "DELETE",
+        SQL::Grant(_) => "GRANT",
+        SQL::Revoke(_) => "REVOKE",
+    }
+}
+
+/// Returns the first table named by a statement, if it can be found. Leading comments, such as a tag, are skipped.
+fn table_name(statement: &str) -> Option<String> {
+    TABLE_NAME.captures(skip_leading_comments(statement)).map(|captures| captures[1].to_string())
+}
+
+/// Times a unit of database work and reports it when finished.
+///
+/// With the `tracing` feature the work runs inside a span carrying the operation, statement kind, table and the tag of
+/// the `DataBase` (see `DataBase::with_tag`), and the row count and duration are recorded on the span when it
+/// finishes. Without it, the same fields are logged with `log::debug!`.
+pub(crate) struct Timed {
+    #[cfg(not(feature = "tracing"))]
+    operation: &'static str,
+    #[cfg(not(feature = "tracing"))]
+    kind: Option<&'static str>,
+    #[cfg(not(feature = "tracing"))]
+    table: Option<String>,
+    #[cfg(not(feature = "tracing"))]
+    tag: Option<String>,
+    start: Instant,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl Timed {
+    fn new(operation: &'static str, kind: Option<&'static str>, table: Option<String>) -> Timed {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "db",
+            operation,
+            kind = tracing::field::Empty,
+            table = tracing::field::Empty,
+            tag = tracing::field::Empty,
+            statements = tracing::field::Empty,
+            rows = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        );
+
+        #[cfg(feature = "tracing")]
+        {
+            if let Some(kind) = kind {
+                span.record("kind", kind);
+            }
+            if let Some(table) = &table {
+                span.record("table", table.as_str());
+            }
+        }
+
+        Timed {
+            #[cfg(not(feature = "tracing"))]
+            operation,
+            #[cfg(not(feature = "tracing"))]
+            kind,
+            #[cfg(not(feature = "tracing"))]
+            table,
+            #[cfg(not(feature = "tracing"))]
+            tag: None,
+            start: Instant::now(),
+            #[cfg(feature = "tracing")]
+            span,
+        }
+    }
+
+    /// Times a single statement run by `DataBase::execute`.
+    pub(crate) fn statement(sql: &SQL) -> Timed {
+        Timed::new("execute", Some(statement_kind(sql)), table_name(&sql.to_string()))
+    }
+
+    /// Times the statements run in one transaction by `DataBase::execute_multiple`.
+    pub(crate) fn transaction(statements: usize) -> Timed {
+        let timed = Timed::new("transaction", None, None);
+
+        #[cfg(feature = "tracing")]
+        timed.span.record("statements", statements);
+        #[cfg(not(feature = "tracing"))]
+        let _ = statements;
+
+        timed
+    }
+
+    /// Times the loading of the schema of `table`.
+    pub(crate) fn schema_load(table: &str) -> Timed {
+        Timed::new("schema_load", None, Some(table.to_string()))
+    }
+
+    /// Records the tag of the statements, if they have one.
+    pub(crate) fn tagged(#[allow(unused_mut)] mut self, tag: Option<&str>) -> Timed {
+        #[cfg(feature = "tracing")]
+        if let Some(tag) = tag {
+            self.span.record("tag", tag);
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        {
+            self.tag = tag.map(str::to_string);
+        }
+
+        self
+    }
+
+    /// Runs `f` inside the span, so events emitted by `f` nest under it.
+    pub(crate) fn in_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        #[cfg(feature = "tracing")]
+        return self.span.in_scope(f);
+
+        #[cfg(not(feature = "tracing"))]
+        f()
+    }
+
+    /// Reports the work as finished, having affected or returned `rows` rows if known.
+    pub(crate) fn finish(self, rows: Option<usize>) -> Duration {
+        let duration = self.start.elapsed();
+
+        #[cfg(feature = "tracing")]
+        {
+            if let Some(rows) = rows {
+                self.span.record("rows", rows);
+            }
+            self.span.record("duration_ms", duration.as_secs_f64() * 1000.0);
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        log::debug!(
+            "{} {} {} [{}] - {} rows in {:?}",
+            self.operation,
+            self.kind.unwrap_or_default(),
+            self.table.as_deref().unwrap_or_default(),
+            self.tag.as_deref().unwrap_or_default(),
+            rows.map_or(String::from("?"), |rows| rows.to_string()),
+            duration
+        );
+
+        duration
+    }
+
+    /// Reports the work as failed with `err`.
+    pub(crate) fn fail<E: std::fmt::Debug>(self, err: &E) {
+        #[cfg(feature = "tracing")]
+        self.span.in_scope(|| tracing::error!(error = ?err, "database operation failed"));
+
+        #[cfg(not(feature = "tracing"))]
+        log::debug!(
+            "{} {} {} [{}] failed - Err:{:?}",
+            self.operation,
+            self.kind.unwrap_or_default(),
+            self.table.as_deref().unwrap_or_default(),
+            self.tag.as_deref().unwrap_or_default(),
+            err
+        );
+
+        self.finish(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::table_name;
+
+    #[test]
+    fn table_name_test() {
+        assert_eq!(table_name("SELECT * FROM user WHERE id = 1"), Some(String::from("user")));
+        assert_eq!(table_name("insert into `post`(id) VALUES (1)"), Some(String::from("post")));
+        assert_eq!(table_name("CREATE TABLE tag (id int)"), Some(String::from("tag")));
+        assert_eq!(table_name("SELECT 1"), None);
+        assert_eq!(table_name("/* sync from legacy */ SELECT * FROM user"), Some(String::from("user")));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn span_fields_test() {
+        use std::{collections::HashMap, fmt, sync::{Arc, Mutex}};
+
+        use tracing::{field::{Field, Visit}, span::{Attributes, Id, Record}, Subscriber};
+        use tracing_subscriber::{layer::{Context, SubscriberExt}, Layer, Registry};
+
+        use crate::sql::SQL;
+
+        use super::Timed;
+
+        type Fields = Arc<Mutex<HashMap<String, String>>>;
+
+        struct Recorder(Fields);
+
+        impl Visit for Recorder {
+            fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                self.0.lock().unwrap().insert(field.name().to_string(), format!("{:?}", value));
+            }
+        }
+
+        struct SpanFields(Fields);
+
+        impl<S: Subscriber> Layer<S> for SpanFields {
+            fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+                attrs.record(&mut Recorder(self.0.clone()));
+            }
+            fn on_record(&self, _id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+                values.record(&mut Recorder(self.0.clone()));
+            }
+        }
+
+        let fields: Fields = Arc::default();
+        let subscriber = Registry::default().with(SpanFields(fields.clone()));
+
+        let rows = tracing::subscriber::with_default(subscriber, || {
+            let timed = Timed::statement(&SQL::new("SELECT * FROM user").unwrap()).tagged(Some("orders:sync"));
+            let rows = timed.in_scope(|| vec![1, 2, 3]);
+            timed.finish(Some(rows.len()));
+            rows
+        });
+
+        let fields = fields.lock().unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(fields.get("operation").map(String::as_str), Some("\"execute\""));
+        assert_eq!(fields.get("kind").map(String::as_str), Some("\"SELECT\""));
+        assert_eq!(fields.get("table").map(String::as_str), Some("\"user\""));
+        assert_eq!(fields.get("tag").map(String::as_str), Some("\"orders:sync\""));
+        assert_eq!(fields.get("rows").map(String::as_str), Some("3"));
+        assert!(fields.contains_key("duration_ms"));
+    }
+}
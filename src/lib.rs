@@ -1,5 +1,15 @@
 pub mod relation;
 pub mod data_base;
 pub mod sql;
+pub mod naming;
+pub mod fixture;
+pub mod export;
+pub mod fmt;
+pub mod lock;
+pub mod error;
+pub mod retry;
 
+pub use error::Error;
+
+mod instrument;
 mod test_tools;
\ No newline at end of file
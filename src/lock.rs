@@ -0,0 +1,347 @@
+use std::{fmt, error, time::Duration};
+
+use mysql::{prelude::*, Conn, Error};
+
+/// The longest lock name MySQL accepts, in characters.
+pub const MAX_LOCK_NAME: usize = 64;
+
+/// An error returned while taking or releasing an advisory lock.
+#[derive(Debug)]
+pub enum LockError {
+    /// The lock name is empty.
+    InvalidName,
+    /// The lock is held by another session, and the caller asked not to wait (see `DataBase::try_lock`).
+    AlreadyLocked{name: String},
+    /// The lock was still held by another session when the timeout ran out.
+    Timeout{name: String, timeout: Duration},
+    /// The server failed the statement, or returned `NULL` from `GET_LOCK`.
+    Execution(Error),
+    /// The lock was not requested, as the `DataBase` is shutting down (see `DataBase::shutdown`).
+    ShuttingDown,
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::InvalidName => write!(f, "lock names cannot be empty"),
+            LockError::AlreadyLocked{name} => write!(f, "lock {} is held by another session", name),
+            LockError::Timeout{name, timeout} => write!(f, "lock {} is still held by another session after {:?}", name, timeout),
+            LockError::Execution(err) => write!(f, "{}", err),
+            LockError::ShuttingDown => write!(f, "the database is shutting down"),
+        }
+    }
+}
+
+impl error::Error for LockError {}
+
+impl From<Error> for LockError {
+    fn from(err: Error) -> Self {
+        LockError::Execution(err)
+    }
+}
+
+/// Returns the name the lock `name` is taken under on the server.
+///
+/// Names of up to `MAX_LOCK_NAME` characters are used as they are. Longer names are cut and end with a hash of the
+/// whole name, so two long names sharing a prefix still get different locks, and every process derives the same one.
+///
+/// # Errors
+///
+/// Returns `LockError::InvalidName` if `name` is empty.
+pub fn lock_name(name: &str) -> Result<String, LockError> {
+    if name.is_empty() {
+        return Err(LockError::InvalidName);
+    }
+
+    if name.chars().count() <= MAX_LOCK_NAME {
+        return Ok(name.to_string());
+    }
+
+    //FNV-1a, which unlike `DefaultHasher` is stable across builds
+    let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3));
+
+    let prefix: String = name.chars().take(MAX_LOCK_NAME - 17).collect();
+
+    Ok(format!("{}#{:016x}", prefix, hash))
+}
+
+/// The statements behind an advisory lock, run on the one session that holds it.
+pub trait LockExecutor {
+    /// Runs `GET_LOCK(name, timeout)`. Returns `Some(true)` if the lock was taken, `Some(false)` if the timeout ran out
+    /// and `None` if the server returned `NULL`.
+    fn get_lock(&mut self, name: &str, timeout: u64) -> Result<Option<bool>, Error>;
+    /// Runs `RELEASE_LOCK(name)`. Returns `Some(true)` if the lock was released, `Some(false)` if it is held by another
+    /// session and `None` if it is not held at all.
+    fn release_lock(&mut self, name: &str) -> Result<Option<bool>, Error>;
+    /// Returns true if this session holds the lock `name`.
+    fn holds_lock(&mut self, name: &str) -> Result<bool, Error>;
+}
+
+impl LockExecutor for Conn {
+    fn get_lock(&mut self, name: &str, timeout: u64) -> Result<Option<bool>, Error> {
+        let acquired: Option<Option<i64>> = self.exec_first("SELECT GET_LOCK(?, ?)", (name, timeout))?;
+
+        Ok(acquired.flatten().map(|acquired| acquired == 1))
+    }
+
+    fn release_lock(&mut self, name: &str) -> Result<Option<bool>, Error> {
+        let released: Option<Option<i64>> = self.exec_first("SELECT RELEASE_LOCK(?)", (name,))?;
+
+        Ok(released.flatten().map(|released| released == 1))
+    }
+
+    fn holds_lock(&mut self, name: &str) -> Result<bool, Error> {
+        let held: Option<Option<i64>> = self.exec_first("SELECT IS_USED_LOCK(?) = CONNECTION_ID()", (name,))?;
+
+        Ok(held.flatten() == Some(1))
+    }
+}
+
+/// An advisory lock held by the session of `conn`, released when the guard is dropped.
+///
+/// The lock belongs to the session that took it, so the guard keeps that connection for as long as it lives and
+/// releases the lock on it. If the connection is lost the server releases the lock itself.
+#[derive(Debug)]
+pub struct LockGuard<C: LockExecutor = Conn> {
+    conn: C,
+    name: String,
+    released: bool,
+}
+
+/// Takes the lock `name` on the session of `conn`, waiting up to `timeout` for another session to release it.
+///
+/// The timeout is rounded up to whole seconds. A zero timeout does not wait.
+///
+/// # Errors
+///
+/// Returns `LockError::AlreadyLocked` if the timeout is zero and the lock is held by another session,
+/// `LockError::Timeout` if it is still held after waiting, or `LockError::Execution` if the statement fails.
+pub fn acquire<C: LockExecutor>(mut conn: C, name: &str, timeout: Duration) -> Result<LockGuard<C>, LockError> {
+    let name = lock_name(name)?;
+
+    let seconds = timeout.as_secs() + (timeout.subsec_nanos() > 0) as u64;
+
+    match conn.get_lock(&name, seconds)? {
+        Some(true) => Ok(LockGuard{conn, name, released: false}),
+        Some(false) if timeout.is_zero() => Err(LockError::AlreadyLocked{name}),
+        Some(false) => Err(LockError::Timeout{name, timeout}),
+        None => Err(LockError::Execution(Error::FromValueError(mysql::Value::NULL))),
+    }
+}
+
+impl<C: LockExecutor> LockGuard<C> {
+    /// Returns the name the lock was taken under (see `lock_name`).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true if the session still holds the lock.
+    ///
+    /// Returns false if the server could not be asked, as the lock is released with the session when the connection
+    /// is lost.
+    pub fn is_held(&mut self) -> bool {
+        !self.released && self.conn.holds_lock(&self.name).unwrap_or(false)
+    }
+
+    /// Releases the lock now, rather than when the guard is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LockError::Execution` if `RELEASE_LOCK` fails.
+    pub fn release(mut self) -> Result<(), LockError> {
+        self.release_now()
+    }
+
+    fn release_now(&mut self) -> Result<(), LockError> {
+        if self.released {
+            return Ok(());
+        }
+
+        self.released = true;
+
+        match self.conn.release_lock(&self.name)? {
+            Some(true) => Ok(()),
+            _ => {
+                log::warn!("Lock {} was no longer held by this session when it was released", self.name);
+                Ok(())
+            },
+        }
+    }
+}
+
+impl<C: LockExecutor> Drop for LockGuard<C> {
+    fn drop(&mut self) {
+        if let Err(err) = self.release_now() {
+            log::error!("Failed to release lock {} - Err:{:?}", self.name, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::HashMap, panic::{self, AssertUnwindSafe}, rc::Rc, thread, time::Duration};
+
+    use mysql::Error;
+
+    use crate::data_base::DataBase;
+
+    use super::{LockError, LockExecutor, MAX_LOCK_NAME, acquire, lock_name};
+
+    /// The locks held on a mock server, by name, and a log of the statements run against it.
+    #[derive(Debug, Default)]
+    struct Server {
+        locks: HashMap<String, usize>,
+        log: Vec<String>,
+    }
+
+    /// A session on a `Server`.
+    #[derive(Debug)]
+    struct MockExecutor {
+        server: Rc<RefCell<Server>>,
+        session: usize,
+    }
+
+    impl LockExecutor for MockExecutor {
+        fn get_lock(&mut self, name: &str, timeout: u64) -> Result<Option<bool>, Error> {
+            let mut server = self.server.borrow_mut();
+            server.log.push(format!("{} GET_LOCK({}, {})", self.session, name, timeout));
+
+            match server.locks.get(name) {
+                Some(session) if *session != self.session => Ok(Some(false)),
+                _ => {
+                    server.locks.insert(name.to_string(), self.session);
+                    Ok(Some(true))
+                },
+            }
+        }
+
+        fn release_lock(&mut self, name: &str) -> Result<Option<bool>, Error> {
+            let mut server = self.server.borrow_mut();
+            server.log.push(format!("{} RELEASE_LOCK({})", self.session, name));
+
+            match server.locks.get(name) {
+                Some(session) if *session == self.session => {
+                    server.locks.remove(name);
+                    Ok(Some(true))
+                },
+                Some(_) => Ok(Some(false)),
+                None => Ok(None),
+            }
+        }
+
+        fn holds_lock(&mut self, name: &str) -> Result<bool, Error> {
+            Ok(self.server.borrow().locks.get(name) == Some(&self.session))
+        }
+    }
+
+    fn sessions() -> (Rc<RefCell<Server>>, MockExecutor, MockExecutor) {
+        let server = Rc::new(RefCell::new(Server::default()));
+
+        (
+            server.clone(),
+            MockExecutor{server: server.clone(), session: 1},
+            MockExecutor{server, session: 2},
+        )
+    }
+
+    #[test]
+    fn lock_name_test() {
+        assert!(matches!(lock_name(""), Err(LockError::InvalidName)));
+        assert_eq!(lock_name("nightly_export").unwrap(), "nightly_export");
+
+        let long = "é".repeat(MAX_LOCK_NAME);
+        assert_eq!(lock_name(&long).unwrap(), long);
+
+        let a = lock_name(&format!("{}a", "x".repeat(MAX_LOCK_NAME))).unwrap();
+        let b = lock_name(&format!("{}b", "x".repeat(MAX_LOCK_NAME))).unwrap();
+
+        assert_eq!(a.chars().count(), MAX_LOCK_NAME);
+        assert_ne!(a, b);
+        assert_eq!(a, lock_name(&format!("{}a", "x".repeat(MAX_LOCK_NAME))).unwrap());
+    }
+
+    #[test]
+    fn acquire_test() {
+        let (server, first, second) = sessions();
+
+        let mut guard = acquire(first, "job", Duration::from_millis(1500)).unwrap();
+        assert!(guard.is_held());
+
+        let err = acquire(second, "job", Duration::ZERO).unwrap_err();
+        assert!(matches!(err, LockError::AlreadyLocked{ref name} if name == "job"));
+        assert_eq!(err.to_string(), "lock job is held by another session");
+
+        drop(guard);
+
+        let second = MockExecutor{server: server.clone(), session: 2};
+        let guard = acquire(second, "job", Duration::from_secs(3)).unwrap();
+        drop(guard);
+
+        assert_eq!(
+            server.borrow().log,
+            vec![
+                "1 GET_LOCK(job, 2)",
+                "2 GET_LOCK(job, 0)",
+                "1 RELEASE_LOCK(job)",
+                "2 GET_LOCK(job, 3)",
+                "2 RELEASE_LOCK(job)",
+            ]
+        );
+        assert!(server.borrow().locks.is_empty());
+    }
+
+    #[test]
+    fn acquire_timeout_test() {
+        let (server, first, second) = sessions();
+
+        let guard = acquire(first, "job", Duration::from_secs(1)).unwrap();
+
+        assert!(matches!(
+            acquire(second, "job", Duration::from_secs(1)),
+            Err(LockError::Timeout{name, ..}) if name == "job"
+        ));
+
+        //released explicitly, so dropping the guard does not release it again
+        guard.release().unwrap();
+
+        assert_eq!(server.borrow().log, vec!["1 GET_LOCK(job, 1)", "2 GET_LOCK(job, 1)", "1 RELEASE_LOCK(job)"]);
+    }
+
+    #[test]
+    fn release_on_panic_test() {
+        let (server, first, _) = sessions();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _guard = acquire(first, "job", Duration::ZERO).unwrap();
+
+            panic!("job failed");
+        }));
+
+        assert!(result.is_err());
+        assert!(server.borrow().locks.is_empty());
+        assert_eq!(server.borrow().log, vec!["1 GET_LOCK(job, 0)", "1 RELEASE_LOCK(job)"]);
+    }
+
+    #[test]
+    #[ignore]
+    fn advisory_lock_test() {
+        let name = "rust_db_interface_advisory_lock_test";
+
+        let mut guard = DataBase::from_env().unwrap().advisory_lock(name, Duration::from_secs(1)).unwrap();
+        assert!(guard.is_held());
+
+        let contender = thread::spawn(move || {
+            let db = DataBase::from_env().unwrap();
+
+            let busy = matches!(db.try_lock(name), Err(LockError::AlreadyLocked{..}));
+            let waited = db.advisory_lock(name, Duration::from_secs(5)).is_ok();
+
+            (busy, waited)
+        });
+
+        thread::sleep(Duration::from_millis(500));
+        drop(guard);
+
+        assert_eq!(contender.join().unwrap(), (true, true));
+    }
+}
@@ -0,0 +1,244 @@
+use std::{fmt, mem, collections::HashMap};
+
+/// Rust keywords that cannot be used as plain identifiers.
+const RUST_KEYWORDS: [&str; 51] = [
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro", "match",
+    "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "self", "static", "struct", "super", "trait",
+    "true", "try", "type", "typeof", "union", "unsafe", "unsized", "use", "virtual", "where",
+];
+
+/// Keywords that cannot be used as raw identifiers either.
+const NON_RAW_KEYWORDS: [&str; 4] = ["Self", "crate", "self", "super"];
+
+/// The naming convention used to turn database identifiers into generated identifiers and back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NamingConvention {
+    /// `snake_case`
+    Snake,
+    /// `camelCase`
+    Camel,
+    /// `PascalCase`
+    Pascal,
+    /// The identifier is kept as is.
+    Preserve,
+}
+
+/// Represents possible errors that can occur when mapping identifiers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NamingError {
+    /// The identifier is empty or has no alphanumeric characters.
+    Empty(String),
+    /// Several names map to the same identifier.
+    Collision{identifier: String, names: Vec<String>},
+}
+
+impl fmt::Display for NamingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NamingError::Empty(name) => write!(f, "\"{}\" has no usable characters", name),
+            NamingError::Collision{identifier, names} => write!(f, "{:?} all map to \"{}\"", names, identifier),
+        }
+    }
+}
+
+impl NamingConvention {
+    /// Returns `name` converted to the naming convention.
+    pub fn convert(&self, name: &str) -> String {
+        match self {
+            NamingConvention::Snake => to_snake_case(name),
+            NamingConvention::Camel => to_camel_case(name),
+            NamingConvention::Pascal => to_pascal_case(name),
+            NamingConvention::Preserve => name.to_string(),
+        }
+    }
+
+    /// Returns `name` converted to the naming convention and made into a valid Rust identifier.
+    ///
+    /// Identifiers starting with a digit are prefixed with `_` and Rust keywords are written as raw identifiers (`r#type`).
+    pub fn rust_identifier(&self, name: &str) -> Result<String, NamingError> {
+        let identifier: String = self.convert(name)
+            .chars()
+            .map(|ch| if ch.is_alphanumeric() || ch == '_' { ch } else { '_' })
+            .collect();
+
+        if !identifier.chars().any(|ch| ch.is_alphanumeric()) {
+            return Err(NamingError::Empty(name.to_string()));
+        }
+
+        if identifier.starts_with(|ch: char| ch.is_ascii_digit()) {
+            return Ok(format!("_{}", identifier));
+        }
+
+        if NON_RAW_KEYWORDS.contains(&identifier.as_str()) {
+            return Ok(format!("{}_", identifier));
+        }
+
+        if RUST_KEYWORDS.contains(&identifier.as_str()) {
+            return Ok(format!("r#{}", identifier));
+        }
+
+        Ok(identifier)
+    }
+
+    /// Maps every name to a Rust identifier, in the order given.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NamingError::Collision` if two names map to the same identifier, instead of silently merging them.
+    pub fn rust_identifiers(&self, names: &[&str]) -> Result<Vec<(String, String)>, NamingError> {
+        let mut mapped: Vec<(String, String)> = Vec::with_capacity(names.len());
+        let mut seen: HashMap<String, Vec<String>> = HashMap::new();
+
+        for name in names {
+            let identifier = self.rust_identifier(name)?;
+
+            seen.entry(identifier.clone())
+                .or_default()
+                .push(name.to_string());
+
+            mapped.push((name.to_string(), identifier));
+        }
+
+        for (_, identifier) in &mapped {
+            let names = &seen[identifier];
+
+            if names.len() > 1 {
+                return Err(NamingError::Collision{identifier: identifier.clone(), names: names.clone()});
+            }
+        }
+
+        Ok(mapped)
+    }
+}
+
+/// Splits an identifier into lowercase words.
+///
+/// Words are separated by non alphanumeric characters and by case changes. A run of capitals is kept as one word,
+/// except for its last letter when that letter starts a new capitalised word (`HTTPServer` → `http`, `server`).
+/// Digits stay attached to the word they follow.
+pub fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for (i, ch) in chars.iter().enumerate() {
+        if !ch.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if ch.is_uppercase() && !current.is_empty() {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+
+            if previous.is_lowercase() || previous.is_ascii_digit() || (previous.is_uppercase() && next_is_lower) {
+                words.push(mem::take(&mut current));
+            }
+        }
+
+        current.extend(ch.to_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+/// Capitalises the first letter of `word`.
+fn capitalise(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Returns `name` in `snake_case`.
+pub fn to_snake_case(name: &str) -> String {
+    words(name).join("_")
+}
+
+/// Returns `name` in `camelCase`.
+pub fn to_camel_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .enumerate()
+        .map(|(i, word)| if i == 0 { word.clone() } else { capitalise(word) })
+        .collect()
+}
+
+/// Returns `name` in `PascalCase`.
+pub fn to_pascal_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| capitalise(word))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NamingConvention, NamingError, words, to_snake_case, to_camel_case, to_pascal_case};
+
+    #[test]
+    fn words_test() {
+        assert_eq!(words("first_name"), vec!["first", "name"]);
+        assert_eq!(words("firstName"), vec!["first", "name"]);
+        assert_eq!(words("FirstName"), vec!["first", "name"]);
+        assert_eq!(words("HTTPServerURL"), vec!["http", "server", "url"]);
+        assert_eq!(words("userID"), vec!["user", "id"]);
+        assert_eq!(words("address2Line"), vec!["address2", "line"]);
+        assert_eq!(words("  first--name__"), vec!["first", "name"]);
+    }
+
+    #[test]
+    fn conversion_test() {
+        assert_eq!(to_snake_case("PersonID"), "person_id");
+        assert_eq!(to_snake_case("date_of_birth"), "date_of_birth");
+        assert_eq!(to_camel_case("date_of_birth"), "dateOfBirth");
+        assert_eq!(to_camel_case("PersonID"), "personId");
+        assert_eq!(to_pascal_case("date_of_birth"), "DateOfBirth");
+        assert_eq!(to_pascal_case("http_server"), "HttpServer");
+        assert_eq!(NamingConvention::Preserve.convert("PersonID"), "PersonID");
+    }
+
+    #[test]
+    fn rust_identifier_test() {
+        let snake = NamingConvention::Snake;
+
+        assert_eq!(snake.rust_identifier("2fa_code"), Ok(String::from("_2fa_code")));
+        assert_eq!(snake.rust_identifier("Type"), Ok(String::from("r#type")));
+        assert_eq!(snake.rust_identifier("match"), Ok(String::from("r#match")));
+        assert_eq!(snake.rust_identifier("self"), Ok(String::from("self_")));
+        assert_eq!(snake.rust_identifier("__"), Err(NamingError::Empty(String::from("__"))));
+        assert_eq!(NamingConvention::Preserve.rust_identifier("first name"), Ok(String::from("first_name")));
+        assert_eq!(NamingConvention::Pascal.rust_identifier("self"), Ok(String::from("Self_")));
+    }
+
+    #[test]
+    fn collision_test() {
+        let snake = NamingConvention::Snake;
+
+        assert_eq!(
+            snake.rust_identifiers(&["userId", "user_id", "name"]),
+            Err(NamingError::Collision{
+                identifier: String::from("user_id"),
+                names: vec![String::from("userId"), String::from("user_id")]
+            })
+        );
+
+        assert_eq!(
+            snake.rust_identifiers(&["userId", "name"]),
+            Ok(vec![
+                (String::from("userId"), String::from("user_id")),
+                (String::from("name"), String::from("name")),
+            ])
+        );
+    }
+}
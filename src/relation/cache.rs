@@ -0,0 +1,178 @@
+use std::{collections::HashMap, sync::Mutex, time::{Duration, Instant}};
+
+use crate::data_base::Executor;
+
+use super::table::{Table, TableLoadError};
+
+/// Tables read from a database, kept so loading them again runs no statements (see `Table::from_db_cached`).
+///
+/// Tables are kept by name until they are invalidated, or, if the cache has a time to live, until it has passed since
+/// they were read. The cache does not see schema changes, so a table that is altered has to be invalidated:
+///
+/// ```ignore
+/// let post = Table::from_db_cached(&db, "post")?; //reads post
+/// let post = Table::from_db_cached(&db, "post")?; //runs no statements
+///
+/// db.execute(&SQL::new("ALTER TABLE post ADD COLUMN views int")?, |_| ())?;
+/// db.schema_cache().invalidate("post");
+/// ```
+///
+/// A cache can be shared by threads. Two threads missing the same table both read it.
+#[derive(Debug, Default)]
+pub struct SchemaCache {
+    /// How long a table is kept after it was read, if not until it is invalidated.
+    ttl: Option<Duration>,
+    /// The tables by name, with the time they were read.
+    tables: Mutex<HashMap<String, (Table, Instant)>>,
+}
+
+impl SchemaCache {
+    /// Returns an empty cache that keeps tables until they are invalidated.
+    pub fn new() -> SchemaCache {
+        SchemaCache::default()
+    }
+
+    /// Returns an empty cache that reads a table again once `ttl` has passed since it was read.
+    pub fn with_ttl(ttl: Duration) -> SchemaCache {
+        SchemaCache { ttl: Some(ttl), ..SchemaCache::default() }
+    }
+
+    /// Returns how long a table is kept after it was read, if the cache has a time to live.
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Returns an empty cache with the time to live of this one, for a `DataBase` switching to another schema.
+    pub(crate) fn empty_like(&self) -> SchemaCache {
+        SchemaCache { ttl: self.ttl, ..SchemaCache::default() }
+    }
+
+    /// Returns the table `table_name`, if it is in the cache and has not expired.
+    pub fn get(&self, table_name: &str) -> Option<Table> {
+        let tables = self.tables.lock().unwrap();
+
+        tables.get(table_name)
+            .filter(|(_, read)| self.ttl.is_none_or(|ttl| read.elapsed() < ttl))
+            .map(|(table, _)| table.clone())
+    }
+
+    /// Returns the table `table_name` from the cache, or reads it from `db` (see `Table::from_db_in`) and keeps it if it
+    /// is not there or has expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `Table::from_db_in`. A table that fails to load is not kept.
+    pub fn load<E: Executor>(&self, db: &E, table_name: &str) -> Result<Table, TableLoadError> {
+        if let Some(table) = self.get(table_name) {
+            return Ok(table);
+        }
+
+        //the lock is not held while reading, so other tables can be looked up meanwhile
+        let table = Table::from_db_in(db, table_name)?;
+
+        self.tables.lock().unwrap().insert(table_name.to_string(), (table.clone(), Instant::now()));
+
+        Ok(table)
+    }
+
+    /// Removes the table `table_name`, so it is read again the next time it is loaded.
+    pub fn invalidate(&self, table_name: &str) {
+        self.tables.lock().unwrap().remove(table_name);
+    }
+
+    /// Removes every table.
+    pub fn invalidate_all(&self) {
+        self.tables.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, sync::Arc, time::Duration};
+
+    use mysql::{Column, Row, consts::ColumnType};
+
+    use crate::{data_base::Executor, sql::SQL};
+
+    use super::SchemaCache;
+
+    /// Returns a table of one `id` column for every `SHOW FULL COLUMNS`, counting the statements run.
+    #[derive(Default)]
+    struct CountingExecutor {
+        statements: Cell<usize>,
+    }
+
+    impl Executor for CountingExecutor {
+        fn execute<E, F>(&self, cmd: &SQL, row_map: F) -> Result<Vec<E>, crate::Error> where F : FnMut(Result<Row, mysql::Error>) -> E {
+            const COLUMNS: [&str; 9] = ["Field", "Type", "Collation", "Null", "Key", "Default", "Extra", "Privileges", "Comment"];
+
+            self.statements.set(self.statements.get() + 1);
+
+            let rows = match cmd.to_string().starts_with("SHOW FULL COLUMNS") {
+                true => {
+                    let columns: Arc<[Column]> = COLUMNS.iter()
+                        .map(|name| Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(name.as_bytes()))
+                        .collect();
+                    let values = [Some("id"), Some("int"), None, Some("NO"), Some("PRI"), None, Some(""), None, Some("")]
+                        .iter()
+                        .map(|value| value.map_or(mysql::Value::NULL, mysql::Value::from))
+                        .collect();
+
+                    vec![mysql_common::row::new_row(values, columns)]
+                },
+                false => Vec::new(),
+            };
+
+            Ok(rows.into_iter().map(Ok).map(row_map).collect())
+        }
+    }
+
+    #[test]
+    fn load_test() {
+        let db = CountingExecutor::default();
+        let cache = SchemaCache::new();
+
+        let post = cache.load(&db, "post").unwrap();
+        let statements = db.statements.get();
+        assert!(statements > 0);
+
+        assert_eq!(cache.load(&db, "post").unwrap(), post);
+        assert_eq!(db.statements.get(), statements);
+        assert_eq!(cache.get("post"), Some(post.clone()));
+        assert_eq!(cache.get("user"), None);
+
+        cache.load(&db, "user").unwrap();
+        assert_eq!(db.statements.get(), statements * 2);
+
+        cache.invalidate("post");
+        assert_eq!(cache.get("post"), None);
+        cache.load(&db, "user").unwrap();
+        assert_eq!(db.statements.get(), statements * 2);
+        cache.load(&db, "post").unwrap();
+        assert_eq!(db.statements.get(), statements * 3);
+
+        cache.invalidate_all();
+        cache.load(&db, "post").unwrap();
+        cache.load(&db, "user").unwrap();
+        assert_eq!(db.statements.get(), statements * 5);
+    }
+
+    #[test]
+    fn ttl_test() {
+        let db = CountingExecutor::default();
+
+        let expired = SchemaCache::with_ttl(Duration::ZERO);
+        expired.load(&db, "post").unwrap();
+        let statements = db.statements.get();
+
+        assert_eq!(expired.ttl(), Some(Duration::ZERO));
+        assert_eq!(expired.get("post"), None);
+        expired.load(&db, "post").unwrap();
+        assert_eq!(db.statements.get(), statements * 2);
+
+        let cache = SchemaCache::with_ttl(Duration::from_secs(3600));
+        cache.load(&db, "post").unwrap();
+        cache.load(&db, "post").unwrap();
+        assert_eq!(db.statements.get(), statements * 3);
+    }
+}
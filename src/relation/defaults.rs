@@ -0,0 +1,570 @@
+use std::{collections::HashMap, fmt, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use crate::sql::value::Value;
+
+use super::table::{Table, Attribute, AttributeType, Constraint};
+
+/// A source of the current time, so defaults such as `CURRENT_TIMESTAMP` can be resolved predictably.
+pub trait Clock {
+    /// Returns the time elapsed since the UNIX epoch, in UTC.
+    fn now(&self) -> Duration;
+}
+
+/// The system clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+    }
+}
+
+/// A clock stopped at the given time since the UNIX epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedClock(pub Duration);
+
+impl Clock for FixedClock {
+    fn now(&self) -> Duration {
+        self.0
+    }
+}
+
+/// The default of a column, as set by `DEFAULT`.
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DefaultValue {
+    /// `DEFAULT NULL`.
+    Null,
+    /// A literal, unquoted. It is quoted when written for a column whose type is quoted (see `DefaultValue::to_sql`).
+    Literal(String),
+    /// An expression written as it is, such as `CURRENT_TIMESTAMP` or `(UUID())`.
+    Expression(String),
+}
+
+impl DefaultValue {
+    /// Returns the SQL following `DEFAULT` for a column of type `data_type`.
+    ///
+    /// Literals are written unquoted for numeric columns when they are numbers (see `AttributeType::is_quoted`), and
+    /// are otherwise escaped and quoted.
+    pub fn to_sql(&self, data_type: &AttributeType) -> String {
+        match self {
+            DefaultValue::Literal(literal) if !data_type.is_quoted() && literal.trim().parse::<f64>().is_ok() => {
+                literal.trim().to_string()
+            },
+            default => default.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DefaultValue {
+    /// Writes the default without knowing the column's type, so literals are always quoted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DefaultValue::Null => write!(f, "NULL"),
+            DefaultValue::Literal(literal) => write!(f, "{}", Value::Text(literal.clone())),
+            DefaultValue::Expression(expression) => write!(f, "{}", expression),
+        }
+    }
+}
+
+/// Returns the default of a column, from the `Default` and `Extra` fields of `SHOW FULL COLUMNS`.
+///
+/// Expression defaults (`DEFAULT_GENERATED`, or `CURRENT_TIMESTAMP` on servers older than MySQL 8.0) are kept as they
+/// are, in parentheses unless they are a timestamp function. Other defaults are literals. A `NULL` default is reported
+/// the same as no default, and is left out by the caller.
+pub(super) fn read_default(default: &str, extra: &str) -> DefaultValue {
+    let timestamp = default.to_ascii_uppercase().starts_with("CURRENT_TIMESTAMP");
+
+    match (timestamp, extra.contains("DEFAULT_GENERATED"), default.starts_with('(')) {
+        (true, _, _) | (false, true, true) => DefaultValue::Expression(default.to_string()),
+        (false, true, false) => DefaultValue::Expression(format!("({})", default)),
+        (false, false, _) => DefaultValue::Literal(default.to_string()),
+    }
+}
+
+/// Returns the date of a number of days since the UNIX epoch, in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u8;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u8;
+
+    (year_of_era + era * 400 + (month <= 2) as i64, month, day)
+}
+
+/// Returns the time read from `clock` as a `Value::DateTime`, keeping `precision` fractional digits of the seconds.
+fn timestamp(clock: &dyn Clock, precision: u32) -> Value {
+    let now = clock.now();
+    let seconds = now.as_secs() as i64;
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+    let unit = 10u32.pow(6 - precision.min(6));
+
+    Value::DateTime {
+        year: year as u16,
+        month,
+        day,
+        hour: (time / 3_600) as u8,
+        minute: (time / 60 % 60) as u8,
+        second: (time % 60) as u8,
+        micro_second: now.subsec_micros() / unit * unit,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(String),
+    Text(String),
+    Word(String),
+    Symbol(char),
+}
+
+fn tokenize(expression: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            ch if ch.is_whitespace() => {
+                chars.next();
+            },
+            '0'..='9' | '.' => {
+                let mut number = String::new();
+                while let Some(&ch) = chars.peek().filter(|ch| ch.is_ascii_digit() || **ch == '.') {
+                    number.push(ch);
+                    chars.next();
+                }
+                tokens.push(Token::Number(number));
+            },
+            '\'' | '"' => {
+                let quote = ch;
+                let mut text = String::new();
+                chars.next();
+                loop {
+                    match chars.next()? {
+                        ch if ch == quote && chars.peek() == Some(&quote) => {
+                            text.push(quote);
+                            chars.next();
+                        },
+                        ch if ch == quote => break,
+                        '\\' => text.push(chars.next()?),
+                        ch => text.push(ch),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            },
+            ch if ch.is_ascii_alphabetic() || ch == '_' => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek().filter(|ch| ch.is_ascii_alphanumeric() || **ch == '_') {
+                    word.push(ch.to_ascii_uppercase());
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            },
+            '+' | '-' | '*' | '/' | '(' | ')' => {
+                tokens.push(Token::Symbol(ch));
+                chars.next();
+            },
+            _ => return None,
+        }
+    }
+
+    Some(tokens)
+}
+
+/// Evaluates the constant expressions a column default is usually made of: literals, `NULL`, `TRUE`/`FALSE`,
+/// arithmetic over numbers and the current date and time functions.
+struct Evaluator<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    clock: &'a dyn Clock,
+}
+
+impl<'a> Evaluator<'a> {
+    fn evaluate(expression: &str, clock: &'a dyn Clock) -> Option<Value> {
+        let mut evaluator = Evaluator { tokens: tokenize(expression)?, position: 0, clock };
+
+        let value = evaluator.sum()?;
+
+        match evaluator.position == evaluator.tokens.len() {
+            true => Some(value),
+            false => None,
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        match self.peek() == Some(&Token::Symbol(symbol)) {
+            true => {
+                self.position += 1;
+                true
+            },
+            false => false,
+        }
+    }
+
+    fn sum(&mut self) -> Option<Value> {
+        let mut value = self.product()?;
+
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Symbol(operator @ ('+' | '-'))) => *operator,
+                _ => return Some(value),
+            };
+            self.position += 1;
+
+            value = arithmetic(operator, value, self.product()?)?;
+        }
+    }
+
+    fn product(&mut self) -> Option<Value> {
+        let mut value = self.unary()?;
+
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Symbol(operator @ ('*' | '/'))) => *operator,
+                _ => return Some(value),
+            };
+            self.position += 1;
+
+            value = arithmetic(operator, value, self.unary()?)?;
+        }
+    }
+
+    fn unary(&mut self) -> Option<Value> {
+        match self.eat('-') {
+            true => arithmetic('-', Value::Int(0), self.unary()?),
+            false => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Option<Value> {
+        match self.next()? {
+            Token::Number(number) => match number.parse::<i64>() {
+                Ok(number) => Some(Value::Int(number)),
+                Err(_) => number.parse::<f64>().ok().map(Value::Float),
+            },
+            Token::Text(text) => Some(Value::Text(text)),
+            Token::Symbol('(') => {
+                let value = self.sum()?;
+                self.eat(')').then_some(value)
+            },
+            Token::Word(word) => {
+                //optional call parentheses, with the fractional seconds precision
+                let precision = match self.eat('(') {
+                    true => match self.next()? {
+                        Token::Symbol(')') => 0,
+                        Token::Number(precision) => {
+                            let precision = precision.parse::<u32>().ok()?;
+                            self.eat(')').then_some(precision)?
+                        },
+                        _ => return None,
+                    },
+                    false => 0,
+                };
+
+                match word.as_str() {
+                    "NULL" => Some(Value::Null),
+                    "TRUE" => Some(Value::Bool(true)),
+                    "FALSE" => Some(Value::Bool(false)),
+                    "CURRENT_TIMESTAMP" | "NOW" | "LOCALTIME" | "LOCALTIMESTAMP" | "UTC_TIMESTAMP" => {
+                        Some(timestamp(self.clock, precision))
+                    },
+                    "CURRENT_DATE" | "CURDATE" | "UTC_DATE" => match timestamp(self.clock, 0) {
+                        Value::DateTime{year, month, day, ..} => Some(Value::Date{year, month, day}),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Applies an arithmetic operator to two numbers. `NULL` operands give `NULL`, and division always gives a float.
+fn arithmetic(operator: char, left: Value, right: Value) -> Option<Value> {
+    let as_float = |value: &Value| match value {
+        Value::Int(val) => Some(*val as f64),
+        Value::UInt(val) => Some(*val as f64),
+        Value::Float(val) => Some(*val),
+        _ => None,
+    };
+
+    match (&left, &right) {
+        (Value::Null, _) | (_, Value::Null) => Some(Value::Null),
+        (Value::Int(a), Value::Int(b)) if operator != '/' => match operator {
+            '+' => a.checked_add(*b),
+            '-' => a.checked_sub(*b),
+            _ => a.checked_mul(*b),
+        }.map(Value::Int),
+        _ => {
+            let (a, b) = (as_float(&left)?, as_float(&right)?);
+
+            match operator {
+                '+' => Some(Value::Float(a + b)),
+                '-' => Some(Value::Float(a - b)),
+                '*' => Some(Value::Float(a * b)),
+                _ if b == 0.0 => Some(Value::Null),
+                _ => Some(Value::Float(a / b)),
+            }
+        },
+    }
+}
+
+/// Converts an evaluated default to the value the column would store.
+fn coerce(value: Value, data_type: &AttributeType) -> Value {
+    match (value, data_type) {
+        (value, AttributeType::Unsigned{val, ..}) => coerce(value, val),
+        (
+            Value::Text(val),
+            AttributeType::TinyInt(_) |
+            AttributeType::SmallInt(_) |
+            AttributeType::MediumInt(_) |
+            AttributeType::Int(_) |
+            AttributeType::BigInt(_) |
+            AttributeType::Year
+        ) => match val.trim().parse::<i64>() {
+            Ok(val) => Value::Int(val),
+            Err(_) => Value::Text(val),
+        },
+        (Value::Text(val), AttributeType::Float(_) | AttributeType::Double(_) | AttributeType::Decimal(..)) => match val.trim().parse::<f64>() {
+            Ok(val) => Value::Float(val),
+            Err(_) => Value::Text(val),
+        },
+        (Value::Text(val), AttributeType::Bool | AttributeType::Boolean) => match val.trim().parse::<i64>() {
+            Ok(val) => Value::Bool(val != 0),
+            Err(_) => Value::Text(val),
+        },
+        (Value::Int(val), AttributeType::Bool | AttributeType::Boolean) => Value::Bool(val != 0),
+        (Value::DateTime{year, month, day, ..}, AttributeType::Date) => Value::Date{year, month, day},
+        (value, _) => value,
+    }
+}
+
+impl Attribute {
+    /// Returns the attribute's default, if it has one.
+    pub fn default_value(&self) -> Option<&DefaultValue> {
+        self.constraint
+            .iter()
+            .find_map(|c| match c {
+                Constraint::Default(default) => Some(default),
+                _ => None,
+            })
+    }
+
+    /// Returns the attribute's `DEFAULT` expression, if it has one (see `DefaultValue::to_sql`).
+    pub fn default_expression(&self) -> Option<String> {
+        self.default_value().map(|default| default.to_sql(&self.data_type))
+    }
+
+    /// Returns the value the attribute gets when it is left out of an insert.
+    ///
+    /// Auto incremented attributes give `Value::Default`, as their value is only known once the row is inserted.
+    /// Nullable attributes without a default give `Value::Null`. Returns `None` if the attribute is `NOT NULL` without
+    /// a default, or if its default is an expression that cannot be evaluated on the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock used for `CURRENT_TIMESTAMP` and the other current time functions.
+    pub fn resolve_default(&self, clock: &dyn Clock) -> Option<Value> {
+        if self.constraint.contains(&Constraint::AutoIncrement) {
+            return Some(Value::Default);
+        }
+
+        match self.default_expression() {
+            Some(expression) => Evaluator::evaluate(&expression, clock).map(|value| coerce(value, &self.data_type)),
+            None if self.constraint.contains(&Constraint::NotNull) => None,
+            None => Some(Value::Null),
+        }
+    }
+}
+
+impl Table {
+    /// Returns `partial` with every missing column filled in with its default (see `Attribute::resolve_default`).
+    ///
+    /// Columns whose default cannot be resolved are left out.
+    pub fn complete_row(&self, partial: &HashMap<String, Value>, clock: &dyn Clock) -> HashMap<String, Value> {
+        let mut row = partial.clone();
+
+        for attr in &self.attributes {
+            if row.contains_key(&attr.name) {
+                continue;
+            }
+
+            if let Some(value) = attr.resolve_default(clock) {
+                row.insert(attr.name.clone(), value);
+            }
+        }
+
+        row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::{HashMap, HashSet}, time::Duration};
+
+    use crate::sql::value::Value;
+
+    use super::{
+        super::table::{Table, Attribute, AttributeType, Constraint},
+        FixedClock,
+        DefaultValue,
+        read_default,
+        civil_from_days,
+    };
+
+    //2023-03-14 15:09:26.535897 UTC
+    const CLOCK: FixedClock = FixedClock(Duration::from_micros(1_678_806_566_535_897));
+
+    fn attribute(name: &str, data_type: AttributeType, constraint: &[Constraint]) -> Attribute {
+        Attribute {
+            name: String::from(name),
+            data_type,
+            raw_type: String::new(),
+            constraint: constraint.iter().cloned().collect::<HashSet<Constraint>>(),
+        }
+    }
+
+    fn default(expression: &str) -> Constraint {
+        Constraint::Default(DefaultValue::Expression(String::from(expression)))
+    }
+
+    #[test]
+    fn civil_from_days_test() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_430), (2023, 3, 14));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn read_default_test() {
+        let expression = |expression: &str| DefaultValue::Expression(String::from(expression));
+
+        assert_eq!(read_default("active", ""), DefaultValue::Literal(String::from("active")));
+        assert_eq!(read_default("0", ""), DefaultValue::Literal(String::from("0")));
+        assert_eq!(read_default("CURRENT_TIMESTAMP", "DEFAULT_GENERATED"), expression("CURRENT_TIMESTAMP"));
+        assert_eq!(read_default("CURRENT_TIMESTAMP", "on update CURRENT_TIMESTAMP"), expression("CURRENT_TIMESTAMP"));
+        assert_eq!(read_default("(1 + 2)", "DEFAULT_GENERATED"), expression("(1 + 2)"));
+        assert_eq!(read_default("rand()", "DEFAULT_GENERATED"), expression("(rand())"));
+    }
+
+    #[test]
+    fn default_value_test() {
+        let column = |name: &str, data_type: AttributeType, default: DefaultValue| {
+            attribute(name, data_type, &[Constraint::Default(default)]).column_definition()
+        };
+
+        assert_eq!(
+            column("status", AttributeType::VarChar(16), DefaultValue::Literal(String::from("it's new"))),
+            "status varchar(16) DEFAULT 'it''s new'"
+        );
+        assert_eq!(column("balance", AttributeType::Int(11), DefaultValue::Literal(String::from("0"))), "balance int(11) DEFAULT 0");
+        assert_eq!(column("code", AttributeType::Int(11), DefaultValue::Literal(String::from("0); --"))), "code int(11) DEFAULT '0); --'");
+        assert_eq!(column("note", AttributeType::Text, DefaultValue::Null), "note text DEFAULT NULL");
+        assert_eq!(
+            column("created", AttributeType::DateTime(None), DefaultValue::Expression(String::from("CURRENT_TIMESTAMP"))),
+            "created datetime DEFAULT CURRENT_TIMESTAMP"
+        );
+
+        let balance = attribute("balance", AttributeType::Int(11), &[Constraint::Default(DefaultValue::Literal(String::from("7")))]);
+        assert_eq!(balance.resolve_default(&CLOCK), Some(Value::Int(7)));
+        assert_eq!(attribute("note", AttributeType::Text, &[Constraint::Default(DefaultValue::Null)]).resolve_default(&CLOCK), Some(Value::Null));
+    }
+
+    #[test]
+    fn resolve_default_test_1() {
+        let cases = [
+            (attribute("created", AttributeType::DateTime(None), &[default("CURRENT_TIMESTAMP")]), Some(
+                Value::DateTime{year: 2023, month: 3, day: 14, hour: 15, minute: 9, second: 26, micro_second: 0}
+            )),
+            (attribute("updated", AttributeType::TimeStamp(Some(3)), &[default("CURRENT_TIMESTAMP(3)")]), Some(
+                Value::DateTime{year: 2023, month: 3, day: 14, hour: 15, minute: 9, second: 26, micro_second: 535_000}
+            )),
+            (attribute("day", AttributeType::Date, &[default("(CURRENT_DATE)")]), Some(
+                Value::Date{year: 2023, month: 3, day: 14}
+            )),
+            (attribute("born", AttributeType::Date, &[default("NOW()")]), Some(
+                Value::Date{year: 2023, month: 3, day: 14}
+            )),
+        ];
+
+        for (attr, expected) in cases {
+            assert_eq!(attr.resolve_default(&CLOCK), expected, "{}", attr.name);
+        }
+    }
+
+    #[test]
+    fn resolve_default_test_2() {
+        let cases = [
+            (attribute("status", AttributeType::VarChar(16), &[default("'it''s active'")]), Some(Value::Text(String::from("it's active")))),
+            (attribute("count", AttributeType::Int(11), &[default("'0'")]), Some(Value::Int(0))),
+            (attribute("price", AttributeType::Decimal(8, 2), &[default("'9.99'")]), Some(Value::Float(9.99))),
+            (attribute("flag", AttributeType::Bool, &[default("'1'")]), Some(Value::Bool(true))),
+            (attribute("score", AttributeType::Int(11), &[default("(2 * (3 + 4) - -1)")]), Some(Value::Int(15))),
+            (attribute("ratio", AttributeType::Float(11), &[default("(1 / 4)")]), Some(Value::Float(0.25))),
+            (attribute("note", AttributeType::Text, &[default("NULL")]), Some(Value::Null)),
+            (attribute("nothing", AttributeType::Int(11), &[default("(NULL + 1)")]), Some(Value::Null)),
+            (attribute("seed", AttributeType::Int(11), &[default("(rand())")]), None),
+            (attribute("broken", AttributeType::Int(11), &[default("(1 +")]), None),
+            (attribute("id", AttributeType::Int(11), &[Constraint::AutoIncrement, Constraint::NotNull]), Some(Value::Default)),
+            (attribute("optional", AttributeType::Int(11), &[]), Some(Value::Null)),
+            (attribute("required", AttributeType::Int(11), &[Constraint::NotNull]), None),
+        ];
+
+        for (attr, expected) in cases {
+            assert_eq!(attr.resolve_default(&CLOCK), expected, "{}", attr.name);
+        }
+    }
+
+    #[test]
+    fn complete_row_test() {
+        let table = Table {
+            name: String::from("account"),
+            attributes: vec![
+                attribute("id", AttributeType::Int(11), &[Constraint::AutoIncrement, Constraint::NotNull]),
+                attribute("name", AttributeType::VarChar(64), &[Constraint::NotNull]),
+                attribute("status", AttributeType::VarChar(16), &[default("'active'"), Constraint::NotNull]),
+                attribute("created", AttributeType::DateTime(None), &[default("CURRENT_TIMESTAMP")]),
+                attribute("email", AttributeType::VarChar(64), &[Constraint::NotNull]),
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+
+        let partial = HashMap::from([
+            (String::from("name"), Value::Text(String::from("John"))),
+            (String::from("status"), Value::Text(String::from("banned"))),
+        ]);
+
+        assert_eq!(
+            table.complete_row(&partial, &CLOCK),
+            HashMap::from([
+                (String::from("id"), Value::Default),
+                (String::from("name"), Value::Text(String::from("John"))),
+                (String::from("status"), Value::Text(String::from("banned"))),
+                (
+                    String::from("created"),
+                    Value::DateTime{year: 2023, month: 3, day: 14, hour: 15, minute: 9, second: 26, micro_second: 0}
+                ),
+            ])
+        );
+    }
+}
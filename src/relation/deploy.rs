@@ -0,0 +1,886 @@
+use std::fmt;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{data_base::DataBase, error::Error, fmt::{Grid, Align, count}, sql::{SQL, QDL, DDL, quote_ident, value::Value}};
+
+use super::{RelationMethods, table::{Table, TableLoadError, Attribute, Constraint, ReferentialAction}};
+
+lazy_static! {
+    static ref INTEGER_WIDTH: Regex = Regex::new(r"^(tinyint|smallint|mediumint|int|integer|bigint)\(\d+\)").unwrap();
+}
+
+/// Options of `deploy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct DeployOptions {
+    /// Whether tables that exist with a different structure are altered to match. When unset they are only reported.
+    pub alter: bool,
+}
+
+/// Tables and columns renamed between an existing schema and the desired one, so they are renamed rather than
+/// dropped and added again, which would lose their data.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Renames {
+    /// The old and new names of every renamed table.
+    pub tables: Vec<(String, String)>,
+    /// The table, by its new name, and the old and new names of every renamed column.
+    pub columns: Vec<(String, String, String)>,
+}
+
+impl Renames {
+    /// Returns the renames undoing these ones, for the down migration.
+    pub fn inverse(&self) -> Renames {
+        Renames {
+            tables: self.tables.iter().map(|(old, new)| (new.clone(), old.clone())).collect(),
+            columns: self.columns
+                .iter()
+                .map(|(table, old, new)| (self.old_table_name(table).to_string(), new.clone(), old.clone()))
+                .collect(),
+        }
+    }
+
+    /// Returns the name the table `name` had before it was renamed, or `name` if it was not.
+    pub fn old_table_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.tables.iter().find(|(_, new)| new == name).map_or(name, |(old, _)| old.as_str())
+    }
+
+    /// Returns true if these renames turn the table `existing` into `desired`.
+    fn renames_table(&self, existing: &str, desired: &str) -> bool {
+        existing != desired && self.tables.iter().any(|(old, new)| old == existing && new == desired)
+    }
+}
+
+/// The statements migrating a table to its desired structure, and those migrating it back.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Migration {
+    pub up: Vec<DDL>,
+    pub down: Vec<DDL>,
+}
+
+/// What `deploy` did with a table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeployAction {
+    /// The table did not exist and was created.
+    Created,
+    /// The table already existed with the same structure.
+    Skipped,
+    /// The table existed with a different structure and was altered with the given statements.
+    Altered(Vec<DDL>),
+    /// The table exists with a different structure, which the given statements would fix. Nothing was run, as
+    /// `DeployOptions::alter` is unset.
+    Differs(Vec<DDL>),
+}
+
+/// What `deploy` did with every table, in the order they were deployed.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct DeployReport {
+    pub tables: Vec<(String, DeployAction)>,
+}
+
+impl DeployReport {
+    /// Returns the action taken for `table`.
+    pub fn action(&self, table: &str) -> Option<&DeployAction> {
+        self.tables.iter().find(|(name, _)| name == table).map(|(_, action)| action)
+    }
+}
+
+impl fmt::Display for DeployReport {
+    /// Writes one row per table with its action and the number of `ALTER` statements, fitting in the formatter's width
+    /// if one is given (see `Grid::render`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut grid = Grid::new(&[("table", Align::Left), ("action", Align::Left), ("statements", Align::Right)]);
+
+        for (table, action) in &self.tables {
+            let (action, statements) = match action {
+                DeployAction::Created => ("create", None),
+                DeployAction::Skipped => ("skip", None),
+                DeployAction::Altered(statements) => ("alter", Some(statements.len())),
+                DeployAction::Differs(statements) => ("differs, not run", Some(statements.len())),
+            };
+
+            grid.push(vec![
+                table.clone(),
+                action.to_string(),
+                statements.map(|statements| count(statements as u64)).unwrap_or_default(),
+            ]);
+        }
+
+        f.write_str(&grid.render(f.width()))
+    }
+}
+
+/// Represents a failure of `deploy`, along with what was done before it.
+#[derive(Debug)]
+pub struct DeployError {
+    /// The table being deployed when the error occurred.
+    pub table: String,
+    pub error: Box<Error>,
+    /// The tables deployed before the error.
+    pub report: DeployReport,
+}
+
+impl fmt::Display for DeployError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to deploy {}: {} - {} tables deployed before", self.table, self.error, self.report.tables.len())
+    }
+}
+
+/// The operations `deploy` needs from a database.
+pub trait DeployExecutor {
+    /// Returns the table named `name` as it exists in the database, or `None` if there is no such table.
+    fn load(&mut self, name: &str) -> Result<Option<Table>, Error>;
+    fn execute(&mut self, statement: &DDL) -> Result<(), Error>;
+}
+
+impl DeployExecutor for &DataBase {
+    fn load(&mut self, name: &str) -> Result<Option<Table>, Error> {
+        let exists = DataBase::execute(
+            self,
+            &SQL::Select(QDL(format!(
+                "SELECT 1 FROM information_schema.TABLES WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = {}",
+                Value::Text(name.to_string())
+            ))),
+            |row| row.is_ok()
+        )?;
+
+        match exists.first() {
+            Some(true) => match Table::from_db_in(*self, name) {
+                Ok(table) => Ok(Some(table)),
+                Err(TableLoadError::Execution(err)) => Err(err),
+                //dropped since it was found
+                Err(_) => Ok(None),
+            },
+            _ => Ok(None),
+        }
+    }
+    fn execute(&mut self, statement: &DDL) -> Result<(), Error> {
+        DataBase::execute(self, &statement.into(), |_| ()).map(|_| ())
+    }
+}
+
+/// The parts of a column that matter when comparing it against an existing one, in a canonical form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ColumnShape {
+    name: String,
+    data_type: String,
+    not_null: bool,
+    unique: bool,
+    auto_increment: bool,
+    default: Option<String>,
+    foreign_key: Option<(String, String, Option<ReferentialAction>, Option<ReferentialAction>)>,
+}
+
+/// Returns the type of `attr` in a canonical form: lowercase, without integer display widths, which MySQL 8.0 no
+/// longer reports, and with aliases resolved.
+fn normalize_type(attr: &Attribute) -> String {
+    let data_type = attr.type_definition().to_ascii_lowercase().replace(' ', "");
+    let data_type = INTEGER_WIDTH.replace(&data_type, "$1").replace("integer", "int");
+
+    match data_type.as_str() {
+        "bool" | "boolean" => String::from("tinyint"),
+        _ => data_type.replace("numeric", "decimal"),
+    }
+}
+
+/// Returns a default expression in a canonical form: the text of a quoted literal, or the expression uppercased
+/// without enclosing parentheses. `DEFAULT NULL` is the same as no default.
+fn normalize_default(default: &str) -> Option<String> {
+    let mut default = default.trim();
+
+    while default.starts_with('(') && default.ends_with(')') {
+        default = default[1..default.len() - 1].trim();
+    }
+
+    if default.len() >= 2 && default.starts_with('\'') && default.ends_with('\'') {
+        return Some(default[1..default.len() - 1].replace("''", "'"));
+    }
+
+    match default.to_ascii_uppercase().replace(' ', "").as_str() {
+        "NULL" => None,
+        "CURRENT_TIMESTAMP()" | "NOW()" | "LOCALTIMESTAMP" | "LOCALTIMESTAMP()" => Some(String::from("CURRENT_TIMESTAMP")),
+        default => Some(default.to_string()),
+    }
+}
+
+impl ColumnShape {
+    fn of(attr: &Attribute, primary_key: bool) -> ColumnShape {
+        let mut shape = ColumnShape {
+            name: attr.name.clone(),
+            data_type: normalize_type(attr),
+            //a primary key is implicitly NOT NULL
+            not_null: primary_key,
+            unique: false,
+            auto_increment: false,
+            default: None,
+            foreign_key: None,
+        };
+
+        for constraint in &attr.constraint {
+            match constraint {
+                Constraint::NotNull => shape.not_null = true,
+                //a primary key is already unique
+                Constraint::Unique => shape.unique = !primary_key,
+                Constraint::AutoIncrement => shape.auto_increment = true,
+                Constraint::Default(default) => shape.default = normalize_default(&default.to_sql(&attr.data_type)),
+                Constraint::ForeignKey{table_name, attribute_name, on_delete, on_update} => {
+                    //RESTRICT and NO ACTION are what the server reports when no action was given
+                    let action = |action: &Option<ReferentialAction>| action.filter(|action| !action.is_default());
+
+                    shape.foreign_key = Some((table_name.clone(), attribute_name.clone(), action(on_delete), action(on_update)))
+                },
+                //checks are table level objects on the server, and are not compared
+                Constraint::Check(_) | Constraint::Encrypted(_) => {},
+            }
+        }
+
+        shape
+    }
+
+    /// Returns true if `self` and `other` only differ by their names.
+    fn same_but_name(&self, other: &ColumnShape) -> bool {
+        ColumnShape{name: other.name.clone(), ..self.clone()} == *other
+    }
+
+    /// Returns true if a `MODIFY COLUMN` is needed to go from `self` to `other`.
+    fn needs_modify(&self, other: &ColumnShape) -> bool {
+        (&self.data_type, self.not_null, self.auto_increment, &self.default) !=
+            (&other.data_type, other.not_null, other.auto_increment, &other.default)
+    }
+}
+
+impl Table {
+    fn shape(&self) -> Vec<ColumnShape> {
+        self.attributes
+            .iter()
+            .enumerate()
+            .map(|(i, attr)| ColumnShape::of(attr, self.primary_key.contains(&i)))
+            .collect()
+    }
+
+    /// Returns true if the table has the same structure as `other`.
+    ///
+    /// Columns are compared by name, in order, after normalizing what the server reports differently from what was
+    /// declared: integer display widths, type aliases, the quoting and parentheses of defaults, `DEFAULT NULL` and the
+    /// implicit `NOT NULL` and uniqueness of the primary key. Encrypted columns compare as their stored type.
+    pub fn structurally_eq(&self, other: &Table) -> bool {
+        self.name == other.name &&
+            self.primary_key_columns() == other.primary_key_columns() &&
+            self.shape() == other.shape()
+    }
+
+    /// Returns the `ALTER TABLE` statements turning the table into `desired`.
+    ///
+    /// Columns are added, dropped and modified, unique indexes and foreign keys are added, and the primary key is
+    /// replaced if it changed. Unique indexes are dropped by the column name, which is the name MySQL gives them by
+    /// default. Foreign keys are not dropped, as their constraint names are not known.
+    pub fn alter_to(&self, desired: &Table) -> Vec<DDL> {
+        self.alter_to_with(desired, &Renames::default())
+    }
+
+    /// Returns the `ALTER TABLE` statements turning the table into `other`, another definition of the same table, or
+    /// nothing if they have the same structure (see `Table::structurally_eq`).
+    ///
+    /// This is `Table::alter_to`. Columns are matched by name, so a renamed column is dropped and added again, losing
+    /// its data; `Table::alter_to_with` renames it instead.
+    pub fn diff(&self, other: &Table) -> Vec<DDL> {
+        self.alter_to(other)
+    }
+
+    /// Returns the statements turning the table into `desired`, renaming what `renames` lists (see `Table::alter_to`).
+    ///
+    /// A renamed table is renamed with `RENAME TABLE` before it is altered, and renamed columns are changed with
+    /// `CHANGE COLUMN`, which also applies their new definition. Column renames whose old column is not in the table,
+    /// or whose new column is not in `desired`, are ignored.
+    pub fn alter_to_with(&self, desired: &Table, renames: &Renames) -> Vec<DDL> {
+        let existing = self.shape();
+        let mut statements: Vec<DDL> = Vec::new();
+
+        let name = match renames.renames_table(&self.name, &desired.name) {
+            true => {
+                statements.push(DDL(format!("RENAME TABLE {} TO {}", quote_ident(&self.name), quote_ident(&desired.name))));
+                &desired.name
+            },
+            false => &self.name,
+        };
+        let alter = |clause: String| DDL(format!("ALTER TABLE {} {}", quote_ident(name), clause));
+
+        //the name every existing column has in desired
+        let renamed = |column: &str| -> String {
+            renames.columns
+                .iter()
+                .find(|(table, old, new)| {
+                    *table == desired.name &&
+                        old == column &&
+                        desired.attributes.iter().any(|attr| attr.name == *new)
+                })
+                .map_or(column.to_string(), |(_, _, new)| new.clone())
+        };
+
+        for column in &existing {
+            if !desired.attributes.iter().any(|attr| attr.name == renamed(&column.name)) {
+                statements.push(alter(format!("DROP COLUMN {}", quote_ident(&column.name))));
+            }
+        }
+
+        for (attr, shape) in desired.attributes.iter().zip(desired.shape()) {
+            let current = match existing.iter().find(|column| renamed(&column.name) == shape.name) {
+                Some(current) => current,
+                None => {
+                    statements.push(alter(format!("ADD COLUMN {}", attr.definition(false))));
+                    if shape.unique {
+                        statements.push(alter(format!("ADD UNIQUE ({})", quote_ident(&attr.name))));
+                    }
+                    if let Some(foreign_key) = attr.fk_clause() {
+                        statements.push(alter(format!("ADD {}", foreign_key)));
+                    }
+                    continue;
+                },
+            };
+
+            if current.name != shape.name {
+                statements.push(alter(format!("CHANGE COLUMN {} {}", quote_ident(&current.name), attr.definition(false))));
+            }
+            else if current.needs_modify(&shape) {
+                statements.push(alter(format!("MODIFY COLUMN {}", attr.definition(false))));
+            }
+
+            match (current.unique, shape.unique) {
+                (false, true) => statements.push(alter(format!("ADD UNIQUE ({})", quote_ident(&attr.name)))),
+                //a renamed column keeps the index named after its old name
+                (true, false) => statements.push(alter(format!("DROP INDEX {}", quote_ident(&current.name)))),
+                _ => {},
+            }
+
+            if current.foreign_key != shape.foreign_key {
+                if let Some(foreign_key) = attr.fk_clause() {
+                    statements.push(alter(format!("ADD {}", foreign_key)));
+                }
+            }
+        }
+
+        let current: Vec<String> = self.primary_key_columns().into_iter().map(renamed).map(|column| quote_ident(&column)).collect();
+        let key = desired.primary_key_columns().into_iter().map(quote_ident).collect::<Vec<String>>().join(", ");
+
+        match (current.is_empty(), desired.primary_key.is_empty()) {
+            (false, false) if current.join(", ") != key => {
+                statements.push(alter(format!("DROP PRIMARY KEY, ADD PRIMARY KEY({})", key)));
+            },
+            (true, false) => statements.push(alter(format!("ADD PRIMARY KEY({})", key))),
+            (false, true) => statements.push(alter(String::from("DROP PRIMARY KEY"))),
+            _ => {},
+        }
+
+        statements
+    }
+
+    /// Returns the statements migrating the table to `desired` and back, renaming what `renames` lists (see
+    /// `Table::alter_to_with`). The down migration undoes the renames.
+    ///
+    /// Dropped columns are added back empty by the down migration, as their data is gone.
+    pub fn migration_to(&self, desired: &Table, renames: &Renames) -> Migration {
+        Migration {
+            up: self.alter_to_with(desired, renames),
+            down: desired.alter_to_with(self, &renames.inverse()),
+        }
+    }
+}
+
+/// Returns the renames likely to have happened between `existing` and `desired`, for the caller to confirm before
+/// passing them to `Table::alter_to_with` or `deploy_with`. Nothing is renamed on a guess.
+///
+/// A table missing from `desired` is taken as renamed to a table missing from `existing` if it is the only such pair
+/// whose columns, in order, have the same types and constraints, whatever their names. Within a table, a dropped column is taken as renamed to an added one if they are the only
+/// dropped and added columns with the same type and constraints.
+pub fn suggest_renames(existing: &[Table], desired: &[Table]) -> Renames {
+    let mut renames = Renames::default();
+
+    let dropped: Vec<&Table> = existing.iter().filter(|table| !desired.iter().any(|t| t.name == table.name)).collect();
+    let added: Vec<&Table> = desired.iter().filter(|table| !existing.iter().any(|t| t.name == table.name)).collect();
+
+    let same_structure = |a: &Table, b: &Table| {
+        let (a_shape, b_shape) = (a.shape(), b.shape());
+
+        a.primary_key == b.primary_key &&
+            a_shape.len() == b_shape.len() &&
+            a_shape.iter().zip(&b_shape).all(|(a, b)| a.same_but_name(b))
+    };
+
+    for old in &dropped {
+        let candidates: Vec<&&Table> = added.iter().filter(|new| same_structure(old, new)).collect();
+
+        if let [new] = candidates.as_slice() {
+            if dropped.iter().filter(|other| same_structure(other, new)).count() == 1 {
+                renames.tables.push((old.name.clone(), new.name.clone()));
+            }
+        }
+    }
+
+    for table in desired {
+        let old_name = renames.old_table_name(&table.name);
+
+        if let Some(old) = existing.iter().find(|t| t.name == old_name) {
+            renames.columns.extend(
+                suggest_column_renames(old, table).into_iter().map(|(old, new)| (table.name.clone(), old, new))
+            );
+        }
+    }
+
+    renames
+}
+
+/// Returns the likely renamed columns of `existing` in `desired` (see `suggest_renames`).
+fn suggest_column_renames(existing: &Table, desired: &Table) -> Vec<(String, String)> {
+    let existing_shape = existing.shape();
+    let desired_shape = desired.shape();
+
+    let dropped: Vec<&ColumnShape> = existing_shape.iter().filter(|c| !desired_shape.iter().any(|d| d.name == c.name)).collect();
+    let added: Vec<&ColumnShape> = desired_shape.iter().filter(|c| !existing_shape.iter().any(|e| e.name == c.name)).collect();
+
+    dropped.iter()
+        .filter_map(|old| {
+            let candidates: Vec<&&ColumnShape> = added.iter().filter(|new| old.same_but_name(new)).collect();
+
+            match candidates.as_slice() {
+                [new] if dropped.iter().filter(|other| other.same_but_name(new)).count() == 1 => {
+                    Some((old.name.clone(), new.name.clone()))
+                },
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Deploys `tables` in order, so it can be run again against a partly deployed schema.
+///
+/// Every table is loaded from the database first. Missing tables are created and tables with the same structure
+/// (see `Table::structurally_eq`) are skipped. Tables with a different structure are altered (see `Table::alter_to`)
+/// if `DeployOptions::alter` is set, and otherwise reported as `DeployAction::Differs`.
+///
+/// # Errors
+///
+/// Returns a `DeployError` holding the actions taken so far if a table could not be loaded or a statement failed.
+pub fn deploy<E: DeployExecutor>(executor: &mut E, tables: &[Table], options: DeployOptions) -> Result<DeployReport, DeployError> {
+    deploy_with(executor, tables, options, &Renames::default())
+}
+
+/// Deploys `tables` like `deploy`, renaming what `renames` lists rather than dropping and adding it again.
+///
+/// A table missing from the database is looked up under its old name before it is created, and altered with
+/// `Table::alter_to_with` if found.
+///
+/// # Errors
+///
+/// Returns a `DeployError` holding the actions taken so far if a table could not be loaded or a statement failed.
+pub fn deploy_with<E: DeployExecutor>(executor: &mut E, tables: &[Table], options: DeployOptions, renames: &Renames) -> Result<DeployReport, DeployError> {
+    let mut report = DeployReport::default();
+
+    for table in tables {
+        let action = deploy_table(executor, table, options, renames);
+
+        match action {
+            Ok(action) => report.tables.push((table.name.clone(), action)),
+            Err(error) => return Err(DeployError { table: table.name.clone(), error: Box::new(error), report }),
+        }
+    }
+
+    Ok(report)
+}
+
+fn deploy_table<E: DeployExecutor>(executor: &mut E, table: &Table, options: DeployOptions, renames: &Renames) -> Result<DeployAction, Error> {
+    let old_name = renames.old_table_name(&table.name);
+
+    let existing = match executor.load(&table.name)? {
+        Some(existing) => Some(existing),
+        None if old_name != table.name => executor.load(old_name)?,
+        None => None,
+    };
+
+    let existing = match existing {
+        Some(existing) => existing,
+        None => {
+            executor.execute(&table.create())?;
+            return Ok(DeployAction::Created);
+        },
+    };
+
+    if existing.structurally_eq(table) {
+        return Ok(DeployAction::Skipped);
+    }
+
+    let statements = existing.alter_to_with(table, renames);
+
+    if !options.alter {
+        return Ok(DeployAction::Differs(statements));
+    }
+
+    for statement in &statements {
+        executor.execute(statement)?;
+    }
+
+    Ok(DeployAction::Altered(statements))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use crate::{error::Error, sql::DDL};
+
+    use super::{
+        super::table::{Table, Attribute, AttributeType, Constraint, ReferentialAction},
+        super::defaults::DefaultValue,
+        DeployExecutor,
+        DeployOptions,
+        DeployAction,
+        Migration,
+        Renames,
+        deploy,
+        deploy_with,
+        suggest_renames,
+    };
+
+    #[derive(Default)]
+    struct MockExecutor {
+        tables: HashMap<String, Table>,
+        log: Vec<String>,
+        fail_on: Option<String>,
+    }
+
+    impl DeployExecutor for MockExecutor {
+        fn load(&mut self, name: &str) -> Result<Option<Table>, Error> {
+            Ok(self.tables.get(name).cloned())
+        }
+        fn execute(&mut self, statement: &DDL) -> Result<(), Error> {
+            if self.fail_on.as_deref().is_some_and(|fail_on| statement.contains(fail_on)) {
+                return Err(Error::query(statement, mysql::Error::FromValueError(mysql::Value::NULL)));
+            }
+            self.log.push(statement.to_string());
+            Ok(())
+        }
+    }
+
+    fn attribute(name: &str, data_type: AttributeType, raw_type: &str, constraint: &[Constraint]) -> Attribute {
+        Attribute {
+            name: String::from(name),
+            data_type,
+            raw_type: String::from(raw_type),
+            constraint: constraint.iter().cloned().collect::<HashSet<Constraint>>(),
+        }
+    }
+
+    fn desired_user() -> Table {
+        Table {
+            name: String::from("user"),
+            attributes: vec![
+                attribute("id", AttributeType::Int(11), "", &[Constraint::AutoIncrement]),
+                attribute("name", AttributeType::VarChar(64), "", &[Constraint::NotNull]),
+                attribute("active", AttributeType::Bool, "", &[Constraint::Default(DefaultValue::Literal(String::from("1")))]),
+                attribute("created", AttributeType::DateTime(None), "", &[Constraint::Default(DefaultValue::Expression(String::from("CURRENT_TIMESTAMP")))]),
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        }
+    }
+
+    //desired_user, as loaded from a MySQL 8.0 server
+    fn existing_user() -> Table {
+        Table {
+            name: String::from("user"),
+            attributes: vec![
+                attribute("id", AttributeType::Unknown(String::from("int")), "int", &[Constraint::AutoIncrement, Constraint::NotNull]),
+                attribute("name", AttributeType::VarChar(64), "varchar(64)", &[Constraint::NotNull]),
+                attribute("active", AttributeType::TinyInt(1), "tinyint(1)", &[Constraint::Default(DefaultValue::Literal(String::from("1")))]),
+                attribute("created", AttributeType::DateTime(None), "datetime", &[Constraint::Default(DefaultValue::Expression(String::from("CURRENT_TIMESTAMP")))]),
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn structurally_eq_test() {
+        assert!(existing_user().structurally_eq(&desired_user()));
+
+        let mut changed = existing_user();
+        changed.attributes[1].data_type = AttributeType::VarChar(128);
+        changed.attributes[1].raw_type = String::from("varchar(128)");
+        assert!(!changed.structurally_eq(&desired_user()));
+
+        let mut changed = existing_user();
+        changed.primary_key = vec![1];
+        assert!(!changed.structurally_eq(&desired_user()));
+    }
+
+    #[test]
+    fn alter_to_test() {
+        let mut desired = desired_user();
+        desired.attributes[1].data_type = AttributeType::VarChar(128);
+        desired.attributes.remove(2);
+        desired.attributes.push(attribute("email", AttributeType::VarChar(255), "", &[Constraint::Unique]));
+        desired.attributes.push(attribute(
+            "team_id",
+            AttributeType::Int(11),
+            "",
+            &[Constraint::ForeignKey{table_name: String::from("team"), attribute_name: String::from("id"), on_delete: None, on_update: None}]
+        ));
+
+        assert_eq!(
+            existing_user().alter_to(&desired),
+            vec![
+                DDL(String::from("ALTER TABLE user DROP COLUMN active")),
+                DDL(String::from("ALTER TABLE user MODIFY COLUMN name varchar(128) NOT NULL")),
+                DDL(String::from("ALTER TABLE user ADD COLUMN email varchar(255)")),
+                DDL(String::from("ALTER TABLE user ADD UNIQUE (email)")),
+                DDL(String::from("ALTER TABLE user ADD COLUMN team_id int(11)")),
+                DDL(String::from("ALTER TABLE user ADD FOREIGN KEY (team_id) REFERENCES team (id)")),
+            ]
+        );
+        assert!(existing_user().alter_to(&desired_user()).is_empty());
+
+        //only actions other than the default count as a change
+        let foreign_key = |on_delete: Option<ReferentialAction>| Constraint::ForeignKey{
+            table_name: String::from("team"),
+            attribute_name: String::from("id"),
+            on_delete,
+            on_update: None,
+        };
+        let mut existing = existing_user();
+        existing.attributes.push(attribute("team_id", AttributeType::Int(11), "", &[foreign_key(None)]));
+
+        let mut desired = existing.clone();
+        desired.attributes[4] = attribute("team_id", AttributeType::Int(11), "", &[foreign_key(Some(ReferentialAction::Restrict))]);
+        assert!(existing.alter_to(&desired).is_empty());
+
+        desired.attributes[4] = attribute("team_id", AttributeType::Int(11), "", &[foreign_key(Some(ReferentialAction::Cascade))]);
+        assert_eq!(
+            existing.alter_to(&desired),
+            vec![DDL(String::from("ALTER TABLE user ADD FOREIGN KEY (team_id) REFERENCES team (id) ON DELETE CASCADE"))]
+        );
+    }
+
+    #[test]
+    fn alter_to_quoted_test() {
+        let rename = |table: &mut Table, name: &str| {
+            table.name = String::from("order");
+            table.attributes[1].name = String::from(name);
+        };
+
+        let mut existing = existing_user();
+        rename(&mut existing, "key");
+        existing.attributes[2].constraint.insert(Constraint::Unique);
+        existing.attributes[2].name = String::from("in stock");
+
+        let mut desired = desired_user();
+        rename(&mut desired, "full name");
+        desired.attributes[1].data_type = AttributeType::VarChar(128);
+        desired.attributes[2].name = String::from("in stock");
+        desired.primary_key = vec![0, 1];
+
+        let renames = Renames{tables: Vec::new(), columns: vec![(String::from("order"), String::from("key"), String::from("full name"))]};
+
+        assert_eq!(
+            existing.alter_to_with(&desired, &renames),
+            vec![
+                DDL(String::from("ALTER TABLE `order` CHANGE COLUMN `key` `full name` varchar(128) NOT NULL")),
+                DDL(String::from("ALTER TABLE `order` DROP INDEX `in stock`")),
+                DDL(String::from("ALTER TABLE `order` DROP PRIMARY KEY, ADD PRIMARY KEY(id, `full name`)")),
+            ]
+        );
+
+        desired.name = String::from("group");
+        desired.attributes = existing.attributes.clone();
+        desired.primary_key = existing.primary_key.clone();
+        let renames = Renames{tables: vec![(String::from("order"), String::from("group"))], columns: Vec::new()};
+
+        assert_eq!(existing.alter_to_with(&desired, &renames), vec![DDL(String::from("RENAME TABLE `order` TO `group`"))]);
+    }
+
+    #[test]
+    fn diff_test() {
+        let alter = |clause: &str| DDL(format!("ALTER TABLE user {}", clause));
+
+        assert!(existing_user().diff(&desired_user()).is_empty());
+
+        //added, dropped and modified columns
+        let mut desired = desired_user();
+        desired.attributes[1].constraint.remove(&Constraint::NotNull);
+        desired.attributes[3].data_type = AttributeType::TimeStamp(None);
+        desired.attributes.remove(2);
+        desired.attributes.push(attribute("bio", AttributeType::Text, "", &[]));
+
+        assert_eq!(
+            existing_user().diff(&desired),
+            vec![
+                alter("DROP COLUMN active"),
+                alter("MODIFY COLUMN name varchar(64)"),
+                alter("MODIFY COLUMN created timestamp DEFAULT CURRENT_TIMESTAMP"),
+                alter("ADD COLUMN bio text"),
+            ]
+        );
+
+        //primary key changes
+        let mut desired = desired_user();
+        desired.primary_key = vec![0, 1];
+        assert_eq!(existing_user().diff(&desired), vec![alter("DROP PRIMARY KEY, ADD PRIMARY KEY(id, name)")]);
+
+        //a key column is NOT NULL, which has to be declared once it is not part of the key
+        let mut keyed = desired_user();
+        keyed.attributes[0].constraint = HashSet::from([Constraint::NotNull]);
+        let mut unkeyed = keyed.clone();
+        unkeyed.primary_key = Vec::new();
+
+        assert_eq!(keyed.diff(&unkeyed), vec![alter("DROP PRIMARY KEY")]);
+        assert_eq!(unkeyed.diff(&keyed), vec![alter("ADD PRIMARY KEY(id)")]);
+    }
+
+    #[test]
+    fn deploy_test() {
+        let team = Table {
+            name: String::from("team"),
+            attributes: vec![attribute("id", AttributeType::Int(11), "", &[])],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+        let mut desired = desired_user();
+        desired.attributes[1].data_type = AttributeType::VarChar(128);
+
+        let mut executor = MockExecutor {
+            tables: HashMap::from([(String::from("user"), existing_user())]),
+            ..MockExecutor::default()
+        };
+
+        //skip, create
+        let report = deploy(&mut executor, &[desired_user(), team.clone()], DeployOptions::default()).unwrap();
+        assert_eq!(report.action("user"), Some(&DeployAction::Skipped));
+        assert_eq!(report.action("team"), Some(&DeployAction::Created));
+        assert_eq!(executor.log, vec!["CREATE TABLE team (id int(11), PRIMARY KEY(id))"]);
+        assert_eq!(report.to_string(), "table  action  statements\nuser   skip\nteam   create");
+
+        //differs without the flag
+        executor.log.clear();
+        let report = deploy(&mut executor, &[desired.clone()], DeployOptions::default()).unwrap();
+        let statements = vec![DDL(String::from("ALTER TABLE user MODIFY COLUMN name varchar(128) NOT NULL"))];
+        assert_eq!(report.action("user"), Some(&DeployAction::Differs(statements.clone())));
+        assert!(executor.log.is_empty());
+        assert_eq!(report.to_string(), "table  action            statements\nuser   differs, not run           1");
+
+        //alter with it
+        let report = deploy(&mut executor, &[desired.clone()], DeployOptions { alter: true }).unwrap();
+        assert_eq!(report.action("user"), Some(&DeployAction::Altered(statements)));
+        assert_eq!(executor.log, vec!["ALTER TABLE user MODIFY COLUMN name varchar(128) NOT NULL"]);
+    }
+
+    #[test]
+    fn deploy_error_test() {
+        let team = Table {
+            name: String::from("team"),
+            attributes: vec![attribute("id", AttributeType::Int(11), "", &[])],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+        let mut executor = MockExecutor {
+            tables: HashMap::from([(String::from("user"), existing_user())]),
+            fail_on: Some(String::from("team")),
+            ..MockExecutor::default()
+        };
+
+        let err = deploy(&mut executor, &[desired_user(), team], DeployOptions::default()).unwrap_err();
+
+        assert_eq!(err.table, "team");
+        assert_eq!(err.report.tables, vec![(String::from("user"), DeployAction::Skipped)]);
+    }
+
+    //desired_user, renamed to account and with name renamed to full_name
+    fn desired_account() -> Table {
+        let mut account = desired_user();
+        account.name = String::from("account");
+        account.attributes[1].name = String::from("full_name");
+        account
+    }
+
+    fn account_renames() -> Renames {
+        Renames {
+            tables: vec![(String::from("user"), String::from("account"))],
+            columns: vec![(String::from("account"), String::from("name"), String::from("full_name"))],
+        }
+    }
+
+    #[test]
+    fn rename_test() {
+        let mut desired = desired_account();
+        desired.attributes[1].data_type = AttributeType::VarChar(128);
+
+        assert_eq!(
+            existing_user().migration_to(&desired, &account_renames()),
+            Migration {
+                up: vec![
+                    DDL(String::from("RENAME TABLE user TO account")),
+                    DDL(String::from("ALTER TABLE account CHANGE COLUMN name full_name varchar(128) NOT NULL")),
+                ],
+                down: vec![
+                    DDL(String::from("RENAME TABLE account TO user")),
+                    DDL(String::from("ALTER TABLE user CHANGE COLUMN full_name name varchar(64) NOT NULL")),
+                ],
+            }
+        );
+
+        //deployed against the table under its old name
+        let mut executor = MockExecutor {
+            tables: HashMap::from([(String::from("user"), existing_user())]),
+            ..MockExecutor::default()
+        };
+
+        let report = deploy_with(&mut executor, &[desired_account()], DeployOptions { alter: true }, &account_renames()).unwrap();
+
+        assert!(matches!(report.action("account"), Some(DeployAction::Altered(_))));
+        assert_eq!(
+            executor.log,
+            vec!["RENAME TABLE user TO account", "ALTER TABLE account CHANGE COLUMN name full_name varchar(64) NOT NULL"]
+        );
+    }
+
+    #[test]
+    fn rename_primary_key_test() {
+        let mut desired = desired_user();
+        desired.attributes[0].name = String::from("user_id");
+
+        let renames = Renames {
+            tables: Vec::new(),
+            columns: vec![(String::from("user"), String::from("id"), String::from("user_id"))],
+        };
+
+        //the key follows the column, so it is not dropped
+        let migration = existing_user().migration_to(&desired, &renames);
+
+        assert_eq!(migration.up, vec![DDL(String::from("ALTER TABLE user CHANGE COLUMN id user_id int(11) AUTO_INCREMENT"))]);
+        assert_eq!(migration.down.len(), 1);
+        assert!(migration.down[0].starts_with("ALTER TABLE user CHANGE COLUMN user_id id int "));
+    }
+
+    #[test]
+    fn suggest_renames_test() {
+        assert_eq!(suggest_renames(&[existing_user()], &[desired_account()]), account_renames());
+
+        //rejected by the caller, the column is dropped and added again
+        let mut desired = desired_user();
+        desired.attributes[1].name = String::from("full_name");
+
+        assert_eq!(
+            suggest_renames(&[existing_user()], &[desired.clone()]).columns,
+            vec![(String::from("user"), String::from("name"), String::from("full_name"))]
+        );
+        assert_eq!(
+            existing_user().alter_to_with(&desired, &Renames::default()),
+            vec![
+                DDL(String::from("ALTER TABLE user DROP COLUMN name")),
+                DDL(String::from("ALTER TABLE user ADD COLUMN full_name varchar(64) NOT NULL")),
+            ]
+        );
+
+        //two candidates with the same shape are not guessed between
+        desired.attributes.push(attribute("nickname", AttributeType::VarChar(64), "", &[Constraint::NotNull]));
+
+        assert_eq!(suggest_renames(&[existing_user()], &[desired]), Renames::default());
+    }
+}
@@ -0,0 +1,56 @@
+use std::{fmt, env::{self, VarError}};
+
+use crate::sql::{redact::register_secret, value::Value};
+
+/// Where the key of an encrypted column comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeySource {
+    /// The value of an environment variable.
+    Env(String),
+    /// The key itself.
+    Key(String),
+}
+
+/// The key of a column encrypted with `AES_ENCRYPT`, held as the SQL literal spliced into statements.
+///
+/// Loading a key registers its literal with the redaction hook (see `sql::redact`), and `Debug` never prints it.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct EncryptionKey {
+    literal: String,
+}
+
+impl EncryptionKey {
+    /// Loads the key from `source`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `VarError` if the key comes from an environment variable that is not set.
+    pub fn load(source: &KeySource) -> Result<EncryptionKey, VarError> {
+        let key = match source {
+            KeySource::Env(var) => env::var(var)?,
+            KeySource::Key(key) => key.clone(),
+        };
+
+        let literal = Value::Text(key).to_string();
+
+        register_secret(&literal);
+
+        Ok(EncryptionKey { literal })
+    }
+
+    /// Returns `AES_ENCRYPT(expression, key)`.
+    pub fn encrypt(&self, expression: &str) -> String {
+        format!("AES_ENCRYPT({}, {})", expression, self.literal)
+    }
+
+    /// Returns `AES_DECRYPT(expression, key)`.
+    pub fn decrypt(&self, expression: &str) -> String {
+        format!("AES_DECRYPT({}, {})", expression, self.literal)
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptionKey(***)")
+    }
+}
@@ -0,0 +1,526 @@
+use std::{fmt, collections::HashMap};
+
+use crate::{data_base::DataBase, error::Error, fmt::{Grid, Align, count}, sql::{SQL, QDL, SQLError, value::Value}};
+
+use super::{registry::Registry, table::Table};
+
+/// What happens to a referencing row when the row it references is deleted, as given by its foreign key's `ON DELETE`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeleteRule {
+    Cascade,
+    Restrict,
+    NoAction,
+    SetNull,
+    SetDefault,
+    /// The rule could not be read, so the hop is treated as cascading.
+    Unknown,
+}
+
+impl DeleteRule {
+    /// Parses a `DELETE_RULE` of `information_schema.REFERENTIAL_CONSTRAINTS`.
+    pub fn parse(rule: &str) -> DeleteRule {
+        match rule.trim().to_ascii_uppercase().as_str() {
+            "CASCADE" => DeleteRule::Cascade,
+            "RESTRICT" => DeleteRule::Restrict,
+            "NO ACTION" => DeleteRule::NoAction,
+            "SET NULL" => DeleteRule::SetNull,
+            "SET DEFAULT" => DeleteRule::SetDefault,
+            _ => DeleteRule::Unknown,
+        }
+    }
+
+    /// Returns true if deleting the referenced row deletes the referencing rows, so the walk continues past them.
+    fn cascades(&self) -> bool {
+        matches!(self, DeleteRule::Cascade | DeleteRule::Unknown)
+    }
+
+    /// Returns true if referencing rows stop the delete.
+    fn blocks(&self) -> bool {
+        matches!(self, DeleteRule::Restrict | DeleteRule::NoAction)
+    }
+}
+
+impl fmt::Display for DeleteRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeleteRule::Cascade => write!(f, "CASCADE"),
+            DeleteRule::Restrict => write!(f, "RESTRICT"),
+            DeleteRule::NoAction => write!(f, "NO ACTION"),
+            DeleteRule::SetNull => write!(f, "SET NULL"),
+            DeleteRule::SetDefault => write!(f, "SET DEFAULT"),
+            DeleteRule::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
+/// A foreign key followed from a referenced table to the table referencing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hop {
+    /// The referencing table.
+    pub table: String,
+    /// The foreign key column of the referencing table.
+    pub column: String,
+    pub referenced_table: String,
+    pub referenced_column: String,
+    pub rule: DeleteRule,
+}
+
+impl Hop {
+    fn same_key(&self, other: &Hop) -> bool {
+        self.table == other.table && self.column == other.column
+    }
+}
+
+/// The rows of one table reached by deleting a row, as returned by `delete_impact`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableImpact {
+    /// The foreign keys followed from the deleted row, the last of which is in the affected table.
+    pub path: Vec<Hop>,
+    /// The number of rows of the table reached through `path`.
+    pub count: u64,
+}
+
+impl TableImpact {
+    /// Returns the name of the affected table.
+    pub fn table(&self) -> &str {
+        &self.path[self.path.len() - 1].table
+    }
+
+    /// Returns what happens to the affected rows.
+    pub fn rule(&self) -> DeleteRule {
+        self.path[self.path.len() - 1].rule
+    }
+}
+
+/// The rows affected by deleting a row, as returned by `delete_impact`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImpactReport {
+    pub table: String,
+    pub impacts: Vec<TableImpact>,
+}
+
+impl ImpactReport {
+    /// Returns the impacts whose rows would stop the delete.
+    pub fn blocking(&self) -> Vec<&TableImpact> {
+        self.impacts
+            .iter()
+            .filter(|impact| impact.count > 0 && impact.rule().blocks())
+            .collect()
+    }
+
+    /// Returns the total number of affected rows per table.
+    pub fn counts(&self) -> HashMap<&str, u64> {
+        let mut counts: HashMap<&str, u64> = HashMap::new();
+
+        for impact in &self.impacts {
+            *counts.entry(impact.table()).or_default() += impact.count;
+        }
+
+        counts
+    }
+}
+
+impl fmt::Display for ImpactReport {
+    /// Writes one row per path with the table reached, its row count, the rule of the last foreign key and the path,
+    /// fitting in the formatter's width if one is given (see `Grid::render`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut grid = Grid::new(&[
+            ("table", Align::Left),
+            ("rows", Align::Right),
+            ("rule", Align::Left),
+            ("path", Align::Left),
+        ]);
+
+        for impact in &self.impacts {
+            let path = impact.path
+                .iter()
+                .map(|hop| format!("{}.{}", hop.table, hop.column))
+                .collect::<Vec<String>>()
+                .join(" -> ");
+
+            grid.push(vec![
+                impact.table().to_string(),
+                count(impact.count),
+                impact.rule().to_string(),
+                format!("{} -> {}", self.table, path),
+            ]);
+        }
+
+        f.write_str(&grid.render(f.width()))
+    }
+}
+
+/// Returns every foreign key path along which deleting a row of `table` reaches other rows, shortest first.
+///
+/// Rules missing from `rules`, keyed by `(table, column)` of the foreign key, are `DeleteRule::Unknown`. The walk only
+/// continues past cascading hops, as rows that are kept or that stop the delete do not affect the rows referencing
+/// them. A foreign key is followed at most once per path, so cycles and self references terminate.
+///
+/// # Errors
+///
+/// Returns `SQLError::Err` if `table` is not in `registry`, or an `SQLError` if its tables could not be loaded.
+pub fn impact_paths(registry: &mut Registry, table: &str, rules: &HashMap<(String, String), DeleteRule>) -> Result<Vec<Vec<Hop>>, SQLError> {
+    let mut references: HashMap<String, Vec<Hop>> = HashMap::new();
+    let mut paths: Vec<Vec<Hop>> = Vec::new();
+    let mut frontier: Vec<Vec<Hop>> = vec![Vec::new()];
+
+    while !frontier.is_empty() {
+        let mut next: Vec<Vec<Hop>> = Vec::new();
+
+        for path in frontier {
+            let from = path.last().map_or(table, |hop| hop.table.as_str());
+            let key = from.to_lowercase();
+
+            //the tables referencing `from`, read from the registry once per table
+            if !references.contains_key(&key) {
+                let hops = registry.references_of(from)?
+                    .into_iter()
+                    .map(|foreign_key| Hop {
+                        rule: rules.get(&(foreign_key.table.clone(), foreign_key.column.clone()))
+                            .copied()
+                            .unwrap_or(DeleteRule::Unknown),
+                        table: foreign_key.table,
+                        column: foreign_key.column,
+                        referenced_table: foreign_key.referenced_table,
+                        referenced_column: foreign_key.referenced_column,
+                    })
+                    .collect();
+
+                references.insert(key.clone(), hops);
+            }
+
+            for hop in &references[&key] {
+                if path.iter().any(|taken| taken.same_key(hop)) {
+                    continue;
+                }
+
+                let mut extended = path.clone();
+                extended.push(hop.clone());
+
+                if hop.rule.cascades() {
+                    next.push(extended.clone());
+                }
+                paths.push(extended);
+            }
+        }
+
+        frontier = next;
+    }
+
+    Ok(paths)
+}
+
+/// Returns a `QDL` counting the rows reached from the row of `root` whose primary key is `primary_key`, joining along
+/// `path`.
+///
+/// Returns `None` if `path` is empty, or if `root` does not have a primary key over a single column.
+///
+/// Every table is aliased by its position on the path, `t0` being `root`, so a table can appear more than once.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(
+///     count_query(&customer, &path, &Value::Int(5)),
+///     Some(QDL(String::from(
+///         "SELECT COUNT(*) FROM invoice_line AS t2 JOIN invoice AS t1 ON t2.invoice_id = t1.id \
+///         JOIN customer AS t0 ON t1.customer_id = t0.id WHERE t0.id = 5"
+///     )))
+/// );
+/// ```
+pub fn count_query(root: &Table, path: &[Hop], primary_key: &Value) -> Option<QDL> {
+    let primary_key_column = match root.primary_key_columns().as_slice() {
+        [column] => column.to_string(),
+        _ => return None,
+    };
+    let last = path.len();
+
+    let mut query = format!("SELECT COUNT(*) FROM {} AS t{}", path.last()?.table, last);
+
+    for (i, hop) in path.iter().enumerate().rev() {
+        let referenced = match i {
+            0 => root.name.as_str(),
+            _ => path[i - 1].table.as_str(),
+        };
+
+        query.push_str(&format!(
+            " JOIN {} AS t{} ON t{}.{} = t{}.{}",
+            referenced,
+            i,
+            i + 1,
+            hop.column,
+            i,
+            hop.referenced_column
+        ));
+    }
+
+    query.push_str(&format!(" WHERE t0.{} = {}", primary_key_column, primary_key));
+
+    Some(QDL(query))
+}
+
+/// Reads the `ON DELETE` rule of every foreign key of the current database, keyed by `(table, column)`.
+///
+/// # Errors
+///
+/// Returns an `Error` if `information_schema` could not be queried.
+pub fn delete_rules(db: &DataBase) -> Result<HashMap<(String, String), DeleteRule>, Error> {
+    let statement = "SELECT k.TABLE_NAME, k.COLUMN_NAME, r.DELETE_RULE \
+        FROM information_schema.KEY_COLUMN_USAGE AS k \
+        JOIN information_schema.REFERENTIAL_CONSTRAINTS AS r \
+        ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME \
+        WHERE k.TABLE_SCHEMA = DATABASE()";
+    let rows = db.execute(
+        &SQL::Select(QDL(String::from(statement))),
+        |row| row.map(mysql::from_row::<(String, String, String)>)
+    )?;
+
+    rows.into_iter()
+        .map(|row| row
+            .map(|(table, column, rule)| ((table, column), DeleteRule::parse(&rule)))
+            .map_err(|err| Error::query(statement, err))
+        )
+        .collect()
+}
+
+/// Reports the rows that deleting the row of `table` whose primary key is `primary_key` would affect, following
+/// foreign keys between the tables of `registry` transitively (see `impact_paths`).
+///
+/// Nothing is deleted: one `COUNT(*)` query is run per path.
+///
+/// # Errors
+///
+/// Returns `SQLError::Err` if `table` is not in `registry` or has no primary key, and `SQLError::Execution` if a query
+/// fails.
+pub fn delete_impact(db: &DataBase, registry: &mut Registry, table: &str, primary_key: &Value) -> Result<ImpactReport, SQLError> {
+    let root = match registry.table(table)? {
+        Some(root) => root.clone(),
+        None => return Err(SQLError::Err(format!("Unknown table {}", table))),
+    };
+
+    let rules = delete_rules(db)?;
+
+    let mut impacts: Vec<TableImpact> = Vec::new();
+
+    for path in impact_paths(registry, table, &rules)? {
+        let query = match count_query(&root, &path, primary_key) {
+            Some(query) => query,
+            None => return Err(SQLError::Err(format!("Table {} has no primary key", table))),
+        };
+
+        let count = db.execute(&SQL::Select(query), |row| row.map(mysql::from_row::<u64>))
+            .map_err(SQLError::from)?
+            .into_iter()
+            .next()
+            .unwrap_or(Ok(0))
+            .map_err(SQLError::Execution)?;
+
+        impacts.push(TableImpact { path, count });
+    }
+
+    Ok(ImpactReport { table: table.to_string(), impacts })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use serial_test::serial;
+
+    use crate::{data_base::DataBase, sql::{SQL, QDL, value::Value}};
+
+    use super::{
+        super::{registry::Registry, table::{Table, Attribute, AttributeType, Constraint}},
+        DeleteRule,
+        Hop,
+        ImpactReport,
+        TableImpact,
+        impact_paths,
+        count_query,
+        delete_impact,
+    };
+
+    fn table(name: &str, foreign_keys: &[(&str, &str, &str)]) -> Table {
+        let mut attributes = vec![
+            Attribute {
+                name: String::from("id"),
+                data_type: AttributeType::Int(11),
+                raw_type: String::new(),
+                constraint: HashSet::from([Constraint::NotNull]),
+            },
+        ];
+
+        for (column, table_name, attribute_name) in foreign_keys {
+            attributes.push(Attribute {
+                name: column.to_string(),
+                data_type: AttributeType::Int(11),
+                raw_type: String::new(),
+                constraint: HashSet::from([Constraint::ForeignKey {
+                    table_name: table_name.to_string(),
+                    attribute_name: attribute_name.to_string(),
+                    on_delete: None,
+                    on_update: None,
+                }]),
+            });
+        }
+
+        Table { name: name.to_string(), attributes, primary_key: vec![0], indexes: Vec::new() }
+    }
+
+    fn shop() -> Vec<Table> {
+        vec![
+            table("customer", &[("referrer_id", "customer", "id")]),
+            table("invoice", &[("customer_id", "customer", "id")]),
+            table("invoice_line", &[("invoice_id", "invoice", "id")]),
+            table("note", &[("customer_id", "customer", "id")]),
+            table("product", &[]),
+        ]
+    }
+
+    fn rules() -> HashMap<(String, String), DeleteRule> {
+        HashMap::from([
+            ((String::from("customer"), String::from("referrer_id")), DeleteRule::SetNull),
+            ((String::from("invoice"), String::from("customer_id")), DeleteRule::Cascade),
+            ((String::from("invoice_line"), String::from("invoice_id")), DeleteRule::Restrict),
+        ])
+    }
+
+    fn names(path: &[Hop]) -> Vec<String> {
+        path.iter().map(|hop| format!("{}.{}", hop.table, hop.column)).collect()
+    }
+
+    #[test]
+    fn delete_rule_test() {
+        assert_eq!(DeleteRule::parse("CASCADE"), DeleteRule::Cascade);
+        assert_eq!(DeleteRule::parse("no action"), DeleteRule::NoAction);
+        assert_eq!(DeleteRule::parse("SET NULL"), DeleteRule::SetNull);
+        assert_eq!(DeleteRule::parse("?"), DeleteRule::Unknown);
+    }
+
+    #[test]
+    fn impact_paths_test() {
+        let paths = impact_paths(&mut Registry::new(shop()), "customer", &rules()).unwrap();
+
+        assert_eq!(
+            paths.iter().map(|path| names(path)).collect::<Vec<Vec<String>>>(),
+            vec![
+                vec![String::from("customer.referrer_id")],
+                vec![String::from("invoice.customer_id")],
+                vec![String::from("note.customer_id")],
+                vec![String::from("invoice.customer_id"), String::from("invoice_line.invoice_id")],
+                //the unknown rule of note is treated as cascading, but nothing references note
+            ]
+        );
+        assert_eq!(paths[3][1].rule, DeleteRule::Restrict);
+
+        assert!(impact_paths(&mut Registry::new(shop()), "missing", &rules()).is_err());
+    }
+
+    #[test]
+    fn impact_paths_cycle_test() {
+        //a -> b -> a, and a self reference, all cascading
+        let tables = vec![
+            table("a", &[("b_id", "b", "id"), ("parent_id", "a", "id")]),
+            table("b", &[("a_id", "a", "id")]),
+        ];
+
+        let paths = impact_paths(&mut Registry::new(tables), "a", &HashMap::new()).unwrap();
+
+        assert_eq!(
+            paths.iter().map(|path| names(path)).collect::<Vec<Vec<String>>>(),
+            vec![
+                vec![String::from("a.parent_id")],
+                vec![String::from("b.a_id")],
+                vec![String::from("a.parent_id"), String::from("b.a_id")],
+                vec![String::from("b.a_id"), String::from("a.b_id")],
+                vec![String::from("a.parent_id"), String::from("b.a_id"), String::from("a.b_id")],
+                vec![String::from("b.a_id"), String::from("a.b_id"), String::from("a.parent_id")],
+            ]
+        );
+    }
+
+    #[test]
+    fn count_query_test() {
+        let tables = shop();
+        let paths = impact_paths(&mut Registry::new(tables.clone()), "customer", &rules()).unwrap();
+
+        assert_eq!(
+            count_query(&tables[0], &paths[3], &Value::Int(5)),
+            Some(QDL(String::from(
+                "SELECT COUNT(*) FROM invoice_line AS t2 \
+                JOIN invoice AS t1 ON t2.invoice_id = t1.id \
+                JOIN customer AS t0 ON t1.customer_id = t0.id \
+                WHERE t0.id = 5"
+            )))
+        );
+        //self reference
+        assert_eq!(
+            count_query(&tables[0], &paths[0], &Value::Text(String::from("x"))),
+            Some(QDL(String::from(
+                "SELECT COUNT(*) FROM customer AS t1 JOIN customer AS t0 ON t1.referrer_id = t0.id WHERE t0.id = 'x'"
+            )))
+        );
+
+        let mut keyless = tables[0].clone();
+        keyless.primary_key = Vec::new();
+        assert_eq!(count_query(&keyless, &paths[0], &Value::Int(5)), None);
+    }
+
+    #[test]
+    fn report_test() {
+        let paths = impact_paths(&mut Registry::new(shop()), "customer", &rules()).unwrap();
+
+        let report = ImpactReport {
+            table: String::from("customer"),
+            impacts: paths.into_iter()
+                .zip([0, 2, 1, 3])
+                .map(|(path, count)| TableImpact { path, count })
+                .collect(),
+        };
+
+        assert_eq!(report.blocking().len(), 1);
+        assert_eq!(report.blocking()[0].table(), "invoice_line");
+        assert_eq!(report.counts().get("invoice"), Some(&2));
+        assert_eq!(
+            report.to_string(),
+            "table         rows  rule      path\n\
+            customer         0  SET NULL  customer -> customer.referrer_id\n\
+            invoice          2  CASCADE   customer -> invoice.customer_id\n\
+            note             1  UNKNOWN   customer -> note.customer_id\n\
+            invoice_line     3  RESTRICT  customer -> invoice.customer_id -> invoice_line.invoice_id"
+        );
+        assert_eq!(
+            format!("{:50}", report).lines().last(),
+            Some("invoice_line     3  RESTRICT  customer -> invoice~")
+        );
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn delete_impact_test() {
+        let db = DataBase::from_env().unwrap();
+
+        db.execute_multiple(&vec![
+            SQL::new("CREATE TABLE impact_customer (id INT PRIMARY KEY)").unwrap(),
+            SQL::new(
+                "CREATE TABLE impact_invoice (id INT PRIMARY KEY, customer_id INT, \
+                FOREIGN KEY (customer_id) REFERENCES impact_customer(id) ON DELETE CASCADE)"
+            ).unwrap(),
+            SQL::new("INSERT INTO impact_customer (id) VALUES (1)").unwrap(),
+            SQL::new("INSERT INTO impact_invoice (id, customer_id) VALUES (1, 1)").unwrap(),
+            SQL::new("INSERT INTO impact_invoice (id, customer_id) VALUES (2, 1)").unwrap(),
+        ]).unwrap();
+
+        let report = delete_impact(&db, &mut Registry::from_db(&db), "impact_customer", &Value::Int(1)).unwrap();
+
+        assert_eq!(report.impacts.len(), 1);
+        assert_eq!(report.impacts[0].count, 2);
+        assert_eq!(report.impacts[0].rule(), DeleteRule::Cascade);
+
+        db.execute_multiple(&vec![
+            SQL::new("DROP TABLE impact_invoice").unwrap(),
+            SQL::new("DROP TABLE impact_customer").unwrap(),
+        ]).unwrap();
+    }
+}
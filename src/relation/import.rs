@@ -0,0 +1,832 @@
+use std::{fmt, collections::HashMap, io::{BufReader, Read}, ops::Range};
+
+use mysql::{prelude::Queryable, Conn, Error};
+
+use crate::{data_base::{DataBase, DEFAULT_MAX_ALLOWED_PACKET}, fmt::{Grid, Align, count}, sql::{QML, value::Value}};
+
+use super::{table::{Table, BatchInsertError}, source::{RowSource, SourceError, CsvRows, JsonlRows}};
+
+/// How `Table::import` groups its statements into transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TransactionMode {
+    /// Every chunk is inserted in one transaction, so a failure leaves the table untouched.
+    #[default]
+    Single,
+    /// Every chunk is committed on its own, so a failure keeps the chunks before it.
+    PerChunk,
+}
+
+/// Options of `Table::import`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct ImportOptions {
+    /// The maximum size, in bytes, of a single insert statement. Defaults to `DataBase::insert_budget`.
+    pub budget: Option<usize>,
+    pub transaction: TransactionMode,
+    /// Coerces every value to its column's type before validating it (see `Value::coerce_to`), so `1` is accepted for a
+    /// `BOOL` column and `"42"` for an `INT` column.
+    pub lenient: bool,
+}
+
+/// The outcome of a successful `Table::import`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportReport {
+    /// The number of rows inserted.
+    pub rows: usize,
+    /// The number of insert statements executed.
+    pub chunks: usize,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut grid = Grid::new(&[("rows", Align::Right), ("chunks", Align::Right)]);
+
+        grid.push(vec![count(self.rows as u64), count(self.chunks as u64)]);
+
+        f.write_str(&grid.render(f.width()))
+    }
+}
+
+/// Options of the streaming imports, such as `Table::import_csv`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StreamOptions {
+    /// How the rows are inserted. Committing `TransactionMode::PerChunk` lets a failed import be resumed where it
+    /// stopped; with `TransactionMode::Single` it has to start over.
+    pub import: ImportOptions,
+    /// The most rows read but not yet inserted at any time. The rows are inserted in batches of this many.
+    pub max_buffered_rows: usize,
+    /// The number of source rows to skip, as returned in `StreamImportError::resume_from` by a failed import.
+    pub resume_from: usize,
+    /// The number of invalid or malformed rows skipped, and logged, before the import fails.
+    pub max_errors: usize,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        StreamOptions{import: ImportOptions::default(), max_buffered_rows: 1000, resume_from: 0, max_errors: 0}
+    }
+}
+
+/// The progress of a streaming import, reported after every batch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct ImportProgress {
+    /// The number of source rows done, including the rows skipped by `StreamOptions::resume_from`.
+    pub rows: usize,
+    /// The number of rows inserted.
+    pub inserted: usize,
+    /// The number of bytes read from the source.
+    pub bytes: u64,
+    /// The number of invalid or malformed rows skipped.
+    pub errors: usize,
+}
+
+/// Represents possible errors that can occur during `Table::import`.
+///
+/// The rows of a streaming import, such as `Table::import_csv`, are indexed by their position in the source and are
+/// written as they are read, so its errors may come after some rows were written.
+#[derive(Debug)]
+pub enum ImportError {
+    /// No connection could be opened, or the database is shutting down. Nothing was written.
+    Connection(crate::Error),
+    /// The row at index `row` failed validation. Nothing was written.
+    InvalidRow{row: usize, reason: String},
+    /// The row at index `row` could not be read from the source.
+    Source{row: usize, error: SourceError},
+    /// A row is too large to be inserted. Nothing was written.
+    Batch(BatchInsertError),
+    /// The chunk at index `chunk`, covering `rows`, could not be inserted.
+    ///
+    /// `committed_rows` is the number of rows kept by earlier chunks, and `untouched` is true if the table was left as
+    /// it was before the import.
+    Chunk{chunk: usize, rows: Range<usize>, error: Error, committed_rows: usize, untouched: bool},
+    /// Every chunk was inserted but the final commit failed.
+    Commit{error: Error, untouched: bool},
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Connection(err) => write!(f, "no connection could be opened: {}", err),
+            ImportError::InvalidRow{row, reason} => write!(f, "row {} is invalid: {}", row, reason),
+            ImportError::Source{row, error} => write!(f, "row {} could not be read: {}", row, error),
+            ImportError::Batch(err) => write!(f, "{}", err),
+            ImportError::Chunk{chunk, rows, error, committed_rows, untouched} => write!(
+                f,
+                "chunk {} (rows {}..{}) failed: {} - {} rows committed, table {}",
+                chunk,
+                rows.start,
+                rows.end,
+                error,
+                committed_rows,
+                if *untouched { "untouched" } else { "modified" }
+            ),
+            ImportError::Commit{error, untouched} => write!(
+                f,
+                "commit failed: {} - table {}",
+                error,
+                if *untouched { "untouched" } else { "may be modified" }
+            ),
+        }
+    }
+}
+
+/// A failed streaming import, along with where to resume it.
+#[derive(Debug)]
+pub struct StreamImportError {
+    pub error: ImportError,
+    /// The number of source rows that are written or were skipped, to pass as `StreamOptions::resume_from` to import
+    /// the rest once the error is fixed.
+    pub resume_from: usize,
+}
+
+impl fmt::Display for StreamImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - resume from row {}", self.error, self.resume_from)
+    }
+}
+
+/// The state of a streaming import between batches.
+struct Stream {
+    progress: ImportProgress,
+    chunks: usize,
+    /// The rows inserted and committed.
+    committed_rows: usize,
+    /// The source rows that are committed or were skipped.
+    resume_from: usize,
+}
+
+/// Rolls back the transaction of a streaming import that is failing, if it runs in one.
+fn abort<E: ImportExecutor>(executor: &mut E, transaction: TransactionMode) {
+    if transaction == TransactionMode::Single {
+        if let Err(err) = executor.rollback() {
+            log::error!("Failed to roll back import - Err:{:?}", err);
+        }
+    }
+}
+
+/// The statements `Table::import` needs to run on a single connection.
+pub trait ImportExecutor {
+    fn begin(&mut self) -> Result<(), Error>;
+    fn execute(&mut self, statement: &QML) -> Result<(), Error>;
+    fn commit(&mut self) -> Result<(), Error>;
+    fn rollback(&mut self) -> Result<(), Error>;
+}
+
+impl ImportExecutor for Conn {
+    fn begin(&mut self) -> Result<(), Error> {
+        self.query_drop("START TRANSACTION")
+    }
+    fn execute(&mut self, statement: &QML) -> Result<(), Error> {
+        self.query_drop(statement.as_str())
+    }
+    fn commit(&mut self) -> Result<(), Error> {
+        self.query_drop("COMMIT")
+    }
+    fn rollback(&mut self) -> Result<(), Error> {
+        self.query_drop("ROLLBACK")
+    }
+}
+
+impl Table {
+    /// Validates, chunks and inserts `rows` into the table.
+    ///
+    /// Every row is checked against the table's columns (see `Attribute::check_value`), after coercing its values when
+    /// `opts.lenient` is set, before anything is written. Then
+    /// the rows are inserted with statements of at most `opts.budget` bytes (see `Table::insert_chunks`), in one or
+    /// many transactions depending on `opts.transaction`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ImportError` naming the row or chunk that failed and whether the table was left untouched.
+    pub fn import(&self, db: &DataBase, rows: impl Iterator<Item = HashMap<String, Value>>, opts: ImportOptions) -> Result<ImportReport, ImportError> {
+        let opts = ImportOptions {
+            budget: Some(opts.budget.unwrap_or_else(|| db.insert_budget())),
+            ..opts
+        };
+
+        let (mut conn, _in_flight) = db.start().map_err(ImportError::Connection)?;
+
+        self.import_with(&mut conn, rows, opts)
+    }
+
+    /// Same as `Table::import`, running the statements on `executor`.
+    pub fn import_with<E: ImportExecutor>(&self, executor: &mut E, rows: impl Iterator<Item = HashMap<String, Value>>, opts: ImportOptions) -> Result<ImportReport, ImportError> {
+        let mut rows: Vec<HashMap<String, Value>> = rows.collect();
+
+        for (row, values) in rows.iter_mut().enumerate() {
+            self.check_row(values, opts.lenient)
+                .map_err(|reason| ImportError::InvalidRow{row, reason})?;
+        }
+
+        let budget = opts.budget.unwrap_or(DEFAULT_MAX_ALLOWED_PACKET / 5 * 4);
+        let chunks = self.insert_chunks(&rows, budget).map_err(ImportError::Batch)?;
+
+        let mut committed_rows = 0;
+
+        if opts.transaction == TransactionMode::Single {
+            if let Err(error) = executor.begin() {
+                let rows = chunks.first().map_or(0..0, |chunk| chunk.rows.clone());
+                return Err(ImportError::Chunk{chunk: 0, rows, error, committed_rows, untouched: true});
+            }
+        }
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            let result = match opts.transaction {
+                TransactionMode::Single => executor.execute(&chunk.statement),
+                TransactionMode::PerChunk => executor.begin()
+                    .and_then(|_| executor.execute(&chunk.statement))
+                    .and_then(|_| executor.commit()),
+            };
+
+            if let Err(error) = result {
+                let rolled_back = executor.rollback().is_ok();
+
+                return Err(
+                    ImportError::Chunk{
+                        chunk: i,
+                        rows: chunk.rows.clone(),
+                        error,
+                        committed_rows,
+                        untouched: rolled_back && committed_rows == 0,
+                    }
+                );
+            }
+
+            if opts.transaction == TransactionMode::PerChunk {
+                committed_rows = chunk.rows.end;
+            }
+        }
+
+        if opts.transaction == TransactionMode::Single {
+            if let Err(error) = executor.commit() {
+                let untouched = executor.rollback().is_ok();
+                return Err(ImportError::Commit{error, untouched});
+            }
+        }
+
+        Ok(ImportReport{rows: rows.len(), chunks: chunks.len()})
+    }
+
+    /// Imports the rows of a CSV file with a header line into the table, streaming it (see `Table::import_stream_with`).
+    ///
+    /// Fields are read as text, so `opts.import.lenient` is needed for columns of other types to be checked as such.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `StreamImportError` naming the row or chunk that failed and where to resume the import.
+    pub fn import_csv(&self, db: &DataBase, reader: impl Read, opts: StreamOptions, progress: impl FnMut(&ImportProgress)) -> Result<ImportReport, StreamImportError> {
+        self.import_stream(db, CsvRows::new(BufReader::new(reader)), opts, progress)
+    }
+
+    /// Imports the rows of a JSON lines file into the table, streaming it (see `Table::import_stream_with`).
+    ///
+    /// # Errors
+    ///
+    /// Returns a `StreamImportError` naming the row or chunk that failed and where to resume the import.
+    pub fn import_jsonl(&self, db: &DataBase, reader: impl Read, opts: StreamOptions, progress: impl FnMut(&ImportProgress)) -> Result<ImportReport, StreamImportError> {
+        self.import_stream(db, JsonlRows::new(BufReader::new(reader)), opts, progress)
+    }
+
+    fn import_stream<S: RowSource>(&self, db: &DataBase, source: S, opts: StreamOptions, progress: impl FnMut(&ImportProgress)) -> Result<ImportReport, StreamImportError> {
+        let opts = StreamOptions {
+            import: ImportOptions {
+                budget: Some(opts.import.budget.unwrap_or_else(|| db.insert_budget())),
+                ..opts.import
+            },
+            ..opts
+        };
+
+        let (mut conn, _in_flight) = db.start()
+            .map_err(|err| StreamImportError{error: ImportError::Connection(err), resume_from: opts.resume_from})?;
+
+        self.import_stream_with(&mut conn, source, opts, progress)
+    }
+
+    /// Reads, validates and inserts the rows of `source` into the table as they are read, in batches of at most
+    /// `opts.max_buffered_rows` rows, so the source is never held in memory. `progress` is called after every batch.
+    ///
+    /// Rows are checked like in `Table::import`, and invalid or malformed rows are skipped until more than
+    /// `opts.max_errors` have been. Every batch is inserted with statements of at most `opts.import.budget` bytes. The
+    /// first `opts.resume_from` rows of the source are read but not inserted.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `StreamImportError` naming the row or chunk that failed, indexed by position in the source, and the
+    /// number of source rows to skip to resume the import.
+    pub fn import_stream_with<E: ImportExecutor, S: RowSource>(&self, executor: &mut E, mut source: S, opts: StreamOptions, mut progress: impl FnMut(&ImportProgress)) -> Result<ImportReport, StreamImportError> {
+        let max_buffered_rows = opts.max_buffered_rows.max(1);
+
+        let mut stream = Stream {
+            progress: ImportProgress{rows: opts.resume_from, ..ImportProgress::default()},
+            chunks: 0,
+            committed_rows: 0,
+            resume_from: opts.resume_from,
+        };
+
+        let fail = |error: ImportError, stream: &Stream| StreamImportError{error, resume_from: stream.resume_from};
+
+        if opts.import.transaction == TransactionMode::Single {
+            if let Err(error) = executor.begin() {
+                let rows = opts.resume_from..opts.resume_from;
+                return Err(fail(ImportError::Chunk{chunk: 0, rows, error, committed_rows: 0, untouched: true}, &stream));
+            }
+        }
+
+        let mut batch: Vec<HashMap<String, Value>> = Vec::with_capacity(max_buffered_rows);
+        //the source row of every row of the batch
+        let mut source_rows: Vec<usize> = Vec::with_capacity(max_buffered_rows);
+
+        let mut row = 0;
+
+        while let Some(values) = source.next() {
+            let index = row;
+            row += 1;
+
+            let values = match values {
+                Err(SourceError::Io(error)) => {
+                    abort(executor, opts.import.transaction);
+                    return Err(fail(ImportError::Source{row: index, error: SourceError::Io(error)}, &stream));
+                },
+                //malformed rows were counted by the run being resumed
+                _ if index < opts.resume_from => continue,
+                Err(error) => Err(ImportError::Source{row: index, error}),
+                Ok(mut values) => self.check_row(&mut values, opts.import.lenient)
+                    .map(|_| values)
+                    .map_err(|reason| ImportError::InvalidRow{row: index, reason}),
+            };
+
+            match values {
+                Ok(values) => {
+                    batch.push(values);
+                    source_rows.push(index);
+                },
+                Err(error) if stream.progress.errors < opts.max_errors => {
+                    log::warn!("Skipped a row importing into {} - {}", self.name, error);
+                    stream.progress.errors += 1;
+                },
+                Err(error) => {
+                    abort(executor, opts.import.transaction);
+                    return Err(fail(error, &stream));
+                },
+            }
+
+            if batch.len() == max_buffered_rows {
+                self.insert_batch(executor, &mut batch, &mut source_rows, row, &mut stream, opts)
+                    .map_err(|error| fail(error, &stream))?;
+
+                stream.progress.bytes = source.bytes_read();
+                progress(&stream.progress);
+            }
+        }
+
+        if !batch.is_empty() || stream.progress.rows < row {
+            self.insert_batch(executor, &mut batch, &mut source_rows, row, &mut stream, opts)
+                .map_err(|error| fail(error, &stream))?;
+
+            stream.progress.bytes = source.bytes_read();
+            progress(&stream.progress);
+        }
+
+        if opts.import.transaction == TransactionMode::Single {
+            if let Err(error) = executor.commit() {
+                let untouched = executor.rollback().is_ok();
+                return Err(fail(ImportError::Commit{error, untouched}, &stream));
+            }
+
+            //with a single transaction, nothing is kept until the import finishes
+            stream.resume_from = row.max(opts.resume_from);
+        }
+
+        Ok(ImportReport{rows: stream.progress.inserted, chunks: stream.chunks})
+    }
+
+    /// Inserts and empties `batch`, for `Table::import_stream_with`. `rows` is the number of source rows read so far.
+    fn insert_batch<E: ImportExecutor>(&self, executor: &mut E, batch: &mut Vec<HashMap<String, Value>>, source_rows: &mut Vec<usize>, rows: usize, stream: &mut Stream, opts: StreamOptions) -> Result<(), ImportError> {
+        let budget = opts.import.budget.unwrap_or(DEFAULT_MAX_ALLOWED_PACKET / 5 * 4);
+
+        let chunks = self.insert_chunks(batch, budget)
+            .map_err(|BatchInsertError::RowTooLarge{row, size, budget}| {
+                abort(executor, opts.import.transaction);
+                ImportError::Batch(BatchInsertError::RowTooLarge{row: source_rows[row], size, budget})
+            })?;
+
+        for chunk in chunks {
+            let result = match opts.import.transaction {
+                TransactionMode::Single => executor.execute(&chunk.statement),
+                TransactionMode::PerChunk => executor.begin()
+                    .and_then(|_| executor.execute(&chunk.statement))
+                    .and_then(|_| executor.commit()),
+            };
+
+            let first = source_rows[chunk.rows.start];
+            let last = source_rows[chunk.rows.end - 1];
+
+            if let Err(error) = result {
+                let rolled_back = executor.rollback().is_ok();
+
+                return Err(
+                    ImportError::Chunk{
+                        chunk: stream.chunks,
+                        rows: first..last + 1,
+                        error,
+                        committed_rows: stream.committed_rows,
+                        untouched: rolled_back && stream.committed_rows == 0,
+                    }
+                );
+            }
+
+            stream.chunks += 1;
+            stream.progress.inserted += chunk.rows.len();
+
+            if opts.import.transaction == TransactionMode::PerChunk {
+                stream.committed_rows += chunk.rows.len();
+                stream.resume_from = last + 1;
+            }
+        }
+
+        //the rows skipped after the last inserted one are done too
+        if opts.import.transaction == TransactionMode::PerChunk {
+            stream.resume_from = rows;
+        }
+
+        stream.progress.rows = rows;
+        batch.clear();
+        source_rows.clear();
+
+        Ok(())
+    }
+
+    /// Coerces, when `lenient` is set, and validates `values`, as `Table::import` does.
+    fn check_row(&self, values: &mut HashMap<String, Value>, lenient: bool) -> Result<(), String> {
+        if lenient {
+            self.coerce_row(values)?;
+        }
+
+        self.validate_values(values)
+    }
+
+    /// Replaces every value of `values` with the value its column stores (see `Value::coerce_to`).
+    fn coerce_row(&self, values: &mut HashMap<String, Value>) -> Result<(), String> {
+        for attr in &self.attributes {
+            if let Some(value) = values.get_mut(&attr.name) {
+                *value = value.coerce_to(&attr.data_type)
+                    .map_err(|err| format!("{}: {}", attr.name, err))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks every value of `values` against its column, and that every `NOT NULL` column is set.
+    fn validate_values(&self, values: &HashMap<String, Value>) -> Result<(), String> {
+        if let Some(column) = values.keys().find(|column| !self.attributes.iter().any(|attr| &attr.name == *column)) {
+            return Err(format!("unknown column {}", column));
+        }
+
+        for attr in &self.attributes {
+            attr.check_value(values.get(&attr.name).unwrap_or(&Value::Null))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, collections::{HashMap, HashSet}, io::{self, Cursor, Read}, rc::Rc};
+
+    use mysql::Error;
+
+    use crate::{relation::{table::{Table, Attribute, AttributeType, Constraint}, source::{CsvRows, RowSource, SourceError}}, sql::{QML, value::Value}};
+
+    use super::{ImportExecutor, ImportOptions, ImportReport, ImportError, ImportProgress, StreamOptions, TransactionMode};
+
+    /// Records the statements it is given, failing the `fail_on`th insert.
+    ///
+    /// `pulled` counts the rows read from a `Counted` source, from which the most rows read but not yet inserted is
+    /// kept in `peak_buffered`.
+    #[derive(Default)]
+    struct MockExecutor {
+        log: Vec<String>,
+        inserts: usize,
+        fail_on: Option<usize>,
+        pulled: Rc<Cell<usize>>,
+        inserted_rows: usize,
+        peak_buffered: usize,
+    }
+
+    impl ImportExecutor for MockExecutor {
+        fn begin(&mut self) -> Result<(), Error> {
+            self.log.push(String::from("BEGIN"));
+            Ok(())
+        }
+        fn execute(&mut self, statement: &QML) -> Result<(), Error> {
+            self.inserts += 1;
+
+            if Some(self.inserts) == self.fail_on {
+                self.log.push(String::from("FAILED"));
+                return Err(Error::FromValueError(mysql::Value::NULL));
+            }
+
+            self.peak_buffered = self.peak_buffered.max(self.pulled.get().saturating_sub(self.inserted_rows));
+            self.inserted_rows += statement.matches("),(").count() + 1;
+
+            self.log.push(statement.to_string());
+            Ok(())
+        }
+        fn commit(&mut self) -> Result<(), Error> {
+            self.log.push(String::from("COMMIT"));
+            Ok(())
+        }
+        fn rollback(&mut self) -> Result<(), Error> {
+            self.log.push(String::from("ROLLBACK"));
+            Ok(())
+        }
+    }
+
+    fn table() -> Table {
+        Table{
+            name: String::from("t"),
+            attributes: vec![
+                Attribute{
+                    name: String::from("id"),
+                    data_type: AttributeType::TinyInt(4),
+                    raw_type: String::new(),
+                    constraint: HashSet::from([Constraint::NotNull])
+                },
+                Attribute{
+                    name: String::from("name"),
+                    data_type: AttributeType::VarChar(4),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        }
+    }
+
+    fn rows(count: i64) -> impl Iterator<Item = HashMap<String, Value>> {
+        (1..=count).map(|id| HashMap::from([
+            (String::from("id"), Value::Int(id)),
+            (String::from("name"), Value::Text(String::from("ab"))),
+        ]))
+    }
+
+    //"INSERT INTO t(id,name) VALUES (1,'ab')" is 38 bytes, so every statement holds one row
+    const ONE_ROW: ImportOptions = ImportOptions{budget: Some(40), transaction: TransactionMode::Single, lenient: false};
+
+    #[test]
+    fn import_test_1() {
+        let mut executor = MockExecutor::default();
+
+        let report = table().import_with(&mut executor, rows(2), ONE_ROW).unwrap();
+
+        assert_eq!(report, ImportReport{rows: 2, chunks: 2});
+        assert_eq!(ImportReport{rows: 12_500, chunks: 3}.to_string(), "  rows  chunks\n12,500       3");
+        assert_eq!(
+            executor.log,
+            vec![
+                "BEGIN",
+                "INSERT INTO t(id,name) VALUES (1,'ab')",
+                "INSERT INTO t(id,name) VALUES (2,'ab')",
+                "COMMIT",
+            ]
+        );
+    }
+
+    #[test]
+    fn import_rollback_test() {
+        let mut executor = MockExecutor{fail_on: Some(2), ..MockExecutor::default()};
+
+        let actual = table().import_with(&mut executor, rows(3), ONE_ROW);
+
+        assert!(
+            matches!(
+                actual,
+                Err(ImportError::Chunk{chunk: 1, ref rows, committed_rows: 0, untouched: true, ..}) if *rows == (1..2)
+            ),
+            "{:?}",
+            actual
+        );
+        assert_eq!(executor.log, vec!["BEGIN", "INSERT INTO t(id,name) VALUES (1,'ab')", "FAILED", "ROLLBACK"]);
+    }
+
+    #[test]
+    fn import_per_chunk_test() {
+        let mut executor = MockExecutor{fail_on: Some(2), ..MockExecutor::default()};
+        let opts = ImportOptions{transaction: TransactionMode::PerChunk, ..ONE_ROW};
+
+        let actual = table().import_with(&mut executor, rows(3), opts);
+
+        assert!(
+            matches!(actual, Err(ImportError::Chunk{chunk: 1, committed_rows: 1, untouched: false, ..})),
+            "{:?}",
+            actual
+        );
+        assert_eq!(
+            executor.log,
+            vec!["BEGIN", "INSERT INTO t(id,name) VALUES (1,'ab')", "COMMIT", "BEGIN", "FAILED", "ROLLBACK"]
+        );
+    }
+
+    #[test]
+    fn import_validation_test() {
+        let mut executor = MockExecutor::default();
+
+        let invalid = rows(3).enumerate().map(|(i, mut row)| {
+            match i {
+                1 => { row.insert(String::from("name"), Value::Text(String::from("abcde"))); },
+                2 => { row.remove("id"); },
+                _ => {},
+            }
+            row
+        });
+
+        let actual = table().import_with(&mut executor, invalid, ONE_ROW);
+
+        assert!(
+            matches!(actual, Err(ImportError::InvalidRow{row: 1, ref reason}) if reason == "value does not fit in name (varchar(4))"),
+            "{:?}",
+            actual
+        );
+        assert!(executor.log.is_empty());
+
+        let missing_id = rows(1).map(|mut row| { row.remove("id"); row });
+        assert!(matches!(
+            table().import_with(&mut executor, missing_id, ONE_ROW),
+            Err(ImportError::InvalidRow{row: 0, ref reason}) if reason == "id cannot be NULL"
+        ));
+
+        let out_of_range = rows(1).map(|mut row| { row.insert(String::from("id"), Value::Int(300)); row });
+        assert!(matches!(
+            table().import_with(&mut executor, out_of_range, ONE_ROW),
+            Err(ImportError::InvalidRow{row: 0, ..})
+        ));
+
+        let unknown = rows(1).map(|mut row| { row.insert(String::from("age"), Value::Int(3)); row });
+        assert!(matches!(
+            table().import_with(&mut executor, unknown, ONE_ROW),
+            Err(ImportError::InvalidRow{row: 0, ref reason}) if reason == "unknown column age"
+        ));
+    }
+
+    #[test]
+    fn import_lenient_test() {
+        let mut executor = MockExecutor::default();
+        let lenient = ImportOptions{lenient: true, ..ONE_ROW};
+
+        let coercible = || rows(1).map(|mut row| {
+            row.insert(String::from("id"), Value::Text(String::from(" 7")));
+            row.insert(String::from("name"), Value::Int(12));
+            row
+        });
+
+        table().import_with(&mut executor, coercible(), ONE_ROW).unwrap();
+        assert_eq!(table().import_with(&mut executor, coercible(), lenient).unwrap(), ImportReport{rows: 1, chunks: 1});
+        assert_eq!(
+            executor.log,
+            vec![
+                "BEGIN",
+                "INSERT INTO t(id,name) VALUES (' 7',12)",
+                "COMMIT",
+                "BEGIN",
+                "INSERT INTO t(id,name) VALUES (7,'12')",
+                "COMMIT",
+            ]
+        );
+
+        let invalid = rows(1).map(|mut row| { row.insert(String::from("id"), Value::Text(String::from("seven"))); row });
+        assert!(matches!(
+            table().import_with(&mut executor, invalid, lenient),
+            Err(ImportError::InvalidRow{row: 0, ref reason}) if reason.starts_with("id: cannot coerce to ")
+        ));
+    }
+
+    /// A CSV file of `rows` rows, generated as it is read.
+    struct Generated {
+        next: usize,
+        rows: usize,
+        pending: Vec<u8>,
+    }
+
+    impl Read for Generated {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.pending.is_empty() && self.next <= self.rows {
+                self.pending = match self.next {
+                    0 => b"id,name\n".to_vec(),
+                    row => format!("{},ab\n", row % 100).into_bytes(),
+                };
+                self.next += 1;
+            }
+
+            let len = buf.len().min(self.pending.len());
+            buf[..len].copy_from_slice(&self.pending[..len]);
+            self.pending.drain(..len);
+
+            Ok(len)
+        }
+    }
+
+    /// Counts the rows read from `source` in `pulled`.
+    struct Counted<S: RowSource> {
+        source: S,
+        pulled: Rc<Cell<usize>>,
+    }
+
+    impl<S: RowSource> Iterator for Counted<S> {
+        type Item = Result<HashMap<String, Value>, SourceError>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let row = self.source.next()?;
+            self.pulled.set(self.pulled.get() + 1);
+            Some(row)
+        }
+    }
+
+    impl<S: RowSource> RowSource for Counted<S> {
+        fn bytes_read(&self) -> u64 {
+            self.source.bytes_read()
+        }
+    }
+
+    #[test]
+    fn import_stream_test() {
+        let mut executor = MockExecutor::default();
+        let source = Counted {
+            source: CsvRows::new(io::BufReader::new(Generated{next: 0, rows: 5000, pending: Vec::new()})),
+            pulled: executor.pulled.clone(),
+        };
+        let opts = StreamOptions {
+            import: ImportOptions{budget: Some(200), transaction: TransactionMode::PerChunk, lenient: false},
+            max_buffered_rows: 64,
+            ..StreamOptions::default()
+        };
+        let mut reports: Vec<ImportProgress> = Vec::new();
+
+        let report = table().import_stream_with(&mut executor, source, opts, |progress| reports.push(*progress)).unwrap();
+
+        let size = "id,name\n".len() + (1..=5000).map(|row| format!("{},ab\n", row % 100).len()).sum::<usize>();
+
+        assert_eq!(report.rows, 5000);
+        assert!(executor.peak_buffered <= 64, "{}", executor.peak_buffered);
+        assert_eq!(reports.len(), 79);
+        assert_eq!(reports[0], ImportProgress{rows: 64, inserted: 64, bytes: reports[0].bytes, errors: 0});
+        assert_eq!(reports[78], ImportProgress{rows: 5000, inserted: 5000, bytes: size as u64, errors: 0});
+    }
+
+    #[test]
+    fn import_stream_resume_test() {
+        //"INSERT INTO t(id,name) VALUES ('0','ab'),('1','ab')" is 51 bytes, so every statement holds two rows
+        let csv = "id,name\n0,ab\n1,ab\n2,abcde\n3,ab\n4,ab\n5,ab\n6,ab\n7,ab\n8,ab\n9,ab\n";
+        let opts = StreamOptions {
+            import: ImportOptions{budget: Some(55), transaction: TransactionMode::PerChunk, lenient: false},
+            max_buffered_rows: 4,
+            max_errors: 1,
+            ..StreamOptions::default()
+        };
+        let insert = |rows: &[u8]| format!(
+            "INSERT INTO t(id,name) VALUES {}",
+            rows.iter().map(|row| format!("('{}','ab')", row)).collect::<Vec<String>>().join(",")
+        );
+
+        //the second batch fails half way
+        let mut executor = MockExecutor{fail_on: Some(4), ..MockExecutor::default()};
+        let mut errors = 0;
+
+        let err = table()
+            .import_stream_with(&mut executor, CsvRows::new(Cursor::new(csv)), opts, |progress| errors = progress.errors)
+            .unwrap_err();
+
+        assert!(
+            matches!(err.error, ImportError::Chunk{chunk: 3, ref rows, committed_rows: 6, untouched: false, ..} if *rows == (7..9)),
+            "{:?}",
+            err
+        );
+        assert_eq!(err.resume_from, 7);
+        assert_eq!(errors, 1);
+
+        let mut executor = MockExecutor::default();
+        let opts = StreamOptions{resume_from: err.resume_from, ..opts};
+
+        let report = table().import_stream_with(&mut executor, CsvRows::new(Cursor::new(csv)), opts, |_| {}).unwrap();
+
+        assert_eq!(report, ImportReport{rows: 3, chunks: 2});
+        assert_eq!(executor.log, vec!["BEGIN", &insert(&[7, 8]), "COMMIT", "BEGIN", &insert(&[9]), "COMMIT"]);
+
+        //in a single transaction, nothing is kept
+        let mut executor = MockExecutor{fail_on: Some(4), ..MockExecutor::default()};
+        let opts = StreamOptions{import: ImportOptions{transaction: TransactionMode::Single, ..opts.import}, resume_from: 0, ..opts};
+
+        let err = table().import_stream_with(&mut executor, CsvRows::new(Cursor::new(csv)), opts, |_| {}).unwrap_err();
+
+        assert!(matches!(err.error, ImportError::Chunk{chunk: 3, committed_rows: 0, untouched: true, ..}), "{:?}", err);
+        assert_eq!(err.resume_from, 0);
+        assert_eq!(executor.log.last().map(String::as_str), Some("ROLLBACK"));
+
+        //too many invalid rows
+        let opts = StreamOptions{max_errors: 0, ..opts};
+        let err = table().import_stream_with(&mut MockExecutor::default(), CsvRows::new(Cursor::new(csv)), opts, |_| {}).unwrap_err();
+
+        assert!(matches!(err.error, ImportError::InvalidRow{row: 2, ..}), "{:?}", err);
+    }
+}
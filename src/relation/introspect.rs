@@ -0,0 +1,910 @@
+use std::{collections::HashMap, fmt};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{data_base::Executor, error::Error, sql::{SQL, QDL, quote_ident, value::Value}};
+
+use super::table::{Table, AttributeType, Constraint, ReferentialAction, Index};
+
+lazy_static! {
+    //quoted identifiers hold any character, a backtick being written twice
+    static ref IDENTIFIER: Regex = Regex::new(r"`((?:[^`]|``)+)`").unwrap();
+    static ref FOREIGN_KEY: Regex = Regex::new(&format!(
+        r"FOREIGN KEY \(`((?:[^`]|``)+)`\) REFERENCES `((?:[^`]|``)+)` \(`((?:[^`]|``)+)`\)(?: ON DELETE ({0}))?(?: ON UPDATE ({0}))?",
+        "RESTRICT|CASCADE|SET NULL|NO ACTION|SET DEFAULT"
+    )).unwrap();
+}
+
+/// The MySQL error codes returned when the account lacks the privilege a statement needs.
+const ACCESS_DENIED_CODES: [u16; 5] = [
+    1044, //ER_DBACCESS_DENIED_ERROR
+    1045, //ER_ACCESS_DENIED_ERROR
+    1142, //ER_TABLEACCESS_DENIED_ERROR
+    1143, //ER_COLUMNACCESS_DENIED_ERROR
+    1227, //ER_SPECIFIC_ACCESS_DENIED_ERROR
+];
+
+/// Returns true if `err` is the server refusing a statement for lack of privileges.
+pub fn is_access_denied(err: &Error) -> bool {
+    err.code().is_some_and(|code| ACCESS_DENIED_CODES.contains(&code))
+}
+
+/// The column, referenced table, referenced column, delete rule and update rule of a foreign key.
+pub type ForeignKeyRow = (String, String, String, String, String);
+
+/// The index name, uniqueness and column of a part of an index.
+pub type IndexRow = (String, bool, Option<String>);
+
+/// Returns the action of a rule read from the server, or `None` if it is the default (see `ReferentialAction::is_default`).
+fn read_rule(rule: Option<&str>) -> Option<ReferentialAction> {
+    rule.and_then(ReferentialAction::parse).filter(|action| !action.is_default())
+}
+
+/// The queries used to discover a table's foreign keys, so the fallbacks can be exercised without a server.
+pub trait IntrospectExecutor {
+    /// Returns the statement of `SHOW CREATE TABLE` for `table`.
+    fn show_create_table(&self, table: &str) -> Result<String, Error>;
+    /// Returns the column, referenced table, referenced column, delete rule and update rule of every foreign key of
+    /// `table`, from `information_schema.KEY_COLUMN_USAGE` and `REFERENTIAL_CONSTRAINTS`, ordered by constraint then
+    /// by position in the constraint.
+    fn key_column_usage(&self, table: &str) -> Result<Vec<ForeignKeyRow>, Error>;
+    /// Returns the name and expression of every `CHECK` constraint of `table`, from
+    /// `information_schema.CHECK_CONSTRAINTS`, ordered by name.
+    fn check_constraints(&self, table: &str) -> Result<Vec<(String, String)>, Error>;
+    /// Returns the name, uniqueness and column of every part of every index of `table`, from `SHOW INDEX`, ordered by
+    /// index then by position in the index. The column is `None` for a part indexing an expression.
+    fn show_index(&self, table: &str) -> Result<Vec<IndexRow>, Error>;
+}
+
+impl<E: Executor> IntrospectExecutor for E {
+    fn show_create_table(&self, table: &str) -> Result<String, Error> {
+        let statement = SQL::new(&format!("SHOW CREATE TABLE {}", quote_ident(table))).unwrap();
+
+        let rows = self.execute(&statement, |row| row.map(|row| row.get::<String, usize>(1).unwrap_or_default()))?;
+
+        rows.into_iter()
+            .collect::<Result<Vec<String>, mysql::Error>>()
+            .map(|rows| rows.concat())
+            .map_err(|err| Error::query(&statement.to_string(), err))
+    }
+
+    fn key_column_usage(&self, table: &str) -> Result<Vec<ForeignKeyRow>, Error> {
+        let statement = SQL::Select(QDL(format!(
+                "SELECT k.COLUMN_NAME, k.REFERENCED_TABLE_NAME, k.REFERENCED_COLUMN_NAME, r.DELETE_RULE, r.UPDATE_RULE \
+                FROM information_schema.KEY_COLUMN_USAGE k \
+                JOIN information_schema.REFERENTIAL_CONSTRAINTS r \
+                ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME \
+                WHERE k.TABLE_SCHEMA = DATABASE() AND k.TABLE_NAME = {} AND k.REFERENCED_TABLE_NAME IS NOT NULL \
+                ORDER BY k.CONSTRAINT_NAME, k.ORDINAL_POSITION",
+                Value::Text(table.to_string())
+            )));
+
+        let rows = self.execute(&statement, |row| row.map(mysql::from_row::<ForeignKeyRow>))?;
+
+        rows.into_iter().collect::<Result<_, _>>().map_err(|err| Error::query(&statement.to_string(), err))
+    }
+
+    fn check_constraints(&self, table: &str) -> Result<Vec<(String, String)>, Error> {
+        let statement = SQL::Select(QDL(format!(
+                "SELECT c.CONSTRAINT_NAME, c.CHECK_CLAUSE \
+                FROM information_schema.TABLE_CONSTRAINTS t \
+                JOIN information_schema.CHECK_CONSTRAINTS c \
+                ON c.CONSTRAINT_SCHEMA = t.CONSTRAINT_SCHEMA AND c.CONSTRAINT_NAME = t.CONSTRAINT_NAME \
+                WHERE t.TABLE_SCHEMA = DATABASE() AND t.TABLE_NAME = {} AND t.CONSTRAINT_TYPE = 'CHECK' \
+                ORDER BY c.CONSTRAINT_NAME",
+                Value::Text(table.to_string())
+            )));
+
+        let rows = self.execute(&statement, |row| row.map(mysql::from_row::<(String, String)>))?;
+
+        rows.into_iter().collect::<Result<_, _>>().map_err(|err| Error::query(&statement.to_string(), err))
+    }
+
+    fn show_index(&self, table: &str) -> Result<Vec<IndexRow>, Error> {
+        //read by name, as the columns of SHOW INDEX differ between versions
+        let statement = SQL::new(&format!("SHOW INDEX FROM {}", quote_ident(table))).unwrap();
+
+        let rows = self.execute(&statement, |row| row.map(|row| {
+            let non_unique: i64 = row.get("Non_unique").unwrap_or_default();
+
+            (row.get("Key_name").unwrap_or_default(), non_unique == 0, row.get("Column_name").unwrap_or_default())
+        }))?;
+
+        rows.into_iter().collect::<Result<_, _>>().map_err(|err| Error::query(&statement.to_string(), err))
+    }
+}
+
+/// The columns of every base table of the current database, named as those of `SHOW FULL COLUMNS` so
+/// `Table::from_column_rows` reads them alike, after the name of their table.
+const SCHEMA_COLUMNS: &str = "SELECT c.TABLE_NAME, c.COLUMN_NAME AS `Field`, c.COLUMN_TYPE AS `Type`, \
+    c.COLLATION_NAME AS `Collation`, c.IS_NULLABLE AS `Null`, c.COLUMN_KEY AS `Key`, c.COLUMN_DEFAULT AS `Default`, \
+    c.EXTRA AS `Extra`, c.PRIVILEGES AS `Privileges`, c.COLUMN_COMMENT AS `Comment` \
+    FROM information_schema.COLUMNS c \
+    JOIN information_schema.TABLES t ON t.TABLE_SCHEMA = c.TABLE_SCHEMA AND t.TABLE_NAME = c.TABLE_NAME \
+    WHERE c.TABLE_SCHEMA = DATABASE() AND t.TABLE_TYPE = 'BASE TABLE' \
+    ORDER BY c.TABLE_NAME, c.ORDINAL_POSITION";
+
+/// `IntrospectExecutor::key_column_usage` for every table of the current database, after the name of their table.
+const SCHEMA_FOREIGN_KEYS: &str = "SELECT k.TABLE_NAME, k.COLUMN_NAME, k.REFERENCED_TABLE_NAME, \
+    k.REFERENCED_COLUMN_NAME, r.DELETE_RULE, r.UPDATE_RULE \
+    FROM information_schema.KEY_COLUMN_USAGE k \
+    JOIN information_schema.REFERENTIAL_CONSTRAINTS r \
+    ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME \
+    WHERE k.TABLE_SCHEMA = DATABASE() AND k.REFERENCED_TABLE_NAME IS NOT NULL \
+    ORDER BY k.TABLE_NAME, k.CONSTRAINT_NAME, k.ORDINAL_POSITION";
+
+/// `IntrospectExecutor::check_constraints` for every table of the current database, after the name of their table.
+const SCHEMA_CHECKS: &str = "SELECT t.TABLE_NAME, c.CONSTRAINT_NAME, c.CHECK_CLAUSE \
+    FROM information_schema.TABLE_CONSTRAINTS t \
+    JOIN information_schema.CHECK_CONSTRAINTS c \
+    ON c.CONSTRAINT_SCHEMA = t.CONSTRAINT_SCHEMA AND c.CONSTRAINT_NAME = t.CONSTRAINT_NAME \
+    WHERE t.TABLE_SCHEMA = DATABASE() AND t.CONSTRAINT_TYPE = 'CHECK' \
+    ORDER BY t.TABLE_NAME, c.CONSTRAINT_NAME";
+
+/// `IntrospectExecutor::show_index` for every table of the current database, from `information_schema.STATISTICS`,
+/// after the name of their table.
+const SCHEMA_INDEXES: &str = "SELECT TABLE_NAME, INDEX_NAME, NON_UNIQUE = 0, COLUMN_NAME \
+    FROM information_schema.STATISTICS \
+    WHERE TABLE_SCHEMA = DATABASE() \
+    ORDER BY TABLE_NAME, INDEX_NAME, SEQ_IN_INDEX";
+
+/// Runs the query `statement`, grouping the rows it returns by the name of their table, which `read` returns with
+/// each row.
+fn by_table<E, T, F>(db: &E, statement: &str, read: F) -> Result<HashMap<String, Vec<T>>, Error>
+where E: Executor, F: Fn(mysql::Row) -> Result<(String, T), mysql::Error> {
+    let query = SQL::Select(QDL(statement.to_string()));
+
+    let rows = db.execute(&query, |row| row.and_then(&read))?
+        .into_iter()
+        .collect::<Result<Vec<(String, T)>, mysql::Error>>()
+        .map_err(|err| Error::query(statement, err))?;
+
+    let mut tables: HashMap<String, Vec<T>> = HashMap::new();
+    for (table, row) in rows {
+        tables.entry(table).or_default().push(row);
+    }
+
+    Ok(tables)
+}
+
+/// Answers the queries of `IntrospectExecutor` for one table at a time from rows read for every table at once, for
+/// `load_schema`.
+///
+/// A part that could not be read for every table at once is `None`, and read table by table from `executor` instead,
+/// so its fallbacks and warnings are those of `Table::from_db_report_in`.
+struct Preloaded<'a, E> {
+    executor: &'a E,
+    foreign_keys: Option<HashMap<String, Vec<ForeignKeyRow>>>,
+    checks: Option<HashMap<String, Vec<(String, String)>>>,
+    indexes: Option<HashMap<String, Vec<IndexRow>>>,
+}
+
+impl<E: IntrospectExecutor> IntrospectExecutor for Preloaded<'_, E> {
+    fn show_create_table(&self, table: &str) -> Result<String, Error> {
+        self.executor.show_create_table(table)
+    }
+
+    fn key_column_usage(&self, table: &str) -> Result<Vec<ForeignKeyRow>, Error> {
+        match &self.foreign_keys {
+            Some(tables) => Ok(tables.get(table).cloned().unwrap_or_default()),
+            None => self.executor.key_column_usage(table),
+        }
+    }
+
+    fn check_constraints(&self, table: &str) -> Result<Vec<(String, String)>, Error> {
+        match &self.checks {
+            Some(tables) => Ok(tables.get(table).cloned().unwrap_or_default()),
+            None => self.executor.check_constraints(table),
+        }
+    }
+
+    fn show_index(&self, table: &str) -> Result<Vec<IndexRow>, Error> {
+        match &self.indexes {
+            Some(tables) => Ok(tables.get(table).cloned().unwrap_or_default()),
+            None => self.executor.show_index(table),
+        }
+    }
+}
+
+/// Returns every base table of the current database of `db`, ordered by name, each with the warnings raised reading
+/// it.
+///
+/// The tables are those `Table::from_db_report_in` reads, but the columns of every table are read with one query to
+/// `information_schema.COLUMNS`, and their foreign keys, checks and indexes with one query each, rather than with a few
+/// queries for every table. A part that cannot be read that way, as when `information_schema` is denied, is read
+/// table by table with the fallbacks of `Table::from_db_report_in`.
+///
+/// # Errors
+///
+/// Returns an `Error` if the columns could not be read.
+pub fn load_schema<E: Executor>(db: &E) -> Result<Vec<(Table, LoadReport)>, Error> {
+    let columns = by_table(db, SCHEMA_COLUMNS, |row| match row.get_opt::<String, usize>(0) {
+        Some(Ok(table)) => Ok((table, row)),
+        _ => Err(mysql::Error::FromRowError(row)),
+    })?;
+
+    let preloaded = Preloaded {
+        executor: db,
+        foreign_keys: by_table(db, SCHEMA_FOREIGN_KEYS, |row| {
+            mysql::from_row_opt::<(String, String, String, String, String, String)>(row)
+                .map(|(table, column, referenced_table, referenced_column, delete_rule, update_rule)| {
+                    (table, (column, referenced_table, referenced_column, delete_rule, update_rule))
+                })
+                .map_err(|err| mysql::Error::FromRowError(err.0))
+        }).ok(),
+        checks: by_table(db, SCHEMA_CHECKS, |row| {
+            mysql::from_row_opt::<(String, String, String)>(row)
+                .map(|(table, name, expression)| (table, (name, expression)))
+                .map_err(|err| mysql::Error::FromRowError(err.0))
+        }).ok(),
+        indexes: by_table(db, SCHEMA_INDEXES, |row| {
+            mysql::from_row_opt::<(String, String, bool, Option<String>)>(row)
+                .map(|(table, name, unique, column)| (table, (name, unique, column)))
+                .map_err(|err| mysql::Error::FromRowError(err.0))
+        }).ok(),
+    };
+
+    let mut tables: Vec<(String, Vec<mysql::Row>)> = columns.into_iter().collect();
+    tables.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(
+        tables.into_iter()
+            .map(|(name, columns)| Table::from_column_rows(&preloaded, &name, columns))
+            .collect()
+    )
+}
+
+/// Represents a part of a table's schema that could not be read the usual way.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadWarning {
+    /// `information_schema` was denied, so foreign keys were parsed from `SHOW CREATE TABLE`.
+    ForeignKeysFromCreateTable{table: String},
+    /// Foreign keys could not be read at all and are missing from the table.
+    ForeignKeysOmitted{table: String, reason: String},
+    /// `CHECK` constraints could not be read, as on servers older than MySQL 8.0.16, and are missing from the table.
+    ChecksOmitted{table: String, reason: String},
+    /// The `CHECK` constraint `name` does not constrain a single column, so it has no attribute to go on and is missing
+    /// from the table.
+    CheckOmitted{table: String, name: String},
+    /// Indexes could not be read and are missing from the table.
+    IndexesOmitted{table: String, reason: String},
+    /// The index `name` indexes an expression, which `Index` cannot represent, and is missing from the table.
+    IndexOmitted{table: String, name: String},
+    /// The type of `column` is not one the crate models, so the column is kept as an `AttributeType::Unknown` holding
+    /// `raw_type`, and its values are not checked.
+    UnknownType{table: String, column: String, raw_type: String},
+}
+
+impl fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadWarning::ForeignKeysFromCreateTable{table} => {
+                write!(f, "information_schema was denied, foreign keys of {} were parsed from SHOW CREATE TABLE", table)
+            },
+            LoadWarning::ForeignKeysOmitted{table, reason} => {
+                write!(f, "foreign keys of {} were omitted: {}", table, reason)
+            },
+            LoadWarning::ChecksOmitted{table, reason} => {
+                write!(f, "checks of {} were omitted: {}", table, reason)
+            },
+            LoadWarning::CheckOmitted{table, name} => {
+                write!(f, "check {} of {} was omitted, as it does not constrain a single column", name, table)
+            },
+            LoadWarning::IndexesOmitted{table, reason} => {
+                write!(f, "indexes of {} were omitted: {}", table, reason)
+            },
+            LoadWarning::IndexOmitted{table, name} => {
+                write!(f, "index {} of {} was omitted, as it indexes an expression", name, table)
+            },
+            LoadWarning::UnknownType{table, column, raw_type} => {
+                write!(f, "column {} of {} has the unsupported type {}, which is kept as written", column, table, raw_type)
+            },
+        }
+    }
+}
+
+/// The warnings raised while loading a table's schema, as returned by `Table::from_db_report`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LoadReport {
+    pub warnings: Vec<LoadWarning>,
+}
+
+impl fmt::Display for LoadReport {
+    /// Writes one warning per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let warnings: Vec<String> = self.warnings.iter().map(|warning| warning.to_string()).collect();
+
+        f.write_str(&warnings.join("\n"))
+    }
+}
+
+/// Returns the foreign keys of a `CREATE TABLE` statement, with the name of their column, in the order they are declared.
+///
+/// Columns that are indexed without referencing another table have no foreign key, and are left out.
+pub fn parse_foreign_keys(create: &str) -> Vec<(String, Constraint)> {
+    let unquote = |identifier: &str| identifier.replace("``", "`");
+
+    FOREIGN_KEY.captures_iter(create)
+        .map(|captures| (
+            unquote(&captures[1]),
+            Constraint::ForeignKey{
+                table_name: unquote(&captures[2]),
+                attribute_name: unquote(&captures[3]),
+                on_delete: read_rule(captures.get(4).map(|rule| rule.as_str())),
+                on_update: read_rule(captures.get(5).map(|rule| rule.as_str())),
+            }
+        ))
+        .collect()
+}
+
+/// Returns the foreign keys of `table`, with the name of their column. A column can have several.
+///
+/// They are read from `information_schema.KEY_COLUMN_USAGE`. If that is denied they are parsed from
+/// `SHOW CREATE TABLE` (see `parse_foreign_keys`). If neither can be read, no foreign keys are returned. Both
+/// fallbacks are recorded in `report`.
+pub fn foreign_keys<E: IntrospectExecutor>(executor: &E, table: &str, report: &mut LoadReport) -> Vec<(String, Constraint)> {
+    let omitted = |report: &mut LoadReport, err: &Error| {
+        report.warnings.push(LoadWarning::ForeignKeysOmitted{table: table.to_string(), reason: err.to_string()});
+        Vec::new()
+    };
+
+    let err = match executor.key_column_usage(table) {
+        Ok(rows) => {
+            return rows.into_iter()
+                .map(|(column, table_name, attribute_name, delete_rule, update_rule)| {
+                    let on_delete = read_rule(Some(&delete_rule));
+                    let on_update = read_rule(Some(&update_rule));
+
+                    (column, Constraint::ForeignKey{table_name, attribute_name, on_delete, on_update})
+                })
+                .collect()
+        },
+        Err(err) => err,
+    };
+
+    if !is_access_denied(&err) {
+        return omitted(report, &err);
+    }
+
+    match executor.show_create_table(table) {
+        Ok(create) => {
+            report.warnings.push(LoadWarning::ForeignKeysFromCreateTable{table: table.to_string()});
+
+            parse_foreign_keys(&create)
+        },
+        Err(err) => omitted(report, &err),
+    }
+}
+
+impl Table {
+    /// Adds the foreign keys of the table to the attributes named in `columns` (see `foreign_keys`).
+    ///
+    /// Nothing is queried if `columns` is empty.
+    pub(super) fn resolve_foreign_keys<E: IntrospectExecutor>(&mut self, executor: &E, columns: &[String], report: &mut LoadReport) {
+        if columns.is_empty() {
+            return;
+        }
+
+        for (column, foreign_key) in foreign_keys(executor, &self.name, report) {
+            let attr = self.attributes
+                .iter_mut()
+                .find(|attr| attr.name == column && columns.contains(&attr.name));
+
+            if let Some(attr) = attr {
+                attr.constraint.insert(foreign_key);
+            }
+        }
+    }
+
+    /// Adds the `CHECK` constraints of the table to the attributes they constrain.
+    ///
+    /// A check goes on the only column its expression names. Checks naming several columns or none are left out, as
+    /// are all checks if they cannot be read, with a warning in `report`.
+    pub(super) fn resolve_checks<E: IntrospectExecutor>(&mut self, executor: &E, report: &mut LoadReport) {
+        let checks = match executor.check_constraints(&self.name) {
+            Ok(checks) => checks,
+            Err(err) => {
+                report.warnings.push(LoadWarning::ChecksOmitted{table: self.name.clone(), reason: err.to_string()});
+                return;
+            },
+        };
+
+        for (name, expression) in checks {
+            let mut columns: Vec<String> = IDENTIFIER.captures_iter(&expression)
+                .map(|captures| captures[1].replace("``", "`"))
+                .filter(|column| self.attributes.iter().any(|attr| attr.name == *column))
+                .collect();
+            columns.sort();
+            columns.dedup();
+
+            match columns.as_slice() {
+                [column] => {
+                    let attr = self.attributes.iter_mut().find(|attr| attr.name == *column).unwrap();
+                    attr.constraint.insert(Constraint::Check(expression));
+                },
+                _ => report.warnings.push(LoadWarning::CheckOmitted{table: self.name.clone(), name}),
+            }
+        }
+    }
+
+    /// Sets the `indexes` of the table from `SHOW INDEX`, ordered by name.
+    ///
+    /// The primary key and unique indexes over a single column are left out, as they are read from the columns (see
+    /// `Table::indexes`). Indexes over an expression are left out, as are all indexes if they cannot be read, with a
+    /// warning in `report`.
+    pub(super) fn resolve_indexes<E: IntrospectExecutor>(&mut self, executor: &E, report: &mut LoadReport) {
+        let rows = match executor.show_index(&self.name) {
+            Ok(rows) => rows,
+            Err(err) => {
+                report.warnings.push(LoadWarning::IndexesOmitted{table: self.name.clone(), reason: err.to_string()});
+                return;
+            },
+        };
+
+        let mut indexes: Vec<(Index, bool)> = Vec::new();
+
+        for (name, unique, column) in rows.into_iter().filter(|(name, ..)| name != "PRIMARY") {
+            if !matches!(indexes.last(), Some((index, _)) if index.name == name) {
+                indexes.push((Index{name: name.clone(), columns: Vec::new(), unique}, false));
+            }
+
+            let (index, expression) = indexes.last_mut().unwrap();
+
+            match column {
+                Some(column) => index.columns.push(column),
+                None => *expression = true,
+            }
+        }
+
+        for (index, expression) in indexes {
+            match (expression, index.unique && index.columns.len() == 1) {
+                (true, _) => report.warnings.push(LoadWarning::IndexOmitted{table: self.name.clone(), name: index.name}),
+                (false, true) => {},
+                (false, false) => self.indexes.push(index),
+            }
+        }
+
+        //servers list indexes in different orders, and `load_schema` reads them by name
+        self.indexes.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    /// Warns in `report` about every attribute whose type is an `AttributeType::Unknown`.
+    pub(super) fn report_unknown_types(&self, report: &mut LoadReport) {
+        for attr in &self.attributes {
+            if let AttributeType::Unknown(_) = attr.data_type {
+                report.warnings.push(LoadWarning::UnknownType{
+                    table: self.name.clone(),
+                    column: attr.name.clone(),
+                    raw_type: attr.type_definition(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::HashSet};
+
+    use mysql::MySqlError;
+
+    use crate::{data_base::Executor, error::Error, sql::SQL};
+
+    use super::super::table::{Table, Attribute, AttributeType, Constraint};
+    use super::super::table::ReferentialAction;
+    use super::super::table::Index;
+    use super::{IntrospectExecutor, ForeignKeyRow, IndexRow, LoadReport, LoadWarning, is_access_denied, load_schema, parse_foreign_keys};
+
+    const CREATE: &str = "CREATE TABLE `post` (\n  \
+        `id` int NOT NULL,\n  \
+        `author` int DEFAULT NULL,\n  \
+        `editor` int DEFAULT NULL,\n  \
+        KEY `author` (`author`),\n  \
+        KEY `editor` (`editor`),\n  \
+        CONSTRAINT `post_ibfk_1` FOREIGN KEY (`author`) REFERENCES `user` (`id`)\n\
+        ) ENGINE=InnoDB";
+
+    fn denied(code: u16) -> Error {
+        Error::query("SHOW CREATE TABLE post", mysql::Error::MySqlError(MySqlError{
+            state: String::from("42000"),
+            message: String::from("denied"),
+            code,
+        }))
+    }
+
+    /// Answers `SHOW CREATE TABLE` and `information_schema` queries, or fails them with the given errors.
+    struct MockExecutor {
+        show_create: Option<u16>,
+        key_column_usage: Option<u16>,
+        check_constraints: Option<u16>,
+        log: RefCell<Vec<&'static str>>,
+    }
+
+    impl MockExecutor {
+        fn new(show_create: Option<u16>, key_column_usage: Option<u16>) -> MockExecutor {
+            MockExecutor{show_create, key_column_usage, check_constraints: None, log: RefCell::default()}
+        }
+    }
+
+    impl IntrospectExecutor for MockExecutor {
+        fn show_create_table(&self, _table: &str) -> Result<String, Error> {
+            self.log.borrow_mut().push("SHOW CREATE TABLE");
+
+            match self.show_create {
+                Some(code) => Err(denied(code)),
+                None => Ok(String::from(CREATE)),
+            }
+        }
+        fn key_column_usage(&self, _table: &str) -> Result<Vec<ForeignKeyRow>, Error> {
+            self.log.borrow_mut().push("KEY_COLUMN_USAGE");
+
+            match self.key_column_usage {
+                Some(code) => Err(denied(code)),
+                None => Ok(vec![(
+                    String::from("author"),
+                    String::from("user"),
+                    String::from("id"),
+                    String::from("NO ACTION"),
+                    String::from("RESTRICT"),
+                )]),
+            }
+        }
+        fn check_constraints(&self, _table: &str) -> Result<Vec<(String, String)>, Error> {
+            self.log.borrow_mut().push("CHECK_CONSTRAINTS");
+
+            match self.check_constraints {
+                Some(code) => Err(denied(code)),
+                None => Ok(vec![
+                    (String::from("post_chk_1"), String::from("(`author` > 0)")),
+                    (String::from("post_chk_2"), String::from("(`author` <> `editor`)")),
+                    (String::from("post_chk_3"), String::from("(`editor` between 1 and `editor` + 10)")),
+                ]),
+            }
+        }
+        fn show_index(&self, _table: &str) -> Result<Vec<IndexRow>, Error> {
+            self.log.borrow_mut().push("SHOW INDEX");
+
+            let part = |name: &str, unique: bool, column: Option<&str>| (String::from(name), unique, column.map(String::from));
+
+            Ok(vec![
+                part("PRIMARY", true, Some("id")),
+                part("author", false, Some("author")),
+                part("author_editor", true, Some("author")),
+                part("author_editor", true, Some("editor")),
+                part("editor", true, Some("editor")),
+                part("editor_year", false, None),
+            ])
+        }
+    }
+
+    fn post() -> Table {
+        let attr = |name: &str| Attribute{
+            name: String::from(name),
+            data_type: AttributeType::Int(11),
+            raw_type: String::new(),
+            constraint: HashSet::new(),
+        };
+
+        Table{
+            name: String::from("post"),
+            attributes: vec![attr("id"), attr("author"), attr("editor")],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        }
+    }
+
+    fn author_key() -> Constraint {
+        Constraint::ForeignKey{table_name: String::from("user"), attribute_name: String::from("id"), on_delete: None, on_update: None}
+    }
+
+    fn key_of<'a>(foreign_keys: &'a [(String, Constraint)], column: &str) -> Option<&'a Constraint> {
+        foreign_keys.iter().find(|(name, _)| name == column).map(|(_, key)| key)
+    }
+
+    #[test]
+    fn parse_foreign_keys_test() {
+        let foreign_keys = parse_foreign_keys(CREATE);
+
+        assert_eq!(foreign_keys.len(), 1);
+        assert_eq!(key_of(&foreign_keys, "author"), Some(&author_key()));
+    }
+
+    #[test]
+    fn parse_foreign_keys_action_test() {
+        let create = "CREATE TABLE `comment` (\n  \
+            `post` int DEFAULT NULL,\n  \
+            `author` int DEFAULT NULL,\n  \
+            `editor` int DEFAULT NULL,\n  \
+            CONSTRAINT `comment_ibfk_1` FOREIGN KEY (`post`) REFERENCES `post` (`id`) ON DELETE CASCADE ON UPDATE SET NULL,\n  \
+            CONSTRAINT `comment_ibfk_2` FOREIGN KEY (`author`) REFERENCES `user` (`id`) ON UPDATE CASCADE,\n  \
+            CONSTRAINT `comment_ibfk_3` FOREIGN KEY (`editor`) REFERENCES `user` (`id`) ON DELETE NO ACTION\n\
+            ) ENGINE=InnoDB";
+
+        let foreign_keys = parse_foreign_keys(create);
+        let actions = |column: &str| match key_of(&foreign_keys, column) {
+            Some(Constraint::ForeignKey{on_delete, on_update, ..}) => (*on_delete, *on_update),
+            _ => panic!("no foreign key on {}", column),
+        };
+
+        assert_eq!(actions("post"), (Some(ReferentialAction::Cascade), Some(ReferentialAction::SetNull)));
+        assert_eq!(actions("author"), (None, Some(ReferentialAction::Cascade)));
+        //the default action is the same as none
+        assert_eq!(actions("editor"), (None, None));
+    }
+
+    #[test]
+    fn parse_foreign_keys_identifier_test() {
+        let create = "CREATE TABLE `audit_log` (\n  \
+            `id` int NOT NULL,\n  \
+            `created_by` int DEFAULT NULL,\n  \
+            `order id` int DEFAULT NULL,\n  \
+            `batch_no` int DEFAULT NULL,\n  \
+            KEY `batch_no` (`batch_no`),\n  \
+            CONSTRAINT `audit_log_ibfk_1` FOREIGN KEY (`created_by`) REFERENCES `user_account` (`account_id`),\n  \
+            CONSTRAINT `audit_log_ibfk_2` FOREIGN KEY (`order id`) REFERENCES `we``ird$table` (`id`)\n\
+            ) ENGINE=InnoDB";
+
+        let foreign_keys = parse_foreign_keys(create);
+
+        assert_eq!(
+            key_of(&foreign_keys, "created_by"),
+            Some(&Constraint::ForeignKey{table_name: String::from("user_account"), attribute_name: String::from("account_id"), on_delete: None, on_update: None})
+        );
+        assert_eq!(
+            key_of(&foreign_keys, "order id"),
+            Some(&Constraint::ForeignKey{table_name: String::from("we`ird$table"), attribute_name: String::from("id"), on_delete: None, on_update: None})
+        );
+
+        //indexed, but not a foreign key
+        assert_eq!(key_of(&foreign_keys, "batch_no"), None);
+        assert_eq!(foreign_keys.len(), 2);
+    }
+
+    #[test]
+    fn is_access_denied_test() {
+        assert!(is_access_denied(&denied(1142)));
+        assert!(is_access_denied(&denied(1044)));
+        assert!(!is_access_denied(&denied(1146)));
+        assert!(!is_access_denied(&Error::query("SHOW INDEX FROM post", mysql::Error::FromValueError(mysql::Value::NULL))));
+    }
+
+    #[test]
+    fn resolve_foreign_keys_test_1() {
+        let executor = MockExecutor::new(None, None);
+        let mut report = LoadReport::default();
+        let mut table = post();
+
+        table.resolve_foreign_keys(&executor, &[String::from("author"), String::from("editor")], &mut report);
+
+        assert!(table.attributes[1].constraint.contains(&author_key()));
+        assert!(table.attributes[2].constraint.is_empty());
+        assert_eq!(report, LoadReport::default());
+        assert_eq!(*executor.log.borrow(), vec!["KEY_COLUMN_USAGE"]);
+
+        let executor = MockExecutor::new(None, None);
+        table.resolve_foreign_keys(&executor, &[], &mut report);
+
+        assert!(executor.log.borrow().is_empty());
+    }
+
+    #[test]
+    fn resolve_foreign_keys_test_2() {
+        let executor = MockExecutor::new(None, Some(1142));
+        let mut report = LoadReport::default();
+        let mut table = post();
+
+        table.resolve_foreign_keys(&executor, &[String::from("author")], &mut report);
+
+        assert!(table.attributes[1].constraint.contains(&author_key()));
+        assert_eq!(report.warnings, vec![LoadWarning::ForeignKeysFromCreateTable{table: String::from("post")}]);
+        assert_eq!(
+            report.to_string(),
+            "information_schema was denied, foreign keys of post were parsed from SHOW CREATE TABLE"
+        );
+        assert_eq!(*executor.log.borrow(), vec!["KEY_COLUMN_USAGE", "SHOW CREATE TABLE"]);
+    }
+
+    #[test]
+    fn resolve_foreign_keys_test_3() {
+        let executor = MockExecutor::new(Some(1142), Some(1044));
+        let mut report = LoadReport::default();
+        let mut table = post();
+
+        table.resolve_foreign_keys(&executor, &[String::from("author")], &mut report);
+
+        assert!(table.attributes[1].constraint.is_empty());
+        assert!(matches!(
+            report.warnings.as_slice(),
+            [LoadWarning::ForeignKeysOmitted{table, ..}] if table == "post"
+        ));
+
+        //errors other than access denied are not retried against SHOW CREATE TABLE
+        let executor = MockExecutor::new(None, Some(1146));
+        let mut report = LoadReport::default();
+
+        table.resolve_foreign_keys(&executor, &[String::from("author")], &mut report);
+
+        assert!(table.attributes[1].constraint.is_empty());
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(*executor.log.borrow(), vec!["KEY_COLUMN_USAGE"]);
+    }
+
+    #[test]
+    fn resolve_checks_test() {
+        let executor = MockExecutor::new(None, None);
+        let mut report = LoadReport::default();
+        let mut table = post();
+
+        table.resolve_checks(&executor, &mut report);
+
+        assert_eq!(table.attributes[1].constraint, HashSet::from([Constraint::Check(String::from("(`author` > 0)"))]));
+        assert_eq!(
+            table.attributes[2].constraint,
+            HashSet::from([Constraint::Check(String::from("(`editor` between 1 and `editor` + 10)"))])
+        );
+        assert_eq!(report.warnings, vec![LoadWarning::CheckOmitted{table: String::from("post"), name: String::from("post_chk_2")}]);
+
+        //servers without CHECK_CONSTRAINTS
+        let mut executor = MockExecutor::new(None, None);
+        executor.check_constraints = Some(1109);
+        let mut report = LoadReport::default();
+        let mut table = post();
+
+        table.resolve_checks(&executor, &mut report);
+
+        assert!(table.attributes.iter().all(|attr| attr.constraint.is_empty()));
+        assert!(matches!(
+            report.warnings.as_slice(),
+            [LoadWarning::ChecksOmitted{table, ..}] if table == "post"
+        ));
+    }
+
+    #[test]
+    fn resolve_indexes_test() {
+        let executor = MockExecutor::new(None, None);
+        let mut report = LoadReport::default();
+        let mut table = post();
+
+        table.resolve_indexes(&executor, &mut report);
+
+        assert_eq!(
+            table.indexes,
+            vec![
+                Index{name: String::from("author"), columns: vec![String::from("author")], unique: false},
+                Index{name: String::from("author_editor"), columns: vec![String::from("author"), String::from("editor")], unique: true},
+            ]
+        );
+        assert_eq!(report.warnings, vec![LoadWarning::IndexOmitted{table: String::from("post"), name: String::from("editor_year")}]);
+    }
+
+    #[test]
+    fn report_unknown_types_test() {
+        let mut report = LoadReport::default();
+        let mut table = post();
+
+        table.attributes.push(Attribute{
+            name: String::from("area"),
+            data_type: AttributeType::Unknown(String::from("geometry")),
+            raw_type: String::from("geometry"),
+            constraint: HashSet::new(),
+        });
+        table.report_unknown_types(&mut report);
+
+        assert_eq!(
+            report.warnings,
+            vec![LoadWarning::UnknownType{table: String::from("post"), column: String::from("area"), raw_type: String::from("geometry")}]
+        );
+        assert_eq!(report.to_string(), "column area of post has the unsupported type geometry, which is kept as written");
+    }
+
+    /// Answers the queries of `load_schema` for the tables `user` and `post`, recording them, optionally denying
+    /// `information_schema.KEY_COLUMN_USAGE`.
+    #[derive(Default)]
+    struct SchemaExecutor {
+        deny_key_column_usage: bool,
+        log: RefCell<Vec<String>>,
+    }
+
+    impl SchemaExecutor {
+        fn row(values: Vec<mysql::Value>, names: &[&str]) -> mysql::Row {
+            use std::sync::Arc;
+
+            use mysql::{Column, consts::ColumnType};
+
+            let columns: Arc<[Column]> = names.iter()
+                .map(|name| Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(name.as_bytes()))
+                .collect();
+
+            mysql_common::row::new_row(values, columns)
+        }
+
+        fn text(values: &[Option<&str>]) -> Vec<mysql::Value> {
+            values.iter().map(|value| value.map_or(mysql::Value::NULL, mysql::Value::from)).collect()
+        }
+    }
+
+    impl Executor for SchemaExecutor {
+        fn execute<E, F>(&self, cmd: &SQL, row_map: F) -> Result<Vec<E>, Error> where F : FnMut(Result<mysql::Row, mysql::Error>) -> E {
+            const COLUMNS: [&str; 10] = ["TABLE_NAME", "Field", "Type", "Collation", "Null", "Key", "Default", "Extra", "Privileges", "Comment"];
+
+            let statement = cmd.to_string();
+            self.log.borrow_mut().push(statement.clone());
+
+            let column = |values: &[Option<&str>]| SchemaExecutor::row(SchemaExecutor::text(values), &COLUMNS);
+
+            let rows = match statement.as_str() {
+                statement if statement.contains("information_schema.COLUMNS") => vec![
+                    column(&[Some("user"), Some("id"), Some("int"), None, Some("NO"), Some("PRI"), None, Some(""), None, Some("")]),
+                    column(&[Some("post"), Some("id"), Some("int"), None, Some("NO"), Some("PRI"), None, Some(""), None, Some("")]),
+                    column(&[Some("post"), Some("author"), Some("int"), None, Some("YES"), Some("MUL"), None, Some(""), None, Some("")]),
+                    column(&[Some("user"), Some("email"), Some("varchar(255)"), None, Some("NO"), Some("UNI"), None, Some(""), None, Some("")]),
+                ],
+                statement if statement.contains("KEY_COLUMN_USAGE") && self.deny_key_column_usage => return Err(denied(1142)),
+                statement if statement.contains("KEY_COLUMN_USAGE") => vec![SchemaExecutor::row(
+                    SchemaExecutor::text(&[Some("post"), Some("author"), Some("user"), Some("id"), Some("CASCADE"), Some("RESTRICT")]),
+                    &["TABLE_NAME", "COLUMN_NAME", "REFERENCED_TABLE_NAME", "REFERENCED_COLUMN_NAME", "DELETE_RULE", "UPDATE_RULE"]
+                )],
+                statement if statement.contains("information_schema.STATISTICS") => vec![
+                    SchemaExecutor::row(
+                        vec![mysql::Value::from("post"), mysql::Value::from("PRIMARY"), mysql::Value::Int(1), mysql::Value::from("id")],
+                        &["TABLE_NAME", "INDEX_NAME", "NON_UNIQUE = 0", "COLUMN_NAME"]
+                    ),
+                    SchemaExecutor::row(
+                        vec![mysql::Value::from("post"), mysql::Value::from("post_author"), mysql::Value::Int(0), mysql::Value::from("author")],
+                        &["TABLE_NAME", "INDEX_NAME", "NON_UNIQUE = 0", "COLUMN_NAME"]
+                    ),
+                ],
+                statement if statement.starts_with("SHOW CREATE TABLE") => vec![SchemaExecutor::row(
+                    SchemaExecutor::text(&[Some("post"), Some(CREATE)]),
+                    &["Table", "Create Table"]
+                )],
+                _ => Vec::new(),
+            };
+
+            Ok(rows.into_iter().map(Ok).map(row_map).collect())
+        }
+    }
+
+    #[test]
+    fn load_schema_test() {
+        let executor = SchemaExecutor::default();
+        let tables = load_schema(&executor).unwrap();
+
+        assert_eq!(tables.iter().map(|(table, _)| table.name.as_str()).collect::<Vec<&str>>(), vec!["post", "user"]);
+        assert!(tables.iter().all(|(_, report)| report.warnings.is_empty()));
+
+        let (post, user) = (&tables[0].0, &tables[1].0);
+
+        assert_eq!(post.attributes.iter().map(|attr| attr.name.as_str()).collect::<Vec<&str>>(), vec!["id", "author"]);
+        assert_eq!(post.primary_key, vec![0]);
+        assert!(post.attributes[1].constraint.contains(&Constraint::ForeignKey{
+            table_name: String::from("user"),
+            attribute_name: String::from("id"),
+            on_delete: Some(ReferentialAction::Cascade),
+            on_update: None,
+        }));
+        assert_eq!(post.indexes, vec![Index{name: String::from("post_author"), columns: vec![String::from("author")], unique: false}]);
+        assert!(user.attributes[1].constraint.contains(&Constraint::Unique));
+        assert!(user.indexes.is_empty());
+
+        //one query for each part of the schema, whatever the number of tables
+        assert_eq!(executor.log.borrow().len(), 4);
+    }
+
+    #[test]
+    fn load_schema_fallback_test() {
+        let executor = SchemaExecutor{deny_key_column_usage: true, ..Default::default()};
+        let tables = load_schema(&executor).unwrap();
+
+        let (post, report) = &tables[0];
+
+        assert!(post.attributes[1].constraint.iter().any(|constraint| matches!(constraint, Constraint::ForeignKey{..})));
+        assert_eq!(report.warnings, vec![LoadWarning::ForeignKeysFromCreateTable{table: String::from("post")}]);
+
+        //only post has an indexed column whose foreign keys are read again, table by table
+        let log = executor.log.borrow();
+        assert_eq!(log.iter().filter(|statement| statement.contains("KEY_COLUMN_USAGE")).count(), 2);
+        assert_eq!(log.iter().filter(|statement| statement.starts_with("SHOW CREATE TABLE")).count(), 1);
+    }
+}
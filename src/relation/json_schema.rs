@@ -0,0 +1,223 @@
+use serde_json::{json, Map, Value};
+
+use super::table::{Table, Attribute, AttributeType, Constraint};
+
+impl AttributeType {
+    /// Returns the JSON schema keywords describing a value of the type, without `NULL`.
+    ///
+    /// Binary types are strings with the `binary` format. Types the crate does not model accept any value.
+    fn json_schema(&self) -> Map<String, Value> {
+        let integer = || match self.integer_range() {
+            //the bounds of a 64 bit integer, signed or not, fit in an i64 and a u64
+            Some((minimum, maximum)) => json!({"type": "integer", "minimum": minimum as i64, "maximum": maximum as u64}),
+            None => json!({"type": "integer"}),
+        };
+        let string = |max_length: u64| json!({"type": "string", "maxLength": max_length});
+        let binary = |max_length: u64| json!({"type": "string", "format": "binary", "maxLength": max_length});
+        let format = |format: &str| json!({"type": "string", "format": format});
+
+        let schema = match self {
+            AttributeType::Char(val) => string(*val as u64),
+            AttributeType::VarChar(val) => string(*val as u64),
+            AttributeType::TinyText => string(255),
+            AttributeType::Text => string(65_535),
+            AttributeType::MediumText => string(16_777_215),
+            AttributeType::LongText => string(4_294_967_295),
+            AttributeType::Binary(val) => binary(*val as u64),
+            AttributeType::VarBinary(val) => binary(*val as u64),
+            AttributeType::TinyBlob => binary(255),
+            AttributeType::Blob(val) => binary(*val as u64),
+            AttributeType::MediumBlob => binary(16_777_215),
+            AttributeType::LongBlob => binary(4_294_967_295),
+            AttributeType::Enum{val} => json!({"type": "string", "enum": val}),
+            AttributeType::Set{..} => json!({"type": "string"}),
+
+            AttributeType::Bit(val) => json!({"type": "integer", "minimum": 0, "maximum": (1u128 << val) - 1}),
+            AttributeType::Bool | AttributeType::Boolean => json!({"type": "boolean"}),
+            AttributeType::TinyInt(_) |
+            AttributeType::SmallInt(_) |
+            AttributeType::MediumInt(_) |
+            AttributeType::Int(_) |
+            AttributeType::BigInt(_) => integer(),
+            AttributeType::Float(_) | AttributeType::Double(_) | AttributeType::Decimal(..) => json!({"type": "number"}),
+            AttributeType::Unsigned{val, ..} => match val.as_ref() {
+                AttributeType::Float(_) | AttributeType::Double(_) | AttributeType::Decimal(..) => {
+                    json!({"type": "number", "minimum": 0})
+                },
+                _ => integer(),
+            },
+
+            AttributeType::Date => format("date"),
+            AttributeType::DateTime(_) | AttributeType::TimeStamp(_) => format("date-time"),
+            AttributeType::Time(_) => format("time"),
+            AttributeType::Year => json!({"type": "integer", "minimum": 1901, "maximum": 2155}),
+
+            AttributeType::Uuid => format("uuid"),
+
+            AttributeType::Json | AttributeType::Unknown(_) => json!({}),
+        };
+
+        match schema {
+            Value::Object(schema) => schema,
+            _ => Map::new(),
+        }
+    }
+}
+
+impl Attribute {
+    /// Returns the JSON schema of the attribute's values.
+    ///
+    /// Nullable attributes use the type array form, `"type": ["string", "null"]`, and add `null` to their enum values.
+    fn json_schema(&self, primary_key: bool) -> Value {
+        let mut schema = self.data_type.json_schema();
+
+        if !self.constraint.contains(&Constraint::NotNull) {
+            if let Some(Value::String(data_type)) = schema.get("type").cloned() {
+                schema.insert(String::from("type"), json!([data_type, "null"]));
+            }
+            if let Some(Value::Array(values)) = schema.get_mut("enum") {
+                values.push(Value::Null);
+            }
+        }
+
+        if primary_key {
+            schema.insert(String::from("x-primary-key"), Value::Bool(true));
+        }
+
+        for constraint in &self.constraint {
+            if let Constraint::ForeignKey{table_name, attribute_name, ..} = constraint {
+                schema.insert(String::from("x-references"), json!({"table": table_name, "column": attribute_name}));
+            }
+        }
+
+        Value::Object(schema)
+    }
+}
+
+impl Table {
+    /// Returns a JSON schema object describing a row of the table, for validating request bodies that mirror it.
+    ///
+    /// Every column is a property typed from its `AttributeType`, with `maxLength` for strings and the range for
+    /// integers. Columns that are `NOT NULL` and not auto incremented are required. The primary key is marked with
+    /// `x-primary-key` and foreign keys with `x-references`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let schema = table.to_json_schema();
+    ///
+    /// assert_eq!(schema["properties"]["name"], json!({"type": "string", "maxLength": 64}));
+    /// ```
+    pub fn to_json_schema(&self) -> Value {
+        let mut properties = Map::new();
+
+        for (i, attr) in self.attributes.iter().enumerate() {
+            properties.insert(attr.name.clone(), attr.json_schema(self.primary_key.contains(&i)));
+        }
+
+        let required: Vec<&str> = self.attributes
+            .iter()
+            .filter(|attr| {
+                attr.constraint.contains(&Constraint::NotNull) && !attr.constraint.contains(&Constraint::AutoIncrement)
+            })
+            .map(|attr| attr.name.as_str())
+            .collect();
+
+        json!({
+            "title": self.name,
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use serde_json::json;
+
+    use super::super::table::{Table, Attribute, AttributeType, Constraint};
+
+    #[test]
+    fn to_json_schema_test() {
+        let table = Table {
+            name: String::from("post"),
+            attributes: vec![
+                Attribute {
+                    name: String::from("id"),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
+                    constraint: HashSet::from([Constraint::NotNull, Constraint::AutoIncrement]),
+                },
+                Attribute {
+                    name: String::from("title"),
+                    data_type: AttributeType::VarChar(120),
+                    raw_type: String::new(),
+                    constraint: HashSet::from([Constraint::NotNull]),
+                },
+                Attribute {
+                    name: String::from("summary"),
+                    data_type: AttributeType::Char(16),
+                    raw_type: String::new(),
+                    constraint: HashSet::new(),
+                },
+                Attribute {
+                    name: String::from("status"),
+                    data_type: AttributeType::Enum{val: vec![String::from("draft"), String::from("live")]},
+                    raw_type: String::new(),
+                    constraint: HashSet::new(),
+                },
+                Attribute {
+                    name: String::from("author"),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
+                    constraint: HashSet::from([
+                        Constraint::NotNull,
+                        Constraint::ForeignKey{table_name: String::from("user"), attribute_name: String::from("id"), on_delete: None, on_update: None},
+                    ]),
+                },
+                Attribute {
+                    name: String::from("published"),
+                    data_type: AttributeType::DateTime(None),
+                    raw_type: String::new(),
+                    constraint: HashSet::new(),
+                },
+                Attribute {
+                    name: String::from("geometry"),
+                    data_type: AttributeType::Unknown(String::from("point")),
+                    raw_type: String::from("point"),
+                    constraint: HashSet::new(),
+                },
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+
+        assert_eq!(
+            table.to_json_schema(),
+            json!({
+                "title": "post",
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer", "minimum": -2147483648i64, "maximum": 2147483647, "x-primary-key": true},
+                    "title": {"type": "string", "maxLength": 120},
+                    "summary": {"type": ["string", "null"], "maxLength": 16},
+                    "status": {"type": ["string", "null"], "enum": ["draft", "live", null]},
+                    "author": {
+                        "type": "integer",
+                        "minimum": -2147483648i64,
+                        "maximum": 2147483647,
+                        "x-references": {"table": "user", "column": "id"},
+                    },
+                    "published": {"type": ["string", "null"], "format": "date-time"},
+                    "geometry": {},
+                },
+                "required": ["title", "author"],
+                "additionalProperties": false,
+            })
+        );
+    }
+}
@@ -1,14 +1,30 @@
-use mysql::Error;
+use crate::{data_base::{DataBase, Executor}, error::Error};
 
-use crate::data_base::DataBase;
+use self::{table::Table, view::View, type_override::TypeOverrideMap};
 
-use self::{table::Table, view::View};
+pub use self::registry::Registry;
 
-use super::sql::{SQL, DDL, QDL};
+use super::sql::{SQL, DDL, QDL, SQLError};
 
 pub mod table;
 pub mod view;
 pub mod paths;
+pub mod type_override;
+pub mod import;
+pub mod source;
+pub mod encryption;
+pub mod tightening;
+pub mod impact;
+pub mod defaults;
+pub mod deploy;
+pub mod introspect;
+pub mod parse;
+pub mod registry;
+pub mod cache;
+#[cfg(feature = "serde")]
+pub mod json_schema;
+#[cfg(feature = "serde")]
+pub mod schema;
 
 /// A trait representing methods for generating SQL statements for relations.
 pub trait RelationMethods {
@@ -36,61 +52,54 @@ impl Relation {
     ///
     /// This function will return an error if there is a problem accessing the database or executing the required queries.
     pub fn get_relations() -> Result<Vec<Relation>, Error> {
-        match DataBase::from_env() {
-            Ok(db) => {
-                let rows: Vec<Relation> = db.execute(
-                    &SQL::new("SHOW FULL TABLES").unwrap(),
-                    |row| {
-                        match row {
-                            Ok(row) => {
-                                let name: String = row.get(0).unwrap();
-                                let relation_type: String = row.get(1).unwrap();
-
-                                return Some((name, relation_type));
-                            },
-                            Err(_err) => {
-                                return None;
-                            }
-                        };
-                    }
-                ).unwrap()
-                .iter()
-                .filter(|val| {
-                    match val {
-                        Some(_str) => true,
-                        None => false,
-                    }
-                })
-                .map(|val| {
-                    match val {
-                        Some(name_type_pair) => name_type_pair.clone(),
-                        None => panic!(),
-                    }
-                })
-                .map(|row| {
-                    let row : (&str, &str) = (&row.0, &row.1);
-                    match row {
-                        (name, "BASE TABLE") => {
-                            Relation::Table(Table::from_db(name).unwrap())
-                        },
-                        (name, "VIEW") => {
-                            Relation::View(View::from_db(name).unwrap())
-                        },
-                        _ => {
-                            panic!("Invalid row")
-                        }
-                    }
-                })
-                .collect();
-
-                return Ok(rows)
-            },
-            Err(err) => {
-                panic!("{:?}", err);
+        Relation::get_relations_in(&DataBase::from_env()?)
+    }
+
+    /// Same as `Relation::get_relations`, but reads the relations from `db`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is a problem accessing the database or executing the required queries.
+    pub fn get_relations_in<E: Executor>(db: &E) -> Result<Vec<Relation>, Error> {
+        let statement = SQL::new("SHOW FULL TABLES").unwrap();
+
+        let rows = db.execute(&statement, |row| row.map(mysql::from_row::<(String, String)>))?;
+
+        let mut relations: Vec<Relation> = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            let (name, relation_type) = row.map_err(|err| Error::query(&statement.to_string(), err))?;
+
+            match relation_type.as_str() {
+                "BASE TABLE" => relations.push(Relation::Table(Table::from_db_in(db, &name)?)),
+                "VIEW" => relations.push(Relation::View(View::from_db_in(db, &name)?)),
+                //system views are left out
+                _ => {},
             }
         }
+
+        Ok(relations)
     }
-    
+
+    /// Returns a vector of `Relation`s from the database, with `overrides` applied to the attributes of every table.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SQLError::Execution` if the relations could not be loaded and `SQLError::Err` if an override is
+    /// incompatible with a column's type.
+    pub fn get_relations_with(overrides: &TypeOverrideMap) -> Result<Vec<Relation>, SQLError> {
+        let mut relations = Relation::get_relations()?;
+
+        for relation in relations.iter_mut() {
+            if let Relation::Table(table) = relation {
+                overrides.apply(table)
+                    .map_err(|err| SQLError::Err(err.to_string()))?;
+            }
+        }
+
+        Ok(relations)
+    }
+
     /// Returns the name of the relation as a `String`.
     pub fn name(&self) -> String {
         match self {
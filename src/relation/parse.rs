@@ -0,0 +1,710 @@
+use std::{collections::HashSet, fmt};
+
+use super::{
+    defaults::DefaultValue,
+    table::{Table, Attribute, AttributeType, Constraint, ReferentialAction, Index},
+};
+
+/// Represents possible errors that can occur when parsing a `CREATE TABLE` statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The statement does not start with `CREATE TABLE`, or has no column definitions.
+    NotCreateTable,
+    /// A quoted string, quoted identifier or parenthesis is not closed.
+    Unterminated,
+    /// A definition ends early or has a token where another is expected, such as a column with no type.
+    Unexpected(String),
+    /// A key or check names a column that is not defined in the statement.
+    UnknownColumn(String),
+    /// A definition the table structures cannot hold, such as a foreign key over several columns.
+    Unsupported(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::NotCreateTable => write!(f, "statement is not a CREATE TABLE statement"),
+            ParseError::Unterminated => write!(f, "statement has an unterminated quote or parenthesis"),
+            ParseError::Unexpected(token) => write!(f, "unexpected {} in table definition", token),
+            ParseError::UnknownColumn(column) => write!(f, "column {} is not defined", column),
+            ParseError::Unsupported(definition) => write!(f, "unsupported definition {}", definition),
+        }
+    }
+}
+
+/// A token of a statement. Parenthesized groups keep their text as written, for types and expressions.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Ident(String),
+    Str(String),
+    Group{raw: String, tokens: Vec<Token>},
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Ident(ident) => write!(f, "`{}`", ident.replace('`', "``")),
+            Token::Str(value) => write!(f, "'{}'", value),
+            Token::Group{raw, ..} => write!(f, "({})", raw),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+struct Lexer<'a> {
+    sql: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn next(&mut self) -> Option<char> {
+        let ch = self.sql[self.pos..].chars().next()?;
+        self.pos += ch.len_utf8();
+
+        Some(ch)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.sql[self.pos..].chars().next()
+    }
+
+    /// Returns the tokens up to the end of the statement, or up to the closing parenthesis if `nested`.
+    fn tokens(&mut self, nested: bool) -> Result<Vec<Token>, ParseError> {
+        let mut tokens = Vec::new();
+
+        while let Some(ch) = self.next() {
+            let start = self.pos;
+
+            match ch {
+                ')' if nested => return Ok(tokens),
+                ')' => return Err(ParseError::Unexpected(String::from(")"))),
+                '(' => {
+                    let inner = self.tokens(true)?;
+
+                    tokens.push(Token::Group{raw: self.sql[start..self.pos - 1].trim().to_string(), tokens: inner});
+                },
+                ',' => tokens.push(Token::Comma),
+                '\'' | '"' => tokens.push(Token::Str(self.quoted(ch)?)),
+                '`' => tokens.push(Token::Ident(self.quoted(ch)?)),
+                ch if ch.is_whitespace() || ch == ';' => {},
+                _ => {
+                    while matches!(self.peek(), Some(ch) if !ch.is_whitespace() && !"(),;'\"`".contains(ch)) {
+                        self.next();
+                    }
+
+                    tokens.push(Token::Word(self.sql[start - ch.len_utf8()..self.pos].to_string()));
+                },
+            }
+        }
+
+        match nested {
+            true => Err(ParseError::Unterminated),
+            false => Ok(tokens),
+        }
+    }
+
+    /// Returns the unescaped contents of a string or identifier quoted with `quote`, whose opening quote is read.
+    fn quoted(&mut self, quote: char) -> Result<String, ParseError> {
+        let mut value = String::new();
+
+        loop {
+            match self.next().ok_or(ParseError::Unterminated)? {
+                '\\' if quote != '`' => match self.next().ok_or(ParseError::Unterminated)? {
+                    '0' => value.push('\0'),
+                    'n' => value.push('\n'),
+                    'r' => value.push('\r'),
+                    't' => value.push('\t'),
+                    'Z' => value.push('\x1a'),
+                    ch => value.push(ch),
+                },
+                ch if ch == quote && self.peek() == Some(quote) => {
+                    self.next();
+                    value.push(quote);
+                },
+                ch if ch == quote => return Ok(value),
+                ch => value.push(ch),
+            }
+        }
+    }
+}
+
+/// A cursor over the tokens of one definition.
+struct Definition<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl<'a> Definition<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += token.is_some() as usize;
+
+        token
+    }
+
+    /// Returns the next token as an upper case keyword, without consuming it. Quoted identifiers are not keywords.
+    fn keyword(&self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Word(word)) => Some(word.to_ascii_uppercase()),
+            _ => None,
+        }
+    }
+
+    /// Consumes the next token if it is one of `keywords`.
+    fn accept(&mut self, keywords: &[&str]) -> bool {
+        let accepted = self.keyword().is_some_and(|keyword| keywords.contains(&keyword.as_str()));
+        self.pos += accepted as usize;
+
+        accepted
+    }
+
+    fn expect(&mut self, keyword: &str) -> Result<(), ParseError> {
+        match self.accept(&[keyword]) {
+            true => Ok(()),
+            false => Err(self.unexpected()),
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, ParseError> {
+        match self.peek() {
+            Some(Token::Ident(ident)) | Some(Token::Word(ident)) => {
+                self.next();
+                Ok(ident.clone())
+            },
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// Returns the name of a table, leaving out the database it is qualified with.
+    fn table_name(&mut self) -> Result<String, ParseError> {
+        let quoted = matches!(self.peek(), Some(Token::Ident(_)));
+        let name = self.identifier()?;
+
+        match self.peek() {
+            Some(Token::Word(word)) if word == "." || (!quoted && name.ends_with('.')) => {
+                self.pos += (word == ".") as usize;
+                self.identifier()
+            },
+            Some(Token::Word(word)) if word.starts_with('.') => {
+                self.next();
+                Ok(word[1..].to_string())
+            },
+            _ if !quoted && name.ends_with('.') => self.identifier(),
+            _ => match (quoted, name.rsplit_once('.')) {
+                (false, Some((_, table))) => Ok(table.to_string()),
+                _ => Ok(name),
+            },
+        }
+    }
+
+    fn group(&mut self) -> Result<(&'a String, &'a Vec<Token>), ParseError> {
+        match self.peek() {
+            Some(Token::Group{raw, tokens}) => {
+                self.next();
+                Ok((raw, tokens))
+            },
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// Returns the columns of a key, leaving out prefix lengths and sort orders.
+    fn columns(&mut self) -> Result<Vec<String>, ParseError> {
+        let (_, tokens) = self.group()?;
+
+        tokens.split(|token| *token == Token::Comma)
+            .map(|part| Definition{tokens: part, pos: 0}.identifier())
+            .collect()
+    }
+
+    /// Returns the action of an `ON DELETE` or `ON UPDATE` clause, `None` for the default (see `read_rule`).
+    fn action(&mut self) -> Result<Option<ReferentialAction>, ParseError> {
+        let mut rule = self.keyword().ok_or_else(|| self.unexpected())?;
+        self.next();
+
+        if rule == "SET" || rule == "NO" {
+            rule += &format!(" {}", self.keyword().ok_or_else(|| self.unexpected())?);
+            self.next();
+        }
+
+        match ReferentialAction::parse(&rule) {
+            Some(action) => Ok(Some(action).filter(|action| !action.is_default())),
+            None => Err(ParseError::Unexpected(rule)),
+        }
+    }
+
+    /// Returns the foreign key of a `REFERENCES` clause, whose keyword is read.
+    fn references(&mut self) -> Result<Constraint, ParseError> {
+        let table_name = self.table_name()?;
+        let mut columns = self.columns()?;
+        let (mut on_delete, mut on_update) = (None, None);
+
+        while self.accept(&["ON"]) {
+            match self.keyword().as_deref() {
+                Some("DELETE") => { self.next(); on_delete = self.action()?; },
+                Some("UPDATE") => { self.next(); on_update = self.action()?; },
+                _ => return Err(self.unexpected()),
+            }
+        }
+
+        match columns.len() {
+            1 => Ok(Constraint::ForeignKey{table_name, attribute_name: columns.remove(0), on_delete, on_update}),
+            _ => Err(ParseError::Unsupported(format!("REFERENCES {} ({})", table_name, columns.join(", ")))),
+        }
+    }
+
+    /// Returns the error for the next token, which is not the one expected.
+    fn unexpected(&self) -> ParseError {
+        match self.peek() {
+            Some(token) => ParseError::Unexpected(token.to_string()),
+            None => ParseError::Unexpected(String::from("end of definition")),
+        }
+    }
+}
+
+/// Returns the `DEFAULT` of a column definition, whose keyword is read. `NULL` is no default, as it is when loading.
+fn default_value(definition: &mut Definition) -> Result<Option<DefaultValue>, ParseError> {
+    if matches!(definition.peek(), None | Some(Token::Ident(_)) | Some(Token::Comma)) {
+        return Err(definition.unexpected());
+    }
+
+    match definition.next() {
+        Some(Token::Str(literal)) => Ok(Some(DefaultValue::Literal(literal.clone()))),
+        Some(Token::Word(word)) if word.eq_ignore_ascii_case("NULL") => Ok(None),
+        Some(Token::Word(word)) if word.parse::<f64>().is_ok() => Ok(Some(DefaultValue::Literal(word.clone()))),
+        Some(Token::Word(word)) => match definition.peek() {
+            Some(Token::Group{raw, ..}) => {
+                definition.next();
+                Ok(Some(DefaultValue::Expression(format!("{}({})", word, raw))))
+            },
+            _ => Ok(Some(DefaultValue::Expression(word.clone()))),
+        },
+        Some(Token::Group{raw, ..}) => Ok(Some(DefaultValue::Expression(format!("({})", raw)))),
+        _ => Ok(None),
+    }
+}
+
+/// Returns the type of a column definition as written, such as `int(11) unsigned` or `enum('a','b')`.
+fn column_type(definition: &mut Definition) -> Result<String, ParseError> {
+    let mut raw_type = match definition.peek() {
+        Some(Token::Word(word)) => word.clone(),
+        _ => return Err(definition.unexpected()),
+    };
+    definition.next();
+
+    if let Some(Token::Group{raw, ..}) = definition.peek() {
+        definition.next();
+
+        match ["ENUM", "SET"].contains(&raw_type.to_ascii_uppercase().as_str()) {
+            true => raw_type += &format!("({})", raw),
+            false => raw_type += &format!("({})", raw.split_whitespace().collect::<String>()),
+        }
+    }
+
+    let sign = |keyword: &String| ["UNSIGNED", "ZEROFILL", "SIGNED"].contains(&keyword.as_str());
+
+    while let Some(keyword) = definition.keyword().filter(sign) {
+        definition.next();
+
+        if keyword != "SIGNED" {
+            raw_type += &format!(" {}", keyword.to_ascii_lowercase());
+        }
+    }
+
+    Ok(raw_type)
+}
+
+/// Reads a column definition, returning the attribute and whether it is declared the primary key.
+fn column(definition: &mut Definition) -> Result<(Attribute, bool), ParseError> {
+    let name = definition.identifier()?;
+    let raw_type = column_type(definition)?;
+    let mut constraint = HashSet::new();
+    let mut primary = false;
+
+    while let Some(token) = definition.peek() {
+        let keyword = definition.keyword().ok_or_else(|| ParseError::Unexpected(token.to_string()))?;
+        definition.next();
+
+        match keyword.as_str() {
+            "NOT" => {
+                definition.expect("NULL")?;
+                constraint.insert(Constraint::NotNull);
+            },
+            "NULL" | "VISIBLE" | "INVISIBLE" => {},
+            "DEFAULT" => {
+                if let Some(default) = default_value(definition)? {
+                    constraint.insert(Constraint::Default(default));
+                }
+            },
+            "AUTO_INCREMENT" => { constraint.insert(Constraint::AutoIncrement); },
+            "UNIQUE" => {
+                definition.accept(&["KEY"]);
+                constraint.insert(Constraint::Unique);
+            },
+            "PRIMARY" => {
+                definition.expect("KEY")?;
+                primary = true;
+            },
+            "KEY" => primary = true,
+            "CHECK" => {
+                let (expression, _) = definition.group()?;
+                constraint.insert(Constraint::Check(expression.clone()));
+            },
+            "REFERENCES" => { constraint.insert(definition.references()?); },
+            "CONSTRAINT" => {
+                if !definition.keyword().is_some_and(|keyword| ["CHECK", "REFERENCES"].contains(&keyword.as_str())) {
+                    definition.identifier()?;
+                }
+            },
+            "COMMENT" | "COLLATE" | "CHARSET" | "COLUMN_FORMAT" | "STORAGE" => { definition.next(); },
+            "CHARACTER" => {
+                definition.expect("SET")?;
+                definition.next();
+            },
+            "ON" => {
+                definition.expect("UPDATE")?;
+                default_value(definition)?;
+            },
+            _ => return Err(ParseError::Unexpected(token.to_string())),
+        }
+    }
+
+    let data_type = AttributeType::from(&raw_type).unwrap_or_else(|| AttributeType::Unknown(raw_type.clone()));
+
+    Ok((Attribute{name, data_type, raw_type, constraint}, primary))
+}
+
+/// Adds the position of every attribute named in an expression to `columns`.
+fn named_columns(tokens: &[Token], attributes: &[Attribute], columns: &mut Vec<usize>) {
+    for token in tokens {
+        match token {
+            Token::Word(name) | Token::Ident(name) => {
+                columns.extend(attributes.iter().position(|attr| attr.name.eq_ignore_ascii_case(name)));
+            },
+            Token::Group{tokens, ..} => named_columns(tokens, attributes, columns),
+            _ => {},
+        }
+    }
+}
+
+impl Table {
+    /// Parses a MySQL `CREATE TABLE` statement, such as the output of `Table::create` or `SHOW CREATE TABLE`.
+    ///
+    /// Identifiers may be quoted with backticks, and table options after the definitions, such as
+    /// `ENGINE=InnoDB`, are ignored. Keys, checks and foreign keys are read both inline and as table level
+    /// definitions, and kept the way `Table::from_db` keeps them: a unique key over one column is a `Unique`
+    /// constraint, a table level check is kept on the one column it names, and `DEFAULT NULL` and the default
+    /// referential actions are left out. Column types are read like those loaded from the server, so a type that is
+    /// not supported is kept as written in `AttributeType::Unknown`.
+    ///
+    /// ```ignore
+    /// let table = Table::from_create_statement(&fs::read_to_string("schema/user.sql")?)?;
+    ///
+    /// assert_eq!(Table::from_db(&db, "user")?.diff(&table), vec![]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseError` if `sql` is not a `CREATE TABLE` statement, or has a definition that cannot be read
+    /// or held, such as a foreign key over several columns or a `FULLTEXT` index.
+    pub fn from_create_statement(sql: &str) -> Result<Table, ParseError> {
+        let tokens = Lexer{sql, pos: 0}.tokens(false)?;
+        let mut statement = Definition{tokens: &tokens, pos: 0};
+
+        if !statement.accept(&["CREATE"]) {
+            return Err(ParseError::NotCreateTable);
+        }
+        statement.accept(&["TEMPORARY"]);
+        if !statement.accept(&["TABLE"]) {
+            return Err(ParseError::NotCreateTable);
+        }
+        if statement.accept(&["IF"]) {
+            statement.expect("NOT")?;
+            statement.expect("EXISTS")?;
+        }
+
+        let name = statement.table_name()?;
+        let body = match statement.group() {
+            Ok((_, body)) if !body.is_empty() => body,
+            _ => return Err(ParseError::NotCreateTable),
+        };
+
+        let mut table = Table{name, attributes: Vec::new(), primary_key: Vec::new(), indexes: Vec::new()};
+        let mut primary_key = Vec::new();
+        let mut foreign_keys = Vec::new();
+        let mut checks = Vec::new();
+
+        for tokens in body.split(|token| *token == Token::Comma) {
+            let mut definition = Definition{tokens, pos: 0};
+
+            let constrained = |keyword: String| ["PRIMARY", "UNIQUE", "FOREIGN", "CHECK"].contains(&keyword.as_str());
+
+            if definition.accept(&["CONSTRAINT"]) && !definition.keyword().is_some_and(constrained) {
+                definition.identifier()?;
+            }
+
+            match definition.keyword().as_deref() {
+                Some("PRIMARY") => {
+                    definition.next();
+                    definition.expect("KEY")?;
+                    primary_key = definition.columns()?;
+                },
+                Some(keyword @ ("UNIQUE" | "KEY" | "INDEX")) => {
+                    let unique = keyword == "UNIQUE";
+                    definition.next();
+                    if unique {
+                        definition.accept(&["KEY", "INDEX"]);
+                    }
+
+                    let name = match definition.peek() {
+                        Some(Token::Group{..}) => None,
+                        _ => Some(definition.identifier()?),
+                    };
+                    let columns = definition.columns()?;
+
+                    table.indexes.push(Index{name: name.unwrap_or_else(|| columns[0].clone()), columns, unique});
+                },
+                Some("FOREIGN") => {
+                    definition.next();
+                    definition.expect("KEY")?;
+                    if !matches!(definition.peek(), Some(Token::Group{..})) {
+                        definition.identifier()?;
+                    }
+
+                    let columns = definition.columns()?;
+                    if columns.len() != 1 {
+                        return Err(ParseError::Unsupported(format!("FOREIGN KEY ({})", columns.join(", "))));
+                    }
+
+                    definition.expect("REFERENCES")?;
+                    foreign_keys.push((columns[0].clone(), definition.references()?));
+                },
+                Some("CHECK") => {
+                    definition.next();
+                    checks.push(definition.group()?);
+                },
+                Some("FULLTEXT" | "SPATIAL") => {
+                    return Err(ParseError::Unsupported(tokens.iter().map(Token::to_string).collect::<Vec<_>>().join(" ")));
+                },
+                _ => {
+                    let (attribute, primary) = column(&mut definition)?;
+
+                    if primary {
+                        primary_key = vec![attribute.name.clone()];
+                    }
+                    table.attributes.push(attribute);
+                },
+            }
+
+            if let Some(token) = definition.peek() {
+                return Err(ParseError::Unexpected(token.to_string()));
+            }
+        }
+
+        let position = |table: &Table, column: &str| table.attributes
+            .iter()
+            .position(|attr| attr.name.eq_ignore_ascii_case(column))
+            .ok_or_else(|| ParseError::UnknownColumn(column.to_string()));
+
+        for column in &primary_key {
+            table.primary_key.push(position(&table, column)?);
+        }
+
+        for (column, foreign_key) in foreign_keys {
+            let index = position(&table, &column)?;
+            table.attributes[index].constraint.insert(foreign_key);
+        }
+
+        for (expression, tokens) in checks {
+            let mut columns = Vec::new();
+            named_columns(tokens, &table.attributes, &mut columns);
+            columns.sort_unstable();
+            columns.dedup();
+
+            match columns.as_slice() {
+                [index] => {
+                    table.attributes[*index].constraint.insert(Constraint::Check(expression.clone()));
+                },
+                _ => return Err(ParseError::Unsupported(format!("CHECK ({})", expression))),
+            }
+        }
+
+        //a unique key over one column is kept as a constraint of the column, as when loading
+        let (single, indexes): (Vec<Index>, Vec<Index>) = table.indexes
+            .drain(..)
+            .partition(|index| index.unique && index.columns.len() == 1);
+        table.indexes = indexes;
+
+        for index in single {
+            let position = position(&table, &index.columns[0])?;
+            table.attributes[position].constraint.insert(Constraint::Unique);
+        }
+        for column in table.indexes.iter().flat_map(|index| index.columns.iter()) {
+            position(&table, column)?;
+        }
+
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::relation::RelationMethods;
+
+    use super::super::{
+        defaults::DefaultValue,
+        table::{Table, Attribute, AttributeType, Constraint, ReferentialAction, Index},
+    };
+    use super::ParseError;
+
+    fn attr(name: &str, data_type: AttributeType, constraint: Vec<Constraint>) -> Attribute {
+        Attribute {
+            name: String::from(name),
+            data_type,
+            raw_type: String::new(),
+            constraint: constraint.into_iter().collect::<HashSet<Constraint>>(),
+        }
+    }
+
+    fn post() -> Table {
+        Table {
+            name: String::from("post"),
+            attributes: vec![
+                attr("id", AttributeType::Int(11), vec![Constraint::NotNull, Constraint::AutoIncrement]),
+                attr("author", AttributeType::Int(11), vec![Constraint::NotNull, Constraint::ForeignKey {
+                    table_name: String::from("user"),
+                    attribute_name: String::from("id"),
+                    on_delete: Some(ReferentialAction::Cascade),
+                    on_update: Some(ReferentialAction::SetNull),
+                }]),
+                attr("slug", AttributeType::VarChar(64), vec![Constraint::Unique]),
+                attr("title", AttributeType::VarChar(255), vec![
+                    Constraint::Default(DefaultValue::Literal(String::from("it's new"))),
+                    Constraint::Check(String::from("char_length(`title`) > 0")),
+                ]),
+                attr(
+                    "views",
+                    AttributeType::Unsigned{val: Box::new(AttributeType::Int(10)), zerofill: false},
+                    vec![Constraint::Default(DefaultValue::Literal(String::from("0")))]
+                ),
+                attr("state", AttributeType::Enum{val: vec![String::from("draft"), String::from("o'k")]}, vec![]),
+                attr("created", AttributeType::TimeStamp(None), vec![
+                    Constraint::Default(DefaultValue::Expression(String::from("CURRENT_TIMESTAMP"))),
+                ]),
+                attr("order", AttributeType::Decimal(10, 2), vec![]),
+            ],
+            primary_key: vec![0],
+            indexes: vec![Index{
+                name: String::from("author_title"),
+                columns: vec![String::from("author"), String::from("title")],
+                unique: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let table = post();
+
+        assert_eq!(Table::from_create_statement(&table.create().to_string()).unwrap(), table);
+
+        let mut no_key = table;
+        no_key.primary_key.clear();
+        no_key.indexes.clear();
+
+        assert_eq!(Table::from_create_statement(&no_key.create().to_string()).unwrap(), no_key);
+    }
+
+    #[test]
+    fn show_create_table_test() {
+        let sql = "CREATE TABLE IF NOT EXISTS `blog`.`comment` (\n  \
+            `id` int NOT NULL AUTO_INCREMENT,\n  \
+            `post` int DEFAULT NULL COMMENT 'the post, if any',\n  \
+            `body` text CHARACTER SET utf8mb4 COLLATE utf8mb4_bin NOT NULL,\n  \
+            `score` int DEFAULT NULL,\n  \
+            PRIMARY KEY (`id`),\n  \
+            UNIQUE KEY `post_body` (`post`,`body`(32)),\n  \
+            KEY `post` (`post`),\n  \
+            CONSTRAINT `comment_ibfk_1` FOREIGN KEY (`post`) REFERENCES `post` (`id`) ON DELETE RESTRICT ON UPDATE CASCADE,\n  \
+            CONSTRAINT `score_range` CHECK ((`score` between 0 and 5))\n\
+            ) ENGINE=InnoDB AUTO_INCREMENT=3 DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_0900_ai_ci;";
+
+        let table = Table::from_create_statement(sql).unwrap();
+
+        assert_eq!(table.name, "comment");
+        assert_eq!(table.primary_key, vec![0]);
+        assert_eq!(table.attributes[1], attr("post", AttributeType::Int(11), vec![Constraint::ForeignKey {
+            table_name: String::from("post"),
+            attribute_name: String::from("id"),
+            on_delete: None,
+            on_update: Some(ReferentialAction::Cascade),
+        }]));
+        assert_eq!(table.attributes[2], attr("body", AttributeType::Text, vec![Constraint::NotNull]));
+        assert_eq!(table.attributes[2].raw_type, "text");
+        assert_eq!(
+            table.attributes[3],
+            attr("score", AttributeType::Int(11), vec![Constraint::Check(String::from("(`score` between 0 and 5)"))])
+        );
+        assert_eq!(table.indexes, vec![
+            Index{name: String::from("post_body"), columns: vec![String::from("post"), String::from("body")], unique: true},
+            Index{name: String::from("post"), columns: vec![String::from("post")], unique: false},
+        ]);
+    }
+
+    #[test]
+    fn inline_key_test() {
+        let table = Table::from_create_statement(
+            "create table tag (id bigint unsigned primary key, label varchar(32) unique key references label (name), \
+            shape point)"
+        ).unwrap();
+
+        assert_eq!(table.primary_key, vec![0]);
+        assert_eq!(
+            table.attributes[0].data_type,
+            AttributeType::Unsigned{val: Box::new(AttributeType::BigInt(20)), zerofill: false}
+        );
+        assert!(table.attributes[1].constraint.contains(&Constraint::Unique));
+        assert!(table.attributes[1].constraint.iter().any(
+            |c| matches!(c, Constraint::ForeignKey{table_name, ..} if table_name == "label")
+        ));
+        assert_eq!(table.attributes[2].data_type, AttributeType::Unknown(String::from("point")));
+        assert_eq!(table.attributes[2].type_definition(), "point");
+    }
+
+    #[test]
+    fn error_test() {
+        assert_eq!(Table::from_create_statement("DROP TABLE user"), Err(ParseError::NotCreateTable));
+        assert_eq!(Table::from_create_statement("CREATE TABLE user ()"), Err(ParseError::NotCreateTable));
+        assert_eq!(Table::from_create_statement("CREATE TABLE user (id int"), Err(ParseError::Unterminated));
+        assert_eq!(Table::from_create_statement("CREATE TABLE user (id int DEFAULT 'a)"), Err(ParseError::Unterminated));
+        assert_eq!(
+            Table::from_create_statement("CREATE TABLE user (id int, PRIMARY KEY (uid))"),
+            Err(ParseError::UnknownColumn(String::from("uid")))
+        );
+        assert_eq!(
+            Table::from_create_statement("CREATE TABLE user (id int NOT EMPTY)"),
+            Err(ParseError::Unexpected(String::from("EMPTY")))
+        );
+        assert_eq!(
+            Table::from_create_statement("CREATE TABLE user (id int DEFAULT)"),
+            Err(ParseError::Unexpected(String::from("end of definition")))
+        );
+        assert_eq!(
+            Table::from_create_statement("CREATE TABLE user (a int, b int, FOREIGN KEY (a, b) REFERENCES pair (a, b))"),
+            Err(ParseError::Unsupported(String::from("FOREIGN KEY (a, b)")))
+        );
+        assert!(matches!(
+            Table::from_create_statement("CREATE TABLE user (bio text, FULLTEXT KEY bio (bio))"),
+            Err(ParseError::Unsupported(_))
+        ));
+        assert_eq!(ParseError::UnknownColumn(String::from("uid")).to_string(), "column uid is not defined");
+    }
+}
@@ -1,11 +1,13 @@
-use std::collections::{HashMap, LinkedList};
+use std::{cmp::Reverse, collections::{BinaryHeap, HashMap, LinkedList}};
 
 use lazy_static::lazy_static;
-use log::info;
-use petgraph::{Graph, adj::NodeIndex, visit::NodeIndexable, Incoming, Outgoing, Directed};
+use log::{info, warn};
+use petgraph::{Graph, adj::NodeIndex, algo::tarjan_scc, visit::NodeIndexable, Incoming, Outgoing, Directed};
 use regex::Regex;
 
-use super::{Relation, view::View, table::Table};
+use crate::sql::SQLError;
+
+use super::{Relation, Registry, view::View, table::Table};
 
 /// DependencyTree is a forest graph of relation nodes and the dependency relationship between relation nodes
 pub type DependencyTree = Graph<NodeIndex, NodeIndex, Directed>;
@@ -30,10 +32,12 @@ pub type DependencyTree = Graph<NodeIndex, NodeIndex, Directed>;
 ///                 Attribute{
 ///                     name: String::from("attr_1"),
 ///                     data_type: AttributeType::Text,
+///                     raw_type: String::new(),
 ///                     constraint: HashSet::new()
 ///                 }
 ///             ],
-///             primary_key: Some(0),
+///             primary_key: vec![0],
+///             indexes: Vec::new(),
 ///         }
 ///     ),
 ///     Relation::Table(
@@ -43,17 +47,21 @@ pub type DependencyTree = Graph<NodeIndex, NodeIndex, Directed>;
 ///                 Attribute{
 ///                     name: String::from("attr_2"),
 ///                     data_type: AttributeType::Text,
+///                     raw_type: String::new(),
 ///                     constraint: HashSet::from([
 ///                             Constraint::ForeignKey{
 ///                                 table_name: String::from("table_1"),
-///                                 attribute_name: String::from("attr_1")
+///                                 attribute_name: String::from("attr_1"),
+///                                 on_delete: None,
+///                                 on_update: None
 ///                             }
 ///                         ]
 ///                     )
 ///                 }
 ///                 foreign_relation!["table_1"]
 ///             ],
-///             primary_key: Some(0),
+///             primary_key: vec![0],
+///             indexes: Vec::new(),
 ///         }
 ///     ),
 /// ];
@@ -107,6 +115,24 @@ pub fn get_dependency_tree(relations: &Vec<Relation>) -> DependencyTree {
     dependency_tree
 }
 
+/// Returns the tables of `registry` as relations, ordered by name, with their dependency tree.
+///
+/// Every table of the registry is loaded. Foreign keys to tables that are not in the registry add no edge.
+///
+/// # Errors
+///
+/// Returns an `SQLError` if the tables of `registry` could not be loaded.
+pub fn get_registry_dependency_tree(registry: &mut Registry) -> Result<(Vec<Relation>, DependencyTree), SQLError> {
+    let relations: Vec<Relation> = registry.tables()?
+        .into_iter()
+        .map(|table| Relation::Table(table.clone()))
+        .collect();
+
+    let dependency_tree = get_dependency_tree(&relations);
+
+    Ok((relations, dependency_tree))
+}
+
 fn name_to_index_hashmap(relations: &Vec<Relation>) -> HashMap<String, usize> {
     let name_to_index: HashMap<String, usize> = {
         let mut name_to_index: HashMap<String, usize> = HashMap::new();
@@ -208,19 +234,17 @@ fn add_view_edges(view: &View, name_to_index: &HashMap<String, usize>, edges: &m
 }
 
 fn add_table_edges(table: &Table, edges: &mut Vec<(usize, usize)>, index: usize, name_to_index: &HashMap<String, usize>) {
-    if let Some(foreign_keys) = table.get_foreign_keys() {
-        foreign_keys.iter()
-            .for_each(
-                |(table_name, _attribute_name)| {
-                    edges.push(
-                        (
-                            *name_to_index.get(table_name).unwrap(),
-                            index
-                        )
-                    );
+    table.foreign_keys()
+        .iter()
+        .for_each(
+            |key| {
+                match name_to_index.get(&key.referenced_table) {
+                    Some(referenced) => edges.push((*referenced, index)),
+                    //dangling, see `Registry::dangling`
+                    None => warn!("{} references unknown table {}", table.name, key.referenced_table),
                 }
-            );
-    }
+            }
+        );
 }
 
 /// Returns a possible order of indexes of `relations` to generate relations without conflicts due to foreign key constraints and views
@@ -241,10 +265,12 @@ fn add_table_edges(table: &Table, edges: &mut Vec<(usize, usize)>, index: usize,
 ///                 Attribute{
 ///                     name: String::from("attr_1"),
 ///                     data_type: AttributeType::Text,
+///                     raw_type: String::new(),
 ///                     constraint: HashSet::new()
 ///                 }
 ///             ],
-///             primary_key: Some(0),
+///             primary_key: vec![0],
+///             indexes: Vec::new(),
 ///         }
 ///     ),
 ///     Relation::Table(
@@ -254,17 +280,21 @@ fn add_table_edges(table: &Table, edges: &mut Vec<(usize, usize)>, index: usize,
 ///                 Attribute{
 ///                     name: String::from("attr_2"),
 ///                     data_type: AttributeType::Text,
+///                     raw_type: String::new(),
 ///                     constraint: HashSet::from([
 ///                             Constraint::ForeignKey{
 ///                                 table_name: String::from("table_1"),
-///                                 attribute_name: String::from("attr_1")
+///                                 attribute_name: String::from("attr_1"),
+///                                 on_delete: None,
+///                                 on_update: None
 ///                             }
 ///                         ]
 ///                     )
 ///                 }
 ///                 foreign_relation!["table_1"]
 ///             ],
-///             primary_key: Some(0),
+///             primary_key: vec![0],
+///             indexes: Vec::new(),
 ///         }
 ///     ),
 /// ];
@@ -301,6 +331,56 @@ pub fn get_generation_path(relations: &Vec<Relation>, dependency_tree: &Dependen
     result
 }
 
+/// Returns the indexes of `relations` in an order they can be created in: every relation after the relations it
+/// depends on, and otherwise in index order.
+///
+/// Unlike `get_generation_path`, a cycle is reported rather than broken. A relation depending on itself, such as a
+/// table referencing its own primary key, is not a cycle.
+///
+/// # Errors
+///
+/// Returns the indexes of the relations of a dependency cycle, in index order.
+pub fn get_creation_order(relations: &[Relation], dependency_tree: &DependencyTree) -> Result<Vec<usize>, Vec<usize>> {
+    let index = |node| *dependency_tree.node_weight(node).unwrap() as usize;
+
+    let mut unmet = vec![0usize; relations.len()];
+    for edge in dependency_tree.raw_edges().iter().filter(|edge| edge.source() != edge.target()) {
+        unmet[index(edge.target())] += 1;
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..relations.len()).filter(|i| unmet[*i] == 0).map(Reverse).collect();
+    let mut order = Vec::with_capacity(relations.len());
+
+    while let Some(Reverse(i)) = ready.pop() {
+        order.push(i);
+
+        for dependent in dependency_tree.neighbors_directed(dependency_tree.from_index(i), Outgoing).map(index) {
+            if dependent != i {
+                unmet[dependent] -= 1;
+
+                if unmet[dependent] == 0 {
+                    ready.push(Reverse(dependent));
+                }
+            }
+        }
+    }
+
+    if order.len() == relations.len() {
+        return Ok(order);
+    }
+
+    let mut cycle: Vec<usize> = tarjan_scc(dependency_tree)
+        .into_iter()
+        .find(|component| component.len() > 1)
+        .unwrap_or_default()
+        .into_iter()
+        .map(index)
+        .collect();
+    cycle.sort_unstable();
+
+    Err(cycle)
+}
+
 fn add_dependency(relations: &Vec<Relation>, dependency_tree: &DependencyTree, visited: &mut Vec<bool>, node: usize) -> Option<LinkedList<usize>> {
     if visited[node] {
         return None;
@@ -360,17 +440,18 @@ mod tests{
 
     use petgraph::{Graph, adj::NodeIndex, visit::NodeIndexable, Incoming, Outgoing};
 
-    use crate::backend::{
+    use crate::{
         relation::{
             Relation,
             table::{Table, Attribute, AttributeType, Constraint},
-            paths::{get_dependency_tree, DependencyTree},
-            view::View
+            paths::{get_dependency_tree, get_registry_dependency_tree, DependencyTree},
+            view::View,
+            Registry,
         },
         sql::SQL
     };
 
-    use super::get_generation_path;
+    use super::{get_generation_path, get_creation_order};
 
     //assert_eq_graph checks if two graphs are the same
     #[allow(unused_macros)]
@@ -425,6 +506,7 @@ mod tests{
             Attribute{
                 name: String::from("attr_1"),
                 data_type: AttributeType::Text,
+                raw_type: String::new(),
                 constraint: HashSet::new()
             }
         };
@@ -432,11 +514,14 @@ mod tests{
             Attribute{
                 name: String::from("attr_2"),
                 data_type: AttributeType::Text,
+                raw_type: String::new(),
                 constraint: HashSet::from(
                     [
                         Constraint::ForeignKey{
                             table_name: String::from($table_name),
-                            attribute_name: String::from("attr_1")
+                            attribute_name: String::from("attr_1"),
+                            on_delete: None,
+                            on_update: None
                         }
                     ]
                 )
@@ -453,7 +538,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -462,7 +548,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation!["table_1"]
                     ],
-                    primary_key: None,
+                    primary_key: Vec::new(),
+                    indexes: Vec::new(),
                 }
             ),
         ];
@@ -487,7 +574,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -496,7 +584,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: None,
+                    primary_key: Vec::new(),
+                    indexes: Vec::new(),
                 }
             ),
         ];
@@ -519,7 +608,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -528,7 +618,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(1),
+                    primary_key: vec![1],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -538,7 +629,8 @@ mod tests{
                         foreign_relation!["table_1"],
                         foreign_relation!["table_2"]
                     ],
-                    primary_key: None,
+                    primary_key: Vec::new(),
+                    indexes: Vec::new(),
                 }
             ),
         ];
@@ -564,7 +656,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::View(
@@ -595,7 +688,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -604,7 +698,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: None,
+                    primary_key: Vec::new(),
+                    indexes: Vec::new(),
                 }
             ),
             Relation::View(
@@ -637,7 +732,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -646,7 +742,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -655,7 +752,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::View(
@@ -737,7 +835,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
         ];
@@ -758,7 +857,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -767,7 +867,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation!["table_1"]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
         ];
@@ -788,7 +889,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation!["table_2"]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -797,7 +899,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
         ];
@@ -818,7 +921,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -827,7 +931,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation!["table_1"]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -836,7 +941,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation!["table_2"]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
         ];
@@ -857,7 +963,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -866,7 +973,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation!["table_1"]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -875,7 +983,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
         ];
@@ -896,7 +1005,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation!["table_3"]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -905,7 +1015,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -914,7 +1025,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation!["table_2"]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
         ];
@@ -935,7 +1047,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation![]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -944,7 +1057,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation!["table_1"]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),//0,1,2
             Relation::Table(
@@ -953,7 +1067,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation!["table_2"]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
             Relation::Table(
@@ -962,7 +1077,8 @@ mod tests{
                     attributes: vec![
                         foreign_relation!["table_2"]
                     ],
-                    primary_key: Some(0),
+                    primary_key: vec![0],
+                    indexes: Vec::new(),
                 }
             ),
         ];
@@ -974,4 +1090,98 @@ mod tests{
         assert_path!(relations, dependency_tree, actual);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn registry_dependency_test() {
+        //table_3 references a table that does not exist
+        let mut registry = Registry::new(vec![
+            Table{
+                name: String::from("table_2"),
+                attributes: vec![
+                    foreign_relation!["table_1"]
+                ],
+                primary_key: Vec::new(),
+                indexes: Vec::new(),
+            },
+            Table{
+                name: String::from("table_1"),
+                attributes: vec![
+                    foreign_relation![]
+                ],
+                primary_key: vec![0],
+                indexes: Vec::new(),
+            },
+            Table{
+                name: String::from("table_3"),
+                attributes: vec![
+                    foreign_relation!["table_0"]
+                ],
+                primary_key: Vec::new(),
+                indexes: Vec::new(),
+            },
+        ]);
+
+        let (relations, actual) = get_registry_dependency_tree(&mut registry).unwrap();
+
+        assert_eq!(
+            relations.iter().map(|relation| relation.name()).collect::<Vec<String>>(),
+            vec!["table_1", "table_2", "table_3"]
+        );
+
+        let mut expected: DependencyTree = Graph::new();
+        let v1 = expected.add_node(0);
+        let v2 = expected.add_node(1);
+        expected.add_node(2);
+
+        expected.extend_with_edges([(v1,v2)]);
+
+        assert_eq_graph!(actual, expected, relations);
+    }
+
+    fn referencing(name: &str, referenced: &[&str]) -> Relation {
+        Relation::Table(Table{
+            name: String::from(name),
+            attributes: referenced.iter()
+                .map(|table| Attribute{
+                    name: format!("{}_id", table),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
+                    constraint: HashSet::from([Constraint::ForeignKey{
+                        table_name: String::from(*table),
+                        attribute_name: String::from("id"),
+                        on_delete: None,
+                        on_update: None
+                    }])
+                })
+                .collect(),
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn creation_order_test() {
+        //table_1 references table_2, and table_3 references both
+        let relations = vec![
+            referencing("table_1", &["table_2"]),
+            referencing("table_2", &[]),
+            referencing("table_3", &["table_1", "table_2"]),
+            referencing("table_4", &["table_4"]),
+        ];
+        let dependency_tree = get_dependency_tree(&relations);
+
+        assert_eq!(get_creation_order(&relations, &dependency_tree), Ok(vec![1, 0, 2, 3]));
+    }
+
+    #[test]
+    fn creation_order_cycle_test() {
+        let relations = vec![
+            referencing("table_1", &[]),
+            referencing("table_2", &["table_4"]),
+            referencing("table_3", &["table_2"]),
+            referencing("table_4", &["table_3", "table_1"]),
+        ];
+        let dependency_tree = get_dependency_tree(&relations);
+
+        assert_eq!(get_creation_order(&relations, &dependency_tree), Err(vec![1, 2, 3]));
+    }
+}
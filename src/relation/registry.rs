@@ -0,0 +1,374 @@
+use std::{collections::{BTreeMap, HashMap}, fmt};
+
+use crate::{data_base::DataBase, sql::SQLError};
+
+use super::table::{Table, Constraint};
+
+/// A foreign key, from `column` of `table` to `referenced_column` of `referenced_table`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForeignKey {
+    pub table: String,
+    pub column: String,
+    pub referenced_table: String,
+    pub referenced_column: String,
+}
+
+impl ForeignKey {
+    /// Returns the foreign keys of `table`, in column order.
+    pub fn of(table: &Table) -> Vec<ForeignKey> {
+        table.attributes
+            .iter()
+            .flat_map(|attr| {
+                attr.constraint
+                    .iter()
+                    .filter_map(move |constraint| match constraint {
+                        Constraint::ForeignKey{table_name, attribute_name, ..} => Some(ForeignKey {
+                            table: table.name.clone(),
+                            column: attr.name.clone(),
+                            referenced_table: table_name.clone(),
+                            referenced_column: attribute_name.clone(),
+                        }),
+                        _ => None,
+                    })
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ForeignKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{} -> {}.{}", self.table, self.column, self.referenced_table, self.referenced_column)
+    }
+}
+
+/// Reads the tables of a lazily loaded `Registry`.
+pub trait TableLoader {
+    /// Returns the names of every base table.
+    fn table_names(&mut self) -> Result<Vec<String>, SQLError>;
+    /// Reads the table `name`, one of the names returned by `table_names`.
+    fn load(&mut self, name: &str) -> Result<Table, SQLError>;
+}
+
+impl TableLoader for DataBase {
+    fn table_names(&mut self) -> Result<Vec<String>, SQLError> {
+        self.tables().map_err(SQLError::from)
+    }
+
+    fn load(&mut self, name: &str) -> Result<Table, SQLError> {
+        Table::from_db_in(self, name).map_err(SQLError::from)
+    }
+}
+
+/// The tables of a schema and the foreign keys between them, looked up by case-insensitive name.
+///
+/// A registry either holds a fixed set of tables (see `Registry::new`) or reads them from a `TableLoader` as they are
+/// asked for (see `Registry::from_db`). Loaded tables are kept, so each is read at most once. A table that fails to
+/// load is not kept, and is read again the next time it is asked for.
+///
+/// ```ignore
+/// let mut registry = Registry::from_db(&db);
+///
+/// let orders = registry.table("orders")?;          //reads orders
+/// let outgoing = registry.referenced_by("orders")?; //orders.customer_id -> customer.id
+/// let incoming = registry.references_of("orders")?; //reads every table: invoice.order_id -> orders.id
+/// ```
+pub struct Registry {
+    loader: Option<Box<dyn TableLoader>>,
+    /// The table names by lowercase name, once they are known.
+    names: Option<BTreeMap<String, String>>,
+    /// The loaded tables by lowercase name.
+    tables: HashMap<String, Table>,
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registry")
+            .field("lazy", &self.loader.is_some())
+            .field("names", &self.names)
+            .field("tables", &self.tables)
+            .finish()
+    }
+}
+
+impl Registry {
+    /// Returns a registry of `tables`. Tables that are not in `tables` do not exist.
+    pub fn new(tables: Vec<Table>) -> Registry {
+        Registry {
+            loader: None,
+            names: Some(tables.iter().map(|table| (table.name.to_lowercase(), table.name.clone())).collect()),
+            tables: tables.into_iter().map(|table| (table.name.to_lowercase(), table)).collect(),
+        }
+    }
+
+    /// Returns a registry that reads its tables from `loader` when they are first asked for.
+    pub fn with_loader<L: TableLoader + 'static>(loader: L) -> Registry {
+        Registry { loader: Some(Box::new(loader)), names: None, tables: HashMap::new() }
+    }
+
+    /// Returns a registry of the base tables of the current database of `db`, read when they are first asked for.
+    pub fn from_db(db: &DataBase) -> Registry {
+        Registry::with_loader(db.clone())
+    }
+
+    /// Returns the table names by lowercase name, listing them with the loader the first time.
+    fn names(&mut self) -> Result<&BTreeMap<String, String>, SQLError> {
+        if self.names.is_none() {
+            let names = match &mut self.loader {
+                Some(loader) => loader.table_names()?,
+                None => Vec::new(),
+            };
+
+            self.names = Some(names.into_iter().map(|name| (name.to_lowercase(), name)).collect());
+        }
+
+        Ok(self.names.get_or_insert_with(BTreeMap::new))
+    }
+
+    /// Returns true if the registry has a table named `name`, without loading it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `SQLError` if the table names could not be listed.
+    pub fn contains(&mut self, name: &str) -> Result<bool, SQLError> {
+        Ok(self.names()?.contains_key(&name.to_lowercase()))
+    }
+
+    /// Returns the table named `name`, loading it the first time it is asked for, or `None` if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `SQLError` if the table names could not be listed or the table could not be loaded.
+    pub fn table(&mut self, name: &str) -> Result<Option<&Table>, SQLError> {
+        let key = name.to_lowercase();
+
+        if !self.tables.contains_key(&key) {
+            let name = match self.names()?.get(&key) {
+                Some(name) => name.clone(),
+                None => return Ok(None),
+            };
+
+            let table = match &mut self.loader {
+                Some(loader) => loader.load(&name)?,
+                None => return Ok(None),
+            };
+
+            self.tables.insert(key.clone(), table);
+        }
+
+        Ok(self.tables.get(&key))
+    }
+
+    /// Returns every table, ordered by name, loading the ones not loaded yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `SQLError` if the table names could not be listed or a table could not be loaded.
+    pub fn tables(&mut self) -> Result<Vec<&Table>, SQLError> {
+        let keys: Vec<String> = self.names()?.keys().cloned().collect();
+
+        for key in &keys {
+            self.table(key)?;
+        }
+
+        Ok(keys.iter().filter_map(|key| self.tables.get(key)).collect())
+    }
+
+    /// Returns the incoming foreign keys of `name`: those of the tables referencing it, itself included if it
+    /// references itself. This is every table that has to be considered when a row of `name` is deleted.
+    ///
+    /// Every table is loaded, as any of them may reference `name`. The keys are ordered by table name, then column.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SQLError::Err` if there is no table `name`, or an `SQLError` if a table could not be loaded.
+    pub fn references_of(&mut self, name: &str) -> Result<Vec<ForeignKey>, SQLError> {
+        if !self.contains(name)? {
+            return Err(SQLError::Err(format!("Unknown table {}", name)));
+        }
+
+        Ok(
+            self.tables()?
+                .into_iter()
+                .flat_map(ForeignKey::of)
+                .filter(|key| key.referenced_table.eq_ignore_ascii_case(name))
+                .collect()
+        )
+    }
+
+    /// Returns the outgoing foreign keys of `name`: those of its own columns, to the tables it is referenced by. This
+    /// is every table that has to exist before `name` can be created.
+    ///
+    /// Only `name` is loaded. The keys are in column order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SQLError::Err` if there is no table `name`, or an `SQLError` if it could not be loaded.
+    pub fn referenced_by(&mut self, name: &str) -> Result<Vec<ForeignKey>, SQLError> {
+        match self.table(name)? {
+            Some(table) => Ok(ForeignKey::of(table)),
+            None => Err(SQLError::Err(format!("Unknown table {}", name))),
+        }
+    }
+
+    /// Returns the foreign keys referencing a table or a column that is not in the registry.
+    ///
+    /// Every table is loaded. The keys are ordered by table name, then column.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `SQLError` if a table could not be loaded.
+    pub fn dangling(&mut self) -> Result<Vec<ForeignKey>, SQLError> {
+        let keys: Vec<ForeignKey> = self.tables()?.into_iter().flat_map(ForeignKey::of).collect();
+
+        Ok(
+            keys.into_iter()
+                .filter(|key| {
+                    match self.tables.get(&key.referenced_table.to_lowercase()) {
+                        Some(table) => !table.attributes
+                            .iter()
+                            .any(|attr| attr.name.eq_ignore_ascii_case(&key.referenced_column)),
+                        None => true,
+                    }
+                })
+                .collect()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::{HashMap, HashSet}, rc::Rc};
+
+    use crate::sql::SQLError;
+
+    use super::{
+        super::table::{Table, Attribute, AttributeType, Constraint},
+        ForeignKey,
+        Registry,
+        TableLoader,
+    };
+
+    fn table(name: &str, foreign_keys: &[(&str, &str, &str)]) -> Table {
+        let mut attributes = vec![
+            Attribute {
+                name: String::from("id"),
+                data_type: AttributeType::Int(11),
+                raw_type: String::new(),
+                constraint: HashSet::from([Constraint::NotNull]),
+            },
+        ];
+
+        for (column, table_name, attribute_name) in foreign_keys {
+            attributes.push(Attribute {
+                name: column.to_string(),
+                data_type: AttributeType::Int(11),
+                raw_type: String::new(),
+                constraint: HashSet::from([Constraint::ForeignKey {
+                    table_name: table_name.to_string(),
+                    attribute_name: attribute_name.to_string(),
+                    on_delete: None,
+                    on_update: None,
+                }]),
+            });
+        }
+
+        Table { name: name.to_string(), attributes, primary_key: vec![0], indexes: Vec::new() }
+    }
+
+    fn shop() -> Vec<Table> {
+        vec![
+            table("Customer", &[]),
+            table("orders", &[("customer_id", "customer", "id")]),
+            table("invoice", &[("order_id", "ORDERS", "id"), ("customer_id", "customer", "id")]),
+        ]
+    }
+
+    /// Serves `shop`, counting how often the names are listed and each table is loaded.
+    struct CountingLoader {
+        listed: Rc<RefCell<usize>>,
+        loaded: Rc<RefCell<HashMap<String, usize>>>,
+    }
+
+    impl TableLoader for CountingLoader {
+        fn table_names(&mut self) -> Result<Vec<String>, SQLError> {
+            *self.listed.borrow_mut() += 1;
+
+            Ok(shop().into_iter().map(|table| table.name).collect())
+        }
+
+        fn load(&mut self, name: &str) -> Result<Table, SQLError> {
+            *self.loaded.borrow_mut().entry(name.to_string()).or_insert(0) += 1;
+
+            shop().into_iter()
+                .find(|table| table.name == name)
+                .ok_or_else(|| SQLError::Err(format!("Unknown table {}", name)))
+        }
+    }
+
+    fn key(table: &str, column: &str, referenced_table: &str) -> ForeignKey {
+        ForeignKey {
+            table: table.to_string(),
+            column: column.to_string(),
+            referenced_table: referenced_table.to_string(),
+            referenced_column: String::from("id"),
+        }
+    }
+
+    #[test]
+    fn lazy_load_test() {
+        let listed = Rc::new(RefCell::new(0));
+        let loaded = Rc::new(RefCell::new(HashMap::new()));
+        let mut registry = Registry::with_loader(CountingLoader{listed: listed.clone(), loaded: loaded.clone()});
+
+        assert_eq!(*listed.borrow(), 0);
+
+        assert_eq!(registry.table("ORDERS").unwrap().unwrap().name, "orders");
+        assert_eq!(registry.table("orders").unwrap().unwrap().name, "orders");
+        assert_eq!(registry.referenced_by("Orders").unwrap(), vec![key("orders", "customer_id", "customer")]);
+        assert!(registry.table("missing").unwrap().is_none());
+
+        assert_eq!(*loaded.borrow(), HashMap::from([(String::from("orders"), 1)]));
+
+        assert_eq!(registry.references_of("orders").unwrap(), vec![key("invoice", "order_id", "ORDERS")]);
+        assert_eq!(
+            registry.references_of("customer").unwrap(),
+            vec![key("invoice", "customer_id", "customer"), key("orders", "customer_id", "customer")]
+        );
+        assert!(registry.references_of("missing").is_err());
+
+        assert_eq!(*listed.borrow(), 1);
+        assert_eq!(
+            *loaded.borrow(),
+            HashMap::from([(String::from("Customer"), 1), (String::from("invoice"), 1), (String::from("orders"), 1)])
+        );
+    }
+
+    #[test]
+    fn fixed_registry_test() {
+        let mut registry = Registry::new(shop());
+
+        assert!(registry.contains("customer").unwrap());
+        assert!(!registry.contains("missing").unwrap());
+        assert_eq!(
+            registry.tables().unwrap().iter().map(|table| table.name.as_str()).collect::<Vec<&str>>(),
+            vec!["Customer", "invoice", "orders"]
+        );
+        assert!(registry.referenced_by("missing").is_err());
+    }
+
+    #[test]
+    fn dangling_test() {
+        let mut tables = shop();
+        tables.push(table("refund", &[("invoice_id", "invoice", "number"), ("payment_id", "payment", "id")]));
+
+        let mut registry = Registry::new(tables);
+
+        let dangling = registry.dangling().unwrap();
+
+        assert_eq!(
+            dangling.iter().map(|key| key.to_string()).collect::<Vec<String>>(),
+            vec!["refund.invoice_id -> invoice.number", "refund.payment_id -> payment.id"]
+        );
+        assert!(Registry::new(shop()).dangling().unwrap().is_empty());
+    }
+}
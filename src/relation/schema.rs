@@ -0,0 +1,336 @@
+use std::{fmt, fs, io, path::Path};
+
+use log::warn;
+use serde_json::{json, Value};
+
+use crate::{data_base::DataBase, error::Error};
+
+use super::{table::Table, introspect::load_schema};
+
+/// The version of the JSON layout written by `Table::to_json` and `Schema::to_json`.
+///
+/// Fields added to the layout get a default, so files written by an older version still load. Files written by a
+/// newer version are rejected.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Represents possible errors that can occur while reading or writing a schema file.
+#[derive(Debug)]
+pub enum SchemaFileError {
+    /// The file could not be read or written.
+    Io(io::Error),
+    /// The file is not a table or schema in the JSON layout.
+    Json(serde_json::Error),
+    /// The file was written with a newer version of the layout (see `SCHEMA_VERSION`), or has no version.
+    UnsupportedVersion(Option<u64>),
+}
+
+impl fmt::Display for SchemaFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaFileError::Io(err) => write!(f, "failed to access schema file: {}", err),
+            SchemaFileError::Json(err) => write!(f, "invalid schema file: {}", err),
+            SchemaFileError::UnsupportedVersion(Some(version)) => {
+                write!(f, "schema file version {} is newer than the supported version {}", version, SCHEMA_VERSION)
+            },
+            SchemaFileError::UnsupportedVersion(None) => write!(f, "schema file has no version"),
+        }
+    }
+}
+
+impl From<io::Error> for SchemaFileError {
+    fn from(err: io::Error) -> Self {
+        SchemaFileError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SchemaFileError {
+    fn from(err: serde_json::Error) -> Self {
+        SchemaFileError::Json(err)
+    }
+}
+
+/// Returns the field `key` of a versioned document, checking its version first.
+fn read_versioned(json: &str, key: &str) -> Result<Value, SchemaFileError> {
+    let mut document: Value = serde_json::from_str(json)?;
+
+    match document.get("version").and_then(Value::as_u64) {
+        Some(version) if version <= SCHEMA_VERSION => Ok(document[key].take()),
+        version => Err(SchemaFileError::UnsupportedVersion(version)),
+    }
+}
+
+impl Table {
+    /// Returns the table as pretty printed JSON, in a layout tagged with `SCHEMA_VERSION`.
+    ///
+    /// ```ignore
+    /// {
+    ///   "version": 1,
+    ///   "table": {"name": "user", "attributes": [...], "primary_key": [0], "indexes": []}
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchemaFileError::Json` if an attribute is encrypted, as keys are never written.
+    pub fn to_json(&self) -> Result<String, SchemaFileError> {
+        let table = serde_json::to_value(self)?;
+
+        Ok(serde_json::to_string_pretty(&json!({"version": SCHEMA_VERSION, "table": table}))?)
+    }
+
+    /// Reads a table written by `Table::to_json`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchemaFileError::UnsupportedVersion` if `json` was written by a newer version, and
+    /// `SchemaFileError::Json` if it is not a table.
+    pub fn from_json(json: &str) -> Result<Table, SchemaFileError> {
+        Ok(serde_json::from_value(read_versioned(json, "table")?)?)
+    }
+}
+
+/// The tables of a database, kept ordered by name, as saved to and loaded from a schema file.
+///
+/// A schema committed to a repository can be compared with the database, to catch drift:
+///
+/// ```ignore
+/// let expected = Schema::load("schema/production.json")?;
+///
+/// assert_eq!(Schema::from_db(&db)?, expected);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schema {
+    pub tables: Vec<Table>,
+}
+
+impl Schema {
+    /// Returns a schema of `tables`.
+    pub fn new(mut tables: Vec<Table>) -> Schema {
+        tables.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Schema { tables }
+    }
+
+    /// Returns the schema of the base tables of the current database of `db`.
+    ///
+    /// Every table is read at once, with a query for the columns of all of them and one for each of their foreign
+    /// keys, checks and indexes (see `introspect::load_schema`), so the number of queries does not grow with the number
+    /// of tables. The warnings raised reading a table are logged, as by `Table::from_db_in`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `crate::Error` if the columns of the tables could not be read.
+    pub fn from_db(db: &DataBase) -> Result<Schema, Error> {
+        let tables = load_schema(db)?
+            .into_iter()
+            .map(|(table, report)| {
+                for warning in &report.warnings {
+                    warn!("{}", warning);
+                }
+
+                table
+            })
+            .collect();
+
+        Ok(Schema::new(tables))
+    }
+
+    /// Returns the table named `name`, compared ignoring case.
+    pub fn table(&self, name: &str) -> Option<&Table> {
+        self.tables.iter().find(|table| table.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns an iterator over the tables, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = &Table> {
+        self.tables.iter()
+    }
+
+    /// Returns the schema as pretty printed JSON, in a layout tagged with `SCHEMA_VERSION`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchemaFileError::Json` if an attribute is encrypted, as keys are never written.
+    pub fn to_json(&self) -> Result<String, SchemaFileError> {
+        let tables = serde_json::to_value(&self.tables)?;
+
+        Ok(serde_json::to_string_pretty(&json!({"version": SCHEMA_VERSION, "tables": tables}))?)
+    }
+
+    /// Reads a schema written by `Schema::to_json`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchemaFileError::UnsupportedVersion` if `json` was written by a newer version, and
+    /// `SchemaFileError::Json` if it is not a schema.
+    pub fn from_json(json: &str) -> Result<Schema, SchemaFileError> {
+        Ok(Schema::new(serde_json::from_value(read_versioned(json, "tables")?)?))
+    }
+
+    /// Writes the schema to the file at `path` (see `Schema::to_json`), replacing it if it exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchemaFileError::Io` if the file could not be written, and the errors of `Schema::to_json`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SchemaFileError> {
+        Ok(fs::write(path, self.to_json()? + "\n")?)
+    }
+
+    /// Reads the schema in the file at `path`, as written by `Schema::save`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchemaFileError::Io` if the file could not be read, and the errors of `Schema::from_json`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Schema, SchemaFileError> {
+        Schema::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+impl<'a> IntoIterator for &'a Schema {
+    type Item = &'a Table;
+    type IntoIter = std::slice::Iter<'a, Table>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tables.iter()
+    }
+}
+
+impl IntoIterator for Schema {
+    type Item = Table;
+    type IntoIter = std::vec::IntoIter<Table>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tables.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use crate::{
+        data_base::DataBase,
+        sql::SQL,
+        test_tools::{db_env::DbEnv, file_env::FileEnv},
+    };
+
+    use super::super::table::{Table, Attribute, AttributeType, Constraint, ReferentialAction};
+    use super::super::encryption::{EncryptionKey, KeySource};
+    use super::{Schema, SchemaFileError, SCHEMA_VERSION};
+
+    fn attr(name: &str, data_type: AttributeType, constraint: Vec<Constraint>) -> Attribute {
+        Attribute {
+            name: String::from(name),
+            data_type,
+            raw_type: String::new(),
+            constraint: constraint.into_iter().collect(),
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Table {
+                name: String::from("schema_post"),
+                attributes: vec![
+                    attr("id", AttributeType::Int(11), vec![Constraint::NotNull, Constraint::AutoIncrement]),
+                    attr("author", AttributeType::Int(11), vec![Constraint::ForeignKey {
+                        table_name: String::from("schema_user"),
+                        attribute_name: String::from("id"),
+                        on_delete: Some(ReferentialAction::Cascade),
+                        on_update: None,
+                    }]),
+                ],
+                primary_key: vec![0],
+                indexes: Vec::new(),
+            },
+            Table {
+                name: String::from("schema_user"),
+                attributes: vec![
+                    attr("id", AttributeType::Int(11), vec![Constraint::NotNull]),
+                    attr("email", AttributeType::VarChar(255), vec![Constraint::NotNull, Constraint::Unique]),
+                ],
+                primary_key: vec![0],
+                indexes: Vec::new(),
+            },
+        ])
+    }
+
+    #[test]
+    fn table_json_test() {
+        let table = schema().tables.remove(0);
+        let json = table.to_json().unwrap();
+
+        assert!(json.contains(&format!("\n  \"version\": {}", SCHEMA_VERSION)), "{}", json);
+        assert_eq!(Table::from_json(&json).unwrap(), table);
+
+        let key = EncryptionKey::load(&KeySource::Key(String::from("k"))).unwrap();
+        let mut encrypted = table.clone();
+        encrypted.attributes[1].constraint.insert(Constraint::Encrypted(key));
+
+        assert!(matches!(encrypted.to_json(), Err(SchemaFileError::Json(_))));
+    }
+
+    #[test]
+    fn save_load_test() {
+        let schema = schema();
+        let _file = FileEnv::new("schema_save_load_test.json", "");
+
+        schema.save("schema_save_load_test.json").unwrap();
+
+        assert_eq!(Schema::load("schema_save_load_test.json").unwrap(), schema);
+        assert_eq!(schema.table("SCHEMA_USER").map(|table| table.name.as_str()), Some("schema_user"));
+        assert_eq!(schema.iter().map(|table| table.name.as_str()).collect::<Vec<&str>>(), vec!["schema_post", "schema_user"]);
+        assert_eq!((&schema).into_iter().count(), 2);
+        assert_eq!(schema.clone().into_iter().collect::<Vec<Table>>(), schema.tables);
+        assert!(matches!(Schema::load("schema_missing_file.json"), Err(SchemaFileError::Io(_))));
+    }
+
+    #[test]
+    fn version_test() {
+        //a version 1 file, before any field was added
+        let old = r#"{"version": 1, "tables": [
+            {"name": "tag", "attributes": [{"name": "id", "data_type": {"int": 11}, "constraint": ["not_null"]}], "primary_key": [0]}
+        ]}"#;
+        let schema = Schema::from_json(old).unwrap();
+
+        assert_eq!(schema.tables[0].attributes[0], attr("id", AttributeType::Int(11), vec![Constraint::NotNull]));
+        assert!(schema.tables[0].indexes.is_empty());
+
+        let newer = format!(r#"{{"version": {}, "tables": []}}"#, SCHEMA_VERSION + 1);
+
+        assert!(matches!(Schema::from_json(&newer), Err(SchemaFileError::UnsupportedVersion(Some(_)))));
+        assert!(matches!(Schema::from_json(r#"{"tables": []}"#), Err(SchemaFileError::UnsupportedVersion(None))));
+        assert!(matches!(Table::from_json(r#"{"version": 1, "tables": []}"#), Err(SchemaFileError::Json(_))));
+        assert_eq!(
+            SchemaFileError::UnsupportedVersion(Some(SCHEMA_VERSION + 1)).to_string(),
+            format!("schema file version {} is newer than the supported version {}", SCHEMA_VERSION + 1, SCHEMA_VERSION)
+        );
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn from_db_matches_fixture_test() {
+        let _env = DbEnv::new(
+            vec![
+                SQL::new("CREATE TABLE schema_user (id int NOT NULL, email varchar(255) NOT NULL UNIQUE, PRIMARY KEY (id))").unwrap(),
+                SQL::new(
+                    "CREATE TABLE schema_post (id int NOT NULL AUTO_INCREMENT, author int, PRIMARY KEY (id), \
+                    FOREIGN KEY (author) REFERENCES schema_user (id) ON DELETE CASCADE)"
+                ).unwrap(),
+            ],
+            vec![
+                SQL::new("DROP TABLE IF EXISTS schema_post").unwrap(),
+                SQL::new("DROP TABLE IF EXISTS schema_user").unwrap(),
+            ]
+        );
+        let _file = FileEnv::new("schema_fixture_test.json", &schema().to_json().unwrap());
+
+        let expected = Schema::load("schema_fixture_test.json").unwrap();
+        let actual = Schema::from_db(&DataBase::from_env().unwrap()).unwrap();
+        let actual = Schema::new(
+            actual.tables.into_iter().filter(|table| table.name.starts_with("schema_")).collect()
+        );
+
+        assert_eq!(actual, expected);
+    }
+}
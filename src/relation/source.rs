@@ -0,0 +1,410 @@
+use std::{fmt, collections::HashMap, io::{self, BufRead}, str::CharIndices};
+
+use crate::sql::value::Value;
+
+/// A CSV record, along with the line it starts on. Fields are `None` if they are empty and unquoted.
+type Record = (usize, Vec<Option<String>>);
+
+/// Represents possible errors that can occur while reading rows from a source.
+#[derive(Debug)]
+pub enum SourceError {
+    /// The source could not be read. Nothing after it can be read either.
+    Io(io::Error),
+    /// The row starting on `line` is malformed. The rows after it can still be read.
+    Parse{line: usize, reason: String},
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceError::Io(err) => write!(f, "failed to read: {}", err),
+            SourceError::Parse{line, reason} => write!(f, "line {}: {}", line, reason),
+        }
+    }
+}
+
+/// The rows of a file being imported, read one at a time (see `Table::import_stream_with`).
+pub trait RowSource: Iterator<Item = Result<HashMap<String, Value>, SourceError>> {
+    /// Returns the number of bytes read from the underlying reader so far.
+    fn bytes_read(&self) -> u64;
+}
+
+/// The rows of a CSV file with a header line, as written by `export::write_csv`.
+///
+/// Fields are read as `Value::Text`, except empty unquoted fields, which are `NULL`; `""` is an empty string. Quoted
+/// fields may span lines. Blank lines are skipped.
+pub struct CsvRows<R: BufRead> {
+    reader: R,
+    header: Option<Vec<String>>,
+    bytes: u64,
+    line: usize,
+}
+
+impl<R: BufRead> CsvRows<R> {
+    pub fn new(reader: R) -> CsvRows<R> {
+        CsvRows{reader, header: None, bytes: 0, line: 0}
+    }
+
+    /// Reads the next record.
+    fn record(&mut self) -> Option<Result<Record, SourceError>> {
+        let mut fields: Vec<Option<String>> = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        let mut start = 0;
+
+        loop {
+            let mut line = String::new();
+
+            match self.reader.read_line(&mut line) {
+                Ok(0) if in_quotes => {
+                    return Some(Err(SourceError::Parse{line: start, reason: String::from("unterminated quoted field")}));
+                },
+                Ok(0) => return None,
+                Ok(n) => {
+                    self.bytes += n as u64;
+                    self.line += 1;
+                },
+                Err(err) => return Some(Err(SourceError::Io(err))),
+            }
+
+            if !in_quotes {
+                if line.trim_end_matches(['\r', '\n']).is_empty() {
+                    continue;
+                }
+                start = self.line;
+            }
+
+            let mut chars = line.chars().peekable();
+
+            while let Some(ch) = chars.next() {
+                match (in_quotes, ch) {
+                    (true, '"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    },
+                    (true, '"') => in_quotes = false,
+                    (true, ch) => field.push(ch),
+                    (false, '"') if field.is_empty() && !quoted => {
+                        quoted = true;
+                        in_quotes = true;
+                    },
+                    (false, ',') => {
+                        fields.push(match quoted || !field.is_empty() {
+                            true => Some(std::mem::take(&mut field)),
+                            false => None,
+                        });
+                        quoted = false;
+                    },
+                    (false, '\r') if chars.peek() == Some(&'\n') => {},
+                    (false, '\n') => {},
+                    (false, ch) => field.push(ch),
+                }
+            }
+
+            if !in_quotes {
+                fields.push(match quoted || !field.is_empty() {
+                    true => Some(field),
+                    false => None,
+                });
+
+                return Some(Ok((start, fields)));
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for CsvRows<R> {
+    type Item = Result<HashMap<String, Value>, SourceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.header.is_none() {
+            let header = match self.record()? {
+                Ok((_, header)) => header.into_iter().map(Option::unwrap_or_default).collect(),
+                Err(err) => return Some(Err(err)),
+            };
+            self.header = Some(header);
+        }
+
+        let (line, fields) = match self.record()? {
+            Ok(record) => record,
+            Err(err) => return Some(Err(err)),
+        };
+
+        let header = self.header.as_ref().unwrap();
+
+        if fields.len() != header.len() {
+            return Some(Err(SourceError::Parse{
+                line,
+                reason: format!("expected {} fields, found {}", header.len(), fields.len()),
+            }));
+        }
+
+        Some(Ok(
+            header.iter()
+                .cloned()
+                .zip(fields.into_iter().map(|field| field.map_or(Value::Null, Value::Text)))
+                .collect()
+        ))
+    }
+}
+
+impl<R: BufRead> RowSource for CsvRows<R> {
+    fn bytes_read(&self) -> u64 {
+        self.bytes
+    }
+}
+
+/// The rows of a JSON lines file, one flat object per line keyed by column name, as written by `export::write_jsonl`.
+///
+/// Strings are read as `Value::Text`, numbers as `Value::Int`, `Value::UInt` or `Value::Float`, booleans as
+/// `Value::Bool` and `null` as `Value::Null`. Nested arrays and objects are rejected. Blank lines are skipped.
+pub struct JsonlRows<R: BufRead> {
+    reader: R,
+    bytes: u64,
+    line: usize,
+}
+
+impl<R: BufRead> JsonlRows<R> {
+    pub fn new(reader: R) -> JsonlRows<R> {
+        JsonlRows{reader, bytes: 0, line: 0}
+    }
+}
+
+impl<R: BufRead> Iterator for JsonlRows<R> {
+    type Item = Result<HashMap<String, Value>, SourceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut line = String::new();
+
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(n) => {
+                    self.bytes += n as u64;
+                    self.line += 1;
+                },
+                Err(err) => return Some(Err(SourceError::Io(err))),
+            }
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            return Some(parse_object(&line).map_err(|reason| SourceError::Parse{line: self.line, reason}));
+        }
+    }
+}
+
+impl<R: BufRead> RowSource for JsonlRows<R> {
+    fn bytes_read(&self) -> u64 {
+        self.bytes
+    }
+}
+
+/// A cursor over the text of a JSON line.
+struct Json<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Json<'_> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.text[self.pos..].chars().next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.peek() {
+            Some(ch) if ch == expected => {
+                self.pos += ch.len_utf8();
+                Ok(())
+            },
+            Some(ch) => Err(format!("expected '{}', found '{}' at column {}", expected, ch, self.pos + 1)),
+            None => Err(format!("expected '{}', found the end of the line", expected)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+
+        let mut text = String::new();
+        let mut chars = self.text[self.pos..].char_indices();
+
+        loop {
+            let (i, ch) = chars.next().ok_or("unterminated string")?;
+
+            match ch {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(text);
+                },
+                '\\' => {
+                    let (_, escape) = chars.next().ok_or("unterminated string")?;
+
+                    text.push(match escape {
+                        '"' | '\\' | '/' => escape,
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let high = code_unit(&mut chars)?;
+                            let code = match high {
+                                0xd800..=0xdbff => {
+                                    let low = match (chars.next(), chars.next()) {
+                                        (Some((_, '\\')), Some((_, 'u'))) => code_unit(&mut chars)?,
+                                        _ => return Err(String::from("unpaired surrogate")),
+                                    };
+                                    0x10000 + ((high - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff)
+                                },
+                                code => code,
+                            };
+
+                            char::from_u32(code).ok_or("unpaired surrogate")?
+                        },
+                        escape => return Err(format!("invalid escape \\{}", escape)),
+                    });
+                },
+                ch => text.push(ch),
+            }
+        }
+    }
+
+    fn value(&mut self, column: &str) -> Result<Value, String> {
+        match self.peek() {
+            Some('"') => self.string().map(Value::Text),
+            Some('{') | Some('[') => Err(format!("{}: nested values are not supported", column)),
+            Some(_) => {
+                let rest = &self.text[self.pos..];
+                let end = rest.find([',', '}', ' ', '\t', '\r', '\n']).unwrap_or(rest.len());
+                let token = &rest[..end];
+                self.pos += end;
+
+                match token {
+                    "null" => Ok(Value::Null),
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    _ => token.parse::<i64>().map(Value::Int)
+                        .or_else(|_| token.parse::<u64>().map(Value::UInt))
+                        .or_else(|_| token.parse::<f64>().map(Value::Float))
+                        .map_err(|_| format!("{}: invalid value {}", column, token)),
+                }
+            },
+            None => Err(format!("{}: missing value", column)),
+        }
+    }
+}
+
+/// Reads the four hex digits of a `\u` escape.
+fn code_unit(chars: &mut CharIndices<'_>) -> Result<u32, String> {
+    let hex: String = chars.by_ref().take(4).map(|(_, ch)| ch).collect();
+
+    u32::from_str_radix(&hex, 16).map_err(|_| format!("invalid escape \\u{}", hex))
+}
+
+/// Returns the columns of a flat JSON object.
+fn parse_object(line: &str) -> Result<HashMap<String, Value>, String> {
+    let mut json = Json{text: line, pos: 0};
+    let mut row = HashMap::new();
+
+    json.expect('{')?;
+
+    if json.peek() == Some('}') {
+        json.pos += 1;
+    }
+    else {
+        loop {
+            let column = json.string()?;
+            json.expect(':')?;
+            let value = json.value(&column)?;
+
+            if row.insert(column.clone(), value).is_some() {
+                return Err(format!("{}: duplicate column", column));
+            }
+
+            match json.peek() {
+                Some(',') => json.pos += 1,
+                _ => {
+                    json.expect('}')?;
+                    break;
+                },
+            }
+        }
+    }
+
+    match json.peek() {
+        None => Ok(row),
+        Some(_) => Err(format!("unexpected text after the object at column {}", json.pos + 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, io::Cursor};
+
+    use crate::sql::value::Value;
+
+    use super::{CsvRows, JsonlRows, RowSource, SourceError};
+
+    fn text(val: &str) -> Value {
+        Value::Text(String::from(val))
+    }
+
+    #[test]
+    fn csv_rows_test() {
+        let csv = "id,name,note\r\n1,\"Smith, J\",\n\n2,\"say \"\"hi\"\"\nthere\",\"\"\n3,x\n";
+        let mut rows = CsvRows::new(Cursor::new(csv));
+
+        assert_eq!(
+            rows.next().unwrap().unwrap(),
+            HashMap::from([(String::from("id"), text("1")), (String::from("name"), text("Smith, J")), (String::from("note"), Value::Null)])
+        );
+        assert_eq!(
+            rows.next().unwrap().unwrap(),
+            HashMap::from([(String::from("id"), text("2")), (String::from("name"), text("say \"hi\"\nthere")), (String::from("note"), text(""))])
+        );
+        assert!(matches!(rows.next(), Some(Err(SourceError::Parse{line: 6, ..}))));
+        assert!(rows.next().is_none());
+        assert_eq!(rows.bytes_read(), csv.len() as u64);
+
+        let mut unterminated = CsvRows::new(Cursor::new("id\n\"1\n"));
+        assert!(matches!(unterminated.next(), Some(Err(SourceError::Parse{line: 2, ..}))));
+    }
+
+    #[test]
+    fn jsonl_rows_test() {
+        let jsonl = "{\"id\":1,\"name\":\"caf\\u00e9 \\ud83d\\ude00\",\"ok\":true,\"n\":null}\n\n\
+            { \"id\" : 18446744073709551615 , \"score\": -1.5e2 }\n\
+            {\"id\":[1]}\n\
+            {\"id\":1} x\n";
+        let mut rows = JsonlRows::new(Cursor::new(jsonl));
+
+        assert_eq!(
+            rows.next().unwrap().unwrap(),
+            HashMap::from([
+                (String::from("id"), Value::Int(1)),
+                (String::from("name"), text("café 😀")),
+                (String::from("ok"), Value::Bool(true)),
+                (String::from("n"), Value::Null),
+            ])
+        );
+        assert_eq!(
+            rows.next().unwrap().unwrap(),
+            HashMap::from([(String::from("id"), Value::UInt(u64::MAX)), (String::from("score"), Value::Float(-150.0))])
+        );
+        assert!(matches!(
+            rows.next(),
+            Some(Err(SourceError::Parse{line: 4, ref reason})) if reason == "id: nested values are not supported"
+        ));
+        assert!(matches!(rows.next(), Some(Err(SourceError::Parse{line: 5, ..}))));
+        assert!(rows.next().is_none());
+        assert_eq!(rows.bytes_read(), jsonl.len() as u64);
+    }
+}
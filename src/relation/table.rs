@@ -1,127 +1,242 @@
-use std::{fmt::{self, Display}, collections::{HashSet, HashMap}};
-use core::hash::Hash;
+use std::{fmt::{self, Display}, collections::{HashSet, HashMap}, ops::Range};
+use core::hash::{Hash, Hasher};
 
-use log::info;
-use mysql::{Row};
+use log::{info, warn};
+use mysql::Row;
+use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::{data_base::DataBase, sql::{SQL, QDL, DDL, QML}};
+use crate::{data_base::{DataBase, ConfigError, Executor}, error::ER_NO_SUCH_TABLE, instrument::Timed, sql::{SQL, QDL, DDL, QML, SQLError, Prepared, IdentifierError, escape_string, quote_ident, validate_identifier, keywords::ServerVersion, value::{Value, ValueError, MAX_LITERAL_SIZE}}};
 
-use super::RelationMethods;
+use super::{RelationMethods, Registry, type_override::TypeOverrideMap, encryption::EncryptionKey, defaults::{DefaultValue, read_default}, introspect::{IntrospectExecutor, LoadReport}, registry::ForeignKey};
 
 /// A struct representing a table in a relational database
-#[derive(Clone, Debug)]
+///
+/// Tables are equal when their names, attributes in order, primary keys and indexes are.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Table{
     /// The name of the table.
     pub name: String,
     /// A vector of `Attribute`s representing the columns of the table.
+    ///
+    /// Every statement generated for the table lists its columns in this order, whatever the order of the values it
+    /// is given.
     pub attributes: Vec<Attribute>,
-    /// The index of the primary key attribute in the `attributes` vector, if one exists.
-    pub primary_key: Option<usize>,
+    /// The indices in `attributes` of the primary key's columns, in key order. Empty if the table has no primary key.
+    ///
+    /// This used to be an `Option<usize>`, which could not represent a key over several columns, such as that of a
+    /// junction table. `Some(i)` is now `vec![i]` and `None` is `Vec::new()`.
+    pub primary_key: Vec<usize>,
+    /// The secondary indexes of the table, such as `UNIQUE(email, tenant_id)`.
+    ///
+    /// A unique index over a single column is the attribute's `Constraint::Unique` instead, and the primary key is
+    /// `primary_key`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub indexes: Vec<Index>,
 }
 
 impl Table {
-    /// Returns a `Table` with the given name, created from the database.
-    /// Return None if the table does not exist.
+    /// Returns a `Table` with the given name, created from the database of the environment (see `DataBase::from_env`).
+    ///
+    /// Each call opens a new connection. Use `Table::from_db_in` to read tables from a `DataBase` that is already open.
     ///
     /// # Arguments
     ///
     /// * `table_name` - The name of an already existing table.
     ///
+    /// # Errors
+    ///
+    /// Returns `TableLoadError::ConnectionFailed` if no connection could be opened, and the errors of
+    /// `Table::from_db_in`.
+    ///
     /// # Examples
     ///
     /// ```
     /// let table = Table::from_db("employees").unwrap();
     /// ```
-    pub fn from_db(table_name: &str) -> Option<Table> {
-        //println!("\n\n");
-        match DataBase::from_env() {
-            Ok(db) => {
-                        
-                let mut primary_key : Option<usize> = None;
-                let mut col_num: usize = 0;
-                
-                //println!("{}", table_name);
-                let attr : Vec<(Option<Attribute>, bool)> = db.execute(
-                    &SQL::new(&format!("SHOW FULL COLUMNS FROM {}", table_name)).unwrap(),
-                    |row| {
-                        match row {
-                            Ok(column) => {
-                                info!("load row:{:?}", column);
-                                let primary_key_str: String = column.get(4).unwrap();
-
-                                (Attribute::from_row(column, table_name), &primary_key_str == "PRI")
-                            },
-                            Err(_err) => {
-                                todo!()
-                            }
-                        }
-                    }
-                ).unwrap();
-
-                let attr: Vec<Attribute> = attr.iter()
-                .filter_map(
-                    |val| {
-                        match val {
-                            (None, _val) => None,
-                            (Some(val), true) => {
-                                primary_key = Some(col_num.clone());
-                                Some(val.clone())
-                            },
-                            (Some(val), false) => {
-                                col_num+=1;
-                                Some(val.clone())
-                            },
-                        }
-                    }
-                ).collect();
+    pub fn from_db(table_name: &str) -> Result<Table, TableLoadError> {
+        Table::from_db_in(&DataBase::from_env().map_err(TableLoadError::ConnectionFailed)?, table_name)
+    }
 
-                Some(
-                    Table{
-                        name: table_name.to_string(),
-                        attributes: attr,
-                        primary_key: primary_key
-                    }
-                )
-            },
-            Err(_err) => {
-                None
-            }
+    /// Same as `Table::from_db`, but reads the table from `db`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TableLoadError::TableNotFound` if the database has no table `table_name`, and
+    /// `TableLoadError::Execution` if its columns could not be read.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let db = DataBase::from_env().unwrap();
+    ///
+    /// let employees = Table::from_db_in(&db, "employees").unwrap();
+    /// let departments = Table::from_db_in(&db, "departments").unwrap();
+    /// ```
+    pub fn from_db_in<E: Executor>(db: &E, table_name: &str) -> Result<Table, TableLoadError> {
+        let (table, report) = Table::from_db_report_in(db, table_name)?;
+
+        for warning in &report.warnings {
+            warn!("{}", warning);
+        }
+
+        Ok(table)
+    }
+
+    /// Same as `Table::from_db_in`, but keeps the table in the schema cache of `db`, so loading it again runs no
+    /// statements until it is invalidated or expires (see `SchemaCache`).
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `Table::from_db_in`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let db = DataBase::from_env().unwrap().with_schema_cache(SchemaCache::with_ttl(Duration::from_secs(60)));
+    ///
+    /// let employees = Table::from_db_cached(&db, "employees").unwrap();
+    /// ```
+    pub fn from_db_cached(db: &DataBase, table_name: &str) -> Result<Table, TableLoadError> {
+        db.schema_cache().load(db, table_name)
+    }
+
+    /// Same as `Table::from_db`, but also returns the parts of the schema that could not be read the usual way.
+    ///
+    /// Foreign keys are read from `information_schema`, falling back to `SHOW CREATE TABLE` when that is denied and
+    /// omitted when neither can be read (see `introspect::foreign_keys`), instead of failing the whole load. `CHECK`
+    /// constraints that cannot be read, or that constrain several columns, are omitted the same way. Columns of types
+    /// the crate does not model are kept as `AttributeType::Unknown`, with a warning.
+    pub fn from_db_report(table_name: &str) -> Result<(Table, LoadReport), TableLoadError> {
+        Table::from_db_report_in(&DataBase::from_env().map_err(TableLoadError::ConnectionFailed)?, table_name)
+    }
+
+    /// Same as `Table::from_db_report`, but reads the table from `db`.
+    pub fn from_db_report_in<E: Executor>(db: &E, table_name: &str) -> Result<(Table, LoadReport), TableLoadError> {
+        let timed = Timed::schema_load(table_name);
+
+        let table = timed.in_scope(|| Table::load(db, table_name));
+
+        timed.finish(table.as_ref().ok().map(|(table, _)| table.attributes.len()));
+
+        table
+    }
+
+    /// Reads the columns of `table_name` from `db`, for `Table::from_db_report_in`.
+    fn load<E: Executor>(db: &E, table_name: &str) -> Result<(Table, LoadReport), TableLoadError> {
+        let statement = SQL::new(&format!("SHOW FULL COLUMNS FROM {}", quote_ident(table_name))).unwrap();
+
+        let rows = db.execute(&statement, |row| row).map_err(|err| TableLoadError::reading(table_name, err))?;
+
+        let columns = rows.into_iter()
+            .collect::<Result<Vec<Row>, mysql::Error>>()
+            .map_err(|err| TableLoadError::reading(table_name, crate::Error::query(&statement.to_string(), err)))?;
+
+        Ok(Table::from_column_rows(db, table_name, columns))
+    }
+
+    /// Builds the table `table_name` from the rows of `SHOW FULL COLUMNS`, or rows with the same column names, reading
+    /// its foreign keys, checks and indexes from `executor`.
+    pub(super) fn from_column_rows<E: IntrospectExecutor>(executor: &E, table_name: &str, columns: Vec<Row>) -> (Table, LoadReport) {
+        let mut attr : Vec<(Option<Attribute>, String)> = Vec::with_capacity(columns.len());
+
+        for column in columns {
+            info!("load row:{:?}", column);
+            let key: String = column.get("Key").unwrap();
+
+            attr.push((Attribute::from_row(column), key));
+        }
+
+        //indexed columns may be foreign keys
+        let indexed: Vec<String> = attr.iter()
+            .filter_map(|(attr, key)| attr.as_ref().filter(|_| key == "MUL").map(|attr| attr.name.clone()))
+            .collect();
+
+        let mut table = Table::from_columns(
+            table_name,
+            attr.into_iter().map(|(attr, key)| (attr, key == "PRI")).collect()
+        );
+        let mut report = LoadReport::default();
+
+        table.resolve_foreign_keys(executor, &indexed, &mut report);
+        table.resolve_checks(executor, &mut report);
+        table.resolve_indexes(executor, &mut report);
+        table.report_unknown_types(&mut report);
+
+        (table, report)
+    }
+
+    /// Builds a `Table` from its columns in definition order, each flagged if it is part of the primary key.
+    ///
+    /// Columns that could not be read are dropped. The primary key is every flagged column among the remaining
+    /// attributes, in definition order, so the attributes keep the order of the table definition.
+    fn from_columns(table_name: &str, columns: Vec<(Option<Attribute>, bool)>) -> Table {
+        let columns: Vec<(Attribute, bool)> = columns
+            .into_iter()
+            .filter_map(|(attr, primary_key)| attr.map(|attr| (attr, primary_key)))
+            .collect();
+
+        Table{
+            name: table_name.to_string(),
+            primary_key: columns.iter()
+                .enumerate()
+                .filter(|(_, (_, primary_key))| *primary_key)
+                .map(|(index, _)| index)
+                .collect(),
+            indexes: Vec::new(),
+            attributes: columns.into_iter().map(|(attr, _)| attr).collect(),
         }
     }
 
+    /// Returns a `Table` with the given name, created from the database, with `overrides` applied to its attributes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `SQLError` if the table could not be loaded (see `Table::from_db`), and `SQLError::Err` if an
+    /// override is incompatible with a column's type.
+    pub fn from_db_with(table_name: &str, overrides: &TypeOverrideMap) -> Result<Table, SQLError> {
+        let mut table = Table::from_db(table_name)?;
+
+        overrides.apply(&mut table)
+            .map_err(|err| SQLError::Err(err.to_string()))?;
+
+        Ok(table)
+    }
+
+    /// Returns the foreign keys of the table, in column order, or an empty vector if it has none.
+    ///
+    /// An attribute with several foreign keys has one `ForeignKey` for each.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// for key in table.foreign_keys() {
+    ///     println!("JOIN {} ON {}.{} = {}.{}", key.referenced_table, key.table, key.column, key.referenced_table, key.referenced_column);
+    /// }
+    /// ```
+    pub fn foreign_keys(&self) -> Vec<ForeignKey> {
+        ForeignKey::of(self)
+    }
+
     /// Returns a vector of foreign key tuples for the table.
     ///
-    /// The tuples contain the name of the table and the name of the attribute that the foreign key references.
+    /// The tuples contain the name of the table and the name of the attribute that the foreign key references. An
+    /// attribute with several foreign keys has one tuple for each.
     ///
     /// # Examples
     ///
     /// ```
     /// let foreign_keys = table.get_foreign_keys().unwrap();
     /// ```
+    #[deprecated(note = "use `Table::foreign_keys`, which also names the column of each foreign key")]
     pub fn get_foreign_keys(&self) -> Option<Vec<(String, String)>> {
-
-        let foreign_key: Vec<(String, String)> = self.attributes
-            .iter()
-            .filter(|a| {//filter out all attributes without foreign key constraint
-                for constraint in &a.constraint{
-                    if let Constraint::ForeignKey { .. } = constraint {
-                        return true
-                    }
-                }
-                return false
-            })
-            .map(|a| {//turn a into foreign key
-                for constraint in &a.constraint{
-                    if let Constraint::ForeignKey { table_name, attribute_name  } = constraint {
-                        return (table_name.clone(), attribute_name.clone())
-                    }
-                }
-                panic!()
-            })
+        let foreign_key: Vec<(String, String)> = self.foreign_keys()
+            .into_iter()
+            .map(|key| (key.referenced_table, key.referenced_column))
             .collect();
 
-        if foreign_key.len() == 0 {
+        if foreign_key.is_empty() {
             return None
         }
 
@@ -130,6 +245,10 @@ impl Table {
 
     /// Returns a `QML` representing an `INSERT` statement for the table with the given values.
     ///
+    /// Values are given unquoted: values of string, binary and temporal columns are escaped and quoted, and values of
+    /// numeric columns are written as they are (see `Attribute::value_expression`). `NULL` is written as it is into a
+    /// column of any type.
+    ///
     /// # Arguments
     ///
     /// * `values` - A `HashMap` of column names and values to insert into the table.
@@ -144,42 +263,48 @@ impl Table {
     ///         Attribute{
     ///             name: String::from("PersonID"),
     ///             data_type: AttributeType::Int(16),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("LastName"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("FirstName"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("Address"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("City"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///     ],
-    ///     primary_key: None,
+    ///     primary_key: Vec::new(),
+    ///     indexes: Vec::new(),
     /// };
     /// 
     /// let mut values = HashMap::new();
     /// 
     /// values.insert(String::from("PersonID"), String::from("23"));
-    /// values.insert(String::from("LastName"), String::from("'Doe'"));
-    /// values.insert(String::from("FirstName"), String::from("'John'"));
-    /// values.insert(String::from("Address"), String::from("'1st Street'"));
-    /// values.insert(String::from("City"), String::from("'Night City'"));
+    /// values.insert(String::from("LastName"), String::from("Doe"));
+    /// values.insert(String::from("FirstName"), String::from("John"));
+    /// values.insert(String::from("Address"), String::from("1st Street"));
+    /// values.insert(String::from("City"), String::from("Night City"));
     /// 
     /// let actual = table.insert(&values);
-    /// assert_eq!(actual, Some(QML(String::from("INSERT INTO table_1(PersonID,LastName,FirstName,Address,City) VALUES (23,'Doe','John','1st Street','Night City')"))));
+    /// assert_eq!(actual, Ok(QML(String::from("INSERT INTO table_1(PersonID,LastName,FirstName,Address,City) VALUES (23,'Doe','John','1st Street','Night City')"))));
     /// ```
     ///
     /// Creating an insertion statement where some the columns have an inserted value
@@ -190,43 +315,49 @@ impl Table {
     ///         Attribute{
     ///             name: String::from("PersonID"),
     ///             data_type: AttributeType::Int(16),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("LastName"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("FirstName"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("Address"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("City"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///     ],
-    ///     primary_key: None,
+    ///     primary_key: Vec::new(),
+    ///     indexes: Vec::new(),
     /// };
     /// 
     /// let mut values = HashMap::new();
     /// 
     /// values.insert(String::from("PersonID"), String::from("23"));
-    /// values.insert(String::from("LastName"), String::from("'Doe'"));
-    /// values.insert(String::from("FirstName"), String::from("'John'"));
+    /// values.insert(String::from("LastName"), String::from("Doe"));
+    /// values.insert(String::from("FirstName"), String::from("John"));
     /// 
     /// let actual = table.insert(&values);
-    /// assert_eq!(actual, Some(QML(String::from("INSERT INTO table_1(PersonID,LastName,FirstName) VALUES (23,'Doe','John')"))));
+    /// assert_eq!(actual, Ok(QML(String::from("INSERT INTO table_1(PersonID,LastName,FirstName) VALUES (23,'Doe','John')"))));
     /// ```
     ///
-    /// Failed insertion creation results in `Option::None` being returned
+    /// Failed insertion creation results in an `InsertError` being returned
     /// ```rust
     /// let table = Table{
     ///     name: String::from("table_1"),
@@ -234,300 +365,1881 @@ impl Table {
     ///         Attribute{
     ///             name: String::from("PersonID"),
     ///             data_type: AttributeType::Int(16),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("LastName"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("FirstName"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("Address"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///         Attribute{
     ///             name: String::from("City"),
     ///             data_type: AttributeType::VarChar(255),
+    ///             raw_type: String::new(),
     ///             constraint: HashSet::new()
     ///         },
     ///     ],
-    ///     primary_key: None,
+    ///     primary_key: Vec::new(),
+    ///     indexes: Vec::new(),
     /// };
     /// 
     /// let mut values = HashMap::new();
     ///
     /// let actual = table.insert(&values);
     /// 
-    /// assert_eq!(actual, None);
+    /// assert_eq!(actual, Err(InsertError::EmptyValues));
     /// ```
-    pub fn insert(&self, values: &HashMap<String, String>) -> Option<QML>{//should be turned into Result<SQL, ERROR why couldn't be parsed>
-        let (columns, values) = self.attributes
-            .iter()
-            .filter(|attr| {
-                if let Some(_) = values.get(&attr.name) {
-                    return true;
-                }
-                return false;
-            })
-            .map(|attr| {
-                (
-                    attr.name.clone(),
-                    {
-                        match &attr.data_type{
-                            // AttributeType::Char(_) |
-                            // AttributeType::VarChar(_) |
-                            // AttributeType::Binary(_) |
-                            // AttributeType::VarBinary(_) |
-                            // AttributeType::TinyBlob |
-                            // AttributeType::TinyText |
-                            // AttributeType::Text(_) |
-                            // AttributeType::Blob(_) |
-                            // AttributeType::MediumText |
-                            // AttributeType::MediumBlob |
-                            // AttributeType::LongText |
-                            // AttributeType::LongBlob |
-                            
-                            // AttributeType::Date |
-                            // AttributeType::DateTime |
-                            // AttributeType::Time => format!("\'{}\'", values.get(&attr.name).unwrap()),
-
-                            _ => values.get(&attr.name).unwrap().to_string()
-                        }
-                    }
-                )
-            })
-            .fold(
-                (String::new(), String::new()),
-                |(columns, values), (column, value)| (format!("{},{}", columns, column), format!("{},{}", values, value))
-            );
+    ///
+    /// # Errors
+    ///
+    /// Returns `InsertError::EmptyValues` if `values` is empty, `InsertError::UnknownColumns` listing the keys that are
+    /// not attributes of the table, `InsertError::MissingRequired` if it leaves out a required column (see
+    /// `Table::validate_row`), `InsertError::NullNotAllowed` if a `NOT NULL` column is given `NULL` and
+    /// `InsertError::TypeMismatch` if a value of a numeric column is not a number or a value of an enum or set column is
+    /// not made of its values (see `AttributeType::is_member`).
+    pub fn insert(&self, values: &HashMap<String, String>) -> Result<QML, InsertError> {
+        self.check_insert(values)?;
 
-        if ("", "") == (&columns, &values) {
-            return None;
+        match self.insert_columns(values) {
+            Some((columns, values)) => Ok(QML(format!("INSERT INTO {}({}) VALUES ({})", quote_ident(&self.name), columns, values))),
+            None => Err(InsertError::EmptyValues),
         }
+    }
 
-        let (m1, m2) = (columns.len(), values.len());
+    /// Same as `Table::insert`, but returns `None` whatever went wrong, and leaves out columns that are not
+    /// attributes of the table, with a warning, without checking for required columns.
+    #[deprecated(note = "use `Table::insert`, which reports why no statement could be built")]
+    pub fn insert_or_none(&self, values: &HashMap<String, String>) -> Option<QML> {
+        let (columns, values) = self.insert_columns(values)?;
 
-        Some(QML(format!("INSERT INTO {}({}) VALUES ({})", &self.name, &columns[1..m1], &values[1..m2])))
+        Some(QML(format!("INSERT INTO {}({}) VALUES ({})", quote_ident(&self.name), columns, values)))
     }
 
-}
+    /// Same as `Table::insert`, but takes typed values, which are rendered as in `Table::insert_values`.
+    ///
+    /// Each value is first coerced to the type of its column (see `Value::coerce_to`), so numeric text becomes a number
+    /// for a numeric column, while text that is not a number is caught here rather than by the server. Maps of
+    /// strings can be converted with `value::from_strings`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `Table::insert`, with `InsertError::TypeMismatch` if a value cannot be coerced to the type
+    /// of its column or is not made of the values of its enum or set column, `InsertError::NullNotAllowed` if
+    /// `Value::Null` is given to a `NOT NULL` column, and `InsertError::Literal` if a value renders to a literal larger than `MAX_LITERAL_SIZE`.
+    pub fn insert_typed(&self, values: &HashMap<String, Value>) -> Result<QML, InsertError> {
+        let coerced = self.coerce_insert(values)?;
 
-impl Display for Table {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let attr : Vec<String> = self.attributes
-            .iter()
-            .map(|attr| {
-                attr.to_string() 
-            })
-            .collect();
-        
-        let attr = attr.join(",");
+        match self.insert_values(&coerced, MAX_LITERAL_SIZE) {
+            Ok(Some(insert)) => Ok(insert),
+            Ok(None) => Err(InsertError::EmptyValues),
+            Err(err) => Err(InsertError::Literal(err)),
+        }
+    }
+
+    /// Same as `Table::insert_typed`, but returns the `INSERT` with a `?` placeholder for each value, bound to it
+    /// rather than rendered as a literal (see `SQL::prepared`).
+    ///
+    /// Values for binary columns are bound as bytes, and `Value::Default` is written into the statement as `DEFAULT`.
+    /// As nothing is rendered there is no limit on the size of a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `Table::insert_typed`, but for `InsertError::Literal`.
+    pub fn insert_prepared(&self, values: &HashMap<String, Value>) -> Result<Prepared, InsertError> {
+        let values = self.coerce_insert(values)?;
 
-        match self.primary_key {
-            Some(index) => {
-                let primary_key = &self.attributes[index].name;
+        let mut columns: Vec<String> = Vec::new();
+        let mut placeholders: Vec<String> = Vec::new();
+        let mut params: Vec<Value> = Vec::new();
 
-                write!(f, "CREATE TABLE {} ({}, PRIMARY KEY({}))", self.name, attr, primary_key)
-            },
-            None => write!(f, "CREATE TABLE {} ({})", self.name, attr),
+        for attr in &self.attributes {
+            let value = match values.get(&attr.name) {
+                Some(value) => value.clone(),
+                None => continue,
+            };
+
+            columns.push(quote_ident(&attr.name));
+
+            match value {
+                Value::Default => placeholders.push(attr.write_expression(&value.to_string())),
+                value => {
+                    placeholders.push(attr.write_expression("?"));
+                    params.push(match attr.data_type.is_binary() {
+                        true => value.into_bytes(),
+                        false => value,
+                    });
+                },
+            }
         }
-    }
-}
 
-impl RelationMethods for Table {
-    fn select(&self) -> QDL {
-        QDL(format!("SELECT * FROM {}", self.name))
-    }
-    fn drop(&self) -> DDL{
-        DDL(format!("DROP TABLE {}", self.name))
-    }
-    fn create(&self) -> DDL{
-        DDL(self.to_string())
-    }
-}
+        let insert = format!("INSERT INTO {}({}) VALUES ({})", quote_ident(&self.name), columns.join(","), placeholders.join(","));
 
-/// An attribute in a table of a relational database
-#[derive(Clone, Debug)]
-pub struct Attribute{
-    /// The name of the attribute.
-    pub name: String,
-    /// The data type of the attribute.
-    pub data_type: AttributeType,
-    /// A vector of Constraints on the attribute.
-    pub constraint: HashSet<Constraint>
-}
+        Ok(Prepared::new(SQL::Insert(QML(insert)), params))
+    }
 
-impl Attribute {
-    fn from_row(row: Row, table_name: &str) -> Option<Attribute> {
-        let name: String = row.get(0).unwrap();
-        let data_type: String = row.get(1).unwrap();
+    /// Checks `values` for `Table::insert_typed` and `Table::insert_prepared`, returning them coerced to the types of
+    /// their columns.
+    fn coerce_insert(&self, values: &HashMap<String, Value>) -> Result<HashMap<String, Value>, InsertError> {
+        if values.is_empty() {
+            return Err(InsertError::EmptyValues);
+        }
 
-        info!("name:{}\tdata_type:{}", name, data_type);
+        let unknown = self.unknown_columns(values);
 
-        let data_type = match AttributeType::from(&data_type.to_ascii_uppercase()) {
-            Some(val) => val,
-            None => return None,
-        };
+        if !unknown.is_empty() {
+            return Err(InsertError::UnknownColumns(unknown));
+        }
 
-        Some(
-            Attribute {
-                name: name.clone(),
-                data_type: data_type,
-                constraint: {
-                    let mut tmp : HashSet<Constraint> = HashSet::new();
+        if let Some(column) = self.missing_columns(values).into_iter().next() {
+            return Err(InsertError::MissingRequired(column));
+        }
 
-                    {
-                        let nullable : String = row.get(3).unwrap();
-                        
-                        if nullable == "NO" {
-                            let _result = &tmp.insert(Constraint::NotNull);
-                        }
-                    }
+        let mut coerced: HashMap<String, Value> = HashMap::new();
 
-                    {
-                        let auto_inc : String = row.get(6).unwrap();
-                        
-                        if auto_inc == "auto_increment" {
-                            let _result = &tmp.insert(Constraint::AutoIncrement);
-                        }
-                    }
+        for attr in &self.attributes {
+            if let Some(value) = values.get(&attr.name) {
+                let mismatch = || InsertError::TypeMismatch{
+                    column: attr.name.clone(),
+                    expected: attr.type_definition(),
+                    got: value.to_string(),
+                };
+                let value = value.coerce_to(&attr.data_type).map_err(|_| mismatch())?;
 
-                    {
-                        let key : String = row.get(4).unwrap();
-                        
-                        if key == "UNI" {
-                            let _result = &tmp.insert(Constraint::Unique);
-                        }
-                        else if key == "MUL" {
-                            let db = DataBase::from_env().unwrap();
-
-                            let _tmp: Vec<Constraint> = db.execute(&SQL::new(&format!(r"SHOW CREATE TABLE `{}`;", table_name)).unwrap(), |row| {
-                                let command : String = row.unwrap().get(1).unwrap();
-                                
-                                let tag_check: Regex = Regex::new(&format!("FOREIGN KEY \\(`{}`\\) REFERENCES `([a-zA-Z0-9]+)` \\(`([a-zA-Z0-9]+)`\\)", name)).unwrap();
-
-                                let captures = tag_check.captures(&command).unwrap();
-
-                                Constraint::ForeignKey{
-                                    table_name: captures.get(1).unwrap().as_str().to_string(),
-                                    attribute_name: captures.get(2).unwrap().as_str().to_string()
-                                }
-                            }).unwrap();
-
-                            _tmp.iter()
-                            .for_each(|constraint| {
-                                let _result = &tmp.insert(constraint.clone());
-                            });
-                        }
+                if let Value::Text(text) = &value {
+                    if !attr.data_type.is_member(text) {
+                        return Err(mismatch());
                     }
+                }
 
-                    tmp
+                if value == Value::Null && !attr.is_nullable() {
+                    return Err(InsertError::NullNotAllowed(attr.name.clone()));
                 }
+
+                coerced.insert(attr.name.clone(), value);
             }
-        )
-    }
+        }
 
-    /// Returns a string representation of the attribute's schema.
-    pub fn schema_fmt(&self) -> String {
-        format!("{} {}", self.name, self.data_type)
+        Ok(coerced)
     }
-}
 
-impl fmt::Display for Attribute {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut foreign_key = None;
-        let constraint_str: String = {
-            let mut constraints_vec: Vec<String> = Vec::new();
-            for c in &self.constraint {
-                if let Constraint::ForeignKey { .. } = c {
-                    foreign_key = Some(format!("FOREIGN KEY({}) REFERENCES {}", self.name, c.to_string()));
-                    continue;
-                }
+    /// Returns the keys of `values` that are not attributes of the table, sorted.
+    fn unknown_columns<V>(&self, values: &HashMap<String, V>) -> Vec<String> {
+        let mut unknown: Vec<String> = values.keys()
+            .filter(|column| !self.attributes.iter().any(|attr| attr.name == **column))
+            .cloned()
+            .collect();
+        unknown.sort();
 
-                constraints_vec.push(c.to_string());
-            }
+        unknown
+    }
 
-            constraints_vec.join(" ")
-        };
-        let tmp = match constraint_str.len() {
-            0 => format!("{} {}", self.name, self.data_type),
-            _ => format!("{} {} {}", self.name, self.data_type, constraint_str),
-        };
+    /// Logs a warning naming the keys of `values` that are not attributes of the table, for the builders that leave
+    /// them out of the statement.
+    fn warn_unknown_columns(&self, values: &HashMap<String, String>) {
+        let unknown = self.unknown_columns(values);
 
-        match foreign_key {
-            Some(foreign_key) => write!(f, "{}, {}", tmp, foreign_key),
-            None => write!(f, "{}", tmp),
+        if !unknown.is_empty() {
+            warn!("Ignoring values for unknown columns of {}: {}", self.name, unknown.join(", "));
         }
     }
-}
 
-/// Constraint defines the restrictions of an attribute
-#[derive(Clone, Hash, Eq, Debug)]
-pub enum Constraint{
-    /// The attribute must not contain a null value.
-    NotNull,
-    /// The attribute must contain a unique value.
-    Unique,
-    /// The attribute is a foreign key that references another attribute in a different table.
-    ForeignKey{
-        /// The name of the table that the foreign key attribute references.
-        table_name: String,
-        /// The name of the attribute that the foreign key references.
-        attribute_name: String
-    },
-    /// The attribute is an auto-incrementing integer.
-    AutoIncrement,
-}
-impl PartialEq for Constraint {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            // (
-            //     Self::ForeignKey { .. },
-            //     Self::ForeignKey { .. }
-            // ) => true,
-            
-            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+    /// Logs a warning and returns `None` if one of `values` is rejected by `Table::check_literals`, for the builders
+    /// returning `None` rather than an error.
+    fn warn_rejected_literals(&self, values: &HashMap<String, String>) -> Option<()> {
+        match self.check_literals(values) {
+            Ok(()) => Some(()),
+            Err(err) => {
+                warn!("Not building a statement for {}: {}", self.name, err);
+                None
+            },
         }
     }
-}
 
-impl fmt::Display for Constraint{
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Constraint::NotNull => write!(f, "Not Null"),
-            Constraint::Unique => write!(f, "Unique"),
-            Constraint::ForeignKey{table_name: table,attribute_name: attr} => write!(f, "{}({})", table, attr),
-            Constraint::AutoIncrement => write!(f, "Auto_increment"),
+    /// Checks `values` for `Table::insert`, reporting the first problem found.
+    fn check_insert(&self, values: &HashMap<String, String>) -> Result<(), InsertError> {
+        if values.is_empty() {
+            return Err(InsertError::EmptyValues);
+        }
+
+        let unknown = self.unknown_columns(values);
+
+        if !unknown.is_empty() {
+            return Err(InsertError::UnknownColumns(unknown));
         }
+
+        if let Err(missing) = self.validate_row(values) {
+            return Err(InsertError::MissingRequired(missing.columns[0].clone()));
+        }
+
+        self.check_literals(values)
     }
-}
 
-/// AttributeType defines every type of MySQL datatype
-#[derive(Clone, Debug)]
-pub enum AttributeType{
-    //string data types
-    Char(u8),
-    VarChar(u16),
-    Binary(u8),
-    VarBinary(u16),
-    TinyBlob,
-    TinyText,
-    Text,
-    Blob(u16),
-    MediumText,
-    MediumBlob,
-    LongText,
-    LongBlob,
-    Enum{val: Vec<String>},
-    Set{val: Vec<AttributeType>},
+    /// Checks that each of `values` can be written as a literal of its column (see `Attribute::value_expression`), for
+    /// every builder taking values as strings, reporting the first that cannot.
+    fn check_literals(&self, values: &HashMap<String, String>) -> Result<(), InsertError> {
+        for attr in &self.attributes {
+            if let Some(value) = values.get(&attr.name) {
+                if value.trim().eq_ignore_ascii_case("NULL") {
+                    match attr.is_nullable() {
+                        true => continue,
+                        false => return Err(InsertError::NullNotAllowed(attr.name.clone())),
+                    }
+                }
 
-    //numeric data types
+                if !attr.data_type.accepts_bare(value) || !attr.data_type.is_member(value) {
+                    return Err(InsertError::TypeMismatch{
+                        column: attr.name.clone(),
+                        expected: attr.type_definition(),
+                        got: value.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the names of the primary key's columns, in key order. Empty if the table has no primary key.
+    pub fn primary_key_columns(&self) -> Vec<&str> {
+        self.primary_key.iter().map(|index| self.attributes[*index].name.as_str()).collect()
+    }
+
+    /// Checks that the name of the table and of each of its columns is accepted by the server (see
+    /// `validate_identifier`), for tables built from user input rather than read with `Table::from_db`.
+    ///
+    /// Names are quoted by the statement builders when they have to be (see `quote_ident`), so reserved words and
+    /// names with spaces are valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidName` with the first name that is rejected, the table's name first.
+    pub fn validate_names(&self) -> Result<(), InvalidName> {
+        let names = std::iter::once(&self.name).chain(self.attributes.iter().map(|attr| &attr.name));
+
+        for name in names {
+            validate_identifier(name).map_err(|error| InvalidName{name: name.clone(), error})?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `values` sets every column an insert has to set (see `Attribute::is_required`).
+    ///
+    /// # Errors
+    ///
+    /// Returns `MissingColumns` listing every required column that `values` does not set, in attribute order.
+    pub fn validate_row(&self, values: &HashMap<String, String>) -> Result<(), MissingColumns> {
+        let columns = self.missing_columns(values);
+
+        match columns.is_empty() {
+            true => Ok(()),
+            false => Err(MissingColumns{table: self.name.clone(), columns}),
+        }
+    }
+
+    /// Returns the required columns that `values` does not set, in attribute order.
+    fn missing_columns<V>(&self, values: &HashMap<String, V>) -> Vec<String> {
+        self.attributes
+            .iter()
+            .filter(|attr| attr.is_required() && !values.contains_key(&attr.name))
+            .map(|attr| attr.name.clone())
+            .collect()
+    }
+
+    /// Same as `Table::insert`, but emits `INSERT IGNORE`, so rows duplicating a key are skipped instead of failing
+    /// the statement. Returns `None`, with a warning, if no column is set or a value is not a literal of its column.
+    pub fn insert_ignore(&self, values: &HashMap<String, String>) -> Option<QML> {
+        let (columns, values) = self.insert_columns(values)?;
+
+        Some(QML(format!("INSERT IGNORE INTO {}({}) VALUES ({})", quote_ident(&self.name), columns, values)))
+    }
+
+    /// Returns the column list and the value list of an `INSERT` of `values`, for `Table::insert` and
+    /// `Table::insert_ignore`, or `None` if no column is set or a value is rejected by `Table::check_literals`.
+    fn insert_columns(&self, values: &HashMap<String, String>) -> Option<(String, String)> {
+        self.warn_unknown_columns(values);
+        self.warn_rejected_literals(values)?;
+
+        let (columns, values) = self.attributes
+            .iter()
+            .filter(|attr| {
+                if let Some(_) = values.get(&attr.name) {
+                    return true;
+                }
+                return false;
+            })
+            .map(|attr| {
+                (
+                    quote_ident(&attr.name),
+                    attr.value_expression(values.get(&attr.name).unwrap())
+                )
+            })
+            .fold(
+                (String::new(), String::new()),
+                |(columns, values), (column, value)| (format!("{},{}", columns, column), format!("{},{}", values, value))
+            );
+
+        if ("", "") == (&columns, &values) {
+            return None;
+        }
+
+        let (m1, m2) = (columns.len(), values.len());
+
+        Some((columns[1..m1].to_string(), values[1..m2].to_string()))
+    }
+
+    /// Returns a `QML` representing an `INSERT` of `values` that updates the existing row instead if the insert would
+    /// duplicate a primary or unique key.
+    ///
+    /// Every provided column but those of the primary key is updated; without a primary key every provided column is.
+    /// If only primary key columns are provided, the existing row is kept as it is. Returns `None` if no column is set,
+    /// or, with a warning, if a value is not a literal of its column as checked by `Table::insert`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// # use rust_db_interface::{relation::table::Table, sql::QML};
+    /// # let table = Table::from_create_statement("CREATE TABLE table_1 (PersonID int, City varchar(255), PRIMARY KEY (PersonID))").unwrap();
+    /// let values = HashMap::from([
+    ///     (String::from("PersonID"), String::from("23")),
+    ///     (String::from("City"), String::from("Night City")),
+    /// ]);
+    ///
+    /// let actual = table.upsert(&values);
+    /// assert_eq!(
+    ///     actual,
+    ///     Some(QML(String::from("INSERT INTO table_1(PersonID,City) VALUES (23,'Night City') ON DUPLICATE KEY UPDATE City=VALUES(City)")))
+    /// );
+    /// ```
+    pub fn upsert(&self, values: &HashMap<String, String>) -> Option<QML> {
+        let (columns, literals) = self.insert_columns(values)?;
+        let insert = format!("INSERT INTO {}({}) VALUES ({})", quote_ident(&self.name), columns, literals);
+
+        let primary_key = self.primary_key_columns();
+
+        let updates: Vec<String> = self.attributes
+            .iter()
+            .filter(|attr| values.contains_key(&attr.name) && !primary_key.contains(&attr.name.as_str()))
+            .map(|attr| quote_ident(&attr.name))
+            .map(|column| format!("{}=VALUES({})", column, column))
+            .collect();
+
+        let updates = match (updates.is_empty(), primary_key.first()) {
+            (true, Some(primary_key)) => format!("{}={}", quote_ident(primary_key), quote_ident(primary_key)),
+            _ => updates.join(","),
+        };
+
+        Some(QML(format!("{} ON DUPLICATE KEY UPDATE {}", insert, updates)))
+    }
+
+    /// Returns a single multi-row `INSERT` statement for `rows`, whose values are quoted as in `Table::insert`.
+    ///
+    /// Every row has to set the same columns, all of them attributes of the table. Columns are listed in attribute
+    /// order. Returns `Ok(None)` if there are no rows. The statement is not split, so large batches have to be sized by
+    /// the caller, or built from typed values with `Table::insert_batches`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// # use rust_db_interface::{relation::table::Table, sql::QML};
+    /// # let table = Table::from_create_statement("CREATE TABLE table_1 (PersonID int, LastName varchar(255))").unwrap();
+    /// let rows = vec![
+    ///     HashMap::from([(String::from("PersonID"), String::from("23")), (String::from("LastName"), String::from("Doe"))]),
+    ///     HashMap::from([(String::from("PersonID"), String::from("24")), (String::from("LastName"), String::from("Roe"))]),
+    /// ];
+    ///
+    /// let actual = table.insert_many(&rows);
+    /// assert_eq!(actual, Ok(Some(QML(String::from("INSERT INTO table_1(PersonID,LastName) VALUES (23,'Doe'),(24,'Roe')")))));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MismatchedRow` for the first row that sets a column that is not an attribute of the table, has a
+    /// value that is not a literal of its column (see `Table::insert`), or does not set the same columns as the first
+    /// row.
+    pub fn insert_many(&self, rows: &[HashMap<String, String>]) -> Result<Option<QML>, MismatchedRow> {
+        let first = match rows.first() {
+            Some(first) => first,
+            None => return Ok(None),
+        };
+
+        let attributes: Vec<&Attribute> = self.attributes
+            .iter()
+            .filter(|attr| first.contains_key(&attr.name))
+            .collect();
+
+        let mut tuples: Vec<String> = Vec::with_capacity(rows.len());
+
+        for (row, values) in rows.iter().enumerate() {
+            if let Some(column) = self.unknown_columns(values).into_iter().next() {
+                return Err(MismatchedRow::Unknown{row, column});
+            }
+
+            for attr in &self.attributes {
+                match (first.contains_key(&attr.name), values.contains_key(&attr.name)) {
+                    (true, false) => return Err(MismatchedRow::Missing{row, column: attr.name.clone()}),
+                    (false, true) => return Err(MismatchedRow::Extra{row, column: attr.name.clone()}),
+                    _ => {},
+                }
+            }
+
+            self.check_literals(values).map_err(|error| MismatchedRow::Rejected{row, error})?;
+
+            let literals: Vec<String> = attributes
+                .iter()
+                .map(|attr| attr.value_expression(&values[&attr.name]))
+                .collect();
+
+            tuples.push(format!("({})", literals.join(",")));
+        }
+
+        if attributes.is_empty() {
+            return Ok(None);
+        }
+
+        let columns: Vec<String> = attributes.iter().map(|attr| quote_ident(&attr.name)).collect();
+
+        Ok(Some(QML(format!("INSERT INTO {}({}) VALUES {}", quote_ident(&self.name), columns.join(","), tuples.join(",")))))
+    }
+
+    /// Returns a `QML` representing an `UPDATE` statement setting the columns of the rows matching `condition`.
+    ///
+    /// As with `Table::insert`, `values` are quoted by the type of their column, and columns that are not attributes of
+    /// the table are ignored. Returns `None` if no column is set, with a warning if a value is not a literal of its
+    /// column, or if `condition` is empty, as the statement would then update every row (see `Table::update_all`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// # use rust_db_interface::{relation::table::Table, sql::QML};
+    /// # let table = Table::from_create_statement("CREATE TABLE table_1 (PersonID int, LastName varchar(255), City varchar(255))").unwrap();
+    /// let mut values = HashMap::new();
+    ///
+    /// values.insert(String::from("LastName"), String::from("Doe"));
+    /// values.insert(String::from("City"), String::from("Night City"));
+    ///
+    /// let actual = table.update(&values, "PersonID=23");
+    /// assert_eq!(actual, Some(QML(String::from("UPDATE table_1 SET LastName='Doe',City='Night City' WHERE PersonID=23"))));
+    /// ```
+    pub fn update(&self, values: &HashMap<String, String>, condition: &str) -> Option<QML> {
+        if condition.trim().is_empty() {
+            return None;
+        }
+
+        let assignments = self.assignments(values)?;
+
+        Some(QML(format!("UPDATE {} SET {} WHERE {}", quote_ident(&self.name), assignments, condition.trim())))
+    }
+
+    /// Same as `Table::update`, but sets the columns of every row of the table.
+    pub fn update_all(&self, values: &HashMap<String, String>) -> Option<QML> {
+        let assignments = self.assignments(values)?;
+
+        Some(QML(format!("UPDATE {} SET {}", quote_ident(&self.name), assignments)))
+    }
+
+    /// Returns a `QML` representing a `DELETE` statement for the rows matching `condition`.
+    ///
+    /// Returns `None` if `condition` is empty, as the statement would then delete every row (see `Table::delete_all`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use rust_db_interface::{relation::table::Table, sql::QML};
+    /// # let table = Table::from_create_statement("CREATE TABLE table_1 (PersonID int)").unwrap();
+    /// let actual = table.delete_where("PersonID=23");
+    /// assert_eq!(actual, Some(QML(String::from("DELETE FROM table_1 WHERE PersonID=23"))));
+    /// ```
+    pub fn delete_where(&self, condition: &str) -> Option<QML> {
+        if condition.trim().is_empty() {
+            return None;
+        }
+
+        Some(QML(format!("DELETE FROM {} WHERE {}", quote_ident(&self.name), condition.trim())))
+    }
+
+    /// Returns a `QML` representing a `DELETE` statement for every row of the table.
+    pub fn delete_all(&self) -> QML {
+        QML(format!("DELETE FROM {}", quote_ident(&self.name)))
+    }
+
+    /// Returns the columns selected by `RelationMethods::select`: `*`, or every column if some have to be decrypted.
+    fn projection(&self) -> String {
+        if !self.attributes.iter().any(|attr| attr.encryption_key().is_some()) {
+            return String::from("*");
+        }
+
+        let columns: Vec<String> = self.attributes
+            .iter()
+            .map(|attr| attr.read_expression())
+            .collect();
+
+        columns.join(",")
+    }
+
+    /// Returns the `column=value` list of an `UPDATE` of `values`, in attribute order, or `None` if no column is set or
+    /// a value is rejected by `Table::check_literals`.
+    fn assignments(&self, values: &HashMap<String, String>) -> Option<String> {
+        self.warn_unknown_columns(values);
+        self.warn_rejected_literals(values)?;
+
+        let assignments: Vec<String> = self.attributes
+            .iter()
+            .filter_map(|attr| {
+                values.get(&attr.name)
+                    .map(|value| format!("{}={}", quote_ident(&attr.name), attr.value_expression(value)))
+            })
+            .collect();
+
+        match assignments.is_empty() {
+            true => None,
+            false => Some(assignments.join(",")),
+        }
+    }
+
+    /// Returns a `QDL` selecting `columns` of the rows matching `condition`, or every column if `columns` is `None`.
+    ///
+    /// Encrypted columns are decrypted as in `RelationMethods::select`. An empty `condition` selects every row.
+    /// Returns `None` if `columns` is empty or contains a name that is not an attribute of the table.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use rust_db_interface::{relation::table::Table, sql::QDL};
+    /// # let table = Table::from_create_statement("CREATE TABLE table_1 (FirstName varchar(255), LastName varchar(255), City varchar(255))").unwrap();
+    /// let actual = table.select_where(Some(&["FirstName", "LastName"]), "City='Night City'");
+    /// assert_eq!(actual, Some(QDL(String::from("SELECT FirstName,LastName FROM table_1 WHERE City='Night City'"))));
+    /// ```
+    pub fn select_where(&self, columns: Option<&[&str]>, condition: &str) -> Option<QDL> {
+        let projection = match columns {
+            Some(columns) => {
+                if columns.is_empty() {
+                    return None;
+                }
+
+                let expressions: Option<Vec<String>> = columns
+                    .iter()
+                    .map(|column| {
+                        self.attributes
+                            .iter()
+                            .find(|attr| attr.name == *column)
+                            .map(|attr| attr.read_expression())
+                    })
+                    .collect();
+
+                expressions?.join(",")
+            },
+            None => self.projection(),
+        };
+
+        match condition.trim() {
+            "" => Some(QDL(format!("SELECT {} FROM {}", projection, quote_ident(&self.name)))),
+            condition => Some(QDL(format!("SELECT {} FROM {} WHERE {}", projection, quote_ident(&self.name), condition))),
+        }
+    }
+
+    /// Returns a `QDL` counting the rows of every distinct combination of `columns`, most frequent first.
+    ///
+    /// Returns `None` if `columns` is empty or contains a name that is not an attribute of the table.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use rust_db_interface::{relation::table::Table, sql::QDL};
+    /// # let table = Table::from_create_statement("CREATE TABLE table_1 (LastName varchar(255), City varchar(255))").unwrap();
+    /// let actual = table.group_count_query(&["City", "LastName"], 10);
+    /// assert_eq!(actual, Some(QDL(String::from("SELECT City,LastName,COUNT(*) FROM table_1 GROUP BY City,LastName ORDER BY COUNT(*) DESC LIMIT 10"))));
+    /// ```
+    pub fn group_count_query(&self, columns: &[&str], limit: usize) -> Option<QDL> {
+        if columns.is_empty() {
+            return None;
+        }
+
+        if !columns.iter().all(|column| self.attributes.iter().any(|attr| attr.name == *column)) {
+            return None;
+        }
+
+        let columns = columns.iter().map(|column| quote_ident(column)).collect::<Vec<String>>().join(",");
+
+        Some(QDL(format!(
+            "SELECT {},COUNT(*) FROM {} GROUP BY {} ORDER BY COUNT(*) DESC LIMIT {}",
+            columns,
+            quote_ident(&self.name),
+            columns,
+            limit
+        )))
+    }
+
+    /// Returns the number of rows of every distinct combination of `columns`, most frequent first.
+    ///
+    /// `NULL` values are grouped together and reported as `Value::Null` in the group key.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Invalid` if `columns` is empty or names an unknown column, `Error::Parse` if a count could not be
+    /// read, and an `Error` if the query fails.
+    pub fn group_count<E: Executor>(&self, db: &E, columns: &[&str], limit: usize) -> Result<Vec<GroupCount>, crate::Error> {
+        let query = match self.group_count_query(columns, limit) {
+            Some(query) => query,
+            None => return Err(crate::Error::Invalid(format!("group columns {:?} of table {}", columns, self.name))),
+        };
+
+        let statement = query.to_string();
+        let rows = db.execute(
+            &SQL::Select(query),
+            |row| row.map(|row| GroupCount::from_values(row.unwrap()))
+        )?;
+
+        rows.into_iter()
+            .map(|row| match row {
+                Ok(Some(group)) => Ok(group),
+                Ok(None) => Err(crate::Error::Parse(format!("the COUNT(*) of a group of table {}", self.name))),
+                Err(err) => Err(crate::Error::query(&statement, err)),
+            })
+            .collect()
+    }
+
+    /// Returns the number of rows in the table.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::NotFound` if the table does not exist, and an `Error` if the query fails.
+    pub fn count(&self, db: &DataBase) -> Result<u64, crate::Error> {
+        let statement = format!("SELECT COUNT(*) FROM {}", quote_ident(&self.name));
+        let rows = db.execute(&SQL::Select(QDL(statement.clone())), |row| row.map(mysql::from_row::<u64>))
+            .map_err(|err| match err.code() {
+                Some(ER_NO_SUCH_TABLE) => crate::Error::NotFound(self.name.clone()),
+                _ => err,
+            })?;
+
+        rows.into_iter()
+            .next()
+            .unwrap_or(Ok(0))
+            .map_err(|err| crate::Error::query(&statement, err))
+    }
+
+    /// Returns true if the current database of `db` has a table named as the table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if `information_schema` could not be queried.
+    pub fn exists(&self, db: &DataBase) -> Result<bool, crate::Error> {
+        let statement = format!(
+            "SELECT COUNT(*) FROM information_schema.TABLES WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = {}",
+            Value::Text(self.name.clone())
+        );
+        let rows = db.execute(&SQL::Select(QDL(statement.clone())), |row| row.map(mysql::from_row::<u64>))?;
+
+        match rows.into_iter().next() {
+            Some(count) => Ok(count.map_err(|err| crate::Error::query(&statement, err))? > 0),
+            None => Ok(false),
+        }
+    }
+
+    /// Returns a `QML` representing an `INSERT` statement for the table with the given typed values.
+    ///
+    /// Columns are listed in the order of `attributes`. Values are rendered as SQL literals. Values for `Binary`, `VarBinary` and blob columns are inserted as hex literals,
+    /// even when they are given as `Value::Text`.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - A `HashMap` of column names and values to insert into the table.
+    /// * `literal_limit` - The maximum size, in bytes, of a single rendered value (see `value::MAX_LITERAL_SIZE`).
+    ///
+    /// # Errors
+    ///
+    /// Returns `ValueError::NullNotAllowed` if `Value::Null` is given to a `NOT NULL` column (see
+    /// `Attribute::is_nullable`), and `ValueError::LiteralTooLarge` if a value renders to a literal larger than
+    /// `literal_limit`.
+    /// Returns `Ok(None)` if none of the values belong to a column of the table.
+    pub fn insert_values(&self, values: &HashMap<String, Value>, literal_limit: usize) -> Result<Option<QML>, ValueError> {
+        let mut columns: Vec<String> = Vec::new();
+        let mut literals: Vec<String> = Vec::new();
+
+        for attr in &self.attributes {
+            let value = match values.get(&attr.name) {
+                Some(value) => value.clone(),
+                None => continue,
+            };
+
+            if value == Value::Null && !attr.is_nullable() {
+                return Err(ValueError::NullNotAllowed(attr.name.clone()));
+            }
+
+            let value = match attr.data_type.is_binary() {
+                true => value.into_bytes(),
+                false => value,
+            };
+
+            columns.push(quote_ident(&attr.name));
+            literals.push(attr.write_expression(&value.to_bounded_literal(literal_limit)?));
+        }
+
+        if columns.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(QML(format!("INSERT INTO {}({}) VALUES ({})", quote_ident(&self.name), columns.join(","), literals.join(",")))))
+    }
+
+    /// Inserts `values` into the table and returns the id generated for its `AUTO_INCREMENT` column.
+    ///
+    /// Returns `0` if the table does not generate ids.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Invalid` if a value cannot be written to its column (see `Table::insert_values`), and an `Error`
+    /// if the insert fails.
+    pub fn insert_and_get_id(&self, db: &DataBase, values: &HashMap<String, Value>) -> Result<u64, crate::Error> {
+        let insert = match self.insert_values(values, MAX_LITERAL_SIZE) {
+            Ok(Some(insert)) => insert,
+            Ok(None) => QML(format!("INSERT INTO {}() VALUES ()", quote_ident(&self.name))),
+            Err(err) => return Err(crate::Error::Invalid(err.to_string())),
+        };
+
+        let result = db.execute_write(&insert)?;
+
+        Ok(result.last_insert_id.unwrap_or_default())
+    }
+
+    /// Returns multi-row `INSERT` statements for `rows`, each no larger than `budget` bytes.
+    ///
+    /// Rendered row tuples are added to a statement until the next one would push it over `budget`, at which point a
+    /// new statement is started. The column list covers every column set by any of the rows, in the order of
+    /// `attributes`; a row that does not set one of them inserts `DEFAULT`. Values are rendered as in `Table::insert_values`.
+    /// Unlike `Table::insert_many`, rows do not have to set the same columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The rows to insert, as column names and values.
+    /// * `budget` - The maximum size, in bytes, of a single statement (see `DataBase::insert_budget`).
+    ///
+    /// # Errors
+    ///
+    /// Returns `BatchInsertError::RowTooLarge` with the index of the first row that does not fit in a statement on its own.
+    pub fn insert_batches(&self, rows: &[HashMap<String, Value>], budget: usize) -> Result<Vec<QML>, BatchInsertError> {
+        Ok(
+            self.insert_chunks(rows, budget)?
+                .into_iter()
+                .map(|chunk| chunk.statement)
+                .collect()
+        )
+    }
+
+    /// Same as `Table::insert_batches`, but also returns the range of `rows` inserted by each statement.
+    pub fn insert_chunks(&self, rows: &[HashMap<String, Value>], budget: usize) -> Result<Vec<InsertChunk>, BatchInsertError> {
+        let attributes: Vec<&Attribute> = self.attributes
+            .iter()
+            .filter(|attr| rows.iter().any(|row| row.contains_key(&attr.name)))
+            .collect();
+
+        if attributes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let prefix = format!(
+            "INSERT INTO {}({}) VALUES ",
+            quote_ident(&self.name),
+            attributes.iter().map(|attr| quote_ident(&attr.name)).collect::<Vec<String>>().join(",")
+        );
+
+        let mut chunks: Vec<InsertChunk> = Vec::new();
+        let mut current = prefix.clone();
+        let mut start = 0;
+
+        for (row, values) in rows.iter().enumerate() {
+            let too_large = |size: usize| BatchInsertError::RowTooLarge{row, size, budget};
+
+            let mut literals: Vec<String> = Vec::with_capacity(attributes.len());
+            for attr in &attributes {
+                let literal = match values.get(&attr.name) {
+                    Some(value) if attr.data_type.is_binary() => value.clone().into_bytes().to_bounded_literal(budget),
+                    Some(value) => value.to_bounded_literal(budget),
+                    None => {
+                        literals.push(String::from("DEFAULT"));
+                        continue;
+                    },
+                };
+
+                match literal {
+                    Ok(literal) => literals.push(attr.write_expression(&literal)),
+                    Err(ValueError::LiteralTooLarge{size, ..}) => return Err(too_large(size)),
+                    //`Value::to_bounded_literal` only reports the size of the literal
+                    Err(ValueError::NullNotAllowed(_)) => unreachable!(),
+                }
+            }
+
+            let tuple = format!("({})", literals.join(","));
+
+            if prefix.len() + tuple.len() > budget {
+                return Err(too_large(prefix.len() + tuple.len()));
+            }
+
+            if start < row && current.len() + 1 + tuple.len() > budget {
+                chunks.push(InsertChunk{rows: start..row, statement: QML(std::mem::replace(&mut current, prefix.clone()))});
+                start = row;
+            }
+
+            if start < row {
+                current.push(',');
+            }
+            current.push_str(&tuple);
+        }
+
+        if start < rows.len() {
+            chunks.push(InsertChunk{rows: start..rows.len(), statement: QML(current)});
+        }
+
+        Ok(chunks)
+    }
+
+    /// Moves the columns named in `order` to the front of `attributes`, in that order, so generated statements list
+    /// them first. The remaining columns keep their relative order after them, and `primary_key` follows its column.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UnknownColumn` with the first name that is not a column of the table, leaving the table unchanged.
+    pub fn canonicalize_column_order(&mut self, order: &[&str]) -> Result<(), UnknownColumn> {
+        let mut indices: Vec<usize> = Vec::with_capacity(self.attributes.len());
+
+        for name in order {
+            match self.attributes.iter().position(|attr| &attr.name == name) {
+                Some(index) if indices.contains(&index) => {},
+                Some(index) => indices.push(index),
+                None => return Err(UnknownColumn{table: self.name.clone(), column: name.to_string()}),
+            }
+        }
+
+        let rest: Vec<usize> = (0..self.attributes.len()).filter(|index| !indices.contains(index)).collect();
+        indices.extend(rest);
+
+        self.primary_key = self.primary_key
+            .iter()
+            .filter_map(|primary_key| indices.iter().position(|index| index == primary_key))
+            .collect();
+        self.attributes = indices.into_iter().map(|index| self.attributes[index].clone()).collect();
+
+        Ok(())
+    }
+
+    /// Inserts `rows` into the table in a single transaction, using statements sized to the server's
+    /// `max_allowed_packet` (see `Table::insert_batches`).
+    ///
+    /// Returns the number of statements executed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SQLError::Err` if a row does not fit in a statement, and `SQLError::Execution` if an insert fails.
+    pub fn insert_rows(&self, db: &DataBase, rows: &[HashMap<String, Value>]) -> Result<usize, SQLError> {
+        let statements: Vec<SQL> = self.insert_batches(rows, db.insert_budget())
+            .map_err(|err| SQLError::Err(err.to_string()))?
+            .into_iter()
+            .map(SQL::Insert)
+            .collect();
+
+        db.execute_multiple(&statements).map_err(SQLError::from)?;
+
+        Ok(statements.len())
+    }
+
+}
+
+/// A multi-row `INSERT` statement, as returned by `Table::insert_chunks`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InsertChunk {
+    /// The indices of the rows inserted by the statement.
+    pub rows: Range<usize>,
+    pub statement: QML,
+}
+
+/// Represents possible errors that can occur when building batched insert statements.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchInsertError {
+    /// The row at index `row` renders to a `size` byte statement, more than the `budget` of a whole statement.
+    RowTooLarge{row: usize, size: usize, budget: usize},
+}
+
+impl Display for BatchInsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchInsertError::RowTooLarge{row, size, budget} => {
+                write!(f, "row {} needs a {} byte statement, the budget is {} bytes", row, size, budget)
+            },
+        }
+    }
+}
+
+/// Represents a row of `Table::insert_many` that cannot be inserted along with the others.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MismatchedRow {
+    /// The row does not set `column`, which the first row sets.
+    Missing{row: usize, column: String},
+    /// The row sets `column`, which the first row does not.
+    Extra{row: usize, column: String},
+    /// The row sets `column`, which is not an attribute of the table.
+    Unknown{row: usize, column: String},
+    /// A value of the row is not a literal of its column.
+    Rejected{row: usize, error: InsertError},
+}
+
+impl Display for MismatchedRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MismatchedRow::Missing{row, column} => write!(f, "row {} does not set {}, which row 0 sets", row, column),
+            MismatchedRow::Extra{row, column} => write!(f, "row {} sets {}, which row 0 does not", row, column),
+            MismatchedRow::Unknown{row, column} => write!(f, "row {} sets {}, which is not a column of the table", row, column),
+            MismatchedRow::Rejected{row, error} => write!(f, "row {}: {}", row, error),
+        }
+    }
+}
+
+/// Represents the reasons `Table::insert` and `Table::insert_typed` cannot build a statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InsertError {
+    /// No value was given.
+    EmptyValues,
+    /// The values set columns that are not attributes of the table, such as a misspelled column name.
+    UnknownColumns(Vec<String>),
+    /// The column has to be set (see `Attribute::is_required`).
+    MissingRequired(String),
+    /// The value of `column` is not a literal of its type.
+    TypeMismatch{column: String, expected: String, got: String},
+    /// The column is given `NULL`, but is `NOT NULL` (see `Attribute::is_nullable`).
+    NullNotAllowed(String),
+    /// A value is too large to be rendered (see `Table::insert_typed`).
+    Literal(ValueError),
+}
+
+impl Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InsertError::EmptyValues => write!(f, "no values to insert"),
+            InsertError::UnknownColumns(columns) => write!(f, "unknown columns {}", columns.join(", ")),
+            InsertError::MissingRequired(column) => write!(f, "{} requires a value", column),
+            InsertError::TypeMismatch{column, expected, got} => write!(f, "{} expects {}, got {}", column, expected, got),
+            InsertError::NullNotAllowed(column) => write!(f, "{} cannot be NULL", column),
+            InsertError::Literal(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// Represents the required columns a row of a table does not set, as returned by `Table::validate_row`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingColumns {
+    pub table: String,
+    pub columns: Vec<String>,
+}
+
+impl Display for MissingColumns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} requires a value for {}", self.table, self.columns.join(", "))
+    }
+}
+
+/// Represents the reasons a table cannot be read with `Table::from_db`.
+#[derive(Debug)]
+pub enum TableLoadError {
+    /// No connection to the database could be opened.
+    ConnectionFailed(ConfigError),
+    /// The database has no table of this name.
+    TableNotFound(String),
+    /// The columns of the table could not be read.
+    Execution(crate::Error),
+}
+
+impl TableLoadError {
+    /// Returns the error of reading the columns of `table_name`, telling a missing table from other failures.
+    fn reading(table_name: &str, err: crate::Error) -> TableLoadError {
+        match err.code() {
+            Some(ER_NO_SUCH_TABLE) => TableLoadError::TableNotFound(table_name.to_string()),
+            _ => TableLoadError::Execution(err),
+        }
+    }
+}
+
+impl Display for TableLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableLoadError::ConnectionFailed(err) => write!(f, "failed to connect to the database: {:?}", err),
+            TableLoadError::TableNotFound(name) => write!(f, "table {} does not exist", name),
+            TableLoadError::Execution(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<TableLoadError> for SQLError {
+    fn from(err: TableLoadError) -> Self {
+        match err {
+            TableLoadError::ConnectionFailed(err) => SQLError::FailedToConnect(err.into()),
+            TableLoadError::Execution(err) => err.into(),
+            err => SQLError::Err(err.to_string()),
+        }
+    }
+}
+
+/// Represents a table or column name that the server would reject, as returned by `Table::validate_names`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidName {
+    pub name: String,
+    pub error: IdentifierError,
+}
+
+impl Display for InvalidName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a valid name: {}", self.name, self.error)
+    }
+}
+
+/// Represents a column name that does not belong to a table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownColumn {
+    pub table: String,
+    pub column: String,
+}
+
+impl Display for UnknownColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not a column of {}", self.column, self.table)
+    }
+}
+
+/// The number of rows sharing a group key, as returned by `Table::group_count`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupCount {
+    /// The values of the grouped columns, in the order they were requested.
+    pub key: Vec<Value>,
+    /// The number of rows in the group.
+    pub count: u64,
+}
+
+impl GroupCount {
+    /// Builds a `GroupCount` from a result row of the `Table::group_count_query` statement.
+    fn from_values(mut values: Vec<mysql::Value>) -> Option<GroupCount> {
+        let count = mysql::from_value_opt::<u64>(values.pop()?).ok()?;
+
+        Some(
+            GroupCount {
+                key: values.into_iter().map(Value::from).collect(),
+                count,
+            }
+        )
+    }
+}
+
+/// A secondary index of a table, over one or more of its columns.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Index {
+    /// The name of the index.
+    pub name: String,
+    /// The names of the indexed columns, in index order.
+    pub columns: Vec<String>,
+    /// True if no two rows can have the same values in `columns`.
+    pub unique: bool,
+}
+
+impl Index {
+    /// Returns the `CREATE INDEX` statement adding the index to `table`.
+    pub fn create(&self, table: &str) -> DDL {
+        let unique = if self.unique { "UNIQUE " } else { "" };
+
+        DDL(format!("CREATE {}INDEX {} ON {}({})", unique, quote_ident(&self.name), quote_ident(table), self.column_list()))
+    }
+
+    fn column_list(&self) -> String {
+        self.columns.iter().map(|column| quote_ident(column)).collect::<Vec<String>>().join(", ")
+    }
+}
+
+impl Display for Index {
+    /// Writes the index as a definition of `CREATE TABLE`, such as `UNIQUE KEY email(email, tenant_id)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unique = if self.unique { "UNIQUE " } else { "" };
+
+        write!(f, "{}KEY {}({})", unique, quote_ident(&self.name), self.column_list())
+    }
+}
+
+/// Where `Table::create_with` puts a kind of table level definition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CreateStyle {
+    /// Inside the `CREATE TABLE` statement.
+    Inline,
+    /// In separate statements to run after the `CREATE TABLE` statement.
+    DeferredConstraints,
+}
+
+/// The statement `Table::rename_column` renames a column with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenameStyle {
+    /// `RENAME COLUMN old TO new`, available since MySQL 8.0.
+    RenameColumn,
+    /// `CHANGE COLUMN old new definition`, which also restates the column definition.
+    Change,
+}
+
+impl RenameStyle {
+    /// Returns the statement to use on a server running `version`.
+    pub fn for_version(version: ServerVersion) -> RenameStyle {
+        match version >= ServerVersion::new(8, 0, 0) {
+            true => RenameStyle::RenameColumn,
+            false => RenameStyle::Change,
+        }
+    }
+}
+
+impl Table {
+    /// Returns the `CREATE TABLE` statement of the table, along with the statements deferred out of it.
+    ///
+    /// With `CreateStyle::DeferredConstraints`, foreign keys are added with `ALTER TABLE ... ADD CONSTRAINT` and unique
+    /// columns and the table's `indexes` get a `CREATE INDEX`, so tables referencing each other can all be created
+    /// before any foreign key is. Indexes are returned before foreign keys. `create_with(CreateStyle::Inline,
+    /// CreateStyle::Inline)` is the same as `RelationMethods::create`.
+    ///
+    /// # Arguments
+    ///
+    /// * `foreign_keys` - Where the `FOREIGN KEY` clauses go.
+    /// * `indexes` - Where the unique columns and the table's indexes go.
+    pub fn create_with(&self, foreign_keys: CreateStyle, indexes: CreateStyle) -> (DDL, Vec<DDL>) {
+        let mut definitions: Vec<String> = Vec::with_capacity(self.attributes.len());
+        let mut deferred_indexes: Vec<DDL> = Vec::new();
+        let mut deferred_foreign_keys: Vec<DDL> = Vec::new();
+
+        for attr in &self.attributes {
+            let unique = attr.constraint.contains(&Constraint::Unique);
+
+            match (indexes, unique) {
+                (CreateStyle::DeferredConstraints, true) => {
+                    definitions.push(attr.definition(false));
+                    deferred_indexes.push(
+                        DDL(format!(
+                            "CREATE UNIQUE INDEX {} ON {}({})",
+                            quote_ident(&format!("{}_{}_unique", self.name, attr.name)),
+                            quote_ident(&self.name),
+                            quote_ident(&attr.name)
+                        ))
+                    );
+                },
+                _ => definitions.push(attr.column_definition()),
+            }
+
+            if let Some(foreign_key) = attr.fk_clause() {
+                match foreign_keys {
+                    CreateStyle::Inline => definitions.push(format!(" {}", foreign_key)),
+                    CreateStyle::DeferredConstraints => deferred_foreign_keys.push(
+                        DDL(format!(
+                            "ALTER TABLE {} ADD CONSTRAINT {} {}",
+                            quote_ident(&self.name),
+                            quote_ident(&format!("{}_{}_fk", self.name, attr.name)),
+                            foreign_key
+                        ))
+                    ),
+                }
+            }
+        }
+
+        match indexes {
+            CreateStyle::Inline => definitions.extend(self.indexes.iter().map(|index| format!(" {}", index))),
+            CreateStyle::DeferredConstraints => deferred_indexes.append(&mut self.create_indexes()),
+        }
+
+        let definitions = definitions.join(",");
+        let primary_key: Vec<String> = self.primary_key_columns().into_iter().map(quote_ident).collect();
+
+        let create = match primary_key.is_empty() {
+            false => DDL(
+                format!("CREATE TABLE {} ({}, PRIMARY KEY({}))", quote_ident(&self.name), definitions, primary_key.join(", "))
+            ),
+            true => DDL(format!("CREATE TABLE {} ({})", quote_ident(&self.name), definitions)),
+        };
+
+        deferred_indexes.append(&mut deferred_foreign_keys);
+
+        (create, deferred_indexes)
+    }
+
+    /// Returns the `CREATE INDEX` statements adding the table's `indexes` (see `Index::create`).
+    pub fn create_indexes(&self) -> Vec<DDL> {
+        self.indexes.iter().map(|index| index.create(&self.name)).collect()
+    }
+
+    /// Returns the column names of every unique constraint of the table: the unique attributes, then the unique
+    /// `indexes`. The primary key is left out.
+    pub fn unique_constraints(&self) -> Vec<Vec<&str>> {
+        let columns = self.attributes
+            .iter()
+            .filter(|attr| attr.constraint.contains(&Constraint::Unique))
+            .map(|attr| vec![attr.name.as_str()]);
+        let indexes = self.indexes
+            .iter()
+            .filter(|index| index.unique)
+            .map(|index| index.columns.iter().map(String::as_str).collect());
+
+        columns.chain(indexes).collect()
+    }
+
+    /// Renames the column `old` to `new` in the indexes of the table.
+    fn rename_indexed_column(&mut self, old: &str, new: &str) {
+        for column in self.indexes.iter_mut().flat_map(|index| index.columns.iter_mut()) {
+            if column == old {
+                *column = new.to_string();
+            }
+        }
+    }
+
+    /// Returns a `DDL` representing a `TRUNCATE TABLE` statement, which removes every row but keeps the table definition.
+    ///
+    /// The server refuses to truncate a table referenced by a foreign key of another table (see
+    /// `Table::truncate_with_fk_checks_disabled`).
+    pub fn truncate(&self) -> DDL {
+        DDL(format!("TRUNCATE TABLE {}", quote_ident(&self.name)))
+    }
+
+    /// Same as `Table::truncate`, but with foreign key checks disabled around the statement, so a table referenced by
+    /// other tables can be truncated. The rows referencing it are left as they are.
+    ///
+    /// `FOREIGN_KEY_CHECKS` is a session variable, so the three statements are kept in one `DDL` and run on the same
+    /// connection.
+    pub fn truncate_with_fk_checks_disabled(&self) -> DDL {
+        DDL(format!("SET FOREIGN_KEY_CHECKS=0; TRUNCATE TABLE {}; SET FOREIGN_KEY_CHECKS=1", quote_ident(&self.name)))
+    }
+
+    /// Returns `Table::truncate_with_fk_checks_disabled` if another table of `registry` references the table, and
+    /// `Table::truncate` otherwise. Foreign keys of the table referencing itself do not stop a truncate.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SQLError::Err` if the table is not in `registry`, or an `SQLError` if its tables could not be loaded.
+    pub fn truncate_in(&self, registry: &mut Registry) -> Result<DDL, SQLError> {
+        let referenced = registry.references_of(&self.name)?
+            .iter()
+            .any(|key| !key.table.eq_ignore_ascii_case(&self.name));
+
+        match referenced {
+            true => Ok(self.truncate_with_fk_checks_disabled()),
+            false => Ok(self.truncate()),
+        }
+    }
+
+    /// Returns the `ALTER TABLE` statement renaming the table to `new_name`, and the renamed table.
+    ///
+    /// Foreign keys of the table referencing itself are renamed along with it.
+    pub fn rename(&self, new_name: &str) -> (DDL, Table) {
+        let mut table = self.clone();
+        table.name = new_name.to_string();
+
+        for attr in table.attributes.iter_mut() {
+            attr.constraint = attr.constraint
+                .drain()
+                .map(|constraint| match constraint {
+                    Constraint::ForeignKey{table_name, attribute_name, on_delete, on_update} if table_name == self.name => {
+                        Constraint::ForeignKey{table_name: new_name.to_string(), attribute_name, on_delete, on_update}
+                    },
+                    constraint => constraint,
+                })
+                .collect();
+        }
+
+        (DDL(format!("ALTER TABLE {} RENAME TO {}", quote_ident(&self.name), quote_ident(new_name))), table)
+    }
+
+    /// Returns the `ALTER TABLE` statement renaming the column `old` to `new`, and the table with the column renamed.
+    ///
+    /// Foreign keys of the table referencing the column, and its `indexes`, are renamed along with it. Returns `None` if `old` is not a
+    /// column of the table, or if `new` already is.
+    pub fn rename_column(&self, old: &str, new: &str, style: RenameStyle) -> Option<(DDL, Table)> {
+        let index = self.attributes.iter().position(|attr| attr.name == old)?;
+
+        if self.attributes.iter().any(|attr| attr.name == new) {
+            return None;
+        }
+
+        let mut table = self.clone();
+        table.attributes[index].name = new.to_string();
+        table.rename_indexed_column(old, new);
+
+        for attr in table.attributes.iter_mut() {
+            attr.constraint = attr.constraint
+                .drain()
+                .map(|constraint| match constraint {
+                    Constraint::ForeignKey{table_name, attribute_name, on_delete, on_update} if table_name == self.name && attribute_name == old => {
+                        Constraint::ForeignKey{table_name, attribute_name: new.to_string(), on_delete, on_update}
+                    },
+                    constraint => constraint,
+                })
+                .collect();
+        }
+
+        let statement = match style {
+            RenameStyle::RenameColumn => format!(
+                "ALTER TABLE {} RENAME COLUMN {} TO {}",
+                quote_ident(&self.name),
+                quote_ident(old),
+                quote_ident(new)
+            ),
+            RenameStyle::Change => format!(
+                "ALTER TABLE {} CHANGE COLUMN {} {}",
+                quote_ident(&self.name),
+                quote_ident(old),
+                table.attributes[index].column_definition()
+            ),
+        };
+
+        Some((DDL(statement), table))
+    }
+
+    /// Returns the `ALTER TABLE` statement adding `attr` as the last column, and the table with the column added.
+    ///
+    /// The column is defined as by `Attribute::column_definition`, and a foreign key of `attr` is added in the same
+    /// statement.
+    pub fn add_column(&self, attr: &Attribute) -> (DDL, Table) {
+        let statement = match attr.fk_clause() {
+            Some(foreign_key) => format!(
+                "ALTER TABLE {} ADD COLUMN {}, ADD {}",
+                quote_ident(&self.name),
+                attr.column_definition(),
+                foreign_key
+            ),
+            None => format!("ALTER TABLE {} ADD COLUMN {}", quote_ident(&self.name), attr.column_definition()),
+        };
+
+        let mut table = self.clone();
+        table.attributes.push(attr.clone());
+
+        (DDL(statement), table)
+    }
+
+    /// Returns the `ALTER TABLE` statement dropping the column `name`, and the table without it.
+    ///
+    /// Returns `None` if `name` is not a column of the table. Dropping a primary key column removes it from the key, so
+    /// dropping the only one leaves the table without a primary key. It is removed from the table's `indexes` the same
+    /// way.
+    pub fn drop_column(&self, name: &str) -> Option<(DDL, Table)> {
+        let index = self.attributes.iter().position(|attr| attr.name == name)?;
+
+        let mut table = self.clone();
+        table.attributes.remove(index);
+        table.primary_key = self.primary_key
+            .iter()
+            .filter(|primary_key| **primary_key != index)
+            .map(|primary_key| if *primary_key > index { primary_key - 1 } else { *primary_key })
+            .collect();
+
+        //the server drops the column from its indexes, and indexes left without a column with it
+        for index in table.indexes.iter_mut() {
+            index.columns.retain(|column| column != name);
+        }
+        table.indexes.retain(|index| !index.columns.is_empty());
+
+        Some((DDL(format!("ALTER TABLE {} DROP COLUMN {}", quote_ident(&self.name), quote_ident(name))), table))
+    }
+
+    /// Returns the `ALTER TABLE` statement redefining the column `name` as `new`, and the table with `new` in its place.
+    ///
+    /// The column is changed with `MODIFY COLUMN`, or with `CHANGE COLUMN` if `new` has another name. Foreign keys are
+    /// not added or dropped, as the constraint names of the existing ones are not known. Returns `None` if `name` is
+    /// not a column of the table, or if `new` is renamed to another existing column.
+    pub fn modify_column(&self, name: &str, new: &Attribute) -> Option<(DDL, Table)> {
+        let index = self.attributes.iter().position(|attr| attr.name == name)?;
+
+        let statement = match new.name == name {
+            true => format!("ALTER TABLE {} MODIFY COLUMN {}", quote_ident(&self.name), new.column_definition()),
+            false => {
+                if self.attributes.iter().any(|attr| attr.name == new.name) {
+                    return None;
+                }
+                format!("ALTER TABLE {} CHANGE COLUMN {} {}", quote_ident(&self.name), quote_ident(name), new.column_definition())
+            },
+        };
+
+        let mut table = self.clone();
+        table.attributes[index] = new.clone();
+        table.rename_indexed_column(name, &new.name);
+
+        Some((DDL(statement), table))
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let attr : Vec<String> = self.attributes
+            .iter()
+            .map(|attr| {
+                attr.to_string() 
+            })
+            .collect();
+        
+        let indexes: Vec<String> = self.indexes.iter().map(|index| format!(", {}", index)).collect();
+        let attr = attr.join(",") + &indexes.concat();
+
+        let primary_key: Vec<String> = self.primary_key_columns().into_iter().map(quote_ident).collect();
+
+        match primary_key.is_empty() {
+            false => write!(f, "CREATE TABLE {} ({}, PRIMARY KEY({}))", quote_ident(&self.name), attr, primary_key.join(", ")),
+            true => write!(f, "CREATE TABLE {} ({})", quote_ident(&self.name), attr),
+        }
+    }
+}
+
+impl RelationMethods for Table {
+    fn select(&self) -> QDL {
+        QDL(format!("SELECT {} FROM {}", self.projection(), quote_ident(&self.name)))
+    }
+    fn drop(&self) -> DDL{
+        DDL(format!("DROP TABLE {}", quote_ident(&self.name)))
+    }
+    fn create(&self) -> DDL{
+        DDL(self.to_string())
+    }
+}
+
+/// An attribute in a table of a relational database
+///
+/// Attributes are equal when their names, data types and constraints are. `raw_type` is left out, as the server
+/// writes the same type differently across versions, such as `int(11)` and `int`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attribute{
+    /// The name of the attribute.
+    pub name: String,
+    /// The data type of the attribute.
+    pub data_type: AttributeType,
+    /// The column type as reported by `SHOW FULL COLUMNS`, kept verbatim for types the crate does not model.
+    ///
+    /// Empty when the attribute was not loaded from the database.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub raw_type: String,
+    /// A vector of Constraints on the attribute.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_constraints"))]
+    pub constraint: HashSet<Constraint>
+}
+
+/// Serializes the constraints of an attribute in the order of its column definition, so the output is stable.
+#[cfg(feature = "serde")]
+fn serialize_constraints<S: serde::Serializer>(constraints: &HashSet<Constraint>, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut sorted: Vec<&Constraint> = constraints.iter().collect();
+    sorted.sort_by_cached_key(|constraint| (constraint.rank(), constraint.to_string()));
+
+    serializer.collect_seq(sorted)
+}
+
+impl PartialEq for Attribute {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.data_type == other.data_type && self.constraint == other.constraint
+    }
+}
+
+impl Eq for Attribute {}
+
+impl Hash for Attribute {
+    //a HashSet cannot be hashed, so the constraints are left out, which equal attributes still agree on
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.data_type.hash(state);
+    }
+}
+
+impl Attribute {
+    /// Returns the attribute described by a row of `SHOW FULL COLUMNS`.
+    ///
+    /// Fields are read by name, as some servers omit the `Collation` and `Privileges` columns. Foreign keys are added
+    /// afterwards by `Table::resolve_foreign_keys`.
+    fn from_row(row: Row) -> Option<Attribute> {
+        let name: String = row.get("Field").unwrap();
+        let data_type: String = row.get("Type").unwrap();
+
+        info!("name:{}\tdata_type:{}", name, data_type);
+
+        let raw_type = data_type;
+
+        let data_type = match AttributeType::from(&raw_type) {
+            Some(val) => val,
+            None => AttributeType::Unknown(raw_type.clone()),
+        };
+
+        Some(
+            Attribute {
+                name: name.clone(),
+                data_type: data_type,
+                raw_type,
+                constraint: {
+                    let mut tmp : HashSet<Constraint> = HashSet::new();
+
+                    {
+                        let nullable : String = row.get("Null").unwrap();
+                        
+                        if nullable == "NO" {
+                            let _result = &tmp.insert(Constraint::NotNull);
+                        }
+                    }
+
+                    {
+                        let auto_inc : String = row.get("Extra").unwrap();
+                        
+                        if auto_inc == "auto_increment" {
+                            let _result = &tmp.insert(Constraint::AutoIncrement);
+                        }
+
+                        let default : Option<String> = row.get("Default").unwrap();
+
+                        if let Some(default) = default {
+                            let _result = &tmp.insert(Constraint::Default(read_default(&default, &auto_inc)));
+                        }
+                    }
+
+                    {
+                        let key : String = row.get("Key").unwrap();
+                        
+                        if key == "UNI" {
+                            let _result = &tmp.insert(Constraint::Unique);
+                        }
+                    }
+
+                    tmp
+                }
+            }
+        )
+    }
+
+    /// Returns a string representation of the attribute's schema.
+    pub fn schema_fmt(&self) -> String {
+        format!("{} {}", self.name, self.type_definition())
+    }
+
+    /// Returns the column definition of the attribute: the name, data type and every column level constraint.
+    ///
+    /// The foreign key clause is left out, as it is a table level definition (see `Attribute::fk_clause`).
+    /// This is the form expected by statements such as `ALTER TABLE ... MODIFY COLUMN`.
+    pub fn column_definition(&self) -> String {
+        self.definition(true)
+    }
+
+    /// Returns the column definition, leaving out `Unique` unless `unique` is set.
+    ///
+    /// Constraints are written in a fixed order whatever the order of the set (see `Constraint::rank`).
+    pub(super) fn definition(&self, unique: bool) -> String {
+        let mut constraints: Vec<(u8, String)> = self.constraint
+            .iter()
+            .filter(|c| !matches!(c, Constraint::ForeignKey { .. } | Constraint::Encrypted(_)))
+            .filter(|c| unique || **c != Constraint::Unique)
+            .map(|c| match c {
+                Constraint::Default(default) => (c.rank(), format!("DEFAULT {}", default.to_sql(&self.data_type))),
+                c => (c.rank(), c.to_string()),
+            })
+            .collect();
+        constraints.sort();
+
+        let constraint_str: String = constraints
+            .into_iter()
+            .map(|(_, constraint)| constraint)
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        match constraint_str.len() {
+            0 => format!("{} {}", quote_ident(&self.name), self.type_definition()),
+            _ => format!("{} {} {}", quote_ident(&self.name), self.type_definition(), constraint_str),
+        }
+    }
+
+    /// Returns the type used in the column definition: `raw_type` verbatim if the type is `AttributeType::Unknown`,
+    /// otherwise the parsed type.
+    pub fn type_definition(&self) -> String {
+        match (&self.data_type, self.raw_type.is_empty()) {
+            (AttributeType::Unknown(_), false) => self.raw_type.clone(),
+            _ => self.data_type.to_string(),
+        }
+    }
+
+    /// Returns true if an insert has to set the attribute: it is `NOT NULL`, not auto incremented and has no default.
+    pub fn is_required(&self) -> bool {
+        self.constraint.contains(&Constraint::NotNull) &&
+            !self.constraint.contains(&Constraint::AutoIncrement) &&
+            self.default_expression().is_none()
+    }
+
+    /// Returns true if `NULL` can be inserted into the attribute: it is not `NOT NULL`, or it is auto incremented, in which
+    /// case `NULL` generates the next id.
+    pub fn is_nullable(&self) -> bool {
+        !self.constraint.contains(&Constraint::NotNull) || self.constraint.contains(&Constraint::AutoIncrement)
+    }
+
+    /// Checks that `value` can be inserted into the attribute, returning the reason if it cannot.
+    ///
+    /// `NOT NULL` is enforced unless the attribute is auto incremented, and values are checked against the range or
+    /// length of the column's type, and text against the values of an enum or set. Text is accepted by numeric and temporal
+    /// columns, which the server converts.
+    pub fn check_value(&self, value: &Value) -> Result<(), String> {
+        if let Value::Text(val) = value {
+            if !self.data_type.is_member(val) {
+                return Err(format!("value is not one of the values of {} ({})", self.name, self.type_definition()));
+            }
+        }
+
+        let storage = match self.data_type.storage() {
+            Some(storage) => storage,
+            None => return Ok(()),
+        };
+
+        let fits = match (value, storage) {
+            (Value::Default, _) => true,
+            (Value::Null, _) => self.is_nullable(),
+
+            (Value::Int(val), Storage::Integer(_) | Storage::Unsigned(_)) => self.data_type.holds(*val as i128),
+            (Value::UInt(val), Storage::Integer(_) | Storage::Unsigned(_)) => self.data_type.holds(*val as i128),
+            (Value::Bool(_), Storage::Integer(_) | Storage::Unsigned(_) | Storage::Bit(_)) => true,
+            (Value::Int(_) | Value::UInt(_) | Value::Float(_), Storage::Float | Storage::Double | Storage::Decimal(..)) => true,
+
+            (Value::Text(val), Storage::Text(size)) => val.chars().count() as u64 <= size,
+            (Value::Text(val), Storage::Binary(size)) => val.len() as u64 <= size,
+            (Value::Bytes(val), Storage::Binary(size) | Storage::Text(size)) => val.len() as u64 <= size,
+            (Value::Text(val), Storage::Integer(_) | Storage::Unsigned(_)) => {
+                val.trim().parse::<i128>().is_ok_and(|val| self.data_type.holds(val))
+            },
+            (Value::Text(_), _) => true,
+            (Value::Int(_) | Value::UInt(_) | Value::Float(_) | Value::Bool(_), Storage::Text(_)) => true,
+
+            (Value::Date{..}, Storage::Date | Storage::DateTime | Storage::TimeStamp | Storage::Text(_)) => true,
+            (Value::DateTime{..}, Storage::DateTime | Storage::TimeStamp | Storage::Text(_)) => true,
+
+            _ => false,
+        };
+
+        match (fits, value) {
+            (true, _) => Ok(()),
+            (false, Value::Null) => Err(format!("{} cannot be NULL", self.name)),
+            (false, _) => Err(format!("value does not fit in {} ({})", self.name, self.type_definition())),
+        }
+    }
+
+    /// Returns the key of the attribute if it is an encrypted column.
+    pub fn encryption_key(&self) -> Option<&EncryptionKey> {
+        self.constraint
+            .iter()
+            .find_map(|c| match c {
+                Constraint::Encrypted(key) => Some(key),
+                _ => None,
+            })
+    }
+
+    /// Returns the expression reading the attribute: `AES_DECRYPT(name, key) AS name` for encrypted columns,
+    /// otherwise the name.
+    pub fn read_expression(&self) -> String {
+        match self.encryption_key() {
+            Some(key) => format!("{} AS {}", key.decrypt(&quote_ident(&self.name)), quote_ident(&self.name)),
+            None => quote_ident(&self.name),
+        }
+    }
+
+    /// Returns the expression writing `literal` to the attribute: `AES_ENCRYPT(literal, key)` for encrypted columns,
+    /// otherwise `literal`. `DEFAULT` is never wrapped.
+    pub fn write_expression(&self, literal: &str) -> String {
+        match (self.encryption_key(), literal) {
+            (_, "DEFAULT") | (None, _) => literal.to_string(),
+            (Some(key), _) => key.encrypt(literal),
+        }
+    }
+
+    /// Returns the expression writing the unquoted `value` to the attribute, for the builders taking values as
+    /// strings, such as `Table::insert`.
+    ///
+    /// Values of types that are written quoted (see `AttributeType::is_quoted`) are escaped and quoted, so `O'Brien`
+    /// becomes `'O''Brien'`. `NULL` and the values of other types are written as they are. The literal is then passed
+    /// to `Attribute::write_expression`.
+    pub fn value_expression(&self, value: &str) -> String {
+        match self.data_type.is_quoted() && !value.trim().eq_ignore_ascii_case("NULL") {
+            true => self.write_expression(&format!("'{}'", escape_string(value))),
+            false => self.write_expression(value),
+        }
+    }
+
+    /// Returns the table level `FOREIGN KEY` clause of the attribute, or `None` if the attribute does not reference another relation.
+    ///
+    /// An attribute with several foreign keys gives the first of them in the order of their clauses.
+    pub fn fk_clause(&self) -> Option<String> {
+        self.constraint
+            .iter()
+            .filter(|c| matches!(c, Constraint::ForeignKey { .. }))
+            .map(|c| format!("FOREIGN KEY ({}) {}", quote_ident(&self.name), c))
+            .min()
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.fk_clause() {
+            Some(foreign_key) => write!(f, "{}, {}", self.column_definition(), foreign_key),
+            None => write!(f, "{}", self.column_definition()),
+        }
+    }
+}
+
+/// Constraint defines the restrictions of an attribute
+///
+/// Constraints are equal when their variant and fields are, so an attribute can hold several foreign keys.
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Constraint{
+    /// The attribute must not contain a null value.
+    NotNull,
+    /// The attribute must contain a unique value.
+    Unique,
+    /// The attribute is a foreign key that references another attribute in a different table.
+    ForeignKey{
+        /// The name of the table that the foreign key attribute references.
+        table_name: String,
+        /// The name of the attribute that the foreign key references.
+        attribute_name: String,
+        /// What happens to the row when the referenced row is deleted, or `None` for the server's default.
+        on_delete: Option<ReferentialAction>,
+        /// What happens to the row when the referenced attribute is updated, or `None` for the server's default.
+        on_update: Option<ReferentialAction>,
+    },
+    /// The attribute is an auto-incrementing integer.
+    AutoIncrement,
+    /// The attribute's default, following `DEFAULT` in its column definition.
+    Default(DefaultValue),
+    /// The attribute must satisfy the expression, written as `CHECK (expression)` in its column definition. MySQL
+    /// enforces checks from 8.0.16, and a column's checks may only name that column.
+    Check(String),
+    /// The attribute is stored encrypted with `AES_ENCRYPT`. Values are encrypted by the insert builders and decrypted
+    /// by `select`. This is not a SQL constraint and is left out of column definitions.
+    ///
+    /// The key is never serialized, so serializing an encrypted attribute is an error.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Encrypted(EncryptionKey),
+}
+impl Constraint {
+    /// Returns the position of the constraint in a column definition: `NOT NULL`, `DEFAULT`, `AUTO_INCREMENT`,
+    /// `UNIQUE`, `CHECK`, then the foreign keys. Constraints of the same rank are written in the order of their text.
+    fn rank(&self) -> u8 {
+        match self {
+            Constraint::NotNull => 0,
+            Constraint::Default(_) => 1,
+            Constraint::AutoIncrement => 2,
+            Constraint::Unique => 3,
+            Constraint::Check(_) => 4,
+            Constraint::ForeignKey{..} => 5,
+            Constraint::Encrypted(_) => 6,
+        }
+    }
+}
+
+impl fmt::Display for Constraint{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constraint::NotNull => write!(f, "NOT NULL"),
+            Constraint::Unique => write!(f, "UNIQUE"),
+            Constraint::ForeignKey{table_name: table,attribute_name: attr, on_delete, on_update} => {
+                write!(f, "REFERENCES {} ({})", quote_ident(table), quote_ident(attr))?;
+
+                if let Some(action) = on_delete {
+                    write!(f, " ON DELETE {}", action)?;
+                }
+                if let Some(action) = on_update {
+                    write!(f, " ON UPDATE {}", action)?;
+                }
+
+                Ok(())
+            },
+            Constraint::AutoIncrement => write!(f, "AUTO_INCREMENT"),
+            Constraint::Default(expression) => write!(f, "DEFAULT {}", expression),
+            Constraint::Check(expression) => write!(f, "CHECK ({})", expression),
+            Constraint::Encrypted(_) => Ok(()),
+        }
+    }
+}
+
+/// ReferentialAction defines what happens to the rows of a foreign key when the row they reference changes.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ReferentialAction {
+    /// The change to the referenced row is rejected.
+    Restrict,
+    /// The rows are deleted or updated along with the referenced row.
+    Cascade,
+    /// The foreign key of the rows is set to `NULL`.
+    SetNull,
+    /// The same as `Restrict` in InnoDB.
+    NoAction,
+    /// The foreign key of the rows is set to its default. InnoDB rejects it.
+    SetDefault,
+}
+
+impl ReferentialAction {
+    /// Returns the action named by `rule`, as written after `ON DELETE` or reported by `information_schema`.
+    pub fn parse(rule: &str) -> Option<ReferentialAction> {
+        match rule.trim().to_ascii_uppercase().split_whitespace().collect::<Vec<&str>>().join(" ").as_str() {
+            "RESTRICT" => Some(ReferentialAction::Restrict),
+            "CASCADE" => Some(ReferentialAction::Cascade),
+            "SET NULL" => Some(ReferentialAction::SetNull),
+            "NO ACTION" => Some(ReferentialAction::NoAction),
+            "SET DEFAULT" => Some(ReferentialAction::SetDefault),
+            _ => None,
+        }
+    }
+
+    /// Returns true for `Restrict` and `NoAction`, which InnoDB treats the same and takes when no action is given.
+    pub fn is_default(&self) -> bool {
+        matches!(self, ReferentialAction::Restrict | ReferentialAction::NoAction)
+    }
+}
+
+impl fmt::Display for ReferentialAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferentialAction::Restrict => write!(f, "RESTRICT"),
+            ReferentialAction::Cascade => write!(f, "CASCADE"),
+            ReferentialAction::SetNull => write!(f, "SET NULL"),
+            ReferentialAction::NoAction => write!(f, "NO ACTION"),
+            ReferentialAction::SetDefault => write!(f, "SET DEFAULT"),
+        }
+    }
+}
+
+/// AttributeType defines every type of MySQL datatype
+///
+/// Types are equal when their variants and parameters are, so `Uuid` is not equal to `Char(36)`. With the `serde`
+/// feature, a type is written as its variant in snake case holding its parameters, such as `{"var_char": 255}`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AttributeType{
+    //string data types
+    Char(u8),
+    VarChar(u16),
+    Binary(u8),
+    VarBinary(u16),
+    TinyBlob,
+    TinyText,
+    Text,
+    Blob(u16),
+    MediumText,
+    MediumBlob,
+    LongText,
+    LongBlob,
+    Enum{val: Vec<String>},
+    Set{val: Vec<String>},
+
+    //numeric data types
     Bit(u8),
     TinyInt(u8),
     Bool,
@@ -537,283 +2249,2367 @@ pub enum AttributeType{
     Int(u8),
     BigInt(u8),
     Float(u8),
+    /// A double precision floating point number, with its (digits, fractional digits) if declared.
+    Double(Option<(u8, u8)>),
+    Decimal(u8, u8),
+    /// A numeric type declared `UNSIGNED`, and `ZEROFILL` if `zerofill` is set.
+    Unsigned{val: Box<AttributeType>, zerofill: bool},
+
+    //Date time
+    //the fractional second precision, if declared
+    Date,
+    DateTime(Option<u8>),
+    TimeStamp(Option<u8>),
+    Time(Option<u8>),
+    Year,
+
+    /// A JSON document.
+    Json,
+
+    /// A UUID, stored as `char(36)`.
+    Uuid,
+
+    /// A type the crate does not model, holding the type as it was read.
+    Unknown(String),
+}
+
+//patterns match at the start of the type, up to the end of a word, so `DATE` does not match `DATETIME`
+//each use compiles its pattern once, on first use, as a table load reads the type of every column
+macro_rules! regex_check {
+    ($regex_expr : literal, $raw_str: expr, $output_variant : ident, $parse_type_1: ty, $parse_type_2: ty, optional) => {
+        {
+            lazy_static! {
+                static ref CHECK : Regex = Regex::new(concat!("^", $regex_expr, r"(?:\((\d+),(\d+)\))?(?:$|[^A-Z0-9_])")).unwrap();
+            };
+
+            if let Some(size) = CHECK.captures($raw_str) {
+                return match (size.get(1), size.get(2)) {
+                    (Some(tmp_1), Some(tmp_2)) => tmp_1.as_str().parse::<$parse_type_1>().ok()
+                        .zip(tmp_2.as_str().parse::<$parse_type_2>().ok())
+                        .map(|size| AttributeType::$output_variant(Some(size))),
+                    _ => Some(AttributeType::$output_variant(None)),
+                }
+            }
+        }
+    };
+    ($regex_expr : literal, $raw_str: expr, $output_variant : ident, $parse_type: ty, optional) => {
+        {
+            lazy_static! {
+                static ref CHECK : Regex = Regex::new(concat!("^", $regex_expr, r"(?:\((\d+)\))?(?:$|[^A-Z0-9_])")).unwrap();
+            };
+
+            if let Some(size) = CHECK.captures($raw_str) {
+                return match size.get(1) {
+                    Some(tmp) => tmp.as_str().parse::<$parse_type>().ok().map(|tmp| AttributeType::$output_variant(Some(tmp))),
+                    None => Some(AttributeType::$output_variant(None)),
+                }
+            }
+        }
+    };
+    ($regex_expr : literal, $raw_str: expr, $output_variant : ident, $parse_type_1: ty, $parse_type_2: ty) => {
+        {
+            lazy_static! {
+                static ref CHECK : Regex = Regex::new(concat!("^", $regex_expr, "(?:$|[^A-Z0-9_])")).unwrap();
+            };
+
+            if let Some(size) = CHECK.captures($raw_str) {
+                let tmp_1 = size.get(1).unwrap().as_str();
+                let tmp_2 = size.get(2).unwrap().as_str();
+
+                return Some(AttributeType::$output_variant(tmp_1.parse::<$parse_type_1>().unwrap(), tmp_2.parse::<$parse_type_2>().unwrap()))
+            }
+        }
+    };
+    ($regex_expr : literal, $raw_str: expr, $output_variant : ident, $parse_type: ty, default $default: expr) => {
+        {
+            lazy_static! {
+                static ref CHECK : Regex = Regex::new(concat!("^", $regex_expr, r"(?:\((\d+)\))?(?:$|[^A-Z0-9_])")).unwrap();
+            };
+
+            if let Some(size) = CHECK.captures($raw_str) {
+                let tmp = size.get(1).map_or(Some($default), |tmp| tmp.as_str().parse::<$parse_type>().ok());
+
+                return tmp.map(AttributeType::$output_variant)
+            }
+        }
+    };
+    ($regex_expr : literal, $raw_str: expr, $output_variant : ident, $parse_type: ty) => {
+        {
+            lazy_static! {
+                static ref CHECK : Regex = Regex::new(concat!("^", $regex_expr, "(?:$|[^A-Z0-9_])")).unwrap();
+            };
+
+            if let Some(size) = CHECK.captures($raw_str) {
+                let tmp = size.get(1).unwrap().as_str();
+
+                return Some(AttributeType::$output_variant(tmp.parse::<$parse_type>().unwrap()))
+            }
+        }
+    };
+    ($regex_expr : literal, $raw_str: expr, $output_variant : ident) => {
+        {
+            lazy_static! {
+                static ref CHECK : Regex = Regex::new(concat!("^", $regex_expr, "(?:$|[^A-Z0-9_])")).unwrap();
+            };
+
+            if CHECK.is_match($raw_str) {
+                return Some(AttributeType::$output_variant)
+            }
+        }
+    };
+}
+
+/// Returns the values of a type written `keyword('a','b')`, such as an `enum`, with the quotes doubled inside them
+/// undone, or `None` if `raw_str` is not one. `keyword` is uppercase and ends with the opening parenthesis.
+fn quoted_values(raw_str: &str, keyword: &str) -> Option<Vec<String>> {
+    if !raw_str.get(..keyword.len())?.eq_ignore_ascii_case(keyword) {
+        return None;
+    }
+
+    let mut chars = raw_str[keyword.len()..].trim_end().strip_suffix(')')?.chars().peekable();
+    let mut values = Vec::new();
+
+    loop {
+        if chars.next() != Some('\'') {
+            return None;
+        }
+
+        let mut value = String::new();
+
+        loop {
+            match chars.next()? {
+                '\'' if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    value.push('\'');
+                },
+                '\'' => break,
+                ch => value.push(ch),
+            }
+        }
+
+        values.push(value);
+
+        match chars.next() {
+            Some(',') => continue,
+            None => return Some(values),
+            Some(_) => return None,
+        }
+    }
+}
+
+impl AttributeType {
+    /// Returns the `AttributeType` variant corresponding to the given string, ignoring case but for the values of an
+    /// `enum` or `set`.
+    ///
+    /// Every variant but `Uuid` and `Unknown` is read back from its `Display`. Integer and
+    /// bit types may omit their width, as MySQL 8.0.19 and later do for integers, and get their default width. A
+    /// trailing `UNSIGNED` or `ZEROFILL`, which implies `UNSIGNED`, is only accepted after a numeric type.
+    pub(super) fn from(raw_str: &str) -> Option<AttributeType> {
+        if let Some(val) = quoted_values(raw_str, "ENUM(") {
+            return Some(AttributeType::Enum{val});
+        }
+        if let Some(val) = quoted_values(raw_str, "SET(") {
+            return Some(AttributeType::Set{val});
+        }
+
+        let raw_str = raw_str.to_ascii_uppercase();
+        let raw_str = raw_str.as_str();
+
+        let mut base = raw_str.trim_end();
+        let (mut unsigned, mut zerofill) = (false, false);
+
+        loop {
+            if let Some(rest) = base.strip_suffix(" UNSIGNED") {
+                unsigned = true;
+                base = rest.trim_end();
+            }
+            else if let Some(rest) = base.strip_suffix(" ZEROFILL") {
+                zerofill = true;
+                base = rest.trim_end();
+            }
+            else {
+                break;
+            }
+        }
+
+        if unsigned || zerofill {
+            return AttributeType::from(base)
+                .filter(|val| val.is_numeric())
+                .map(|val| AttributeType::Unsigned{val: Box::new(val), zerofill});
+        }
+
+        regex_check!(r"CHAR\((\d+)\)", raw_str, Char, u8);
+        regex_check!(r"VARCHAR\((\d+)\)", raw_str, VarChar, u16);
+        regex_check!(r"BINARY\((\d+)\)", raw_str, Binary, u8);
+        regex_check!(r"VARBINARY\((\d+)\)", raw_str, VarBinary, u16);
+        regex_check!(r"TINYBLOB", raw_str, TinyBlob);
+        regex_check!(r"TINYTEXT", raw_str, TinyText);
+        regex_check!(r"TEXT", raw_str, Text);
+        regex_check!(r"BLOB\((\d+)\)", raw_str, Blob, u16);
+        regex_check!(r"MEDIUMTEXT", raw_str, MediumText);
+        regex_check!(r"MEDIUMBLOB", raw_str, MediumBlob);
+        regex_check!(r"LONGTEXT", raw_str, LongText);
+        regex_check!(r"LONGBLOB", raw_str, LongBlob);
+
+        regex_check!(r"BIT", raw_str, Bit, u8, default 1);
+        regex_check!(r"TINYINT", raw_str, TinyInt, u8, default 4);
+        regex_check!(r"BOOL", raw_str, Bool);
+        regex_check!(r"BOOLEAN", raw_str, Boolean);
+        regex_check!(r"SMALLINT", raw_str, SmallInt, u8, default 6);
+        regex_check!(r"MEDIUMINT", raw_str, MediumInt, u8, default 9);
+        regex_check!(r"INT", raw_str, Int, u8, default 11);
+        regex_check!(r"INTEGER", raw_str, Int, u8, default 11);
+        regex_check!(r"BIGINT", raw_str, BigInt, u8, default 20);
+        regex_check!(r"FLOAT\((\d+)\)", raw_str, Float, u8);
+        regex_check!(r"DOUBLE", raw_str, Double, u8, u8, optional);
+        regex_check!(r"DECIMAL\((\d+),(\d+)\)", raw_str, Decimal, u8, u8);
+
+        regex_check!(r"DATE", raw_str, Date);
+        regex_check!(r"DATETIME", raw_str, DateTime, u8, optional);
+        regex_check!(r"TIMESTAMP", raw_str, TimeStamp, u8, optional);
+        regex_check!(r"TIME", raw_str, Time, u8, optional);
+        regex_check!(r"YEAR", raw_str, Year);
+
+        regex_check!(r"JSON", raw_str, Json);
+
+        return None
+    }
+}
+
+impl AttributeType {
+    /// Returns true if literals of the data type are written as quoted strings: every type but the numeric ones, whose
+    /// literals are written as they are. Literals of `AttributeType::Unknown` are quoted, as the server converts strings
+    /// to most types.
+    pub fn is_quoted(&self) -> bool {
+        !matches!(
+            self,
+            AttributeType::Bit(_) |
+            AttributeType::TinyInt(_) |
+            AttributeType::Bool |
+            AttributeType::Boolean |
+            AttributeType::SmallInt(_) |
+            AttributeType::MediumInt(_) |
+            AttributeType::Int(_) |
+            AttributeType::BigInt(_) |
+            AttributeType::Float(_) |
+            AttributeType::Double(_) |
+            AttributeType::Decimal(_, _) |
+            AttributeType::Unsigned{..}
+        )
+    }
+
+    /// Returns true if the data type is an integer, fixed or floating point type that can be declared `UNSIGNED`.
+    fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            AttributeType::TinyInt(_) |
+            AttributeType::SmallInt(_) |
+            AttributeType::MediumInt(_) |
+            AttributeType::Int(_) |
+            AttributeType::BigInt(_) |
+            AttributeType::Float(_) |
+            AttributeType::Double(_) |
+            AttributeType::Decimal(_, _)
+        )
+    }
+
+    /// Returns the smallest and largest values of an integer type, which depend on whether it is `UNSIGNED`, or `None`
+    /// if the type is not an integer.
+    pub(crate) fn integer_range(&self) -> Option<(i128, i128)> {
+        match self.storage() {
+            Some(Storage::Integer(bits)) => Some((-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)),
+            Some(Storage::Unsigned(bits)) => Some((0, (1i128 << bits) - 1)),
+            _ => None,
+        }
+    }
+
+    /// Returns true if `val` is within `AttributeType::integer_range`.
+    fn holds(&self, val: i128) -> bool {
+        self.integer_range().is_some_and(|(min, max)| (min..=max).contains(&val))
+    }
+
+    /// Returns true if `value` can be written as it is to a column of the type that is not quoted (see
+    /// `AttributeType::is_quoted`): a number of the right kind, or `NULL` or `DEFAULT`. Values of quoted types are always
+    /// accepted.
+    pub fn accepts_bare(&self, value: &str) -> bool {
+        let value = value.trim();
+
+        if value.eq_ignore_ascii_case("NULL") || value.eq_ignore_ascii_case("DEFAULT") {
+            return true;
+        }
+
+        match self {
+            AttributeType::Bit(_) | AttributeType::Bool | AttributeType::Boolean => {
+                value.parse::<u64>().is_ok() || value.eq_ignore_ascii_case("TRUE") || value.eq_ignore_ascii_case("FALSE")
+            },
+            AttributeType::TinyInt(_) |
+            AttributeType::SmallInt(_) |
+            AttributeType::MediumInt(_) |
+            AttributeType::Int(_) |
+            AttributeType::BigInt(_) => value.parse::<i128>().is_ok(),
+            AttributeType::Unsigned{val, ..} => val.accepts_bare(value),
+            AttributeType::Float(_) | AttributeType::Double(_) | AttributeType::Decimal(_, _) => {
+                value.parse::<f64>().is_ok_and(|number| number.is_finite())
+            },
+            _ => true,
+        }
+    }
+
+    /// Returns false if the data type is an `Enum` that does not have `value` among its values, or a `Set` that does not
+    /// have each of the comma separated members of `value` among its values. Values are compared ignoring case as with
+    /// the server's default collation. Returns true for every other type.
+    pub fn is_member(&self, value: &str) -> bool {
+        let declared = |val: &Vec<String>, member: &str| val.iter().any(|val| val.eq_ignore_ascii_case(member));
+
+        match self {
+            AttributeType::Enum{val} => declared(val, value),
+            //the empty set is written as an empty string
+            AttributeType::Set{val} => value.is_empty() || value.split(',').all(|member| declared(val, member)),
+            _ => true,
+        }
+    }
+
+    /// Returns true if the data type stores binary data.
+    pub fn is_binary(&self) -> bool {
+        matches!(
+            self,
+            AttributeType::Binary(_) |
+            AttributeType::VarBinary(_) |
+            AttributeType::TinyBlob |
+            AttributeType::Blob(_) |
+            AttributeType::MediumBlob |
+            AttributeType::LongBlob
+        )
+    }
+}
+
+/// How the server stores a data type, used to check whether one type can stand in for another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Storage {
+    /// An integer of the given number of bits.
+    Integer(u8),
+    /// An unsigned integer of the given number of bits.
+    Unsigned(u8),
+    Bit(u8),
+    Float,
+    Double,
+    /// A fixed point number, as (integer digits, fractional digits).
     Decimal(u8, u8),
+    /// Text of up to the given number of characters.
+    Text(u64),
+    /// Binary data of up to the given number of bytes.
+    Binary(u64),
+    Date,
+    DateTime,
+    TimeStamp,
+    Time,
+    Year,
+    Json,
+}
+
+impl AttributeType {
+    /// Returns how the data type is stored, or `None` for types that can only be compared for equality.
+    fn storage(&self) -> Option<Storage> {
+        match self {
+            AttributeType::Char(val) => Some(Storage::Text(*val as u64)),
+            AttributeType::VarChar(val) => Some(Storage::Text(*val as u64)),
+            AttributeType::TinyText => Some(Storage::Text(255)),
+            AttributeType::Text => Some(Storage::Text(65_535)),
+            AttributeType::MediumText => Some(Storage::Text(16_777_215)),
+            AttributeType::LongText => Some(Storage::Text(4_294_967_295)),
+            AttributeType::Uuid => Some(Storage::Text(36)),
+
+            AttributeType::Binary(val) => Some(Storage::Binary(*val as u64)),
+            AttributeType::VarBinary(val) => Some(Storage::Binary(*val as u64)),
+            AttributeType::TinyBlob => Some(Storage::Binary(255)),
+            AttributeType::Blob(val) => Some(Storage::Binary(*val as u64)),
+            AttributeType::MediumBlob => Some(Storage::Binary(16_777_215)),
+            AttributeType::LongBlob => Some(Storage::Binary(4_294_967_295)),
+
+            AttributeType::Bit(val) => Some(Storage::Bit(*val)),
+            AttributeType::TinyInt(_) |
+            AttributeType::Bool |
+            AttributeType::Boolean => Some(Storage::Integer(8)),
+            AttributeType::SmallInt(_) => Some(Storage::Integer(16)),
+            AttributeType::MediumInt(_) => Some(Storage::Integer(24)),
+            AttributeType::Int(_) => Some(Storage::Integer(32)),
+            AttributeType::BigInt(_) => Some(Storage::Integer(64)),
+            AttributeType::Float(_) => Some(Storage::Float),
+            AttributeType::Double(_) => Some(Storage::Double),
+            AttributeType::Decimal(digits, scale) => Some(Storage::Decimal(digits.saturating_sub(*scale), *scale)),
+            AttributeType::Unsigned{val, ..} => match val.storage() {
+                Some(Storage::Integer(bits)) => Some(Storage::Unsigned(bits)),
+                storage => storage,
+            },
+
+            AttributeType::Date => Some(Storage::Date),
+            AttributeType::DateTime(_) => Some(Storage::DateTime),
+            AttributeType::TimeStamp(_) => Some(Storage::TimeStamp),
+            AttributeType::Time(_) => Some(Storage::Time),
+            AttributeType::Year => Some(Storage::Year),
+
+            AttributeType::Json => Some(Storage::Json),
+
+            AttributeType::Enum{..} |
+            AttributeType::Set{..} |
+            AttributeType::Unknown(_) => None,
+        }
+    }
+
+    /// Returns true if a column of this type can hold every value of `other`, so `other` can be used in its place.
+    ///
+    /// A column whose type the crate does not model (`AttributeType::Unknown`) is assumed to hold anything.
+    pub fn can_store(&self, other: &AttributeType) -> bool {
+        if let AttributeType::Unknown(_) = self {
+            return true;
+        }
+
+        match (self.storage(), other.storage()) {
+            (Some(Storage::Integer(bits)), Some(Storage::Integer(other_bits))) => other_bits <= bits,
+            (Some(Storage::Unsigned(bits)), Some(Storage::Unsigned(other_bits))) => other_bits <= bits,
+            (Some(Storage::Integer(bits)), Some(Storage::Unsigned(other_bits))) => other_bits < bits,
+            (Some(Storage::Bit(bits)), Some(Storage::Bit(other_bits))) => other_bits <= bits,
+            (Some(Storage::Double), Some(Storage::Float)) => true,
+            (Some(Storage::Decimal(digits, scale)), Some(Storage::Decimal(other_digits, other_scale))) => {
+                other_digits <= digits && other_scale <= scale
+            },
+            (Some(Storage::Text(size)), Some(Storage::Text(other_size))) => other_size <= size,
+            (Some(Storage::Binary(size)), Some(Storage::Binary(other_size))) => other_size <= size,
+            (Some(storage), Some(other_storage)) => storage == other_storage,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for AttributeType{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            //string data types
+            AttributeType::Char(val) => write!(f, "char({})", val),
+            AttributeType::VarChar(val) => write!(f, "varchar({})", val),
+            AttributeType::Binary(val) => write!(f, "binary({})", val),
+            AttributeType::VarBinary(val) => write!(f, "varbinary({})", val),
+            AttributeType::TinyBlob => write!(f, "tinyblob"),
+            AttributeType::TinyText => write!(f, "tinytext"),
+            AttributeType::Text => write!(f, "text"),
+            AttributeType::Blob(val) => write!(f, "blob({})", val),
+            AttributeType::MediumText => write!(f, "mediumtext"),
+            AttributeType::MediumBlob => write!(f, "mediumblob"),
+            AttributeType::LongText => write!(f, "longtext"),
+            AttributeType::LongBlob => write!(f, "longblob"),
+            AttributeType::Enum{val} => {
+                let val: Vec<String> = val.iter().map(|val| format!("'{}'", val.replace('\'', "''"))).collect();
+
+                write!(f, "enum({})", val.join(","))
+            },
+            AttributeType::Set{val} => {
+                let val: Vec<String> = val.iter().map(|val| format!("'{}'", val.replace('\'', "''"))).collect();
+
+                write!(f, "set({})", val.join(","))
+            },
+
+            //numeric data types
+            AttributeType::Bit(val) => write!(f, "bit({})", val),
+            AttributeType::TinyInt(val) => write!(f, "tinyint({})", val),
+            AttributeType::Bool => write!(f, "bool"),
+            AttributeType::Boolean => write!(f, "boolean"),
+            AttributeType::SmallInt(val) => write!(f, "smallint({})", val),
+            AttributeType::MediumInt(val) => write!(f, "mediumint({})", val),
+            AttributeType::Int(val) => write!(f, "int({})", val),
+            AttributeType::BigInt(val) => write!(f, "bigint({})", val),
+            AttributeType::Float(val) => write!(f, "float({})", val),
+            AttributeType::Double(None) => write!(f, "double"),
+            AttributeType::Double(Some((val_1, val_2))) => write!(f, "double({},{})", val_1, val_2),
+            AttributeType::Decimal(val_1 , val_2) => write!(f, "decimal({},{})", val_1, val_2),
+            AttributeType::Unsigned{val, zerofill: false} => write!(f, "{} unsigned", val),
+            AttributeType::Unsigned{val, zerofill: true} => write!(f, "{} unsigned zerofill", val),
+
+            //Date time
+            AttributeType::Date => write!(f, "date"),
+            AttributeType::DateTime(None) => write!(f, "datetime"),
+            AttributeType::DateTime(Some(val)) => write!(f, "datetime({})", val),
+            AttributeType::TimeStamp(None) => write!(f, "timestamp"),
+            AttributeType::TimeStamp(Some(val)) => write!(f, "timestamp({})", val),
+            AttributeType::Time(None) => write!(f, "time"),
+            AttributeType::Time(Some(val)) => write!(f, "time({})", val),
+            AttributeType::Year => write!(f, "year"),
+
+            AttributeType::Json => write!(f, "json"),
+
+            AttributeType::Uuid => write!(f, "char(36)"),
+
+            AttributeType::Unknown(val) => write!(f, "{}", val),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused_imports)]
+    use std::collections::{HashSet, HashMap};
+
+    use serial_test::serial;
+
+    use crate::{
+        data_base::{DataBase, Executor},
+        sql::{SQL, DDL, QML, QDL, SQLError, IdentifierError, keywords::ServerVersion, redact::redact, value::{self, Value, ValueError, MAX_LITERAL_SIZE}},
+        relation::encryption::{EncryptionKey, KeySource},
+        relation::{RelationMethods, Registry},
+        test_tools::db_env::DbEnv
+    };
+
+    use super::{Table, Attribute, AttributeType, Constraint, GroupCount, BatchInsertError, CreateStyle, RenameStyle, UnknownColumn, MismatchedRow, MissingColumns, InsertError, InvalidName, TableLoadError, ForeignKey, ReferentialAction, DefaultValue, Index};
+
+    //table Create statement
+    #[test]
+    fn create_test_1() {
+        let table = Table{
+            name: String::from("table_1"),
+            attributes: vec![
+                Attribute{
+                    name: String::from("attr_1"),
+                    data_type: AttributeType::Text,
+                    raw_type: String::new(),
+                    constraint: HashSet::from(
+                        [
+                            Constraint::NotNull,
+                            Constraint::Unique
+                        ]
+                    )
+                }
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+
+        assert_eq!(*table.create(), "CREATE TABLE table_1 (attr_1 text NOT NULL UNIQUE, PRIMARY KEY(attr_1))")
+    }
+
+    #[test]
+    fn create_test_2() {
+        let table = Table{
+            name: String::from("table_1"),
+            attributes: vec![
+                Attribute{
+                    name: String::from("attr_1"),
+                    data_type: AttributeType::Text,
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                }
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+
+        assert_eq!(*table.create(), "CREATE TABLE table_1 (attr_1 text, PRIMARY KEY(attr_1))")
+    }
+
+    #[test]
+    fn create_test_3() {
+        let attr = |name: &str| Attribute{
+            name: String::from(name),
+            data_type: AttributeType::Int(11),
+            raw_type: String::new(),
+            constraint: HashSet::from([Constraint::NotNull])
+        };
+
+        let table = Table{
+            name: String::from("user_role"),
+            attributes: vec![attr("user_id"), attr("role_id"), attr("granted_by")],
+            primary_key: vec![0, 1],
+            indexes: Vec::new(),
+        };
+
+        assert_eq!(table.primary_key_columns(), vec!["user_id", "role_id"]);
+        assert_eq!(
+            *table.create(),
+            "CREATE TABLE user_role (user_id int(11) NOT NULL,role_id int(11) NOT NULL,granted_by int(11) NOT NULL, PRIMARY KEY(user_id, role_id))"
+        );
+
+        let values = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs.iter().map(|(column, value)| (column.to_string(), value.to_string())).collect()
+        };
+
+        assert_eq!(
+            table.upsert(&values(&[("user_id", "1"), ("role_id", "2"), ("granted_by", "3")])),
+            Some(QML(String::from(
+                "INSERT INTO user_role(user_id,role_id,granted_by) VALUES (1,2,3) ON DUPLICATE KEY UPDATE granted_by=VALUES(granted_by)"
+            )))
+        );
+        assert_eq!(
+            table.upsert(&values(&[("user_id", "1"), ("role_id", "2")])),
+            Some(QML(String::from("INSERT INTO user_role(user_id,role_id) VALUES (1,2) ON DUPLICATE KEY UPDATE user_id=user_id")))
+        );
+    }
+
+    fn order_table() -> Table {
+        Table{
+            name: String::from("order"),
+            attributes: vec![
+                Attribute{
+                    name: String::from("rank"),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+                Attribute{
+                    name: String::from("first name"),
+                    data_type: AttributeType::Text,
+                    raw_type: String::new(),
+                    constraint: HashSet::from([Constraint::ForeignKey{
+                        table_name: String::from("group"),
+                        attribute_name: String::from("we`ird"),
+                        on_delete: None,
+                        on_update: None,
+                    }])
+                },
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn quoted_names_test() {
+        let table = order_table();
+
+        assert_eq!(table.validate_names(), Ok(()));
+        assert_eq!(
+            *table.create(),
+            "CREATE TABLE `order` (`rank` int(11),`first name` text, FOREIGN KEY (`first name`) REFERENCES `group` (`we``ird`), PRIMARY KEY(`rank`))"
+        );
+        assert_eq!(
+            table.insert(&HashMap::from([(String::from("rank"), String::from("1")), (String::from("first name"), String::from("Jo"))])),
+            Ok(QML(String::from("INSERT INTO `order`(`rank`,`first name`) VALUES (1,'Jo')")))
+        );
+        assert_eq!(*table.select(), "SELECT * FROM `order`");
+        assert_eq!(*table.drop(), "DROP TABLE `order`");
+        assert!(matches!(SQL::from(table.create()), SQL::Create(_)));
+
+        let mut invalid = table.clone();
+        invalid.attributes[1].name = String::from("trailing ");
+
+        let err = invalid.validate_names().unwrap_err();
+
+        assert_eq!(err, InvalidName{name: String::from("trailing "), error: IdentifierError::TrailingSpace});
+        assert_eq!(err.to_string(), "\"trailing \" is not a valid name: identifier ends with a space");
+    }
+
+    #[test]
+    fn quoted_names_test_2() {
+        let table = order_table();
+        let values = literal_row(&[("rank", "1"), ("first name", "Jo")]);
+
+        assert_eq!(
+            table.update(&values, "`rank`=2"),
+            Some(QML(String::from("UPDATE `order` SET `rank`=1,`first name`='Jo' WHERE `rank`=2")))
+        );
+        assert_eq!(table.update_all(&values), Some(QML(String::from("UPDATE `order` SET `rank`=1,`first name`='Jo'"))));
+        assert_eq!(
+            table.insert_ignore(&values),
+            Some(QML(String::from("INSERT IGNORE INTO `order`(`rank`,`first name`) VALUES (1,'Jo')")))
+        );
+        assert_eq!(
+            table.upsert(&values),
+            Some(QML(String::from(
+                "INSERT INTO `order`(`rank`,`first name`) VALUES (1,'Jo') ON DUPLICATE KEY UPDATE `first name`=VALUES(`first name`)"
+            )))
+        );
+        assert_eq!(table.delete_where("`rank`=2"), Some(QML(String::from("DELETE FROM `order` WHERE `rank`=2"))));
+        assert_eq!(table.delete_all(), QML(String::from("DELETE FROM `order`")));
+        assert_eq!(
+            table.select_where(Some(&["first name"]), ""),
+            Some(QDL(String::from("SELECT `first name` FROM `order`")))
+        );
+        assert_eq!(
+            table.select_where(None, "`rank`=2"),
+            Some(QDL(String::from("SELECT * FROM `order` WHERE `rank`=2")))
+        );
+        assert_eq!(
+            table.group_count_query(&["first name", "rank"], 5),
+            Some(QDL(String::from(
+                "SELECT `first name`,`rank`,COUNT(*) FROM `order` GROUP BY `first name`,`rank` ORDER BY COUNT(*) DESC LIMIT 5"
+            )))
+        );
+    }
+
+    #[test]
+    fn quoted_names_test_3() {
+        let mut table = order_table();
+        table.attributes[0].constraint.insert(Constraint::Unique);
+
+        assert_eq!(table.create_with(CreateStyle::Inline, CreateStyle::Inline), (table.create(), Vec::new()));
+        assert_eq!(
+            table.create_with(CreateStyle::DeferredConstraints, CreateStyle::DeferredConstraints),
+            (
+                DDL(String::from("CREATE TABLE `order` (`rank` int(11),`first name` text, PRIMARY KEY(`rank`))")),
+                vec![
+                    DDL(String::from("CREATE UNIQUE INDEX order_rank_unique ON `order`(`rank`)")),
+                    DDL(String::from(
+                        "ALTER TABLE `order` ADD CONSTRAINT `order_first name_fk` FOREIGN KEY (`first name`) REFERENCES `group` (`we``ird`)"
+                    )),
+                ]
+            )
+        );
+        assert_eq!(table.truncate(), DDL(String::from("TRUNCATE TABLE `order`")));
+        assert_eq!(
+            table.truncate_with_fk_checks_disabled(),
+            DDL(String::from("SET FOREIGN_KEY_CHECKS=0; TRUNCATE TABLE `order`; SET FOREIGN_KEY_CHECKS=1"))
+        );
+        assert_eq!(table.rename("group").0, DDL(String::from("ALTER TABLE `order` RENAME TO `group`")));
+        assert_eq!(
+            table.rename_column("first name", "last name", RenameStyle::RenameColumn).unwrap().0,
+            DDL(String::from("ALTER TABLE `order` RENAME COLUMN `first name` TO `last name`"))
+        );
+        assert_eq!(
+            table.rename_column("first name", "last name", RenameStyle::Change).unwrap().0,
+            DDL(String::from("ALTER TABLE `order` CHANGE COLUMN `first name` `last name` text"))
+        );
+
+        let attr = Attribute{
+            name: String::from("key"),
+            data_type: AttributeType::Int(11),
+            raw_type: String::new(),
+            constraint: HashSet::new(),
+        };
+
+        assert_eq!(table.add_column(&attr).0, DDL(String::from("ALTER TABLE `order` ADD COLUMN `key` int(11)")));
+        assert_eq!(
+            table.drop_column("first name").unwrap().0,
+            DDL(String::from("ALTER TABLE `order` DROP COLUMN `first name`"))
+        );
+        assert_eq!(
+            table.modify_column("rank", &Attribute{name: String::from("rank"), ..attr.clone()}).unwrap().0,
+            DDL(String::from("ALTER TABLE `order` MODIFY COLUMN `rank` int(11)"))
+        );
+        assert_eq!(
+            table.modify_column("first name", &attr).unwrap().0,
+            DDL(String::from("ALTER TABLE `order` CHANGE COLUMN `first name` `key` int(11)"))
+        );
+    }
+
+    //alter table statements
+    #[test]
+    fn add_column_test() {
+        let mut table = update_table();
+        table.primary_key = vec![0];
+
+        let (statement, added) = table.add_column(&Attribute{
+            name: String::from("Email"),
+            data_type: AttributeType::VarChar(255),
+            raw_type: String::new(),
+            constraint: HashSet::from([Constraint::NotNull]),
+        });
+
+        assert_eq!(statement, DDL(String::from("ALTER TABLE table_1 ADD COLUMN Email varchar(255) NOT NULL")));
+        assert_eq!(added.attributes.len(), 6);
+        assert_eq!(added.attributes[5].name, "Email");
+        assert_eq!(added.primary_key, vec![0]);
+
+        let (statement, _) = table.add_column(&Attribute{
+            name: String::from("TeamID"),
+            data_type: AttributeType::Int(11),
+            raw_type: String::new(),
+            constraint: HashSet::from([Constraint::ForeignKey{
+                table_name: String::from("team"),
+                attribute_name: String::from("id"),
+                on_delete: None,
+                on_update: None,
+            }]),
+        });
+
+        assert_eq!(
+            statement,
+            DDL(String::from("ALTER TABLE table_1 ADD COLUMN TeamID int(11), ADD FOREIGN KEY (TeamID) REFERENCES team (id)"))
+        );
+    }
+
+    #[test]
+    fn drop_column_test() {
+        let mut table = update_table();
+        table.primary_key = vec![2];
+
+        let (statement, dropped) = table.drop_column("LastName").unwrap();
+
+        assert_eq!(statement, DDL(String::from("ALTER TABLE table_1 DROP COLUMN LastName")));
+        assert_eq!(dropped.attributes.len(), 4);
+        assert_eq!(dropped.attributes[dropped.primary_key[0]].name, "FirstName");
+
+        assert_eq!(table.drop_column("FirstName").unwrap().1.primary_key, Vec::<usize>::new());
+        assert_eq!(table.drop_column("City").unwrap().1.primary_key, vec![2]);
+        assert!(table.drop_column("Country").is_none());
+
+        //a dropped key column leaves the rest of a composite key
+        table.primary_key = vec![0, 2];
+
+        assert_eq!(table.drop_column("LastName").unwrap().1.primary_key, vec![0, 1]);
+        assert_eq!(table.drop_column("PersonID").unwrap().1.primary_key_columns(), vec!["FirstName"]);
+    }
+
+    #[test]
+    fn modify_column_test() {
+        let table = update_table();
+
+        let city = Attribute{
+            name: String::from("City"),
+            data_type: AttributeType::VarChar(64),
+            raw_type: String::new(),
+            constraint: HashSet::from([Constraint::NotNull]),
+        };
+
+        let (statement, modified) = table.modify_column("City", &city).unwrap();
+
+        assert_eq!(statement, DDL(String::from("ALTER TABLE table_1 MODIFY COLUMN City varchar(64) NOT NULL")));
+        assert_eq!(modified.attributes[4].type_definition(), "varchar(64)");
+
+        let town = Attribute{name: String::from("Town"), ..city.clone()};
+        let (statement, modified) = table.modify_column("City", &town).unwrap();
+
+        assert_eq!(statement, DDL(String::from("ALTER TABLE table_1 CHANGE COLUMN City Town varchar(64) NOT NULL")));
+        assert_eq!(modified.attributes[4].name, "Town");
+
+        //unknown column, and a rename onto an existing column
+        assert!(table.modify_column("Country", &city).is_none());
+        assert!(table.modify_column("Address", &city).is_none());
+    }
+
+    //truncate statements
+    #[test]
+    fn truncate_test() {
+        let table = update_table();
+
+        assert_eq!(table.truncate(), DDL(String::from("TRUNCATE TABLE table_1")));
+        assert_eq!(
+            table.truncate_with_fk_checks_disabled(),
+            DDL(String::from("SET FOREIGN_KEY_CHECKS=0; TRUNCATE TABLE table_1; SET FOREIGN_KEY_CHECKS=1"))
+        );
+
+        //only referenced by itself
+        let mut registry = Registry::new(vec![employee(), update_table()]);
+
+        assert_eq!(employee().truncate_in(&mut registry).unwrap(), DDL(String::from("TRUNCATE TABLE employee")));
+
+        //staff.ManagerID references employee
+        let staff = Table{name: String::from("staff"), ..employee()};
+        let mut registry = Registry::new(vec![employee(), staff, update_table()]);
+
+        assert_eq!(
+            employee().truncate_in(&mut registry).unwrap(),
+            DDL(String::from("SET FOREIGN_KEY_CHECKS=0; TRUNCATE TABLE employee; SET FOREIGN_KEY_CHECKS=1"))
+        );
+        assert!(table.truncate_in(&mut Registry::new(Vec::new())).is_err());
+    }
+
+    //rename statements
+    fn employee() -> Table {
+        let mut table = update_table();
+        table.name = String::from("employee");
+        table.primary_key = vec![0];
+        table.attributes.push(Attribute{
+            name: String::from("ManagerID"),
+            data_type: AttributeType::Int(16),
+            raw_type: String::new(),
+            constraint: HashSet::from([Constraint::ForeignKey{
+                table_name: String::from("employee"),
+                attribute_name: String::from("PersonID"),
+                on_delete: None,
+                on_update: None,
+            }]),
+        });
+        table
+    }
+
+    #[test]
+    fn rename_test() {
+        let (statement, renamed) = employee().rename("staff");
+
+        assert_eq!(statement, DDL(String::from("ALTER TABLE employee RENAME TO staff")));
+        assert_eq!(renamed.name, "staff");
+        assert_eq!(renamed.foreign_keys()[0].referenced_table, "staff");
+    }
+
+    #[test]
+    fn foreign_key_equality_test() {
+        let foreign_key = |table_name: &str, attribute_name: &str| Constraint::ForeignKey{
+            table_name: String::from(table_name),
+            attribute_name: String::from(attribute_name),
+            on_delete: None,
+            on_update: None,
+        };
+
+        assert_eq!(foreign_key("a", "x"), foreign_key("a", "x"));
+        assert_ne!(foreign_key("a", "x"), foreign_key("b", "y"));
+        assert_ne!(foreign_key("a", "x"), foreign_key("a", "y"));
+
+        let mut table = employee();
+        let manager = table.attributes.last_mut().unwrap();
+        manager.constraint.insert(foreign_key("manager", "ManagerID"));
+
+        assert_eq!(manager.constraint.len(), 2);
+
+        let mut foreign_keys: Vec<String> = table.foreign_keys().iter().map(|key| key.to_string()).collect();
+        foreign_keys.sort();
+
+        assert_eq!(
+            foreign_keys,
+            vec!["employee.ManagerID -> employee.PersonID", "employee.ManagerID -> manager.ManagerID"]
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn foreign_keys_test() {
+        let table = employee();
+
+        assert_eq!(
+            table.foreign_keys(),
+            vec![ForeignKey{
+                table: String::from("employee"),
+                column: String::from("ManagerID"),
+                referenced_table: String::from("employee"),
+                referenced_column: String::from("PersonID"),
+            }]
+        );
+        assert_eq!(table.get_foreign_keys(), Some(vec![(String::from("employee"), String::from("PersonID"))]));
+
+        assert!(update_table().foreign_keys().is_empty());
+        assert_eq!(update_table().get_foreign_keys(), None);
+    }
+
+    #[test]
+    fn rename_column_test() {
+        let table = employee();
+
+        let (statement, renamed) = table.rename_column("PersonID", "ID", RenameStyle::RenameColumn).unwrap();
+
+        assert_eq!(statement, DDL(String::from("ALTER TABLE employee RENAME COLUMN PersonID TO ID")));
+        assert_eq!(renamed.attributes[renamed.primary_key[0]].name, "ID");
+        assert_eq!(renamed.foreign_keys()[0].referenced_column, "ID");
+
+        let (statement, _) = table.rename_column("City", "Town", RenameStyle::Change).unwrap();
+
+        assert_eq!(statement, DDL(String::from("ALTER TABLE employee CHANGE COLUMN City Town varchar(255)")));
+
+        assert!(table.rename_column("Country", "Nation", RenameStyle::RenameColumn).is_none());
+        assert!(table.rename_column("City", "Address", RenameStyle::RenameColumn).is_none());
+
+        assert_eq!(RenameStyle::for_version(ServerVersion::new(5, 7, 40)), RenameStyle::Change);
+        assert_eq!(RenameStyle::for_version(ServerVersion::new(8, 0, 31)), RenameStyle::RenameColumn);
+    }
+
+    //raw type
+    #[test]
+    fn attribute_type_round_trip_test() {
+        let mut types = vec![
+            AttributeType::TinyBlob,
+            AttributeType::TinyText,
+            AttributeType::Text,
+            AttributeType::MediumText,
+            AttributeType::MediumBlob,
+            AttributeType::LongText,
+            AttributeType::LongBlob,
+            AttributeType::Bool,
+            AttributeType::Boolean,
+            AttributeType::Date,
+            AttributeType::DateTime(None),
+            AttributeType::TimeStamp(None),
+            AttributeType::Time(None),
+            AttributeType::Year,
+            AttributeType::Double(None),
+            AttributeType::Json,
+        ];
+        for size in [1, 8, 36, 255] {
+            types.extend([
+                AttributeType::Char(size),
+                AttributeType::Binary(size),
+                AttributeType::Bit(size),
+                AttributeType::TinyInt(size),
+                AttributeType::SmallInt(size),
+                AttributeType::MediumInt(size),
+                AttributeType::Int(size),
+                AttributeType::BigInt(size),
+                AttributeType::Float(size),
+                AttributeType::Decimal(size, size / 2),
+                AttributeType::Double(Some((size, size / 2))),
+            ]);
+        }
+        for precision in [0, 3, 6] {
+            types.extend([
+                AttributeType::DateTime(Some(precision)),
+                AttributeType::TimeStamp(Some(precision)),
+                AttributeType::Time(Some(precision)),
+            ]);
+        }
+        for size in [1, 255, 65_535] {
+            types.extend([AttributeType::VarChar(size), AttributeType::VarBinary(size), AttributeType::Blob(size)]);
+        }
+
+        //Enum and Set have their own tests, Uuid is written as char(36) and Unknown is whatever was read
+        for data_type in types {
+            let parsed = AttributeType::from(&data_type.to_string().to_uppercase());
+
+            assert_eq!(parsed.map(|parsed| parsed.to_string()), Some(data_type.to_string()), "{:?}", data_type);
+        }
+
+        //MySQL 8.0 leaves out integer display widths
+        assert_eq!(AttributeType::from("INT").map(|parsed| parsed.to_string()), Some(String::from("int(11)")));
+        assert_eq!(AttributeType::from("BIGINT").map(|parsed| parsed.to_string()), Some(String::from("bigint(20)")));
+        assert_eq!(AttributeType::from("DATETIME(3)").map(|parsed| parsed.to_string()), Some(String::from("datetime(3)")));
+        assert!(AttributeType::from("INTERVAL").is_none());
+    }
+
+    #[test]
+    fn integer_width_test() {
+        let cases = [
+            ("TINYINT(1)", "tinyint(1)"), ("TINYINT", "tinyint(4)"),
+            ("SMALLINT(6)", "smallint(6)"), ("SMALLINT", "smallint(6)"),
+            ("MEDIUMINT(9)", "mediumint(9)"), ("MEDIUMINT", "mediumint(9)"),
+            ("INT(11)", "int(11)"), ("INT", "int(11)"),
+            ("INTEGER(10)", "int(10)"), ("INTEGER", "int(11)"),
+            ("BIGINT(20)", "bigint(20)"), ("BIGINT", "bigint(20)"),
+            ("BIT(8)", "bit(8)"), ("BIT", "bit(1)"),
+        ];
+
+        for (raw, expected) in cases {
+            assert_eq!(AttributeType::from(raw).map(|parsed| parsed.to_string()), Some(String::from(expected)), "{}", raw);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn from_timing_test() {
+        use std::time::Instant;
+
+        let raw = [
+            "int(11)", "bigint(20) unsigned", "varchar(255)", "datetime(6)", "decimal(10,2)", "text", "json",
+            "enum('a','b')", "tinyint(1)", "double", "timestamp", "char(36)", "longblob", "year", "interval",
+        ];
+
+        let start = Instant::now();
+        let parsed = raw.iter().cycle().take(10_000).filter(|raw| AttributeType::from(raw).is_some()).count();
+
+        println!("parsed 10000 types in {:?}", start.elapsed());
+        assert_eq!(parsed, 10_000 / raw.len() * (raw.len() - 1) + 10_000 % raw.len());
+    }
+
+    #[test]
+    fn unsigned_test() {
+        let parse = |raw: &str| AttributeType::from(raw).map(|parsed| parsed.to_string());
+
+        assert_eq!(parse("BIGINT(20) UNSIGNED"), Some(String::from("bigint(20) unsigned")));
+        assert_eq!(parse("BIGINT UNSIGNED"), Some(String::from("bigint(20) unsigned")));
+        assert_eq!(parse("INT(10) UNSIGNED ZEROFILL"), Some(String::from("int(10) unsigned zerofill")));
+        assert_eq!(parse("INT(10) ZEROFILL"), Some(String::from("int(10) unsigned zerofill")));
+        assert_eq!(parse("DECIMAL(10,2) UNSIGNED"), Some(String::from("decimal(10,2) unsigned")));
+        assert_eq!(parse("VARCHAR(10) UNSIGNED"), None);
+
+        let id = Attribute{
+            name: String::from("id"),
+            data_type: AttributeType::from("BIGINT(20) UNSIGNED").unwrap(),
+            raw_type: String::from("bigint(20) unsigned"),
+            constraint: HashSet::from([Constraint::NotNull, Constraint::AutoIncrement]),
+        };
+        let table = Table{
+            name: String::from("event"),
+            attributes: vec![id.clone()],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+
+        assert_eq!(*table.create(), "CREATE TABLE event (id bigint(20) unsigned NOT NULL AUTO_INCREMENT, PRIMARY KEY(id))");
+
+        assert_eq!(id.check_value(&Value::UInt(u64::MAX)), Ok(()));
+        assert_eq!(id.check_value(&Value::Text(String::from("18446744073709551615"))), Ok(()));
+        assert!(id.check_value(&Value::Int(-1)).is_err());
+        assert!(id.check_value(&Value::Text(String::from("18446744073709551616"))).is_err());
+
+        let signed = Attribute{data_type: AttributeType::BigInt(20), ..id.clone()};
+
+        assert_eq!(signed.check_value(&Value::Int(i64::MIN)), Ok(()));
+        assert!(signed.check_value(&Value::UInt(u64::MAX)).is_err());
+
+        let unsigned_int = AttributeType::from("INT(10) UNSIGNED").unwrap();
+
+        assert!(id.data_type.can_store(&unsigned_int));
+        assert!(signed.data_type.can_store(&unsigned_int));
+        assert!(!AttributeType::Int(11).can_store(&unsigned_int));
+        assert!(!id.data_type.can_store(&AttributeType::TinyInt(4)));
+    }
+
+    #[test]
+    fn enum_test() {
+        let data_type = AttributeType::from("enum('Draft','a,b','it''s','')").unwrap();
+        let values = vec![String::from("Draft"), String::from("a,b"), String::from("it's"), String::new()];
+
+        assert!(matches!(&data_type, AttributeType::Enum{val} if *val == values));
+        assert_eq!(data_type.to_string(), "enum('Draft','a,b','it''s','')");
+        assert!(AttributeType::from(&data_type.to_string()).is_some_and(|parsed| parsed.to_string() == data_type.to_string()));
+        assert!(AttributeType::from("enum('a','b'").is_none());
+        assert!(AttributeType::from("enum('a' 'b')").is_none());
+
+        let table = Table{
+            name: String::from("post"),
+            attributes: vec![Attribute{
+                name: String::from("state"),
+                data_type,
+                raw_type: String::from("enum('Draft','a,b','it''s','')"),
+                constraint: HashSet::new(),
+            }],
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
+        };
+        let insert = |value: &str| table.insert(&HashMap::from([(String::from("state"), String::from(value))]));
+
+        assert_eq!(insert("it's"), Ok(QML(String::from("INSERT INTO post(state) VALUES ('it''s')"))));
+        assert!(insert("draft").is_ok());
+        assert!(insert("a,b").is_ok());
+        assert_eq!(
+            insert("a"),
+            Err(InsertError::TypeMismatch{
+                column: String::from("state"),
+                expected: String::from("enum('Draft','a,b','it''s','')"),
+                got: String::from("a"),
+            })
+        );
+        assert!(table.insert_typed(&HashMap::from([(String::from("state"), Value::Text(String::from("b")))])).is_err());
+
+        assert_eq!(table.attributes[0].check_value(&Value::Text(String::from("a,b"))), Ok(()));
+        assert!(table.attributes[0].check_value(&Value::Text(String::from("b"))).is_err());
+    }
+
+    #[test]
+    fn set_test() {
+        let data_type = AttributeType::from("set('Read','write','it''s')").unwrap();
+
+        assert!(matches!(&data_type, AttributeType::Set{val} if *val == vec!["Read", "write", "it's"]));
+        assert_eq!(data_type.to_string(), "set('Read','write','it''s')");
+        assert!(AttributeType::from("SET('a',b)").is_none());
+
+        let table = Table{
+            name: String::from("grant"),
+            attributes: vec![Attribute{
+                name: String::from("access"),
+                data_type,
+                raw_type: String::from("set('Read','write','it''s')"),
+                constraint: HashSet::new(),
+            }],
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
+        };
+        let insert = |value: &str| table.insert(&HashMap::from([(String::from("access"), String::from(value))]));
+
+        assert_eq!(insert("read,write"), Ok(QML(String::from("INSERT INTO `grant`(access) VALUES ('read,write')"))));
+        assert!(insert("it's").is_ok());
+        assert!(insert("").is_ok());
+        assert!(matches!(insert("read,delete"), Err(InsertError::TypeMismatch{..})));
+        assert!(matches!(insert("read,"), Err(InsertError::TypeMismatch{..})));
+
+        assert_eq!(table.attributes[0].check_value(&Value::Text(String::from("Read,it's"))), Ok(()));
+        assert!(table.attributes[0].check_value(&Value::Text(String::from("execute"))).is_err());
+    }
+
+    #[test]
+    fn json_double_temporal_test() {
+        let parse = |raw: &str| AttributeType::from(raw).map(|parsed| parsed.to_string());
+
+        assert_eq!(parse("json"), Some(String::from("json")));
+        assert_eq!(parse("double"), Some(String::from("double")));
+        assert_eq!(parse("double(10,2) unsigned"), Some(String::from("double(10,2) unsigned")));
+        assert_eq!(parse("datetime(6)"), Some(String::from("datetime(6)")));
+        assert_eq!(parse("timestamp(3)"), Some(String::from("timestamp(3)")));
+        assert_eq!(parse("time(3)"), Some(String::from("time(3)")));
+        assert_eq!(parse("time"), Some(String::from("time")));
+
+        assert!(AttributeType::Double(None).can_store(&AttributeType::Float(10)));
+        assert!(!AttributeType::Float(10).can_store(&AttributeType::Double(None)));
+
+        let table = Table{
+            name: String::from("event"),
+            attributes: vec![
+                Attribute{
+                    name: String::from("payload"),
+                    data_type: AttributeType::Json,
+                    raw_type: String::from("json"),
+                    constraint: HashSet::new(),
+                },
+                Attribute{
+                    name: String::from("score"),
+                    data_type: AttributeType::Double(None),
+                    raw_type: String::from("double"),
+                    constraint: HashSet::new(),
+                },
+            ],
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
+        };
+        let values = HashMap::from([
+            (String::from("payload"), String::from(r#"{"name": "it's"}"#)),
+            (String::from("score"), String::from("2.5")),
+        ]);
+
+        assert_eq!(table.insert(&values), Ok(QML(String::from(r#"INSERT INTO event(payload,score) VALUES ('{"name": "it''s"}',2.5)"#))));
+        assert!(matches!(
+            table.insert(&HashMap::from([(String::from("score"), String::from("high"))])),
+            Err(InsertError::TypeMismatch{..})
+        ));
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn json_double_temporal_load_test() {
+        let _env = DbEnv::new(
+            vec![SQL::new(
+                "CREATE TABLE modern_types (doc json, ratio double, amount double(10,2), created datetime(6), \
+                updated timestamp(3) NULL, elapsed time(3))"
+            ).unwrap()],
+            vec![SQL::new("DROP TABLE IF EXISTS modern_types").unwrap()]
+        );
+
+        let table = Table::from_db("modern_types").unwrap();
+        let types: Vec<String> = table.attributes.iter().map(|attr| attr.data_type.to_string()).collect();
+
+        assert_eq!(types, vec!["json", "double", "double(10,2)", "datetime(6)", "timestamp(3)", "time(3)"]);
+    }
+
+    #[test]
+    fn equality_test() {
+        assert_eq!(AttributeType::VarChar(255), AttributeType::VarChar(255));
+        assert_ne!(AttributeType::VarChar(255), AttributeType::VarChar(64));
+        assert_ne!(AttributeType::Uuid, AttributeType::Char(36));
+        assert_eq!(AttributeType::from("enum('a','b')"), Some(AttributeType::Enum{val: vec![String::from("a"), String::from("b")]}));
+
+        let attr = |name: &str, raw_type: &str, constraint: Vec<Constraint>| Attribute{
+            name: String::from(name),
+            data_type: AttributeType::Int(11),
+            raw_type: String::from(raw_type),
+            constraint: constraint.into_iter().collect(),
+        };
+
+        //raw_type is left out
+        assert_eq!(
+            attr("id", "int", vec![Constraint::NotNull, Constraint::Unique]),
+            attr("id", "int(11)", vec![Constraint::Unique, Constraint::NotNull])
+        );
+        assert_ne!(attr("id", "", vec![Constraint::NotNull]), attr("id", "", vec![]));
+        assert_ne!(attr("id", "", vec![]), attr("user_id", "", vec![]));
+
+        let set: HashSet<Attribute> = HashSet::from([attr("id", "int", vec![]), attr("id", "int(11)", vec![]), attr("name", "", vec![])]);
+        assert_eq!(set.len(), 2);
+
+        let table = Table{
+            name: String::from("user"),
+            attributes: vec![attr("id", "", vec![Constraint::NotNull]), attr("name", "", vec![])],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+        let mut reordered = table.clone();
+        reordered.attributes.reverse();
+        reordered.primary_key = vec![1];
+
+        assert_eq!(table, table.clone());
+        assert_ne!(table, reordered);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip_test() {
+        let attr = |name: &str, data_type: AttributeType, constraint: Vec<Constraint>| Attribute{
+            name: String::from(name),
+            data_type,
+            raw_type: String::new(),
+            constraint: constraint.into_iter().collect(),
+        };
+
+        let table = Table{
+            name: String::from("post"),
+            attributes: vec![
+                attr(
+                    "id",
+                    AttributeType::Unsigned{val: Box::new(AttributeType::BigInt(20)), zerofill: false},
+                    vec![Constraint::NotNull, Constraint::AutoIncrement]
+                ),
+                attr("author", AttributeType::Int(11), vec![
+                    Constraint::NotNull,
+                    Constraint::ForeignKey{
+                        table_name: String::from("user"),
+                        attribute_name: String::from("id"),
+                        on_delete: Some(ReferentialAction::Cascade),
+                        on_update: None,
+                    },
+                ]),
+                attr("state", AttributeType::Enum{val: vec![String::from("draft"), String::from("it's")]}, vec![
+                    Constraint::Default(DefaultValue::Literal(String::from("draft"))),
+                ]),
+                attr("created", AttributeType::DateTime(Some(6)), vec![]),
+            ],
+            primary_key: vec![0],
+            indexes: vec![Index{
+                name: String::from("author_state"),
+                columns: vec![String::from("author"), String::from("state")],
+                unique: true,
+            }],
+        };
+
+        let json = serde_json::to_string(&table).unwrap();
+
+        assert_eq!(serde_json::from_str::<Table>(&json).unwrap(), table);
+        assert!(json.contains(r#""data_type":{"enum":{"val":["draft","it's"]}}"#), "{}", json);
+        assert!(
+            json.contains(r#"["not_null",{"foreign_key":{"table_name":"user","attribute_name":"id","on_delete":"cascade","on_update":null}}]"#),
+            "{}",
+            json
+        );
+
+        //constraints are written in the order of the column definition
+        for _ in 0..8 {
+            let id = attr("id", AttributeType::Int(11), vec![Constraint::AutoIncrement, Constraint::Unique, Constraint::NotNull]);
+
+            assert!(serde_json::to_string(&id).unwrap().contains(r#""constraint":["not_null","auto_increment","unique"]"#));
+        }
+
+        //the key of an encrypted column is never written
+        let key = EncryptionKey::load(&KeySource::Key(String::from("k"))).unwrap();
+        let encrypted = attr("ssn", AttributeType::VarBinary(255), vec![Constraint::Encrypted(key)]);
+        assert!(serde_json::to_string(&encrypted).is_err());
+
+        //raw_type and indexes may be left out
+        let attr: Attribute = serde_json::from_str(r#"{"name": "code", "data_type": {"var_char": 16}, "constraint": ["unique"]}"#).unwrap();
+        assert_eq!(attr, Attribute{
+            name: String::from("code"),
+            data_type: AttributeType::VarChar(16),
+            raw_type: String::new(),
+            constraint: HashSet::from([Constraint::Unique]),
+        });
+    }
+
+    #[test]
+    fn unknown_type_test_1() {
+        assert!(AttributeType::from("POINT SRID 4326").is_none());
+
+        let attr = Attribute{
+            name: String::from("location"),
+            data_type: AttributeType::Unknown(String::from("POINT SRID 4326")),
+            raw_type: String::from("point srid 4326"),
+            constraint: HashSet::from([Constraint::NotNull])
+        };
+
+        assert_eq!(attr.column_definition(), "location point srid 4326 NOT NULL");
+        assert_eq!(attr.schema_fmt(), "location point srid 4326");
+
+        let attr = Attribute{
+            raw_type: String::from("int(11)"),
+            data_type: AttributeType::Int(11),
+            ..attr
+        };
+
+        assert_eq!(attr.type_definition(), "int(11)");
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn unknown_type_test_2() {
+        let _env = DbEnv::new(
+            vec![SQL::new("CREATE TABLE unknown_type_test (id INT, location POINT, area GEOMETRY NOT NULL)").unwrap()],
+            vec![SQL::new("DROP TABLE IF EXISTS unknown_type_test").unwrap()]
+        );
+
+        let (table, report) = Table::from_db_report("unknown_type_test").unwrap();
+        let location = &table.attributes[1];
+
+        assert_eq!(table.attributes.len(), 3);
+        assert_eq!(location.data_type.to_string(), "point");
+        assert_eq!(location.raw_type, "point");
+        assert_eq!(table.attributes[2].column_definition(), "area geometry NOT NULL");
+        assert!(table.attributes[2].is_required());
+        assert_eq!(report.warnings.len(), 2);
+
+        let db = DataBase::from_env().unwrap();
+        db.execute(&SQL::Drop(table.drop()), |_| ()).unwrap();
+        db.execute(&SQL::Create(table.create()), |_| ()).unwrap();
+
+        let table = Table::from_db("unknown_type_test").unwrap();
+        assert_eq!(table.attributes[1].raw_type, "point");
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn create_round_trip_test() {
+        let attr = |name: &str, data_type: AttributeType, constraint: Vec<Constraint>| Attribute{
+            name: String::from(name),
+            data_type,
+            raw_type: String::new(),
+            constraint: constraint.into_iter().collect(),
+        };
+
+        let parent = Table{
+            name: String::from("round_trip_parent"),
+            attributes: vec![attr("id", AttributeType::Int(11), vec![Constraint::NotNull])],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+        let child = Table{
+            name: String::from("round_trip_child"),
+            attributes: vec![
+                attr("id", AttributeType::Int(11), vec![Constraint::NotNull, Constraint::AutoIncrement]),
+                attr("code", AttributeType::VarChar(16), vec![Constraint::NotNull, Constraint::Unique]),
+                attr("parent_id", AttributeType::Int(11), vec![Constraint::ForeignKey{
+                    table_name: String::from("round_trip_parent"),
+                    attribute_name: String::from("id"),
+                    on_delete: Some(ReferentialAction::Cascade),
+                    on_update: None,
+                }]),
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+
+        let _env = DbEnv::new(
+            vec![SQL::Create(parent.create()), SQL::Create(child.create())],
+            vec![
+                SQL::new("DROP TABLE IF EXISTS round_trip_child").unwrap(),
+                SQL::new("DROP TABLE IF EXISTS round_trip_parent").unwrap(),
+            ]
+        );
+
+        let loaded = Table::from_db("round_trip_child").unwrap();
+
+        assert!(loaded.structurally_eq(&child));
+        assert_eq!(loaded.attributes[2].fk_clause(), child.attributes[2].fk_clause());
+    }
+
+    #[test]
+    fn create_with_test() {
+        let table = Table{
+            name: String::from("employee"),
+            attributes: vec![
+                Attribute{
+                    name: String::from("id"),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+                Attribute{
+                    name: String::from("email"),
+                    data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
+                    constraint: HashSet::from([Constraint::Unique])
+                },
+                Attribute{
+                    name: String::from("manager"),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
+                    constraint: HashSet::from([
+                        Constraint::ForeignKey{
+                            table_name: String::from("employee"),
+                            attribute_name: String::from("id"),
+                            on_delete: None,
+                            on_update: None
+                        }
+                    ])
+                },
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+
+        let inline = table.create_with(CreateStyle::Inline, CreateStyle::Inline);
+
+        assert_eq!(
+            inline,
+            (
+                DDL(String::from("CREATE TABLE employee (id int(11),email varchar(255) UNIQUE,manager int(11), FOREIGN KEY (manager) REFERENCES employee (id), PRIMARY KEY(id))")),
+                vec![]
+            )
+        );
+        assert_eq!(inline.0, table.create());
 
-    //Date time
-    Date,
-    DateTime,
-    TimeStamp,
-    Time,
-    Year
-}
+        assert_eq!(
+            table.create_with(CreateStyle::DeferredConstraints, CreateStyle::DeferredConstraints),
+            (
+                DDL(String::from("CREATE TABLE employee (id int(11),email varchar(255),manager int(11), PRIMARY KEY(id))")),
+                vec![
+                    DDL(String::from("CREATE UNIQUE INDEX employee_email_unique ON employee(email)")),
+                    DDL(String::from("ALTER TABLE employee ADD CONSTRAINT employee_manager_fk FOREIGN KEY (manager) REFERENCES employee (id)")),
+                ]
+            )
+        );
 
-macro_rules! regex_check {
-    ($regex_expr : literal, $raw_str: expr, $output_variant : ident, $parse_type_1: ty, $parse_type_2: ty) => {
-        {
-            let check = Regex::new($regex_expr).unwrap();
+        assert_eq!(
+            table.create_with(CreateStyle::DeferredConstraints, CreateStyle::Inline).0,
+            DDL(String::from("CREATE TABLE employee (id int(11),email varchar(255) UNIQUE,manager int(11), PRIMARY KEY(id))"))
+        );
+    }
 
-            if let Some(tmp) = check.find($raw_str) {
-                if tmp.start() == 0 {
-                    let size = check.captures($raw_str).unwrap();
+    //encrypted columns
+    #[test]
+    fn encrypted_test_1() {
+        let key = EncryptionKey::load(&KeySource::Key(String::from("encrypted_test_1"))).unwrap();
 
-                    let tmp_1 = size.get(1).unwrap().as_str();
-                    let tmp_2 = size.get(2).unwrap().as_str();
+        let table = Table{
+            name: String::from("patient"),
+            attributes: vec![
+                Attribute{
+                    name: String::from("id"),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+                Attribute{
+                    name: String::from("ssn"),
+                    data_type: AttributeType::VarBinary(255),
+                    raw_type: String::new(),
+                    constraint: HashSet::from([Constraint::Encrypted(key)])
+                },
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
 
-                    return Some(AttributeType::$output_variant(tmp_1.parse::<$parse_type_1>().unwrap(), tmp_2.parse::<$parse_type_2>().unwrap()))
-                }
-            }
-        }
-    };
-    ($regex_expr : literal, $raw_str: expr, $output_variant : ident, $parse_type: ty) => {
-        {
-            let check = Regex::new($regex_expr).unwrap();
+        assert_eq!(
+            table.select(),
+            QDL(String::from("SELECT id,AES_DECRYPT(ssn, 'encrypted_test_1') AS ssn FROM patient"))
+        );
+        assert_eq!(
+            table.create(),
+            DDL(String::from("CREATE TABLE patient (id int(11),ssn varbinary(255), PRIMARY KEY(id))"))
+        );
 
-            if let Some(tmp) = check.find($raw_str) {
-                if tmp.start() == 0 {
-                    let size = check.captures($raw_str).unwrap();
+        let values = HashMap::from([
+            (String::from("id"), Value::Int(1)),
+            (String::from("ssn"), Value::Text(String::from("123"))),
+        ]);
+        let insert = table.insert_values(&values, MAX_LITERAL_SIZE).unwrap().unwrap();
 
-                    let tmp = size.get(1).unwrap().as_str();
+        assert_eq!(
+            insert,
+            QML(String::from("INSERT INTO patient(id,ssn) VALUES (1,AES_ENCRYPT(x'313233', 'encrypted_test_1'))"))
+        );
+        assert_eq!(
+            redact(&insert),
+            "INSERT INTO patient(id,ssn) VALUES (1,AES_ENCRYPT(x'313233', '***'))"
+        );
+        assert_eq!(
+            table.insert_batches(&[values], 1024).unwrap(),
+            vec![insert]
+        );
 
-                    return Some(AttributeType::$output_variant(tmp.parse::<$parse_type>().unwrap()))
-                }
-            }
-        }
-    };
-    ($regex_expr : literal, $raw_str: expr, $output_variant : ident) => {
-        {
-            let check = Regex::new($regex_expr).unwrap();
+        assert_eq!(format!("{:?}", table.attributes[1].encryption_key()), "Some(EncryptionKey(***))");
+    }
 
-            if let Some(tmp) = check.find($raw_str) {
-                if tmp.start() == 0 {
-                    return Some(AttributeType::$output_variant)
-                }
-            }
-        }
-    };
-}
+    //attribute rendering
+    #[test]
+    fn column_definition_test_1() {
+        let attr = Attribute{
+            name: String::from("patient_id"),
+            data_type: AttributeType::Int(11),
+            raw_type: String::new(),
+            constraint: HashSet::from(
+                [
+                    Constraint::NotNull,
+                    Constraint::ForeignKey{
+                        table_name: String::from("patients"),
+                        attribute_name: String::from("id"),
+                        on_delete: None,
+                        on_update: None
+                    }
+                ]
+            )
+        };
 
-impl AttributeType {
-    /// Returns the `AttributeType` variant corresponding to the given string.
-    fn from(raw_str: &str) -> Option<AttributeType> {
-        regex_check!(r"CHAR\((\d+)\)", raw_str, Char, u8);
-        regex_check!(r"VARCHAR\((\d+)\)", raw_str, VarChar, u16);
-        regex_check!(r"BINARY\((\d+)\)", raw_str, Binary, u8);
-        regex_check!(r"VARBINARY\((\d+)\)", raw_str, VarBinary, u16);
-        regex_check!(r"TINYBLOB", raw_str, TinyBlob);
-        regex_check!(r"TINYTEXT", raw_str, TinyText);
-        regex_check!(r"TEXT", raw_str, Text);
-        regex_check!(r"BLOB\((\d+)\)", raw_str, Blob, u16);
-        regex_check!(r"MEDIUMTEXT", raw_str, MediumText);
-        regex_check!(r"LONGTEXT", raw_str, LongText);
-        regex_check!(r"LONGBLOB", raw_str, LongBlob);
-        //Enum{val: Vec<String>},
-        //Set{val: Vec<AttributeType>},
+        assert_eq!(attr.column_definition(), "patient_id int(11) NOT NULL");
+        assert_eq!(attr.fk_clause(), Some(String::from("FOREIGN KEY (patient_id) REFERENCES patients (id)")));
+        assert_eq!(attr.to_string(), "patient_id int(11) NOT NULL, FOREIGN KEY (patient_id) REFERENCES patients (id)");
+    }
 
-        regex_check!(r"BIT\((\d+)\)", raw_str, Bit, u8);
-        regex_check!(r"TINYINT\((\d+)\)", raw_str, TinyInt, u8);
-        regex_check!(r"BOOL", raw_str, Bool);
-        regex_check!(r"BOOLEAN", raw_str, Boolean);
-        regex_check!(r"SMALLINT\((\d+)\)", raw_str, SmallInt, u8);
-        regex_check!(r"MEDIUMINT\((\d+)\)", raw_str, MediumInt, u8);
-        regex_check!(r"INT\((\d+)\)", raw_str, Int, u8);
-        regex_check!(r"INTEGER\((\d+)\)", raw_str, Int, u8);
-        regex_check!(r"BigInt\((\d+)\)", raw_str, BigInt, u8);
-        regex_check!(r"FLOAT\((\d+)\)", raw_str, Float, u8);
-        regex_check!(r"DECIMAL\((\d+),(\d+)\)", raw_str, Decimal, u8, u8);
+    #[test]
+    fn referential_action_test() {
+        let mut attr = Attribute{
+            name: String::from("post_id"),
+            data_type: AttributeType::Int(11),
+            raw_type: String::new(),
+            constraint: HashSet::from([Constraint::ForeignKey{
+                table_name: String::from("post"),
+                attribute_name: String::from("id"),
+                on_delete: Some(ReferentialAction::Cascade),
+                on_update: Some(ReferentialAction::SetNull),
+            }])
+        };
 
-        regex_check!(r"DATE", raw_str, Date);
-        regex_check!(r"DATETIME", raw_str, DateTime);
-        regex_check!(r"TIMESTAMP", raw_str, TimeStamp);
-        regex_check!(r"TIME", raw_str, Time);
-        regex_check!(r"YEAR", raw_str, Year);
+        assert_eq!(
+            attr.to_string(),
+            "post_id int(11), FOREIGN KEY (post_id) REFERENCES post (id) ON DELETE CASCADE ON UPDATE SET NULL"
+        );
 
-        return None
+        //the actions are part of the constraint
+        attr.constraint.insert(Constraint::ForeignKey{
+            table_name: String::from("post"),
+            attribute_name: String::from("id"),
+            on_delete: None,
+            on_update: None,
+        });
+        assert_eq!(attr.constraint.len(), 2);
+
+        assert_eq!(ReferentialAction::parse("set  null"), Some(ReferentialAction::SetNull));
+        assert_eq!(ReferentialAction::parse("NO ACTION"), Some(ReferentialAction::NoAction));
+        assert_eq!(ReferentialAction::parse("DROP"), None);
+        assert!(ReferentialAction::Restrict.is_default());
+        assert!(!ReferentialAction::SetDefault.is_default());
     }
-}
 
-impl fmt::Display for AttributeType{
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            //string data types
-            AttributeType::Char(val) => write!(f, "char({})", val),
-            AttributeType::VarChar(val) => write!(f, "varchar({})", val),
-            AttributeType::Binary(val) => write!(f, "binary({})", val),
-            AttributeType::VarBinary(val) => write!(f, "varbinary({})", val),
-            AttributeType::TinyBlob => write!(f, "tinyblob"),
-            AttributeType::TinyText => write!(f, "tinytext"),
-            AttributeType::Text => write!(f, "text"),
-            AttributeType::Blob(val) => write!(f, "blob({})", val),
-            AttributeType::MediumText => write!(f, "mediumtext"),
-            AttributeType::MediumBlob => write!(f, "mediumblob"),
-            AttributeType::LongText => write!(f, "longtext"),
-            AttributeType::LongBlob => write!(f, "longblob"),
-            AttributeType::Enum{..} => todo!(),
-            AttributeType::Set{..} => todo!(),
+    #[test]
+    fn index_test() {
+        let index = |name: &str, columns: &[&str], unique: bool| Index{
+            name: String::from(name),
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+            unique,
+        };
+        let attr = |name: &str, constraint: Vec<Constraint>| Attribute{
+            name: String::from(name),
+            data_type: AttributeType::Int(11),
+            raw_type: String::new(),
+            constraint: constraint.into_iter().collect(),
+        };
 
-            //numeric data types
-            AttributeType::Bit(val) => write!(f, "bit({})", val),
-            AttributeType::TinyInt(val) => write!(f, "tinyint({})", val),
-            AttributeType::Bool => write!(f, "bool"),
-            AttributeType::Boolean => write!(f, "boolean"),
-            AttributeType::SmallInt(val) => write!(f, "smallint({})", val),
-            AttributeType::MediumInt(val) => write!(f, "mediumint({})", val),
-            AttributeType::Int(val) => write!(f, "int({})", val),
-            AttributeType::BigInt(val) => write!(f, "bigint({})", val),
-            AttributeType::Float(val) => write!(f, "float({})", val),
-            AttributeType::Decimal(val_1 , val_2) => write!(f, "decmimal({},{})", val_1, val_2),
+        let table = Table{
+            name: String::from("member"),
+            attributes: vec![attr("id", vec![]), attr("email", vec![]), attr("tenant_id", vec![]), attr("badge", vec![Constraint::Unique])],
+            primary_key: vec![0],
+            indexes: vec![index("email_tenant", &["email", "tenant_id"], true), index("tenant", &["tenant_id"], false)],
+        };
 
-            //Date time
-            AttributeType::Date => write!(f, "date"),
-            AttributeType::DateTime => write!(f, "datetime"),
-            AttributeType::TimeStamp => write!(f, "timeStamp"),
-            AttributeType::Time => write!(f, "time"),
-            AttributeType::Year => write!(f, "year"),
+        assert_eq!(
+            table.create(),
+            DDL(String::from(
+                "CREATE TABLE member (id int(11),email int(11),tenant_id int(11),badge int(11) UNIQUE, \
+                UNIQUE KEY email_tenant(email, tenant_id), KEY tenant(tenant_id), PRIMARY KEY(id))"
+            ))
+        );
+        assert_eq!(table.create_with(CreateStyle::Inline, CreateStyle::Inline).0, table.create());
+        assert_eq!(
+            table.create_indexes(),
+            vec![
+                DDL(String::from("CREATE UNIQUE INDEX email_tenant ON member(email, tenant_id)")),
+                DDL(String::from("CREATE INDEX tenant ON member(tenant_id)")),
+            ]
+        );
+        assert_eq!(table.unique_constraints(), vec![vec!["badge"], vec!["email", "tenant_id"]]);
+
+        let (_, renamed) = table.rename_column("tenant_id", "org_id", RenameStyle::RenameColumn).unwrap();
+        assert_eq!(renamed.indexes[0].columns, vec!["email", "org_id"]);
+
+        let (_, dropped) = table.drop_column("tenant_id").unwrap();
+        assert_eq!(dropped.indexes, vec![index("email_tenant", &["email"], true)]);
+    }
+
+    #[test]
+    fn constraint_order_test() {
+        //each set gets its own hasher, so the sets iterate in different orders
+        let attr = || Attribute{
+            name: String::from("balance"),
+            data_type: AttributeType::Int(11),
+            raw_type: String::new(),
+            constraint: HashSet::from([
+                Constraint::Check(String::from("balance < 1000")),
+                Constraint::Unique,
+                Constraint::AutoIncrement,
+                Constraint::Default(DefaultValue::Literal(String::from("0"))),
+                Constraint::NotNull,
+                Constraint::Check(String::from("balance >= 0")),
+                Constraint::ForeignKey{
+                    table_name: String::from("ledger"),
+                    attribute_name: String::from("id"),
+                    on_delete: None,
+                    on_update: None,
+                },
+            ]),
+        };
+
+        let expected = "balance int(11) NOT NULL DEFAULT 0 AUTO_INCREMENT UNIQUE CHECK (balance < 1000) CHECK (balance >= 0), \
+            FOREIGN KEY (balance) REFERENCES ledger (id)";
+
+        for _ in 0..32 {
+            assert_eq!(attr().to_string(), expected);
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    #![allow(unused_imports)]
-    use std::collections::{HashSet, HashMap};
+    #[test]
+    fn check_test() {
+        let check = |expression: &str| Constraint::Check(String::from(expression));
+        let mut attr = Attribute{
+            name: String::from("price"),
+            data_type: AttributeType::Int(11),
+            raw_type: String::new(),
+            constraint: HashSet::from([check("price > 0")]),
+        };
 
-    use crate::{backend::{sql::QML, relation::RelationMethods}, ui::menu::Tab};
+        assert_eq!(attr.column_definition(), "price int(11) CHECK (price > 0)");
 
-    use super::{Table, Attribute, AttributeType, Constraint};
+        attr.constraint.insert(check("price < 1000"));
+        attr.constraint.insert(check("price > 0"));
+
+        assert_eq!(attr.constraint, HashSet::from([check("price > 0"), check("price < 1000")]));
+    }
 
-    //table Create statement
     #[test]
-    fn create_test_1() {
+    fn column_definition_test_2() {
+        let attr = Attribute{
+            name: String::from("attr_1"),
+            data_type: AttributeType::Text,
+            raw_type: String::new(),
+            constraint: HashSet::new()
+        };
+
+        assert_eq!(attr.column_definition(), "attr_1 text");
+        assert_eq!(attr.fk_clause(), None);
+        assert_eq!(attr.to_string(), "attr_1 text");
+    }
+
+    //do more tests
+
+    //table insert statement
+    #[test]
+    fn insert_test_1() {
         let table = Table{
             name: String::from("table_1"),
             attributes: vec![
                 Attribute{
-                    name: String::from("attr_1"),
-                    data_type: AttributeType::Text,
-                    constraint: HashSet::from(
-                        [
-                            Constraint::NotNull,
-                            Constraint::Unique
-                        ]
-                    )
-                }
+                    name: String::from("PersonID"),
+                    data_type: AttributeType::Int(16),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+                Attribute{
+                    name: String::from("LastName"),
+                    data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+                Attribute{
+                    name: String::from("FirstName"),
+                    data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+                Attribute{
+                    name: String::from("Address"),
+                    data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+                Attribute{
+                    name: String::from("City"),
+                    data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
             ],
-            primary_key: Some(0),
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
         };
 
-        assert_eq!(*table.create(), "CREATE TABLE table_1 (attr_1 text Unique Not Null, PRIMARY KEY(attr_1))")
+        let mut values = HashMap::new();
+
+        values.insert(String::from("PersonID"), String::from("23"));
+        values.insert(String::from("LastName"), String::from("Doe"));
+        values.insert(String::from("FirstName"), String::from("John"));
+        values.insert(String::from("Address"), String::from("1st Street"));
+        values.insert(String::from("City"), String::from("Night City"));
+
+        let actual = table.insert(&values);
+
+        assert_eq!(actual, Ok(QML(String::from("INSERT INTO table_1(PersonID,LastName,FirstName,Address,City) VALUES (23,'Doe','John','1st Street','Night City')"))));
+        assert_eq!(
+            table.insert_ignore(&values),
+            Some(QML(String::from("INSERT IGNORE INTO table_1(PersonID,LastName,FirstName,Address,City) VALUES (23,'Doe','John','1st Street','Night City')")))
+        );
     }
 
     #[test]
-    fn create_test_2() {
+    fn insert_quoting_test() {
+        let attr = |name: &str, data_type: AttributeType| Attribute{
+            name: String::from(name),
+            data_type,
+            raw_type: String::new(),
+            constraint: HashSet::new()
+        };
+
         let table = Table{
-            name: String::from("table_1"),
+            name: String::from("person"),
             attributes: vec![
-                Attribute{
-                    name: String::from("attr_1"),
-                    data_type: AttributeType::Text,
-                    constraint: HashSet::new()
-                }
+                attr("id", AttributeType::BigInt(20)),
+                attr("last_name", AttributeType::VarChar(255)),
+                attr("born", AttributeType::Date),
+                attr("balance", AttributeType::Decimal(10, 2)),
+                attr("note", AttributeType::Text),
             ],
-            primary_key: Some(0),
+            primary_key: vec![0],
+            indexes: Vec::new(),
         };
 
-        assert_eq!(*table.create(), "CREATE TABLE table_1 (attr_1 text, PRIMARY KEY(attr_1))")
+        let values = HashMap::from([
+            (String::from("id"), String::from("7")),
+            (String::from("last_name"), String::from("O'Brien")),
+            (String::from("born"), String::from("1990-01-31")),
+            (String::from("balance"), String::from("-12.50")),
+            (String::from("note"), String::from("back\\slash")),
+        ]);
+
+        assert_eq!(
+            table.insert(&values),
+            Ok(QML(String::from(
+                "INSERT INTO person(id,last_name,born,balance,note) VALUES (7,'O''Brien','1990-01-31',-12.50,'back\\\\slash')"
+            )))
+        );
     }
 
-    //do more tests
+    #[test]
+    fn value_escaping_test() {
+        let table = update_table();
+
+        let values = HashMap::from([
+            (String::from("LastName"), String::from("'); DROP TABLE users; --")),
+            (String::from("City"), String::from("back\\slash\nnew line")),
+        ]);
+
+        assert_eq!(
+            table.insert(&values),
+            Ok(QML(String::from(
+                "INSERT INTO table_1(LastName,City) VALUES ('''); DROP TABLE users; --','back\\\\slash\\nnew line')"
+            )))
+        );
+
+        let values = HashMap::from([(String::from("City"), String::from("it's\r\n"))]);
+
+        assert_eq!(
+            table.update(&values, "PersonID = 1"),
+            Some(QML(String::from("UPDATE table_1 SET City='it''s\\r\\n' WHERE PersonID = 1")))
+        );
+    }
 
-    //table insert statement
     #[test]
-    fn insert_test_1() {
+    fn value_injection_test_1() {
+        let mut table = update_table();
+        table.primary_key = vec![0];
+
+        let values = HashMap::from([
+            (String::from("PersonID"), String::from("1; DROP TABLE users")),
+            (String::from("City"), String::from("x")),
+        ]);
+
+        assert_eq!(table.update(&values, "PersonID=1"), None);
+        assert_eq!(table.update_all(&values), None);
+        assert_eq!(table.upsert(&values), None);
+        assert_eq!(table.insert_ignore(&values), None);
+        assert_eq!(
+            table.insert(&values),
+            Err(InsertError::TypeMismatch{
+                column: String::from("PersonID"),
+                expected: String::from("int(16)"),
+                got: String::from("1; DROP TABLE users"),
+            })
+        );
+
+        //numbers, NULL and DEFAULT are still written as they are
+        let values = HashMap::from([(String::from("PersonID"), String::from("NULL")), (String::from("City"), String::from("x"))]);
+
+        assert_eq!(table.update(&values, "City='y'"), Some(QML(String::from("UPDATE table_1 SET PersonID=NULL,City='x' WHERE City='y'"))));
+        assert_eq!(
+            table.insert_ignore(&literal_row(&[("PersonID", " 1 "), ("City", "x")])),
+            Some(QML(String::from("INSERT IGNORE INTO table_1(PersonID,City) VALUES ( 1 ,'x')")))
+        );
+    }
+
+    #[test]
+    fn value_injection_test_2() {
+        let mut table = update_table();
+        table.attributes[4].data_type = AttributeType::Unknown(String::from("POINT"));
+
+        //values of columns of an unknown type are escaped as strings
+        let values = literal_row(&[("PersonID", "1"), ("City", "1); DROP TABLE users; --")]);
+
+        assert_eq!(
+            table.insert_ignore(&values),
+            Some(QML(String::from("INSERT IGNORE INTO table_1(PersonID,City) VALUES (1,'1); DROP TABLE users; --')")))
+        );
+        assert_eq!(
+            table.upsert(&values),
+            Some(QML(String::from(
+                "INSERT INTO table_1(PersonID,City) VALUES (1,'1); DROP TABLE users; --') \
+                ON DUPLICATE KEY UPDATE PersonID=VALUES(PersonID),City=VALUES(City)"
+            )))
+        );
+        assert_eq!(
+            table.update(&literal_row(&[("City", "x' OR '1'='1")]), "PersonID=1"),
+            Some(QML(String::from("UPDATE table_1 SET City='x'' OR ''1''=''1' WHERE PersonID=1")))
+        );
+    }
+
+    #[test]
+    fn validate_row_test() {
+        let attr = |name: &str, constraint: Vec<Constraint>| Attribute{
+            name: String::from(name),
+            data_type: AttributeType::Int(11),
+            raw_type: String::new(),
+            constraint: constraint.into_iter().collect()
+        };
+
+        let table = Table{
+            name: String::from("account"),
+            attributes: vec![
+                attr("id", vec![Constraint::NotNull, Constraint::AutoIncrement]),
+                attr("owner_id", vec![Constraint::NotNull]),
+                attr("balance", vec![Constraint::NotNull, Constraint::Default(DefaultValue::Literal(String::from("0")))]),
+                attr("branch_id", vec![Constraint::NotNull]),
+                attr("note", vec![]),
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+
+        let err = table.validate_row(&HashMap::new()).unwrap_err();
+
+        assert_eq!(err, MissingColumns{table: String::from("account"), columns: vec![String::from("owner_id"), String::from("branch_id")]});
+        assert_eq!(err.to_string(), "account requires a value for owner_id, branch_id");
+
+        let values = HashMap::from([(String::from("owner_id"), String::from("1")), (String::from("branch_id"), String::from("2"))]);
+
+        assert_eq!(table.validate_row(&values), Ok(()));
+    }
+
+    #[test]
+    fn insert_test_2(){
         let table = Table{
             name: String::from("table_1"),
             attributes: vec![
                 Attribute{
                     name: String::from("PersonID"),
                     data_type: AttributeType::Int(16),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
                 Attribute{
                     name: String::from("LastName"),
                     data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
                 Attribute{
                     name: String::from("FirstName"),
                     data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+                Attribute{
+                    name: String::from("Address"),
+                    data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+                Attribute{
+                    name: String::from("City"),
+                    data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
+            ],
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
+        };
+
+        let mut values = HashMap::new();
+
+        values.insert(String::from("PersonID"), String::from("23"));
+        values.insert(String::from("LastName"), String::from("Doe"));
+        values.insert(String::from("FirstName"), String::from("John"));
+
+        let actual = table.insert(&values);
+
+        assert_eq!(actual, Ok(QML(String::from("INSERT INTO table_1(PersonID,LastName,FirstName) VALUES (23,'Doe','John')"))));
+        assert_eq!(table.insert_ignore(&values), Some(QML(String::from("INSERT IGNORE INTO table_1(PersonID,LastName,FirstName) VALUES (23,'Doe','John')"))));
+    }
+
+    #[test]
+    fn insert_values_test_1() {
+        let table = Table{
+            name: String::from("images"),
+            attributes: vec![
+                Attribute{
+                    name: String::from("id"),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+                Attribute{
+                    name: String::from("name"),
+                    data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+                Attribute{
+                    name: String::from("data"),
+                    data_type: AttributeType::Blob(65535),
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                },
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        };
+
+        let mut values = HashMap::new();
+
+        values.insert(String::from("id"), Value::Int(1));
+        values.insert(String::from("name"), Value::Text(String::from("O'Brien.png")));
+        values.insert(String::from("data"), Value::Bytes(vec![0x89, 0x50, 0x00, 0x47]));
+
+        let actual = table.insert_values(&values, MAX_LITERAL_SIZE);
+        assert_eq!(actual, Ok(Some(QML(String::from("INSERT INTO images(id,name,data) VALUES (1,'O''Brien.png',x'89500047')")))));
+
+        values.insert(String::from("data"), Value::Text(String::from("GIF")));
+
+        let actual = table.insert_values(&values, MAX_LITERAL_SIZE);
+        assert_eq!(actual, Ok(Some(QML(String::from("INSERT INTO images(id,name,data) VALUES (1,'O''Brien.png',x'474946')")))));
+
+        let actual = table.insert_values(&values, 8);
+        assert_eq!(actual, Err(ValueError::LiteralTooLarge{size: 14, limit: 8}));
+
+        let actual = table.insert_values(&HashMap::new(), MAX_LITERAL_SIZE);
+        assert_eq!(actual, Ok(None));
+    }
+
+    //insert many
+    fn batch_table() -> Table {
+        Table{
+            name: String::from("t"),
+            attributes: vec![
                 Attribute{
-                    name: String::from("Address"),
-                    data_type: AttributeType::VarChar(255),
+                    name: String::from("id"),
+                    data_type: AttributeType::Int(11),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
                 Attribute{
-                    name: String::from("City"),
+                    name: String::from("name"),
                     data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
             ],
-            primary_key: None,
-        };
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        }
+    }
 
-        let mut values = HashMap::new();
+    fn batch_rows() -> Vec<HashMap<String, Value>> {
+        (1..=5)
+            .map(|id| HashMap::from([
+                (String::from("id"), Value::Int(id)),
+                (String::from("name"), Value::Text(String::from("ab"))),
+            ]))
+            .collect()
+    }
 
-        values.insert(String::from("PersonID"), String::from("23"));
-        values.insert(String::from("LastName"), String::from("'Doe'"));
-        values.insert(String::from("FirstName"), String::from("'John'"));
-        values.insert(String::from("Address"), String::from("'1st Street'"));
-        values.insert(String::from("City"), String::from("'Night City'"));
+    #[test]
+    fn insert_batches_test_1() {
+        //"INSERT INTO t(id,name) VALUES " is 30 bytes and every tuple "(1,'ab')" is 8 bytes
+        let table = batch_table();
+        let rows = batch_rows();
 
-        let actual = table.insert(&values);
+        assert_eq!(
+            table.insert_batches(&rows, 47),
+            Ok(vec![
+                QML(String::from("INSERT INTO t(id,name) VALUES (1,'ab'),(2,'ab')")),
+                QML(String::from("INSERT INTO t(id,name) VALUES (3,'ab'),(4,'ab')")),
+                QML(String::from("INSERT INTO t(id,name) VALUES (5,'ab')")),
+            ])
+        );
 
-        assert_eq!(actual, Some(QML(String::from("INSERT INTO table_1(PersonID,LastName,FirstName,Address,City) VALUES (23,'Doe','John','1st Street','Night City')"))));
+        assert_eq!(
+            table.insert_batches(&rows, 46),
+            Ok((1..=5).map(|id| QML(format!("INSERT INTO t(id,name) VALUES ({},'ab')", id))).collect())
+        );
+
+        assert_eq!(table.insert_batches(&rows, 1024).map(|statements| statements.len()), Ok(1));
+        assert_eq!(table.insert_batches(&[], 1024), Ok(vec![]));
     }
 
     #[test]
-    fn insert_test_2(){
+    fn insert_batches_test_2() {
+        let table = batch_table();
+        let mut rows = batch_rows();
+
+        rows[2].insert(String::from("name"), Value::Text(String::from("abcdefghij")));
+        rows[3].remove("name");
+
+        assert_eq!(
+            table.insert_batches(&rows, 40),
+            Err(BatchInsertError::RowTooLarge{row: 2, size: 46, budget: 40})
+        );
+
+        assert_eq!(
+            table.insert_batches(&rows[3..], 1024),
+            Ok(vec![QML(String::from("INSERT INTO t(id,name) VALUES (4,DEFAULT),(5,'ab')"))])
+        );
+    }
+
+    //group count
+    #[test]
+    fn group_count_test_1() {
         let table = Table{
             name: String::from("table_1"),
             attributes: vec![
-                Attribute{
-                    name: String::from("PersonID"),
-                    data_type: AttributeType::Int(16),
-                    constraint: HashSet::new()
-                },
                 Attribute{
                     name: String::from("LastName"),
                     data_type: AttributeType::VarChar(255),
-                    constraint: HashSet::new()
-                },
-                Attribute{
-                    name: String::from("FirstName"),
-                    data_type: AttributeType::VarChar(255),
-                    constraint: HashSet::new()
-                },
-                Attribute{
-                    name: String::from("Address"),
-                    data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
                 Attribute{
                     name: String::from("City"),
                     data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
             ],
-            primary_key: None,
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
         };
 
-        let mut values = HashMap::new();
+        assert_eq!(
+            table.group_count_query(&["City"], 5),
+            Some(QDL(String::from("SELECT City,COUNT(*) FROM table_1 GROUP BY City ORDER BY COUNT(*) DESC LIMIT 5")))
+        );
+        assert_eq!(
+            table.group_count_query(&["City", "LastName"], 10),
+            Some(QDL(String::from("SELECT City,LastName,COUNT(*) FROM table_1 GROUP BY City,LastName ORDER BY COUNT(*) DESC LIMIT 10")))
+        );
+        assert_eq!(table.group_count_query(&[], 10), None);
+        assert_eq!(table.group_count_query(&["City", "Country"], 10), None);
+    }
 
-        values.insert(String::from("PersonID"), String::from("23"));
-        values.insert(String::from("LastName"), String::from("'Doe'"));
-        values.insert(String::from("FirstName"), String::from("'John'"));
+    #[test]
+    fn group_count_test_2() {
+        let actual = GroupCount::from_values(vec![
+            mysql::Value::Bytes(b"Night City".to_vec()),
+            mysql::Value::NULL,
+            mysql::Value::Bytes(b"12".to_vec()),
+        ]);
 
-        let actual = table.insert(&values);
+        assert_eq!(
+            actual,
+            Some(GroupCount{
+                key: vec![Value::Text(String::from("Night City")), Value::Null],
+                count: 12
+            })
+        );
+
+        assert_eq!(GroupCount::from_values(vec![]), None);
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn count_test() {
+        let _env = DbEnv::new(
+            vec![
+                SQL::new("CREATE TABLE count_test (id INT PRIMARY KEY)").unwrap(),
+                SQL::new("INSERT INTO count_test (id) VALUES (1), (2), (3)").unwrap(),
+            ],
+            vec![SQL::new("DROP TABLE IF EXISTS count_test").unwrap()]
+        );
+
+        let db = DataBase::from_env().unwrap();
+        let table = Table::from_db("count_test").unwrap();
+
+        assert_eq!(table.count(&db).unwrap(), 3);
+        assert!(table.exists(&db).unwrap());
+
+        let missing = Table{name: String::from("count_test_missing"), ..table};
+
+        assert!(!missing.exists(&db).unwrap());
+        assert!(matches!(missing.count(&db), Err(crate::Error::NotFound(name)) if name == "count_test_missing"));
+    }
+
+    #[test]
+    fn table_load_error_test() {
+        let server_error = |code: u16| mysql::Error::MySqlError(mysql::MySqlError{
+            state: String::from("42S02"),
+            message: String::new(),
+            code,
+        });
+
+        let err = TableLoadError::reading("orders", crate::Error::query("SHOW FULL COLUMNS FROM orders", server_error(1146)));
+
+        assert!(matches!(&err, TableLoadError::TableNotFound(name) if name == "orders"));
+        assert_eq!(err.to_string(), "table orders does not exist");
+        assert!(matches!(SQLError::from(err), SQLError::Err(_)));
+
+        let err = TableLoadError::reading("orders", crate::Error::query("SHOW FULL COLUMNS FROM orders", server_error(1142)));
+
+        assert!(matches!(&err, TableLoadError::Execution(_)));
+        assert!(matches!(SQLError::from(err), SQLError::Execution(_)));
+    }
+
+    #[test]
+    #[ignore]
+    #[serial]
+    fn from_db_missing_test() {
+        let db = DataBase::from_env().unwrap();
 
-        assert_eq!(actual, Some(QML(String::from("INSERT INTO table_1(PersonID,LastName,FirstName) VALUES (23,'Doe','John')"))));
+        assert!(matches!(
+            Table::from_db_in(&db, "from_db_missing_test"),
+            Err(TableLoadError::TableNotFound(name)) if name == "from_db_missing_test"
+        ));
+    }
+
+    /// Answers `SHOW FULL COLUMNS` and the foreign key query of the tables `post` and `comment`, and the group count of
+    /// `table_1` by `City` and `LastName`, with canned rows, fails the column queries for any other table, and returns
+    /// no rows for every other statement, which it records.
+    #[derive(Default)]
+    struct MockExecutor {
+        statements: std::cell::RefCell<Vec<String>>,
+    }
+
+    impl MockExecutor {
+        fn row(names: &[&str], values: &[Option<&str>]) -> mysql::Row {
+            use std::sync::Arc;
+
+            use mysql::{Column, consts::ColumnType};
+
+            let columns: Arc<[Column]> = names.iter()
+                .map(|name| Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(name.as_bytes()))
+                .collect();
+            let values = values.iter()
+                .map(|value| value.map_or(mysql::Value::NULL, mysql::Value::from))
+                .collect();
+
+            mysql_common::row::new_row(values, columns)
+        }
+    }
+
+    impl Executor for MockExecutor {
+        fn execute<E, F>(&self, cmd: &SQL, row_map: F) -> Result<Vec<E>, crate::Error> where F : FnMut(Result<mysql::Row, mysql::Error>) -> E {
+            const COLUMNS: [&str; 9] = ["Field", "Type", "Collation", "Null", "Key", "Default", "Extra", "Privileges", "Comment"];
+
+            let statement = cmd.to_string();
+            self.statements.borrow_mut().push(statement.clone());
+
+            let rows = match statement.as_str() {
+                "SHOW FULL COLUMNS FROM comment" => vec![
+                    MockExecutor::row(&COLUMNS, &[Some("id"), Some("int"), None, Some("NO"), Some("PRI"), None, Some(""), None, Some("")]),
+                    MockExecutor::row(&COLUMNS, &[Some("post"), Some("int"), None, Some("NO"), Some("MUL"), None, Some(""), None, Some("")]),
+                    MockExecutor::row(&COLUMNS, &[Some("author"), Some("int"), None, Some("NO"), Some("MUL"), None, Some(""), None, Some("")]),
+                    MockExecutor::row(&COLUMNS, &[Some("editor"), Some("int"), None, Some("YES"), Some("MUL"), None, Some(""), None, Some("")]),
+                ],
+                "SHOW FULL COLUMNS FROM post" => vec![
+                    MockExecutor::row(&COLUMNS, &[Some("id"), Some("int"), None, Some("NO"), Some("PRI"), None, Some("auto_increment"), None, Some("")]),
+                    MockExecutor::row(&COLUMNS, &[Some("author"), Some("int"), None, Some("YES"), Some("MUL"), None, Some(""), None, Some("")]),
+                    MockExecutor::row(&COLUMNS, &[Some("title"), Some("varchar(255)"), None, Some("NO"), Some("UNI"), Some("untitled"), Some(""), None, Some("")]),
+                ],
+                statement if statement.starts_with("SHOW FULL COLUMNS") => return Err(crate::Error::query(
+                    statement,
+                    mysql::Error::MySqlError(mysql::MySqlError{state: String::from("42S02"), message: String::from("missing"), code: 1146})
+                )),
+                statement if statement.contains("KEY_COLUMN_USAGE") && statement.contains("'comment'") => {
+                    let names = ["COLUMN_NAME", "REFERENCED_TABLE_NAME", "REFERENCED_COLUMN_NAME", "DELETE_RULE", "UPDATE_RULE"];
+
+                    vec![
+                        MockExecutor::row(&names, &[Some("author"), Some("user"), Some("id"), Some("CASCADE"), Some("RESTRICT")]),
+                        MockExecutor::row(&names, &[Some("editor"), Some("user"), Some("id"), Some("SET NULL"), Some("RESTRICT")]),
+                        MockExecutor::row(&names, &[Some("post"), Some("post"), Some("id"), Some("CASCADE"), Some("RESTRICT")]),
+                    ]
+                },
+                statement if statement.contains("KEY_COLUMN_USAGE") => vec![MockExecutor::row(
+                    &["COLUMN_NAME", "REFERENCED_TABLE_NAME", "REFERENCED_COLUMN_NAME", "DELETE_RULE", "UPDATE_RULE"],
+                    &[Some("author"), Some("user"), Some("id"), Some("CASCADE"), Some("RESTRICT")]
+                )],
+                statement if statement.starts_with("SELECT City,LastName,COUNT(*) FROM table_1 GROUP BY") => {
+                    let names = ["City", "LastName", "COUNT(*)"];
+
+                    vec![
+                        MockExecutor::row(&names, &[Some("Night City"), Some("Doe"), Some("3")]),
+                        MockExecutor::row(&names, &[Some("Night City"), None, Some("2")]),
+                        MockExecutor::row(&names, &[None, None, Some("1")]),
+                    ]
+                },
+                _ => Vec::new(),
+            };
+
+            Ok(rows.into_iter().map(Ok).map(row_map).collect())
+        }
+    }
+
+    #[test]
+    fn group_count_mock_test() {
+        let db = MockExecutor::default();
+        let table = Table::from_create_statement("CREATE TABLE table_1 (LastName varchar(255), City varchar(255))").unwrap();
+
+        assert_eq!(
+            table.group_count(&db, &["City", "LastName"], 10).unwrap(),
+            vec![
+                GroupCount{key: vec![Value::from("Night City"), Value::from("Doe")], count: 3},
+                GroupCount{key: vec![Value::from("Night City"), Value::Null], count: 2},
+                GroupCount{key: vec![Value::Null, Value::Null], count: 1},
+            ]
+        );
+        assert_eq!(
+            *db.statements.borrow(),
+            vec![String::from("SELECT City,LastName,COUNT(*) FROM table_1 GROUP BY City,LastName ORDER BY COUNT(*) DESC LIMIT 10")]
+        );
+
+        assert!(matches!(table.group_count(&db, &["Country"], 10), Err(crate::Error::Invalid(_))));
+        assert_eq!(db.statements.borrow().len(), 1);
+    }
+
+    #[test]
+    fn from_db_mock_test() {
+        let (table, report) = Table::from_db_report_in(&MockExecutor::default(), "post").unwrap();
+
+        assert_eq!(
+            table.attributes.iter().map(|attr| (attr.name.as_str(), attr.raw_type.as_str())).collect::<Vec<(&str, &str)>>(),
+            vec![("id", "int"), ("author", "int"), ("title", "varchar(255)")]
+        );
+        assert_eq!(table.primary_key, vec![0]);
+        assert!(table.attributes[0].constraint.contains(&Constraint::AutoIncrement));
+        assert!(table.attributes[2].constraint.contains(&Constraint::Unique));
+        assert!(table.attributes[2].constraint.contains(&Constraint::NotNull));
+        assert_eq!(
+            table.foreign_keys(),
+            vec![ForeignKey{
+                table: String::from("post"),
+                column: String::from("author"),
+                referenced_table: String::from("user"),
+                referenced_column: String::from("id"),
+            }]
+        );
+        assert!(report.warnings.is_empty());
+
+        assert!(matches!(
+            Table::from_db_in(&MockExecutor::default(), "missing"),
+            Err(TableLoadError::TableNotFound(name)) if name == "missing"
+        ));
+    }
+
+    #[test]
+    fn from_db_statements_test() {
+        let db = MockExecutor::default();
+        let table = Table::from_db_in(&db, "comment").unwrap();
+
+        assert_eq!(
+            table.foreign_keys().iter().map(|key| (key.column.as_str(), key.referenced_table.as_str())).collect::<Vec<_>>(),
+            vec![("post", "post"), ("author", "user"), ("editor", "user")]
+        );
+
+        //the foreign keys of every indexed column are read together, on the one executor
+        let statements = db.statements.borrow();
+
+        assert_eq!(statements.first().map(String::as_str), Some("SHOW FULL COLUMNS FROM comment"));
+        assert_eq!(statements.iter().filter(|statement| statement.contains("KEY_COLUMN_USAGE")).count(), 1);
+        assert!(!statements.iter().any(|statement| statement.starts_with("SHOW CREATE TABLE")));
+        assert!(statements.len() <= 4, "{:?}", statements);
     }
 
     #[test]
@@ -824,36 +4620,539 @@ mod tests {
                 Attribute{
                     name: String::from("PersonID"),
                     data_type: AttributeType::Int(16),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
                 Attribute{
                     name: String::from("LastName"),
                     data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
                 Attribute{
                     name: String::from("FirstName"),
                     data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
                 Attribute{
                     name: String::from("Address"),
                     data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
                 Attribute{
                     name: String::from("City"),
                     data_type: AttributeType::VarChar(255),
+                    raw_type: String::new(),
                     constraint: HashSet::new()
                 },
             ],
-            primary_key: None,
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
         };
 
         let values = HashMap::new();
 
         let actual = table.insert(&values);
 
-        assert_eq!(actual, None);
+        assert_eq!(actual, Err(InsertError::EmptyValues));
+        assert_eq!(table.insert_ignore(&values), None);
+    }
+
+    #[test]
+    fn insert_error_test() {
+        let mut table = update_table();
+        table.attributes[1].constraint.insert(Constraint::NotNull);
+
+        let values = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs.iter().map(|(column, value)| (column.to_string(), value.to_string())).collect()
+        };
+
+        //a misspelled column is not left out
+        let err = table.insert(&values(&[("LastName", "Doe"), ("FistName", "John")])).unwrap_err();
+
+        assert_eq!(err, InsertError::UnknownColumns(vec![String::from("FistName")]));
+        assert_eq!(err.to_string(), "unknown columns FistName");
+        assert_eq!(
+            table.insert(&values(&[("LastName", "Doe"), ("Country", "Canada"), ("Age", "30")])),
+            Err(InsertError::UnknownColumns(vec![String::from("Age"), String::from("Country")]))
+        );
+        assert_eq!(
+            table.insert(&values(&[("PersonID", "23")])),
+            Err(InsertError::MissingRequired(String::from("LastName")))
+        );
+
+        let err = table.insert(&values(&[("LastName", "Doe"), ("PersonID", "23; DROP TABLE table_1")])).unwrap_err();
+
+        assert_eq!(
+            err,
+            InsertError::TypeMismatch{
+                column: String::from("PersonID"),
+                expected: String::from("int(16)"),
+                got: String::from("23; DROP TABLE table_1"),
+            }
+        );
+        assert_eq!(err.to_string(), "PersonID expects int(16), got 23; DROP TABLE table_1");
+
+        assert!(table.insert(&values(&[("LastName", "Doe"), ("PersonID", "NULL")])).is_ok());
+    }
+
+    #[test]
+    fn insert_typed_test() {
+        let table = update_table();
+
+        let values = HashMap::from([
+            (String::from("PersonID"), Value::Text(String::from("23"))),
+            (String::from("LastName"), Value::Text(String::from("O'Brien"))),
+            (String::from("City"), Value::Null),
+        ]);
+
+        assert_eq!(
+            table.insert_typed(&values),
+            Ok(QML(String::from("INSERT INTO table_1(PersonID,LastName,City) VALUES (23,'O''Brien',NULL)")))
+        );
+
+        let err = table.insert_typed(&HashMap::from([(String::from("PersonID"), Value::Text(String::from("Doe")))])).unwrap_err();
+
+        assert_eq!(
+            err,
+            InsertError::TypeMismatch{column: String::from("PersonID"), expected: String::from("int(16)"), got: String::from("'Doe'")}
+        );
+        assert_eq!(
+            table.insert_typed(&HashMap::from([(String::from("FistName"), Value::Int(1))])),
+            Err(InsertError::UnknownColumns(vec![String::from("FistName")]))
+        );
+
+        //the old string maps convert to the same statement
+        let strings = HashMap::from([(String::from("PersonID"), String::from("23")), (String::from("City"), String::from("Paris"))]);
+
+        assert_eq!(
+            table.insert_typed(&value::from_strings(&strings)).unwrap(),
+            table.insert(&strings).unwrap()
+        );
+    }
+
+    #[test]
+    fn insert_prepared_test() {
+        let mut table = update_table();
+        table.attributes[2].data_type = AttributeType::VarBinary(255);
+
+        let values = HashMap::from([
+            (String::from("PersonID"), Value::Text(String::from("23"))),
+            (String::from("LastName"), Value::Text(String::from("O'Brien \\ \"; --"))),
+            (String::from("FirstName"), Value::Text(String::from("GIF"))),
+            (String::from("City"), Value::Default),
+        ]);
+
+        let prepared = table.insert_prepared(&values).unwrap();
+
+        assert_eq!(
+            prepared.sql(),
+            &SQL::Insert(QML(String::from("INSERT INTO table_1(PersonID,LastName,FirstName,City) VALUES (?,?,?,DEFAULT)")))
+        );
+        assert_eq!(
+            prepared.params(),
+            &[Value::Int(23), Value::Text(String::from("O'Brien \\ \"; --")), Value::Bytes(b"GIF".to_vec())]
+        );
+
+        assert_eq!(
+            table.insert_prepared(&HashMap::from([(String::from("PersonID"), Value::Text(String::from("Doe")))])).unwrap_err(),
+            table.insert_typed(&HashMap::from([(String::from("PersonID"), Value::Text(String::from("Doe")))])).unwrap_err()
+        );
+        assert_eq!(table.insert_prepared(&HashMap::new()), Err(InsertError::EmptyValues));
+    }
+
+    #[test]
+    fn insert_null_test() {
+        let mut table = update_table();
+
+        assert_eq!(
+            table.insert_typed(&HashMap::from([(String::from("City"), Value::Null)])),
+            Ok(QML(String::from("INSERT INTO table_1(City) VALUES (NULL)")))
+        );
+
+        table.attributes[1].constraint.insert(Constraint::NotNull);
+
+        let err = table.insert_typed(&HashMap::from([
+            (String::from("PersonID"), Value::Int(1)),
+            (String::from("LastName"), Value::Null),
+        ])).unwrap_err();
+
+        assert_eq!(err, InsertError::NullNotAllowed(String::from("LastName")));
+        assert_eq!(err.to_string(), "LastName cannot be NULL");
+
+        assert_eq!(
+            table.insert_values(&HashMap::from([(String::from("LastName"), Value::Null)]), MAX_LITERAL_SIZE),
+            Err(ValueError::NullNotAllowed(String::from("LastName")))
+        );
+
+        //the string builders read NULL the same way for quoted and unquoted columns
+        table.attributes[0].constraint.insert(Constraint::NotNull);
+
+        let values = HashMap::from([(String::from("PersonID"), String::from("NULL")), (String::from("LastName"), String::from("Doe"))]);
+
+        assert_eq!(table.insert(&values), Err(InsertError::NullNotAllowed(String::from("PersonID"))));
+
+        let values = HashMap::from([(String::from("PersonID"), String::from("1")), (String::from("LastName"), String::from("null"))]);
+
+        assert_eq!(table.insert(&values), Err(InsertError::NullNotAllowed(String::from("LastName"))));
+
+        let values = HashMap::from([
+            (String::from("PersonID"), String::from("1")),
+            (String::from("LastName"), String::from("Doe")),
+            (String::from("City"), String::from("NULL")),
+        ]);
+
+        assert_eq!(table.insert(&values), Ok(QML(String::from("INSERT INTO table_1(PersonID,LastName,City) VALUES (1,'Doe',NULL)"))));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn insert_or_none_test() {
+        let table = update_table();
+
+        assert_eq!(
+            table.insert_or_none(&HashMap::from([(String::from("City"), String::from("Toronto")), (String::from("Country"), String::from("Canada"))])),
+            Some(QML(String::from("INSERT INTO table_1(City) VALUES ('Toronto')")))
+        );
+        assert_eq!(table.insert_or_none(&HashMap::new()), None);
+    }
+
+    //table upsert statement
+    #[test]
+    fn upsert_test_1() {
+        let mut table = update_table();
+        table.primary_key = vec![0];
+
+        let values = literal_row(&[("PersonID", "23"), ("City", "Night City"), ("LastName", "Doe")]);
+
+        assert_eq!(
+            table.upsert(&values),
+            Some(QML(String::from(
+                "INSERT INTO table_1(PersonID,LastName,City) VALUES (23,'Doe','Night City') \
+                ON DUPLICATE KEY UPDATE LastName=VALUES(LastName),City=VALUES(City)"
+            )))
+        );
+
+        //only the primary key, so the existing row is kept
+        assert_eq!(
+            table.upsert(&literal_row(&[("PersonID", "23")])),
+            Some(QML(String::from("INSERT INTO table_1(PersonID) VALUES (23) ON DUPLICATE KEY UPDATE PersonID=PersonID")))
+        );
+    }
+
+    #[test]
+    fn upsert_test_2() {
+        let table = update_table();
+
+        assert_eq!(
+            table.upsert(&literal_row(&[("PersonID", "23"), ("City", "Night City")])),
+            Some(QML(String::from(
+                "INSERT INTO table_1(PersonID,City) VALUES (23,'Night City') \
+                ON DUPLICATE KEY UPDATE PersonID=VALUES(PersonID),City=VALUES(City)"
+            )))
+        );
+        assert_eq!(table.upsert(&HashMap::new()), None);
+    }
+
+    fn literal_row(values: &[(&str, &str)]) -> HashMap<String, String> {
+        values.iter().map(|(column, value)| (column.to_string(), value.to_string())).collect()
+    }
+
+    //table multi-row insert statement
+    #[test]
+    fn insert_many_test_1() {
+        let rows = vec![literal_row(&[("LastName", "Doe"), ("PersonID", "23")])];
+
+        assert_eq!(
+            update_table().insert_many(&rows),
+            Ok(Some(QML(String::from("INSERT INTO table_1(PersonID,LastName) VALUES (23,'Doe')"))))
+        );
+        assert_eq!(update_table().insert_many(&[]), Ok(None));
+    }
+
+    #[test]
+    fn insert_many_test_2() {
+        let rows = vec![
+            literal_row(&[("PersonID", "23"), ("City", "Night City")]),
+            literal_row(&[("City", "Toronto"), ("PersonID", "24")]),
+            literal_row(&[("PersonID", "25"), ("City", "O'Brien")]),
+        ];
+
+        assert_eq!(
+            update_table().insert_many(&rows),
+            Ok(Some(QML(String::from(
+                "INSERT INTO table_1(PersonID,City) VALUES (23,'Night City'),(24,'Toronto'),(25,'O''Brien')"
+            ))))
+        );
+    }
+
+    #[test]
+    fn insert_many_test_3() {
+        let missing = vec![
+            literal_row(&[("PersonID", "23"), ("City", "Night City")]),
+            literal_row(&[("PersonID", "24")]),
+        ];
+        let extra = vec![
+            literal_row(&[("PersonID", "23")]),
+            literal_row(&[("PersonID", "24")]),
+            literal_row(&[("PersonID", "25"), ("LastName", "Doe")]),
+        ];
+
+        let err = update_table().insert_many(&missing).unwrap_err();
+
+        assert_eq!(err, MismatchedRow::Missing{row: 1, column: String::from("City")});
+        assert_eq!(err.to_string(), "row 1 does not set City, which row 0 sets");
+        assert_eq!(
+            update_table().insert_many(&extra),
+            Err(MismatchedRow::Extra{row: 2, column: String::from("LastName")})
+        );
+    }
+
+    #[test]
+    fn insert_many_test_4() {
+        let unknown = vec![
+            literal_row(&[("PersonID", "23")]),
+            literal_row(&[("PersonID", "24"), ("Country", "Canada")]),
+        ];
+        let rejected = vec![
+            literal_row(&[("PersonID", "23")]),
+            literal_row(&[("PersonID", "24); DROP TABLE table_1; --")]),
+        ];
+
+        let err = update_table().insert_many(&unknown).unwrap_err();
+
+        assert_eq!(err, MismatchedRow::Unknown{row: 1, column: String::from("Country")});
+        assert_eq!(err.to_string(), "row 1 sets Country, which is not a column of the table");
+        assert!(matches!(
+            update_table().insert_many(&[literal_row(&[("Country", "Canada")])]),
+            Err(MismatchedRow::Unknown{row: 0, ..})
+        ));
+        assert!(matches!(
+            update_table().insert_many(&rejected),
+            Err(MismatchedRow::Rejected{row: 1, error: InsertError::TypeMismatch{..}})
+        ));
+    }
+
+    //table update statement
+    fn update_table() -> Table {
+        let attr = |name: &str, data_type: AttributeType| Attribute{
+            name: String::from(name),
+            data_type,
+            raw_type: String::new(),
+            constraint: HashSet::new()
+        };
+
+        Table{
+            name: String::from("table_1"),
+            attributes: vec![
+                attr("PersonID", AttributeType::Int(16)),
+                attr("LastName", AttributeType::VarChar(255)),
+                attr("FirstName", AttributeType::VarChar(255)),
+                attr("Address", AttributeType::VarChar(255)),
+                attr("City", AttributeType::VarChar(255)),
+            ],
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn update_test_1() {
+        let mut values = HashMap::new();
+
+        values.insert(String::from("LastName"), String::from("Doe"));
+        values.insert(String::from("City"), String::from("Night City"));
+
+        let actual = update_table().update(&values, "PersonID=23");
+
+        assert_eq!(actual, Some(QML(String::from("UPDATE table_1 SET LastName='Doe',City='Night City' WHERE PersonID=23"))));
+    }
+
+    #[test]
+    fn update_test_2() {
+        let mut values = HashMap::new();
+
+        //columns that are not attributes are left out
+        values.insert(String::from("FirstName"), String::from("John"));
+        values.insert(String::from("Country"), String::from("Canada"));
+
+        let actual = update_table().update(&values, "LastName='Doe'");
+
+        assert_eq!(actual, Some(QML(String::from("UPDATE table_1 SET FirstName='John' WHERE LastName='Doe'"))));
+
+        values.remove("FirstName");
+
+        assert_eq!(update_table().update(&values, "LastName='Doe'"), None);
+    }
+
+    #[test]
+    fn update_test_3() {
+        let values = HashMap::new();
+
+        assert_eq!(update_table().update(&values, "PersonID=23"), None);
+        assert_eq!(update_table().update_all(&values), None);
+
+        //no condition, so every row has to be updated on purpose
+        let values = HashMap::from([(String::from("City"), String::from("Night City"))]);
+
+        assert_eq!(update_table().update(&values, ""), None);
+        assert_eq!(update_table().update(&values, "  "), None);
+        assert_eq!(update_table().update_all(&values), Some(QML(String::from("UPDATE table_1 SET City='Night City'"))));
+    }
+
+    //table delete statement
+    #[test]
+    fn delete_test_1() {
+        let table = update_table();
+
+        //primary key
+        let mut keyed = update_table();
+        keyed.primary_key = vec![0];
+
+        assert_eq!(keyed.delete_where("PersonID=23"), Some(QML(String::from("DELETE FROM table_1 WHERE PersonID=23"))));
+        assert_eq!(
+            table.delete_where(" LastName='Doe' AND City='Night City' "),
+            Some(QML(String::from("DELETE FROM table_1 WHERE LastName='Doe' AND City='Night City'")))
+        );
+        assert_eq!(table.delete_all(), QML(String::from("DELETE FROM table_1")));
+    }
+
+    #[test]
+    fn delete_test_2() {
+        //no condition, so every row has to be deleted on purpose
+        assert_eq!(update_table().delete_where(""), None);
+        assert_eq!(update_table().delete_where(" \n "), None);
+    }
+
+    //table select statement
+    #[test]
+    fn select_where_test_1() {
+        let table = update_table();
+
+        assert_eq!(
+            table.select_where(Some(&["FirstName", "LastName"]), "City='Night City'"),
+            Some(QDL(String::from("SELECT FirstName,LastName FROM table_1 WHERE City='Night City'")))
+        );
+        assert_eq!(
+            table.select_where(None, "PersonID=23"),
+            Some(QDL(String::from("SELECT * FROM table_1 WHERE PersonID=23")))
+        );
+        assert_eq!(table.select_where(Some(&["City"]), " "), Some(QDL(String::from("SELECT City FROM table_1"))));
+    }
+
+    #[test]
+    fn select_where_test_2() {
+        let table = update_table();
+
+        assert_eq!(table.select_where(Some(&["FirstName", "Country"]), "PersonID=23"), None);
+        assert_eq!(table.select_where(Some(&[]), "PersonID=23"), None);
+    }
+
+    //column order
+    fn column_order_table() -> Table {
+        let attr = |name: &str| Attribute{
+            name: String::from(name),
+            data_type: AttributeType::Int(11),
+            raw_type: String::new(),
+            constraint: HashSet::new()
+        };
+
+        Table{
+            name: String::from("t"),
+            attributes: ["a", "b", "id", "c", "d", "e", "f", "g"].iter().map(|name| attr(name)).collect(),
+            primary_key: vec![2],
+            indexes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn column_order_test_1() {
+        let table = column_order_table();
+        let columns = vec![
+            (Some(table.attributes[0].clone()), false),
+            (None, false),
+            (Some(table.attributes[1].clone()), false),
+            (Some(table.attributes[2].clone()), true),
+            (Some(table.attributes[3].clone()), true),
+        ];
+
+        let actual = Table::from_columns("t", columns);
+
+        let names: Vec<&str> = actual.attributes.iter().map(|attr| attr.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "id", "c"]);
+        assert_eq!(actual.primary_key, vec![2, 3]);
+    }
+
+    //the primary key is the third column, after columns that are and are not read
+    #[test]
+    fn primary_key_index_test() {
+        let table = column_order_table();
+        let column = |index: usize, primary_key: bool| (Some(table.attributes[index].clone()), primary_key);
+
+        let actual = Table::from_columns("t", vec![column(0, false), column(1, false), column(2, true), column(3, false)]);
+
+        assert_eq!(actual.attributes[actual.primary_key[0]].name, "id");
+
+        let actual = Table::from_columns("t", vec![(None, false), column(0, false), column(2, true), column(3, false)]);
+
+        assert_eq!(actual.attributes[actual.primary_key[0]].name, "id");
+        assert_eq!(actual.primary_key_columns(), vec!["id"]);
+    }
+
+    #[test]
+    fn column_order_test_2() {
+        let table = column_order_table();
+        let names = ["g", "f", "e", "d", "c", "id", "b", "a"];
+
+        //every HashMap is seeded differently, so each one iterates its keys in a different order
+        let values = |id: i64| -> HashMap<String, Value> {
+            names.iter().map(|name| (name.to_string(), Value::Int(id))).collect()
+        };
+        let strings = || -> HashMap<String, String> {
+            names.iter().map(|name| (name.to_string(), String::from("1"))).collect()
+        };
+
+        let insert = table.insert(&strings()).unwrap();
+        let insert_values = table.insert_values(&values(1), MAX_LITERAL_SIZE).unwrap().unwrap();
+        let insert_batches = table.insert_batches(&[values(1), values(2)], 1024).unwrap();
+        let insert_many = table.insert_many(&[strings(), strings()]).unwrap().unwrap();
+
+        assert_eq!(*insert, "INSERT INTO t(a,b,id,c,d,e,f,g) VALUES (1,1,1,1,1,1,1,1)");
+        assert_eq!(*insert_values, "INSERT INTO t(a,b,id,c,d,e,f,g) VALUES (1,1,1,1,1,1,1,1)");
+
+        for _ in 0..32 {
+            assert_eq!(table.insert(&strings()).unwrap(), insert);
+            assert_eq!(table.insert_values(&values(1), MAX_LITERAL_SIZE).unwrap().unwrap(), insert_values);
+            assert_eq!(table.insert_batches(&[values(1), values(2)], 1024).unwrap(), insert_batches);
+            assert_eq!(table.insert_many(&[strings(), strings()]).unwrap().unwrap(), insert_many);
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn column_order_test_3() {
+        let mut table = column_order_table();
+
+        assert_eq!(table.canonicalize_column_order(&["id", "g", "id", "c"]), Ok(()));
+
+        let names: Vec<&str> = table.attributes.iter().map(|attr| attr.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "g", "c", "a", "b", "d", "e", "f"]);
+        assert_eq!(table.primary_key, vec![0]);
+
+        let values: HashMap<String, Value> = HashMap::from([
+            (String::from("a"), Value::Int(1)),
+            (String::from("g"), Value::Int(2)),
+        ]);
+        assert_eq!(
+            table.insert_values(&values, MAX_LITERAL_SIZE),
+            Ok(Some(QML(String::from("INSERT INTO t(g,a) VALUES (2,1)"))))
+        );
+
+        assert_eq!(
+            table.canonicalize_column_order(&["a", "missing"]),
+            Err(UnknownColumn{table: String::from("t"), column: String::from("missing")})
+        );
+        assert_eq!(table.attributes[0].name, "id");
+    }
+}
@@ -0,0 +1,564 @@
+use std::fmt;
+
+use crate::{data_base::DataBase, error::Error, fmt::{Grid, Align, count}, sql::{SQL, QDL, DDL, quote_ident, value::Value}};
+
+use super::table::{Table, Attribute, AttributeType, Constraint};
+
+/// The largest number of distinct values for which a text column is suggested to become an `ENUM`.
+pub const MAX_ENUM_VALUES: u64 = 8;
+
+/// The smallest number of rows per distinct value for which a text column is suggested to become an `ENUM`, so small
+/// tables do not turn every column into an enum.
+pub const MIN_ROWS_PER_ENUM_VALUE: u64 = 10;
+
+/// Whether a suggested type can hold every value of the current type, as decided by `AttributeType::can_store`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Every value the column can hold today still fits.
+    Safe,
+    /// The values observed fit, but values the current type allows may not.
+    Lossy,
+}
+
+/// A proposed change to the type of a column, as returned by `Table::suggest_tightening`.
+#[derive(Clone, Debug)]
+pub struct Suggestion {
+    pub column: String,
+    pub from: AttributeType,
+    pub to: AttributeType,
+    /// The observation the suggestion is based on, such as `max observed length 41`.
+    pub reason: String,
+    /// The `ALTER TABLE ... MODIFY COLUMN` statement applying the suggestion.
+    pub alter: DDL,
+    pub compatibility: Compatibility,
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {} ({}){}",
+            self.column,
+            self.from,
+            self.to,
+            self.reason,
+            match self.compatibility {
+                Compatibility::Safe => "",
+                Compatibility::Lossy => " [lossy]",
+            }
+        )
+    }
+}
+
+/// The statistics gathered for one column by `Table::stats_query`.
+///
+/// Fields that do not apply to the column's type are `None`, as are the statistics of a column holding only `NULL`.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct ColumnStats {
+    pub column: String,
+    /// The length, in characters, of the longest value of a text column.
+    pub max_length: Option<u64>,
+    /// The number of distinct non `NULL` values of a text column.
+    pub distinct: Option<u64>,
+    /// The smallest value of an integer column.
+    pub min: Option<i64>,
+    /// The largest value of an integer column.
+    pub max: Option<i64>,
+    /// The distinct values of a text column, filled in for `ENUM` candidates by `Table::suggest_tightening`.
+    pub values: Vec<String>,
+}
+
+/// The statistics of a table, as read from the result of `Table::stats_query`.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct TableStats {
+    pub rows: u64,
+    pub columns: Vec<ColumnStats>,
+}
+
+impl fmt::Display for TableStats {
+    /// Writes the row count, then one row per column with the statistics that apply to it, fitting in the formatter's
+    /// width if one is given (see `Grid::render`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut grid = Grid::new(&[
+            ("column", Align::Left),
+            ("max length", Align::Right),
+            ("distinct", Align::Right),
+            ("min", Align::Right),
+            ("max", Align::Right),
+        ]);
+
+        for column in &self.columns {
+            let cell = |value: Option<u64>| value.map(count).unwrap_or_else(|| String::from("-"));
+            let signed = |value: Option<i64>| match value {
+                Some(value) if value < 0 => format!("-{}", count(value.unsigned_abs())),
+                value => cell(value.map(|value| value as u64)),
+            };
+
+            grid.push(vec![
+                column.column.clone(),
+                cell(column.max_length),
+                cell(column.distinct),
+                signed(column.min),
+                signed(column.max),
+            ]);
+        }
+
+        writeln!(f, "{} rows", count(self.rows))?;
+        f.write_str(&grid.render(f.width()))
+    }
+}
+
+/// The statistics gathered for a column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Probe {
+    /// `MAX(CHAR_LENGTH(col))` and `COUNT(DISTINCT col)`.
+    Text,
+    /// `MIN(col)` and `MAX(col)`.
+    Integer,
+}
+
+impl Probe {
+    /// Returns the statistics to gather for `attr`, or `None` if its type is not one that can be tightened.
+    ///
+    /// Auto incremented, foreign key and encrypted columns are skipped, as their type is tied to something other than
+    /// the values they hold today.
+    fn of(attr: &Attribute) -> Option<Probe> {
+        let pinned = attr.constraint.contains(&Constraint::AutoIncrement) ||
+            attr.fk_clause().is_some() ||
+            attr.encryption_key().is_some();
+
+        if pinned {
+            return None;
+        }
+
+        match attr.data_type {
+            AttributeType::Char(_) | AttributeType::VarChar(_) => Some(Probe::Text),
+            AttributeType::TinyInt(_) |
+            AttributeType::SmallInt(_) |
+            AttributeType::MediumInt(_) |
+            AttributeType::Int(_) |
+            AttributeType::BigInt(_) => Some(Probe::Integer),
+            _ => None,
+        }
+    }
+
+    fn expressions(&self, column: &str) -> [String; 2] {
+        match self {
+            Probe::Text => [format!("MAX(CHAR_LENGTH({}))", column), format!("COUNT(DISTINCT {})", column)],
+            Probe::Integer => [format!("MIN({})", column), format!("MAX({})", column)],
+        }
+    }
+}
+
+/// Reads a statistic, which arrives as text over the text protocol.
+fn as_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Int(val) => Some(*val),
+        Value::UInt(val) => i64::try_from(*val).ok(),
+        Value::Text(val) => val.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Returns the smallest integer type holding every value between `min` and `max`, with the server's default display
+/// width.
+fn integer_type(min: i64, max: i64) -> AttributeType {
+    let fits = |bits: u32| min >= -(1i64 << (bits - 1)) && max < (1i64 << (bits - 1));
+
+    if fits(8) {
+        AttributeType::TinyInt(4)
+    } else if fits(16) {
+        AttributeType::SmallInt(6)
+    } else if fits(24) {
+        AttributeType::MediumInt(9)
+    } else if fits(32) {
+        AttributeType::Int(11)
+    } else {
+        AttributeType::BigInt(20)
+    }
+}
+
+/// Returns the number of bits of an integer type.
+fn integer_bits(data_type: &AttributeType) -> u32 {
+    match data_type {
+        AttributeType::TinyInt(_) => 8,
+        AttributeType::SmallInt(_) => 16,
+        AttributeType::MediumInt(_) => 24,
+        AttributeType::Int(_) => 32,
+        _ => 64,
+    }
+}
+
+impl TableStats {
+    /// Reads the statistics of `table` from the single row returned by `Table::stats_query`.
+    ///
+    /// Returns `None` if the row does not have the expected number of values or the row count is missing.
+    pub fn from_values(table: &Table, values: &[Value]) -> Option<TableStats> {
+        let probes: Vec<(&Attribute, Probe)> = table.attributes
+            .iter()
+            .filter_map(|attr| Probe::of(attr).map(|probe| (attr, probe)))
+            .collect();
+
+        if values.len() != 1 + 2 * probes.len() {
+            return None;
+        }
+
+        let rows = as_i64(&values[0]).and_then(|rows| u64::try_from(rows).ok())?;
+
+        let columns = probes.iter()
+            .zip(values[1..].chunks(2))
+            .map(|((attr, probe), stats)| {
+                let (first, second) = (as_i64(&stats[0]), as_i64(&stats[1]));
+
+                match probe {
+                    Probe::Text => ColumnStats {
+                        column: attr.name.clone(),
+                        max_length: first.map(|val| val as u64),
+                        distinct: second.map(|val| val as u64),
+                        ..ColumnStats::default()
+                    },
+                    Probe::Integer => ColumnStats {
+                        column: attr.name.clone(),
+                        min: first,
+                        max: second,
+                        ..ColumnStats::default()
+                    },
+                }
+            })
+            .collect();
+
+        Some(TableStats { rows, columns })
+    }
+
+    /// Returns the columns whose distinct values are needed to suggest an `ENUM`.
+    pub fn enum_candidates(&self, table: &Table) -> Vec<&str> {
+        self.columns
+            .iter()
+            .filter(|stats| {
+                table.attributes
+                    .iter()
+                    .find(|attr| attr.name == stats.column)
+                    .is_some_and(|attr| !attr.constraint.contains(&Constraint::Unique))
+            })
+            .filter(|stats| match stats.distinct {
+                Some(distinct) => {
+                    distinct > 0 && distinct <= MAX_ENUM_VALUES && self.rows >= distinct * MIN_ROWS_PER_ENUM_VALUE
+                },
+                None => false,
+            })
+            .map(|stats| stats.column.as_str())
+            .collect()
+    }
+}
+
+impl Table {
+    /// Returns a `QDL` gathering the statistics used by `Table::suggest_tightening` in a single row: the row count,
+    /// then the longest length and distinct count of every text column and the range of every integer column.
+    ///
+    /// Returns `None` if no column can be tightened.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// assert_eq!(
+    ///     table.stats_query(),
+    ///     Some(QDL(String::from("SELECT COUNT(*),MAX(CHAR_LENGTH(status)),COUNT(DISTINCT status),MIN(views),MAX(views) FROM post")))
+    /// );
+    /// ```
+    pub fn stats_query(&self) -> Option<QDL> {
+        let expressions: Vec<String> = self.attributes
+            .iter()
+            .filter_map(|attr| Probe::of(attr).map(|probe| probe.expressions(&quote_ident(&attr.name))))
+            .flatten()
+            .collect();
+
+        if expressions.is_empty() {
+            return None;
+        }
+
+        Some(QDL(format!("SELECT COUNT(*),{} FROM {}", expressions.join(","), quote_ident(&self.name))))
+    }
+
+    /// Returns a `QDL` selecting the distinct non `NULL` values of `column`, in order.
+    pub fn distinct_values_query(&self, column: &str) -> QDL {
+        let column = quote_ident(column);
+
+        QDL(format!("SELECT DISTINCT {} FROM {} WHERE {} IS NOT NULL ORDER BY {}", column, quote_ident(&self.name), column, column))
+    }
+
+    /// Returns the type changes supported by `stats`.
+    ///
+    /// * A `VARCHAR` longer than needed is shortened to the next power of two above its longest value.
+    /// * An integer column is narrowed to the smallest type holding its range.
+    /// * A text column with at most `MAX_ENUM_VALUES` distinct values, and at least `MIN_ROWS_PER_ENUM_VALUE` rows per
+    ///   value, becomes an `ENUM` of those values. This replaces any length suggestion for the column, and needs
+    ///   `ColumnStats::values` to be filled in.
+    pub fn suggestions(&self, stats: &TableStats) -> Vec<Suggestion> {
+        let enum_candidates = stats.enum_candidates(self);
+
+        stats.columns
+            .iter()
+            .filter_map(|column| {
+                let attr = self.attributes.iter().find(|attr| attr.name == column.column)?;
+
+                let (to, reason) = match (&attr.data_type, column) {
+                    (AttributeType::Char(_) | AttributeType::VarChar(_), ColumnStats{distinct: Some(distinct), values, ..})
+                        if enum_candidates.contains(&column.column.as_str()) && !values.is_empty() => {
+                        (AttributeType::Enum{val: values.clone()}, format!("{} distinct values", distinct))
+                    },
+                    (AttributeType::VarChar(size), ColumnStats{max_length: Some(length), ..}) => {
+                        let tightened = length.max(&1).next_power_of_two();
+
+                        if tightened >= *size as u64 {
+                            return None;
+                        }
+
+                        (AttributeType::VarChar(tightened as u16), format!("max observed length {}", length))
+                    },
+                    (data_type, ColumnStats{min: Some(min), max: Some(max), ..}) => {
+                        let tightened = integer_type(*min, *max);
+
+                        if integer_bits(&tightened) >= integer_bits(data_type) {
+                            return None;
+                        }
+
+                        (tightened, format!("observed range {}..={}", min, max))
+                    },
+                    _ => return None,
+                };
+
+                let compatibility = match to.can_store(&attr.data_type) {
+                    true => Compatibility::Safe,
+                    false => Compatibility::Lossy,
+                };
+
+                let tightened = Attribute { data_type: to.clone(), raw_type: String::new(), ..attr.clone() };
+
+                Some(
+                    Suggestion {
+                        column: attr.name.clone(),
+                        from: attr.data_type.clone(),
+                        to,
+                        reason,
+                        alter: DDL(format!("ALTER TABLE {} MODIFY COLUMN {}", quote_ident(&self.name), tightened.column_definition())),
+                        compatibility,
+                    }
+                )
+            })
+            .collect()
+    }
+
+    /// Samples the data of the table and suggests narrower types for its columns (see `Table::suggestions`).
+    ///
+    /// The statistics are read with one query over the whole table, followed by one `SELECT DISTINCT` for every
+    /// `ENUM` candidate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error` if a query fails or its result could not be read.
+    pub fn suggest_tightening(&self, db: &DataBase) -> Result<Vec<Suggestion>, Error> {
+        let query = match self.stats_query() {
+            Some(query) => query,
+            None => return Ok(Vec::new()),
+        };
+
+        let statement = query.to_string();
+        let row: Vec<Value> = db.execute(
+            &SQL::Select(query),
+            |row| row.map(|row| row.unwrap().into_iter().map(Value::from).collect::<Vec<Value>>())
+        )?
+            .into_iter()
+            .next()
+            .unwrap_or(Ok(Vec::new()))
+            .map_err(|err| Error::query(&statement, err))?;
+
+        let mut stats = match TableStats::from_values(self, &row) {
+            Some(stats) => stats,
+            None => return Err(Error::Parse(format!("unexpected statistics of table {}", self.name))),
+        };
+
+        let candidates: Vec<String> = stats.enum_candidates(self)
+            .into_iter()
+            .map(|column| column.to_string())
+            .collect();
+
+        for column in candidates {
+            let query = self.distinct_values_query(&column);
+            let statement = query.to_string();
+            let values = db.execute(
+                &SQL::Select(query),
+                |row| row.map(|row| Value::from(row.unwrap().remove(0)))
+            )?;
+
+            let values = values.into_iter()
+                .map(|value| value.map(|value| match value {
+                    Value::Text(val) => val,
+                    value => value.to_string(),
+                }))
+                .collect::<Result<Vec<String>, mysql::Error>>()
+                .map_err(|err| Error::query(&statement, err))?;
+
+            if let Some(column) = stats.columns.iter_mut().find(|stats| stats.column == column) {
+                column.values = values;
+            }
+        }
+
+        Ok(self.suggestions(&stats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::sql::{QDL, DDL, value::Value};
+
+    use super::{
+        super::table::{Table, Attribute, AttributeType, Constraint},
+        TableStats,
+        ColumnStats,
+        Compatibility,
+    };
+
+    fn attribute(name: &str, data_type: AttributeType, constraint: HashSet<Constraint>) -> Attribute {
+        Attribute {
+            name: String::from(name),
+            data_type,
+            raw_type: String::new(),
+            constraint,
+        }
+    }
+
+    fn post() -> Table {
+        Table {
+            name: String::from("post"),
+            attributes: vec![
+                attribute("id", AttributeType::Int(11), HashSet::from([Constraint::AutoIncrement])),
+                attribute("title", AttributeType::VarChar(255), HashSet::from([Constraint::NotNull])),
+                attribute("status", AttributeType::VarChar(32), HashSet::new()),
+                attribute("views", AttributeType::Int(11), HashSet::new()),
+                attribute("body", AttributeType::Text, HashSet::new()),
+            ],
+            primary_key: vec![0],
+            indexes: Vec::new(),
+        }
+    }
+
+    fn text(val: &str) -> Value {
+        Value::Text(String::from(val))
+    }
+
+    #[test]
+    fn stats_query_test() {
+        assert_eq!(
+            post().stats_query(),
+            Some(QDL(String::from(
+                "SELECT COUNT(*),MAX(CHAR_LENGTH(title)),COUNT(DISTINCT title),MAX(CHAR_LENGTH(status)),\
+                COUNT(DISTINCT status),MIN(views),MAX(views) FROM post"
+            )))
+        );
+        assert_eq!(
+            post().distinct_values_query("status"),
+            QDL(String::from("SELECT DISTINCT status FROM post WHERE status IS NOT NULL ORDER BY status"))
+        );
+
+        let mut table = post();
+        table.attributes.retain(|attr| attr.name == "id" || attr.name == "body");
+        assert_eq!(table.stats_query(), None);
+
+        //reserved words and spaces are quoted
+        let mut table = post();
+        table.name = String::from("order");
+        table.attributes.retain(|attr| attr.name == "status");
+        table.attributes[0].name = String::from("status code");
+        assert_eq!(
+            table.stats_query(),
+            Some(QDL(String::from("SELECT COUNT(*),MAX(CHAR_LENGTH(`status code`)),COUNT(DISTINCT `status code`) FROM `order`")))
+        );
+        assert_eq!(
+            table.distinct_values_query("status code"),
+            QDL(String::from("SELECT DISTINCT `status code` FROM `order` WHERE `status code` IS NOT NULL ORDER BY `status code`"))
+        );
+    }
+
+    #[test]
+    fn stats_from_values_test() {
+        let stats = TableStats::from_values(
+            &post(),
+            &[text("500"), text("41"), text("480"), text("8"), text("4"), text("-3"), text("12044")]
+        ).unwrap();
+
+        assert_eq!(stats.rows, 500);
+        assert_eq!(
+            stats.columns,
+            vec![
+                ColumnStats{ column: String::from("title"), max_length: Some(41), distinct: Some(480), ..ColumnStats::default() },
+                ColumnStats{ column: String::from("status"), max_length: Some(8), distinct: Some(4), ..ColumnStats::default() },
+                ColumnStats{ column: String::from("views"), min: Some(-3), max: Some(12044), ..ColumnStats::default() },
+            ]
+        );
+        assert_eq!(stats.enum_candidates(&post()), vec!["status"]);
+        assert_eq!(
+            stats.to_string(),
+            "500 rows\n\
+            column  max length  distinct  min     max\n\
+            title           41       480    -       -\n\
+            status           8         4    -       -\n\
+            views            -         -   -3  12,044"
+        );
+
+        assert_eq!(TableStats::from_values(&post(), &[text("500")]), None);
+    }
+
+    #[test]
+    fn suggestions_test() {
+        let table = post();
+        let mut stats = TableStats::from_values(
+            &table,
+            &[text("500"), text("41"), text("480"), text("8"), text("4"), text("-3"), text("12044")]
+        ).unwrap();
+        stats.columns[1].values = vec![
+            String::from("archived"),
+            String::from("draft"),
+            String::from("live"),
+            String::from("review"),
+        ];
+
+        let suggestions = table.suggestions(&stats);
+
+        assert_eq!(
+            suggestions.iter().map(|suggestion| suggestion.to_string()).collect::<Vec<String>>(),
+            vec![
+                "title: varchar(255) -> varchar(64) (max observed length 41) [lossy]",
+                "status: varchar(32) -> enum('archived','draft','live','review') (4 distinct values) [lossy]",
+                "views: int(11) -> smallint(6) (observed range -3..=12044) [lossy]",
+            ]
+        );
+        assert_eq!(
+            suggestions.iter().map(|suggestion| suggestion.alter.clone()).collect::<Vec<DDL>>(),
+            vec![
+                DDL(String::from("ALTER TABLE post MODIFY COLUMN title varchar(64) NOT NULL")),
+                DDL(String::from("ALTER TABLE post MODIFY COLUMN status enum('archived','draft','live','review')")),
+                DDL(String::from("ALTER TABLE post MODIFY COLUMN views smallint(6)")),
+            ]
+        );
+        assert!(suggestions.iter().all(|suggestion| suggestion.compatibility == Compatibility::Lossy));
+    }
+
+    #[test]
+    fn suggestions_test_2() {
+        //already tight, empty or too few rows for an enum
+        let table = post();
+
+        let stats = TableStats::from_values(
+            &table,
+            &[text("20"), text("200"), text("20"), text("5"), text("4"), Value::Null, Value::Null]
+        ).unwrap();
+
+        assert_eq!(stats.enum_candidates(&table), Vec::<&str>::new());
+        assert_eq!(
+            table.suggestions(&stats).iter().map(|suggestion| suggestion.to_string()).collect::<Vec<String>>(),
+            vec!["status: varchar(32) -> varchar(8) (max observed length 5) [lossy]"]
+        );
+    }
+}
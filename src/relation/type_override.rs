@@ -0,0 +1,215 @@
+use std::fmt;
+
+use super::table::{Table, AttributeType};
+
+/// Maps `table.column` glob patterns to the `AttributeType` a column should be loaded as.
+///
+/// Patterns support `*` (any run of characters) and `?` (any single character). A pattern without a `.` matches the
+/// column in every table. When several patterns match a column, the first one added is used.
+#[derive(Clone, Debug, Default)]
+pub struct TypeOverrideMap {
+    overrides: Vec<(String, AttributeType)>,
+}
+
+/// Represents possible errors that can occur when applying type overrides.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeOverrideError {
+    /// The column's storage cannot hold every value of the override type.
+    Incompatible{table: String, column: String, raw_type: String, data_type: String},
+}
+
+impl fmt::Display for TypeOverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeOverrideError::Incompatible{table, column, raw_type, data_type} => write!(
+                f,
+                "{}.{} is stored as {} and cannot be overridden as {}",
+                table, column, raw_type, data_type
+            ),
+        }
+    }
+}
+
+impl TypeOverrideMap {
+    pub fn new() -> TypeOverrideMap {
+        TypeOverrideMap::default()
+    }
+
+    /// Adds an override for every column matching `pattern`.
+    pub fn insert(&mut self, pattern: &str, data_type: AttributeType) -> &mut TypeOverrideMap {
+        self.overrides.push((pattern.to_string(), data_type));
+        self
+    }
+
+    /// Returns the override for `table.column`, if any pattern matches it.
+    pub fn get(&self, table: &str, column: &str) -> Option<&AttributeType> {
+        let qualified = format!("{}.{}", table, column);
+
+        self.overrides
+            .iter()
+            .find(|(pattern, _)| match pattern.contains('.') {
+                true => glob_match(pattern, &qualified),
+                false => glob_match(pattern, column),
+            })
+            .map(|(_, data_type)| data_type)
+    }
+
+    /// Replaces the data type of every attribute of `table` that has an override.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TypeOverrideError::Incompatible` if an override does not fit in the column's parsed type (see
+    /// `AttributeType::can_store`). The table is left unchanged in that case.
+    pub fn apply(&self, table: &mut Table) -> Result<(), TypeOverrideError> {
+        let mut overridden: Vec<(usize, AttributeType)> = Vec::new();
+
+        for (i, attr) in table.attributes.iter().enumerate() {
+            let data_type = match self.get(&table.name, &attr.name) {
+                Some(data_type) => data_type,
+                None => continue,
+            };
+
+            if !attr.data_type.can_store(data_type) {
+                return Err(
+                    TypeOverrideError::Incompatible{
+                        table: table.name.clone(),
+                        column: attr.name.clone(),
+                        raw_type: attr.type_definition(),
+                        data_type: data_type.to_string(),
+                    }
+                );
+            }
+
+            overridden.push((i, data_type.clone()));
+        }
+
+        for (i, data_type) in overridden {
+            table.attributes[i].data_type = data_type;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns true if `text` matches the glob `pattern`, case insensitively.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    //position to resume from after the last `*`, as (pattern index, text index)
+    let mut backtrack: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            },
+            Some(ch) if *ch == '?' || *ch == text[t] => {
+                p += 1;
+                t += 1;
+            },
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    t = matched + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|ch| *ch == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::relation::table::{Table, Attribute, AttributeType};
+
+    use super::{TypeOverrideMap, TypeOverrideError, glob_match};
+
+    fn table(name: &str, attributes: Vec<(&str, AttributeType)>) -> Table {
+        Table{
+            name: name.to_string(),
+            attributes: attributes.into_iter()
+                .map(|(name, data_type)| Attribute{
+                    name: name.to_string(),
+                    data_type,
+                    raw_type: String::new(),
+                    constraint: HashSet::new()
+                })
+                .collect(),
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
+        }
+    }
+
+    fn types(table: &Table) -> Vec<String> {
+        table.attributes.iter().map(|attr| format!("{:?}", attr.data_type)).collect()
+    }
+
+    #[test]
+    fn glob_match_test() {
+        assert!(glob_match("*.is_*", "users.is_active"));
+        assert!(glob_match("users.*_id", "Users.account_id"));
+        assert!(glob_match("user?.id", "users.id"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("users.*_id", "orders.account_id"));
+        assert!(!glob_match("user?.id", "user.id"));
+    }
+
+    #[test]
+    fn apply_test_1() {
+        let mut overrides = TypeOverrideMap::new();
+        overrides
+            .insert("*.is_*", AttributeType::Bool)
+            .insert("*.uuid", AttributeType::Uuid)
+            .insert("owner", AttributeType::Uuid);
+
+        let mut users = table("users", vec![
+            ("is_active", AttributeType::TinyInt(1)),
+            ("uuid", AttributeType::Char(36)),
+            ("name", AttributeType::VarChar(255)),
+        ]);
+        let mut orders = table("orders", vec![
+            ("is_paid", AttributeType::TinyInt(1)),
+            ("owner", AttributeType::VarChar(64)),
+        ]);
+
+        assert_eq!(overrides.apply(&mut users), Ok(()));
+        assert_eq!(overrides.apply(&mut orders), Ok(()));
+
+        assert_eq!(types(&users), vec!["Bool", "Uuid", "VarChar(255)"]);
+        assert_eq!(types(&orders), vec!["Bool", "Uuid"]);
+    }
+
+    #[test]
+    fn apply_test_2() {
+        let mut overrides = TypeOverrideMap::new();
+        overrides
+            .insert("users.is_active", AttributeType::Bool)
+            .insert("users.code", AttributeType::BigInt(20));
+
+        let mut users = table("users", vec![
+            ("is_active", AttributeType::TinyInt(1)),
+            ("code", AttributeType::VarChar(10)),
+        ]);
+
+        assert_eq!(
+            overrides.apply(&mut users),
+            Err(TypeOverrideError::Incompatible{
+                table: String::from("users"),
+                column: String::from("code"),
+                raw_type: String::from("varchar(10)"),
+                data_type: String::from("bigint(20)"),
+            })
+        );
+
+        //nothing is applied when an override is rejected
+        assert_eq!(types(&users), vec!["TinyInt(1)", "VarChar(10)"]);
+    }
+}
@@ -1,7 +1,7 @@
 use regex::Regex;
 use lazy_static::lazy_static;
 
-use crate::{ sql::{QDL, SQL, DDL}, data_base::DataBase};
+use crate::{ sql::{QDL, SQL, DDL, quote_ident}, data_base::{DataBase, Executor}, error::{Error, ER_NO_SUCH_TABLE}, instrument::Timed};
 
 use super::RelationMethods;
 
@@ -15,40 +15,57 @@ pub struct View{
 
 impl View {
     /// Returns a `View` created from a database with the given name.
-    pub fn from_db(name: &str) -> Option<View> {
-        let db = DataBase::from_env().unwrap();
-
-        let tmp : Vec<String> = db.execute(
-            &SQL::new(&format!(r"SHOW CREATE TABLE {}", name)).unwrap(),
-            |row| {
-                let tmp_str: String = row.unwrap().get(1).unwrap();
-
-                lazy_static! {
-                    static ref VIEW_REGEX : Regex = Regex::new("[sS][eE][lL][eE][cC][tT] .+").unwrap();
-                };
-
-                VIEW_REGEX.captures(&tmp_str)
-                    .unwrap()
-                    .get(0)
-                    .unwrap()
-                    .as_str()
-                    .to_string()
-            }
-        ).unwrap();
-
-        match tmp.get(0) {
-            Some(query) => {
-                //info!("View::from_db - {:?}", query);
-                Some(
-                    View {
-                        name: name.to_string(),
-                        query: SQL::new(query).unwrap().qdl().unwrap().clone()
-                    }
-                )
-            },
-            None => None
-        }
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Config` if no connection could be opened, and the errors of `View::from_db_in`.
+    pub fn from_db(name: &str) -> Result<View, Error> {
+        View::from_db_in(&DataBase::from_env()?, name)
+    }
+
+    /// Same as `View::from_db`, but reads the view from `db`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::NotFound` if the database has no view `name`, `Error::Parse` if its query could not be read
+    /// from its definition, and the errors of `DataBase::execute`.
+    pub fn from_db_in<E: Executor>(db: &E, name: &str) -> Result<View, Error> {
+        let timed = Timed::schema_load(name);
+
+        let view = timed.in_scope(|| View::load(db, name));
+
+        timed.finish(None);
+
+        view
+    }
+
+    /// Reads the query of the view `name` from `db`, for `View::from_db_in`.
+    fn load<E: Executor>(db: &E, name: &str) -> Result<View, Error> {
+        lazy_static! {
+            static ref VIEW_REGEX : Regex = Regex::new("[sS][eE][lL][eE][cC][tT] .+").unwrap();
+        };
+
+        let statement = SQL::new(&format!(r"SHOW CREATE TABLE {}", name)).unwrap();
+
+        let rows: Vec<Result<String, mysql::Error>> = db.execute(
+            &statement,
+            |row| row.map(|row| row.get::<String, usize>(1).unwrap_or_default())
+        ).map_err(|err| match err.code() {
+            Some(ER_NO_SUCH_TABLE) => Error::NotFound(name.to_string()),
+            _ => err,
+        })?;
+
+        let definition = match rows.into_iter().next() {
+            Some(definition) => definition.map_err(|err| Error::query(&statement.to_string(), err))?,
+            None => return Err(Error::NotFound(name.to_string())),
+        };
+
+        let query = VIEW_REGEX.find(&definition)
+            .and_then(|query| SQL::new(query.as_str()).ok())
+            .and_then(|query| query.qdl().cloned())
+            .ok_or_else(|| Error::Parse(format!("the definition of view {}: {}", name, definition)))?;
 
+        Ok(View { name: name.to_string(), query })
     }
 
     /// Returns a new `View` with the given name and query.
@@ -63,14 +80,14 @@ impl View {
 impl RelationMethods for View{
     /// Returns a `QDL` representing a `SELECT` statement for the view.
     fn select(&self) -> QDL {
-        QDL(format!("SELECT * FROM {}", self.name))
+        QDL(format!("SELECT * FROM {}", quote_ident(&self.name)))
     }
     /// Returns a `DDL` representing a `DROP` statement for the view.
     fn drop(&self) -> DDL{
-        DDL(format!("DROP VIEW {}", self.name))
+        DDL(format!("DROP VIEW {}", quote_ident(&self.name)))
     }
     /// Returns a `DDL` representing a `CREATE` statement for the view.
     fn create(&self) -> DDL {
-        SQL::new(&format!("CREATE VIEW {} AS {}", self.name, *self.query)).unwrap().ddl().unwrap().clone()
+        SQL::new(&format!("CREATE VIEW {} AS {}", quote_ident(&self.name), *self.query)).unwrap().ddl().unwrap().clone()
     }
 }
\ No newline at end of file
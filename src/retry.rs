@@ -0,0 +1,187 @@
+use std::{thread, time::Duration};
+
+use crate::error::Error;
+
+/// The error of a connection the server had already closed, as after its `wait_timeout`.
+pub const CR_SERVER_GONE_ERROR: u16 = 2006;
+/// The error of a connection lost while a statement was running.
+pub const CR_SERVER_LOST: u16 = 2013;
+
+/// How often a `DataBase` runs a statement again after a transient failure (see `DataBase::with_retry`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The number of times a statement is run at most, counting the first. `0` and `1` both disable retries.
+    pub max_attempts: u32,
+    /// The wait before the first retry, doubled before every later one.
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy { max_attempts: 3, backoff: Duration::from_millis(100) }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the wait before retry number `retry`, counting from 1.
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff.saturating_mul(2_u32.saturating_pow(retry.saturating_sub(1)))
+    }
+
+    /// Runs `executor` until it succeeds, fails with an error that is not transient (see `is_transient`), or has run
+    /// `max_attempts` times, waiting `RetryPolicy::delay` between attempts.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt.
+    pub fn run<T, E: RetryExecutor<T>>(&self, executor: &mut E, idempotent: bool) -> Result<T, Error> {
+        let mut attempt = 1;
+
+        loop {
+            match executor.attempt() {
+                Err(err) if attempt < self.max_attempts && is_transient(&err, idempotent) => {
+                    let delay = self.delay(attempt);
+
+                    log::warn!("Retrying in {:?} after attempt {} failed - Err:{}", delay, attempt, err);
+
+                    executor.wait(delay);
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Returns true if statements that failed with `err` can be run again.
+///
+/// A deadlock rolls the whole transaction back, so it is always retried. A lost connection may have been lost after
+/// the server applied a write, so it is only retried if the statements are `idempotent`. Statements rejected because
+/// the `DataBase` is shutting down are never retried.
+pub fn is_transient(err: &Error, idempotent: bool) -> bool {
+    match err {
+        Error::ShuttingDown => false,
+        Error::Deadlock{..} => true,
+        Error::Connection(_) => idempotent,
+        err => idempotent && matches!(err.code(), Some(CR_SERVER_GONE_ERROR | CR_SERVER_LOST)),
+    }
+}
+
+/// The statements a `RetryPolicy` runs, and how it waits between them.
+pub trait RetryExecutor<T> {
+    /// Runs the statements once.
+    fn attempt(&mut self) -> Result<T, Error>;
+    /// Waits `delay` before the next attempt.
+    fn wait(&mut self, delay: Duration);
+}
+
+impl<T, F> RetryExecutor<T> for F where F: FnMut() -> Result<T, Error> {
+    fn attempt(&mut self) -> Result<T, Error> {
+        self()
+    }
+
+    fn wait(&mut self, delay: Duration) {
+        thread::sleep(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, io, time::Duration};
+
+    use mysql::MySqlError;
+
+    use crate::error::{Error, ER_DUP_ENTRY, ER_LOCK_DEADLOCK};
+
+    use super::{RetryExecutor, RetryPolicy, CR_SERVER_LOST, is_transient};
+
+    /// Fails with the queued errors in order, then succeeds with the number of attempts made.
+    #[derive(Default)]
+    struct MockExecutor {
+        errors: VecDeque<Error>,
+        attempts: u32,
+        waits: Vec<Duration>,
+    }
+
+    impl RetryExecutor<u32> for MockExecutor {
+        fn attempt(&mut self) -> Result<u32, Error> {
+            self.attempts += 1;
+
+            match self.errors.pop_front() {
+                Some(err) => Err(err),
+                None => Ok(self.attempts),
+            }
+        }
+
+        fn wait(&mut self, delay: Duration) {
+            self.waits.push(delay);
+        }
+    }
+
+    fn server_error(code: u16) -> Error {
+        let err = MySqlError{state: String::from("40001"), message: String::from("failed"), code};
+
+        Error::query("UPDATE user SET name = 'a'", mysql::Error::MySqlError(err))
+    }
+
+    fn lost_connection() -> Error {
+        Error::Connection(mysql::Error::IoError(io::Error::from(io::ErrorKind::ConnectionReset)))
+    }
+
+    fn executor(errors: Vec<Error>) -> MockExecutor {
+        MockExecutor { errors: errors.into(), ..Default::default() }
+    }
+
+    #[test]
+    fn delay_test() {
+        let policy = RetryPolicy { max_attempts: 5, backoff: Duration::from_millis(10) };
+
+        assert_eq!(
+            (1..5).map(|retry| policy.delay(retry)).collect::<Vec<Duration>>(),
+            vec![Duration::from_millis(10), Duration::from_millis(20), Duration::from_millis(40), Duration::from_millis(80)]
+        );
+        assert_eq!(policy.delay(64), Duration::from_millis(10) * u32::MAX);
+    }
+
+    #[test]
+    fn is_transient_test() {
+        assert!(is_transient(&server_error(ER_LOCK_DEADLOCK), false));
+        assert!(is_transient(&lost_connection(), true));
+        assert!(!is_transient(&lost_connection(), false));
+        assert!(is_transient(&server_error(CR_SERVER_LOST), true));
+        assert!(!is_transient(&server_error(CR_SERVER_LOST), false));
+        assert!(!is_transient(&server_error(ER_DUP_ENTRY), true));
+        assert!(!is_transient(&Error::ShuttingDown, true));
+    }
+
+    #[test]
+    fn run_test() {
+        let policy = RetryPolicy { max_attempts: 4, backoff: Duration::from_millis(10) };
+
+        let mut deadlocks = executor(vec![server_error(ER_LOCK_DEADLOCK), server_error(ER_LOCK_DEADLOCK)]);
+        assert_eq!(policy.run(&mut deadlocks, false).unwrap(), 3);
+        assert_eq!(deadlocks.waits, vec![Duration::from_millis(10), Duration::from_millis(20)]);
+
+        let mut exhausted = executor((0..5).map(|_| lost_connection()).collect());
+        assert!(matches!(policy.run(&mut exhausted, true), Err(Error::Connection(_))));
+        assert_eq!(exhausted.attempts, 4);
+        assert_eq!(
+            exhausted.waits,
+            vec![Duration::from_millis(10), Duration::from_millis(20), Duration::from_millis(40)]
+        );
+
+        let mut write = executor(vec![lost_connection()]);
+        assert!(policy.run(&mut write, false).is_err());
+        assert_eq!(write.attempts, 1);
+        assert!(write.waits.is_empty());
+
+        let mut duplicate = executor(vec![server_error(ER_DUP_ENTRY)]);
+        assert!(matches!(policy.run(&mut duplicate, true), Err(Error::DuplicateKey{..})));
+        assert_eq!(duplicate.attempts, 1);
+
+        let disabled = RetryPolicy { max_attempts: 1, ..policy };
+        let mut deadlock = executor(vec![server_error(ER_LOCK_DEADLOCK)]);
+        assert!(disabled.run(&mut deadlock, false).is_err());
+        assert_eq!(deadlock.attempts, 1);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fs::File, io::{Read, Error}};
+use std::{fs::File, io::{Read, Error}, ops::Range};
 
 use regex::{Regex, Captures};
 use lazy_static::lazy_static;
@@ -11,6 +11,9 @@ use lazy_static::lazy_static;
 ///
 /// The read type must be either "B" for binary data or "S" for a string.
 ///
+/// Directives inside single quoted string literals are left as they are, so a value can hold text that looks like
+/// one.
+///
 /// If the file cannot be opened or read, an error is returned.
 ///
 /// # Examples
@@ -35,46 +38,74 @@ pub fn contents(cmd: &str) -> Result<String, Error> {
 
     let mut cmd = cmd.to_string();
 
-    let mut err_flag:Option<Error> = None;
+    while let Some(caps) = next_directive(&FILE_INPUT_REGEX, &cmd) {
+        let range = caps.get(0).unwrap().range();
+        let file_path = &caps[1];
+        let read_type = &caps[2];
 
-    while FILE_INPUT_REGEX.is_match(&cmd) && err_flag.is_none() {
-        cmd = FILE_INPUT_REGEX.replace(
-            &cmd,
-            |caps: &Captures| {
-                let file_path = &caps[1];
-                let read_type = &caps[2];
+        let mut file = File::open(file_path)?;
 
-                let file = File::open(file_path);
+        let contents = match read_type {
+            "B" => todo!(),//binary
+            "S" => { //string
+                let mut contents: String = String::new();
+                file.read_to_string(&mut contents)?;
 
-                if let Err(err) = file {
-                    err_flag = Some(err);
-                    return format!("");
-                }
+                contents
+            },
+            _=> panic!()
+        };
+
+        cmd.replace_range(range, &contents);
+    }
 
-                let mut file = file.unwrap();
+    Ok(cmd)
+}
 
-                match read_type {
-                    "B" => todo!(),//binary
-                    "S" => { //string
-                        let mut contents: String = String::new();
-                        let result = file.read_to_string(&mut contents);
+/// Returns the first directive of `cmd` that is not inside a single quoted string literal.
+///
+/// Values are written into statements as single quoted literals (see `value::escape_string`), so text in a value that
+/// looks like a directive is left as it is. Directives in double quoted strings are still replaced.
+fn next_directive<'a>(regex: &Regex, cmd: &'a str) -> Option<Captures<'a>> {
+    let literals = single_quoted(cmd);
+
+    regex.captures_iter(cmd)
+        .find(|caps| {
+            let start = caps.get(0).unwrap().start();
 
-                        if let Err(err) = result{
-                            err_flag = Some(err);
-                            return format!("");
-                        }
+            !literals.iter().any(|literal| literal.contains(&start))
+        })
+}
+
+/// Returns the byte ranges of the single quoted string literals of `cmd`, quotes included.
+///
+/// Double quoted strings and backtick quoted identifiers are skipped, so a `'` inside them does not start a literal.
+/// A literal that is not closed runs to the end of `cmd`.
+fn single_quoted(cmd: &str) -> Vec<Range<usize>> {
+    let mut literals: Vec<Range<usize>> = Vec::new();
+    let mut open: Option<(usize, char)> = None;
+    let mut chars = cmd.char_indices();
 
-                        return contents
-                    },
-                    _=> panic!()
+    while let Some((index, ch)) = chars.next() {
+        match open {
+            Some((_, quote)) if ch == '\\' && quote != '`' => {
+                chars.next();
+            },
+            Some((start, quote)) if ch == quote => {
+                if quote == '\'' {
+                    literals.push(start..index + 1);
                 }
-            }
-        ).to_string();
+                open = None;
+            },
+            Some(_) => {},
+            None if matches!(ch, '\'' | '"' | '`') => open = Some((index, ch)),
+            None => {},
+        }
     }
 
-    if err_flag.is_some() {
-        return Err(err_flag.unwrap())
+    if let Some((start, '\'')) = open {
+        literals.push(start..cmd.len());
     }
 
-    Ok(cmd.to_string())
-}
\ No newline at end of file
+    literals
+}
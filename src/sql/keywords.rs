@@ -0,0 +1,354 @@
+use std::{fmt, cmp::Ordering};
+
+/// Maximum length, in characters, of a MySQL identifier.
+pub const MAX_IDENTIFIER_LENGTH: usize = 64;
+
+/// The version of a MySQL server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServerVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+
+impl ServerVersion {
+    pub const fn new(major: u16, minor: u16, patch: u16) -> ServerVersion {
+        ServerVersion { major, minor, patch }
+    }
+
+    /// Parses a version string as returned by `SELECT VERSION()`, ignoring any suffix (`8.0.31-0ubuntu0.22.04.1`).
+    pub fn parse(version: &str) -> Option<ServerVersion> {
+        let mut parts = version
+            .split(|ch: char| !ch.is_ascii_digit() && ch != '.')
+            .next()?
+            .split('.')
+            .map(|part| part.parse::<u16>());
+
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+
+        Some(ServerVersion { major, minor, patch })
+    }
+}
+
+impl Default for ServerVersion {
+    /// Defaults to the latest version the keyword list covers.
+    fn default() -> Self {
+        ServerVersion::new(8, 0, 31)
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The server a version belongs to, as MariaDB numbers its versions apart from MySQL, so `10.6` is not newer than `8.0`
+/// in features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServerFlavor {
+    MySql,
+    MariaDb,
+}
+
+impl ServerFlavor {
+    /// Returns the flavor of a version string as returned by `SELECT VERSION()`, which MariaDB suffixes with
+    /// `-MariaDB` (`10.6.12-MariaDB-0ubuntu0.22.04.1`).
+    pub fn parse(version: &str) -> ServerFlavor {
+        match version.to_ascii_lowercase().contains("mariadb") {
+            true => ServerFlavor::MariaDb,
+            false => ServerFlavor::MySql,
+        }
+    }
+}
+
+/// A reserved word and the server versions that reserve it.
+struct Keyword {
+    word: &'static str,
+    since: ServerVersion,
+    until: Option<ServerVersion>,
+}
+
+const V5_7: ServerVersion = ServerVersion::new(5, 7, 0);
+
+/// Reserved word which has been reserved since 5.7.
+const fn reserved(word: &'static str) -> Keyword {
+    Keyword { word, since: V5_7, until: None }
+}
+
+/// Reserved word which has been reserved since `major.minor.patch`.
+const fn reserved_since(word: &'static str, major: u16, minor: u16, patch: u16) -> Keyword {
+    Keyword { word, since: ServerVersion::new(major, minor, patch), until: None }
+}
+
+/// Reserved word which is no longer reserved from `major.minor.patch` onwards.
+const fn reserved_until(word: &'static str, major: u16, minor: u16, patch: u16) -> Keyword {
+    Keyword { word, since: V5_7, until: Some(ServerVersion::new(major, minor, patch)) }
+}
+
+/// MySQL reserved words, sorted so they can be binary searched.
+static KEYWORDS: [Keyword; 263] = [
+    reserved("ACCESSIBLE"), reserved("ADD"), reserved("ALL"), reserved("ALTER"), reserved_until("ANALYSE", 8, 0, 1),
+    reserved("ANALYZE"), reserved("AND"), reserved("AS"), reserved("ASC"), reserved("ASENSITIVE"),
+    reserved("BEFORE"), reserved("BETWEEN"), reserved("BIGINT"), reserved("BINARY"), reserved("BLOB"),
+    reserved("BOTH"), reserved("BY"), reserved("CALL"), reserved("CASCADE"), reserved("CASE"),
+    reserved("CHANGE"), reserved("CHAR"), reserved("CHARACTER"), reserved("CHECK"), reserved("COLLATE"),
+    reserved("COLUMN"), reserved("CONDITION"), reserved("CONSTRAINT"), reserved("CONTINUE"), reserved("CONVERT"),
+    reserved("CREATE"), reserved("CROSS"), reserved_since("CUBE", 8, 0, 1), reserved_since("CUME_DIST", 8, 0, 2),
+    reserved("CURRENT_DATE"), reserved("CURRENT_TIME"), reserved("CURRENT_TIMESTAMP"), reserved("CURRENT_USER"),
+    reserved("CURSOR"), reserved("DATABASE"), reserved("DATABASES"), reserved("DAY_HOUR"),
+    reserved("DAY_MICROSECOND"), reserved("DAY_MINUTE"), reserved("DAY_SECOND"), reserved("DEC"),
+    reserved("DECIMAL"), reserved("DECLARE"), reserved("DEFAULT"), reserved("DELAYED"), reserved("DELETE"),
+    reserved_since("DENSE_RANK", 8, 0, 2), reserved("DESC"), reserved("DESCRIBE"), reserved("DETERMINISTIC"),
+    reserved("DISTINCT"), reserved("DISTINCTROW"), reserved("DIV"), reserved("DOUBLE"), reserved("DROP"),
+    reserved("DUAL"), reserved("EACH"), reserved("ELSE"), reserved("ELSEIF"), reserved_since("EMPTY", 8, 0, 4),
+    reserved("ENCLOSED"), reserved("ESCAPED"), reserved_since("EXCEPT", 8, 0, 0), reserved("EXISTS"),
+    reserved("EXIT"), reserved("EXPLAIN"), reserved("FALSE"), reserved("FETCH"),
+    reserved_since("FIRST_VALUE", 8, 0, 2), reserved("FLOAT"), reserved("FLOAT4"), reserved("FLOAT8"),
+    reserved("FOR"), reserved("FORCE"), reserved("FOREIGN"), reserved("FROM"), reserved("FULLTEXT"),
+    reserved_since("FUNCTION", 8, 0, 1), reserved("GENERATED"), reserved("GET"), reserved("GRANT"),
+    reserved("GROUP"), reserved_since("GROUPING", 8, 0, 1), reserved_since("GROUPS", 8, 0, 2), reserved("HAVING"),
+    reserved("HIGH_PRIORITY"), reserved("HOUR_MICROSECOND"), reserved("HOUR_MINUTE"), reserved("HOUR_SECOND"),
+    reserved("IF"), reserved("IGNORE"), reserved("IN"), reserved("INDEX"), reserved("INFILE"), reserved("INNER"),
+    reserved("INOUT"), reserved("INSENSITIVE"), reserved("INSERT"), reserved("INT"), reserved("INT1"),
+    reserved("INT2"), reserved("INT3"), reserved("INT4"), reserved("INT8"), reserved("INTEGER"),
+    reserved_since("INTERSECT", 8, 0, 31), reserved("INTERVAL"), reserved("INTO"), reserved("IO_AFTER_GTIDS"),
+    reserved("IO_BEFORE_GTIDS"), reserved("IS"), reserved("ITERATE"), reserved("JOIN"),
+    reserved_since("JSON_TABLE", 8, 0, 4), reserved("KEY"), reserved("KEYS"), reserved("KILL"),
+    reserved_since("LAG", 8, 0, 2), reserved_since("LAST_VALUE", 8, 0, 2), reserved_since("LATERAL", 8, 0, 14),
+    reserved_since("LEAD", 8, 0, 2), reserved("LEADING"), reserved("LEAVE"), reserved("LEFT"), reserved("LIKE"),
+    reserved("LIMIT"), reserved("LINEAR"), reserved("LINES"), reserved("LOAD"), reserved("LOCALTIME"),
+    reserved("LOCALTIMESTAMP"), reserved("LOCK"), reserved("LONG"), reserved("LONGBLOB"), reserved("LONGTEXT"),
+    reserved("LOOP"), reserved("LOW_PRIORITY"), reserved("MASTER_BIND"), reserved("MASTER_SSL_VERIFY_SERVER_CERT"),
+    reserved("MATCH"), reserved("MAXVALUE"), reserved("MEDIUMBLOB"), reserved("MEDIUMINT"), reserved("MEDIUMTEXT"),
+    reserved("MIDDLEINT"), reserved("MINUTE_MICROSECOND"), reserved("MINUTE_SECOND"), reserved("MOD"),
+    reserved("MODIFIES"), reserved("NATURAL"), reserved("NOT"), reserved("NO_WRITE_TO_BINLOG"),
+    reserved_since("NTH_VALUE", 8, 0, 2), reserved_since("NTILE", 8, 0, 2), reserved("NULL"), reserved("NUMERIC"),
+    reserved_since("OF", 8, 0, 1), reserved("ON"), reserved("OPTIMIZE"), reserved("OPTIMIZER_COSTS"),
+    reserved("OPTION"), reserved("OPTIONALLY"), reserved("OR"), reserved("ORDER"), reserved("OUT"),
+    reserved("OUTER"), reserved("OUTFILE"), reserved_since("OVER", 8, 0, 2), reserved("PARTITION"),
+    reserved_since("PERCENT_RANK", 8, 0, 2), reserved("PRECISION"), reserved("PRIMARY"), reserved("PROCEDURE"),
+    reserved("PURGE"), reserved("RANGE"), reserved_since("RANK", 8, 0, 2), reserved("READ"), reserved("READS"),
+    reserved("READ_WRITE"), reserved("REAL"), reserved_since("RECURSIVE", 8, 0, 1), reserved("REFERENCES"),
+    reserved("REGEXP"), reserved("RELEASE"), reserved("RENAME"), reserved("REPEAT"), reserved("REPLACE"),
+    reserved("REQUIRE"), reserved("RESIGNAL"), reserved("RESTRICT"), reserved("RETURN"), reserved("REVOKE"),
+    reserved("RIGHT"), reserved("RLIKE"), reserved_since("ROW", 8, 0, 2), reserved_since("ROWS", 8, 0, 2),
+    reserved_since("ROW_NUMBER", 8, 0, 2), reserved("SCHEMA"), reserved("SCHEMAS"), reserved("SECOND_MICROSECOND"),
+    reserved("SELECT"), reserved("SENSITIVE"), reserved("SEPARATOR"), reserved("SET"), reserved("SHOW"),
+    reserved("SIGNAL"), reserved("SMALLINT"), reserved("SPATIAL"), reserved("SPECIFIC"), reserved("SQL"),
+    reserved("SQLEXCEPTION"), reserved("SQLSTATE"), reserved("SQLWARNING"), reserved("SQL_BIG_RESULT"),
+    reserved("SQL_CALC_FOUND_ROWS"), reserved("SQL_SMALL_RESULT"), reserved("SSL"), reserved("STARTING"),
+    reserved("STORED"), reserved("STRAIGHT_JOIN"), reserved_since("SYSTEM", 8, 0, 3), reserved("TABLE"),
+    reserved("TERMINATED"), reserved("THEN"), reserved("TINYBLOB"), reserved("TINYINT"), reserved("TINYTEXT"),
+    reserved("TO"), reserved("TRAILING"), reserved("TRIGGER"), reserved("TRUE"), reserved("UNDO"),
+    reserved("UNION"), reserved("UNIQUE"), reserved("UNLOCK"), reserved("UNSIGNED"), reserved("UPDATE"),
+    reserved("USAGE"), reserved("USE"), reserved("USING"), reserved("UTC_DATE"), reserved("UTC_TIME"),
+    reserved("UTC_TIMESTAMP"), reserved("VALUES"), reserved("VARBINARY"), reserved("VARCHAR"),
+    reserved("VARCHARACTER"), reserved("VARYING"), reserved("VIRTUAL"), reserved("WHEN"), reserved("WHERE"),
+    reserved("WHILE"), reserved_since("WINDOW", 8, 0, 2), reserved("WITH"), reserved("WRITE"), reserved("XOR"),
+    reserved("YEAR_MONTH"), reserved("ZEROFILL"),
+];
+
+/// Returns true if `word` is a reserved word on a server running `version`.
+///
+/// The comparison is case insensitive.
+pub fn is_reserved(word: &str, version: ServerVersion) -> bool {
+    let found = KEYWORDS.binary_search_by(|keyword| compare_ignore_case(keyword.word, word));
+
+    match found {
+        Ok(i) => {
+            let keyword = &KEYWORDS[i];
+
+            keyword.since <= version && keyword.until.is_none_or(|until| version < until)
+        },
+        Err(_) => false,
+    }
+}
+
+/// Compares an uppercase keyword against `word`, ignoring the case of `word`.
+fn compare_ignore_case(keyword: &str, word: &str) -> Ordering {
+    keyword.bytes().cmp(word.bytes().map(|byte| byte.to_ascii_uppercase()))
+}
+
+/// Represents the reasons an identifier can be rejected by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdentifierError {
+    /// The identifier is empty.
+    Empty,
+    /// The identifier is longer than `MAX_IDENTIFIER_LENGTH` characters.
+    TooLong{length: usize, limit: usize},
+    /// The identifier contains a character that is not allowed, even when quoted.
+    InvalidCharacter{character: char, position: usize},
+    /// The identifier ends with a space, which the server strips.
+    TrailingSpace,
+}
+
+impl fmt::Display for IdentifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentifierError::Empty => write!(f, "identifier is empty"),
+            IdentifierError::TooLong{length, limit} => {
+                write!(f, "identifier is {} characters long, the limit is {}", length, limit)
+            },
+            IdentifierError::InvalidCharacter{character, position} => {
+                write!(f, "identifier contains {:?} at position {}", character, position)
+            },
+            IdentifierError::TrailingSpace => write!(f, "identifier ends with a space"),
+        }
+    }
+}
+
+/// Checks that `identifier` is accepted by the server as a (quoted) identifier.
+///
+/// # Errors
+///
+/// Returns an `IdentifierError` if the identifier is empty, longer than 64 characters, ends with a space, or contains
+/// `NUL` or a character outside the Basic Multilingual Plane.
+pub fn validate_identifier(identifier: &str) -> Result<(), IdentifierError> {
+    if identifier.is_empty() {
+        return Err(IdentifierError::Empty);
+    }
+
+    let length = identifier.chars().count();
+    if length > MAX_IDENTIFIER_LENGTH {
+        return Err(IdentifierError::TooLong{length, limit: MAX_IDENTIFIER_LENGTH});
+    }
+
+    if let Some((position, character)) = identifier.chars()
+        .enumerate()
+        .find(|(_, ch)| *ch == '\0' || *ch as u32 > 0xFFFF) {
+        return Err(IdentifierError::InvalidCharacter{character, position});
+    }
+
+    if identifier.ends_with(' ') {
+        return Err(IdentifierError::TrailingSpace);
+    }
+
+    Ok(())
+}
+
+/// Returns `identifier` wrapped in backticks, with any backtick inside it doubled.
+///
+/// # Errors
+///
+/// Returns an `IdentifierError` if `identifier` fails `validate_identifier`.
+pub fn quote_identifier(identifier: &str) -> Result<String, IdentifierError> {
+    validate_identifier(identifier)?;
+
+    Ok(format!("`{}`", identifier.replace('`', "``")))
+}
+
+/// Returns `identifier` as it is written into generated statements: wrapped in backticks, with any backtick inside it
+/// doubled, if it has to be quoted on the latest server (see `needs_quoting`), otherwise as it is.
+///
+/// Unlike `quote_identifier` the identifier is not validated, so names read from the server are always written back.
+pub fn quote_ident(identifier: &str) -> String {
+    match needs_quoting(identifier, ServerVersion::default()) {
+        true => format!("`{}`", identifier.replace('`', "``")),
+        false => identifier.to_string(),
+    }
+}
+
+/// Returns true if `identifier` has to be quoted to be used on a server running `version`.
+pub fn needs_quoting(identifier: &str, version: ServerVersion) -> bool {
+    identifier.is_empty() ||
+        identifier.chars().all(|ch| ch.is_ascii_digit()) ||
+        !identifier.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$') ||
+        is_reserved(identifier, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        KEYWORDS, MAX_IDENTIFIER_LENGTH, ServerVersion, ServerFlavor, IdentifierError,
+        is_reserved, validate_identifier, quote_identifier, quote_ident, needs_quoting
+    };
+
+    #[test]
+    fn keywords_sorted_test() {
+        for pair in KEYWORDS.windows(2) {
+            assert!(pair[0].word < pair[1].word, "{} is not before {}", pair[0].word, pair[1].word);
+        }
+    }
+
+    #[test]
+    fn is_reserved_test() {
+        let v5_7 = ServerVersion::new(5, 7, 40);
+        let v8_0 = ServerVersion::new(8, 0, 31);
+
+        assert!(is_reserved("select", v5_7));
+        assert!(is_reserved("SELECT", v8_0));
+        assert!(!is_reserved("name", v8_0));
+
+        assert!(!is_reserved("rank", v5_7));
+        assert!(is_reserved("rank", v8_0));
+        assert!(!is_reserved("groups", v5_7));
+        assert!(is_reserved("Groups", v8_0));
+
+        assert!(!is_reserved("lateral", ServerVersion::new(8, 0, 13)));
+        assert!(is_reserved("lateral", ServerVersion::new(8, 0, 14)));
+
+        assert!(is_reserved("analyse", v5_7));
+        assert!(!is_reserved("analyse", v8_0));
+    }
+
+    #[test]
+    fn server_version_test() {
+        assert_eq!(ServerVersion::parse("8.0.31-0ubuntu0.22.04.1"), Some(ServerVersion::new(8, 0, 31)));
+        assert_eq!(ServerVersion::parse("5.7"), Some(ServerVersion::new(5, 7, 0)));
+        assert_eq!(ServerVersion::parse("MariaDB"), None);
+        assert!(ServerVersion::new(5, 7, 40) < ServerVersion::new(8, 0, 0));
+
+        assert_eq!(ServerFlavor::parse("10.6.12-MariaDB-0ubuntu0.22.04.1"), ServerFlavor::MariaDb);
+        assert_eq!(ServerFlavor::parse("5.5.5-10.11.2-mariadb"), ServerFlavor::MariaDb);
+        assert_eq!(ServerFlavor::parse("8.0.31-0ubuntu0.22.04.1"), ServerFlavor::MySql);
+    }
+
+    #[test]
+    fn validate_identifier_test() {
+        assert_eq!(validate_identifier("Person"), Ok(()));
+        assert_eq!(validate_identifier("first name"), Ok(()));
+        assert_eq!(validate_identifier(""), Err(IdentifierError::Empty));
+        assert_eq!(validate_identifier("name "), Err(IdentifierError::TrailingSpace));
+        assert_eq!(
+            validate_identifier("na\0me"),
+            Err(IdentifierError::InvalidCharacter{character: '\0', position: 2})
+        );
+        assert_eq!(
+            validate_identifier("\u{1F600}"),
+            Err(IdentifierError::InvalidCharacter{character: '\u{1F600}', position: 0})
+        );
+    }
+
+    #[test]
+    fn validate_identifier_length_test() {
+        assert_eq!(validate_identifier(&"a".repeat(MAX_IDENTIFIER_LENGTH)), Ok(()));
+        assert_eq!(
+            validate_identifier(&"a".repeat(MAX_IDENTIFIER_LENGTH + 1)),
+            Err(IdentifierError::TooLong{length: 65, limit: 64})
+        );
+
+        //length is counted in characters, not bytes
+        assert_eq!(validate_identifier(&"é".repeat(MAX_IDENTIFIER_LENGTH)), Ok(()));
+    }
+
+    #[test]
+    fn quote_identifier_test() {
+        let version = ServerVersion::default();
+
+        assert_eq!(quote_identifier("rank"), Ok(String::from("`rank`")));
+        assert_eq!(quote_identifier("we`ird"), Ok(String::from("`we``ird`")));
+        assert_eq!(quote_identifier(""), Err(IdentifierError::Empty));
+
+        assert!(needs_quoting("rank", version));
+        assert!(needs_quoting("first name", version));
+        assert!(needs_quoting("123", version));
+        assert!(!needs_quoting("first_name", version));
+
+        assert_eq!(quote_ident("first_name"), "first_name");
+        assert_eq!(quote_ident("order"), "`order`");
+        assert_eq!(quote_ident("we`ird"), "`we``ird`");
+    }
+}
@@ -7,6 +7,19 @@ use lazy_static::lazy_static;
 use super::data_base::{DataBase, DatabaseExecute, DatabaseError};
 
 mod file_insertion;
+pub mod value;
+pub mod result_set;
+pub mod keywords;
+pub mod profile;
+pub mod redact;
+pub mod tag;
+pub mod prepared;
+pub mod script;
+
+pub use keywords::{validate_identifier, quote_identifier, quote_ident, IdentifierError};
+pub use value::escape_string;
+pub use tag::{sanitize_tag, tag_statement};
+pub use prepared::Prepared;
 
 /// Represents possible errors that can occur when executing a SQL command.
 #[derive(Debug)]
@@ -45,14 +58,11 @@ impl DatabaseExecute for DDL{
         match db {
             Ok(db) => {
                 //let sql = SQL::from(self);
-                let tmp: Vec<T> = match db.execute(&self.into(), row_map){
-                    Ok(val) => val,
-                    Err(err) => return Err(SQLError::Execution(err)),
-                };
+                let tmp: Vec<T> = db.execute(&self.into(), row_map)?;
                 Ok(tmp)
             },
             Err(err) => {
-                Err(SQLError::FailedToConnect(err))
+                Err(SQLError::FailedToConnect(err.into()))
             }
         }
     }
@@ -116,14 +126,11 @@ impl DatabaseExecute for QDL{
         let db = DataBase::from_env();
         match db {
             Ok(db) => {
-                let tmp: Vec<T> = match db.execute(&self.into(), row_map){
-                    Ok(val) => val,
-                    Err(err) => return Err(SQLError::Execution(err)),
-                };
+                let tmp: Vec<T> = db.execute(&self.into(), row_map)?;
                 Ok(tmp)
             },
             Err(err) => {
-                Err(SQLError::FailedToConnect(err))
+                Err(SQLError::FailedToConnect(err.into()))
             }
         }
     }
@@ -183,14 +190,11 @@ impl DatabaseExecute for QML{
         let db = DataBase::from_env();
         match db {
             Ok(db) => {
-                let tmp: Vec<T> = match db.execute(&self.into(), row_map){
-                    Ok(val) => val,
-                    Err(err) => return Err(SQLError::Execution(err)),
-                };
+                let tmp: Vec<T> = db.execute(&self.into(), row_map)?;
                 Ok(tmp)
             },
             Err(err) => {
-                Err(SQLError::FailedToConnect(err))
+                Err(SQLError::FailedToConnect(err.into()))
             }
         }
     }
@@ -249,14 +253,11 @@ impl DatabaseExecute for DCL{
         let db = DataBase::from_env();
         match db {
             Ok(db) => {
-                let tmp: Vec<T> = match db.execute(&self.into(), row_map){
-                    Ok(val) => val,
-                    Err(err) => return Err(SQLError::Execution(err)),
-                };
+                let tmp: Vec<T> = db.execute(&self.into(), row_map)?;
                 Ok(tmp)
             },
             Err(err) => {
-                Err(SQLError::FailedToConnect(err))
+                Err(SQLError::FailedToConnect(err.into()))
             }
         }
     }
@@ -422,7 +423,8 @@ macro_rules! SQL_Parse {
                 static ref REGEX: Regex = Regex::new($regex_expr).unwrap();
             };
 
-            if REGEX.is_match(&$sql_cmd){
+            //leading comments, such as a tag, are kept in the command but not classified
+            if REGEX.is_match(tag::skip_leading_comments(&$sql_cmd)){
                 return Ok(SQL::$output_variant($language($sql_cmd.to_string())));
             }
         }
@@ -615,35 +617,37 @@ impl SQL {
     pub fn from_file(file_path: &str) -> Result<Vec<SQL>, std::io::Error> {
         let mut file: File = File::open(file_path)?;
 
-        const BUFFER_SIZE: usize = 100;
+        let mut script = String::new();
+        file.read_to_string(&mut script)?;
 
-        let mut buffer = [0; BUFFER_SIZE];
+        Ok(SQL::from_script(&script))
+    }
+
+    /// Splits a script into its commands.
+    ///
+    /// Commands end at `;` or the end of the script. `;` and `--` inside quoted strings are part of the string, and
+    /// `--` comments outside of them are dropped. Commands that are not valid are logged and skipped.
+    pub fn from_script(script: &str) -> Vec<SQL> {
         let mut parse_mode = ParseMode::Regular;
 
         let mut cmd : Vec<char> = Vec::new();
 
         let mut results: Vec<SQL> = Vec::new();
-        
-        loop {
-            match file.read(&mut buffer) {
-                Ok(0) => {
-                    match SQL::new(&cmd.clone().into_iter().collect::<String>()) {
-                        Ok(val) => results.push(val),
-                        Err(err) => log::error!("Error - {:?}", err),
-                    }
-                    break;
-                },
-                Ok(n) => {
-                    for ch in buffer[..n].iter() {
-                        parse_mode = parse_mode.parse(&mut cmd, &mut results, *ch as char);
-                    }
-                }
-                Err(err) => {
-                    return Err(err)
-                }
+
+        for ch in script.chars() {
+            parse_mode = parse_mode.parse(&mut cmd, &mut results, ch);
+        }
+
+        let cmd: String = cmd.into_iter().collect();
+
+        if !cmd.trim().is_empty() {
+            match SQL::new(&cmd) {
+                Ok(val) => results.push(val),
+                Err(err) => log::error!("Error - {:?}", err),
             }
         }
-        Ok(results)
+
+        results
     }
 
     /// Saves a vector of SQL commands into a file
@@ -890,6 +894,32 @@ mod tests{
             })
     }
 
+    #[test]
+    fn file_insertion_3() {
+        let file_name = "file_insertion_3.txt";
+
+        let _file = FileEnv::new(file_name, "ffffff");
+
+        //directives in single quoted literals are values, and are left as they are
+        for statement in [
+            format!("INSERT INTO tag (colour) VALUES ('#file:({} as S)')", file_name),
+            format!("INSERT INTO tag (colour) VALUES ('it''s #file:({} as S)')", file_name),
+            format!("INSERT INTO tag (colour) VALUES ({})", value::Value::Text(format!("\\' #file:({} as S)", file_name))),
+        ] {
+            assert_eq!(SQL::new(&statement).unwrap(), SQL::Insert(QML(statement.clone())));
+        }
+
+        //a quote in a double quoted string does not start a literal
+        assert_eq!(
+            SQL::new(&format!("INSERT INTO tag (symbol, colour) VALUES (\"it's\", \"#file:({} as S)\")", file_name)).unwrap(),
+            SQL::new("INSERT INTO tag (symbol, colour) VALUES (\"it's\", \"ffffff\")").unwrap()
+        );
+        assert_eq!(
+            SQL::new(&format!("INSERT INTO tag (symbol, colour) VALUES ('a', #file:({} as S))", file_name)).unwrap(),
+            SQL::new("INSERT INTO tag (symbol, colour) VALUES ('a', ffffff)").unwrap()
+        );
+    }
+
 
     //testing SQL parsing
     //Data Definition Language
@@ -1024,6 +1054,17 @@ mod tests{
     }
 
     //Query Manipulation Language
+    #[test]
+    fn tagged_statement_test() {
+        let select = tag_statement("SELECT * FROM user", "orders-service:sync_job");
+        let insert = tag_statement("INSERT INTO t(id) VALUES (1)", "import */ DROP TABLE t; /*");
+
+        assert_eq!(select, "/* orders-service:sync_job */ SELECT * FROM user");
+        assert_eq!(SQL::new(&select).unwrap(), SQL::Select(QDL(select.clone())));
+        assert_eq!(SQL::new(&insert).unwrap(), SQL::Insert(QML(String::from("/* import  DROP TABLE t; */ INSERT INTO t(id) VALUES (1)"))));
+        assert!(matches!(SQL::new("/* only a comment */"), Err(SQLError::NotValidCMD)));
+    }
+
     #[test]
     fn insert_test_1() {
         let input = "INSERT INTO tbl_name (col1,col2) VALUES(15,col1*2)";
@@ -1150,4 +1191,79 @@ mod tests{
             )
         );
     }
-}
\ No newline at end of file
+
+    /// Renders `text`, `bytes` and `int` into an insert and checks it splits as a single statement whose literals
+    /// read back as the original values.
+    fn check_literals(table: &crate::relation::table::Table, text: &str, bytes: &[u8], int: i64) {
+        use std::collections::HashMap;
+        use crate::test_tools::fuzz::{values, Literal};
+        use value::{Value, MAX_LITERAL_SIZE};
+
+        let row = HashMap::from([
+            (String::from("text"), Value::Text(text.to_string())),
+            (String::from("data"), Value::Bytes(bytes.to_vec())),
+            (String::from("num"), Value::Int(int)),
+        ]);
+        let statement = table.insert_values(&row, MAX_LITERAL_SIZE).unwrap().unwrap().0;
+
+        assert_eq!(
+            SQL::from_script(&format!("{};\n", statement)),
+            vec![SQL::Insert(QML(statement.clone()))],
+            "{:?}", text
+        );
+        assert_eq!(
+            values(&statement),
+            Some(vec![Literal::Text(text.to_string()), Literal::Bytes(bytes.to_vec()), Literal::Other(int.to_string())]),
+            "{:?}", text
+        );
+    }
+
+    #[test]
+    fn literal_fuzz_test() {
+        use std::collections::HashSet;
+        use crate::relation::table::{Table, Attribute, AttributeType};
+        use crate::test_tools::fuzz::Fuzzer;
+
+        let attr = |name: &str, data_type: AttributeType| Attribute{
+            name: String::from(name),
+            data_type,
+            raw_type: String::new(),
+            constraint: HashSet::new(),
+        };
+        let table = Table{
+            name: String::from("t"),
+            attributes: vec![
+                attr("text", AttributeType::LongText),
+                attr("data", AttributeType::LongBlob),
+                attr("num", AttributeType::Int(11)),
+            ],
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
+        };
+
+        let mut fuzzer = Fuzzer::new(0x5eed);
+
+        for _ in 0..2000 {
+            let text = fuzzer.string(24);
+            let bytes = fuzzer.bytes(32);
+            let int = fuzzer.next() as i64 - i32::MAX as i64;
+
+            check_literals(&table, &text, &bytes, int);
+        }
+
+        //long values, and runs of the characters that need escaping
+        check_literals(&table, &fuzzer.string(50_000), &fuzzer.bytes(100_000), i64::MIN);
+        check_literals(&table, &"'".repeat(100_000), &[b'\\'; 1000], 0);
+        check_literals(&table, &"\\".repeat(100_000), &[b'\''; 1000], 0);
+        check_literals(&table, "#file:(Cargo.toml as S)", b"#file:(Cargo.toml as S)", 0);
+    }
+
+    #[test]
+    fn escape_string_test() {
+        assert_eq!(escape_string("it's"), "it''s");
+        assert_eq!(escape_string("a\\b"), "a\\\\b");
+        assert_eq!(escape_string("\0\n\r\x1a"), "\\0\\n\\r\\Z");
+        assert_eq!(escape_string("#file:(a.txt as S)"), "#file:(a.txt as S)");
+        assert_eq!(escape_string("'); DROP TABLE users; --"), "''); DROP TABLE users; --");
+    }
+}
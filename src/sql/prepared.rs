@@ -0,0 +1,183 @@
+use std::fmt;
+
+use mysql::Params;
+
+use super::{SQL, SQLError, value::Value};
+
+/// A statement with `?` placeholders and the values bound to them, built by `SQL::prepared`.
+///
+/// The values are sent to the server apart from the statement, so they are never spliced into its text and need no
+/// escaping. Statements of the same text run by `DataBase::execute_prepared_multiple` share one prepared statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prepared {
+    sql: SQL,
+    params: Vec<Value>,
+}
+
+impl Prepared {
+    /// Returns a `Prepared` without checking its parameters, for builders whose placeholders are known to match.
+    pub(crate) fn new(sql: SQL, params: Vec<Value>) -> Prepared {
+        debug_assert_eq!(placeholders(&sql.to_string()), params.len());
+        debug_assert!(!params.contains(&Value::Default));
+
+        Prepared { sql, params }
+    }
+
+    /// Returns the statement, with its placeholders.
+    pub fn sql(&self) -> &SQL {
+        &self.sql
+    }
+
+    /// Returns the values bound to the placeholders, in order.
+    pub fn params(&self) -> &[Value] {
+        &self.params
+    }
+
+    /// Returns the values as they are sent to the driver.
+    pub(crate) fn bound(&self) -> Params {
+        match self.params.is_empty() {
+            true => Params::Empty,
+            false => Params::Positional(self.params.iter().map(param).collect()),
+        }
+    }
+}
+
+impl fmt::Display for Prepared {
+    /// Writes the statement with its placeholders; the values are left out, so it can be logged.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.sql)
+    }
+}
+
+impl SQL {
+    /// Returns `statement` with `params` bound to its `?` placeholders, in order.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let insert = SQL::prepared("INSERT INTO user(name, avatar) VALUES (?, ?)", vec![
+    ///     Value::from("O'Brien"),
+    ///     Value::from(vec![0x89, 0x50, 0x00]),
+    /// ])?;
+    ///
+    /// db.execute_prepared(&insert, |_| ())?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `SQL::new`, and `SQLError::Err` if the number of placeholders is not the number of
+    /// `params`, or a parameter is `Value::Default`, which can only be written into the statement.
+    pub fn prepared(statement: &str, params: Vec<Value>) -> Result<Prepared, SQLError> {
+        let sql = SQL::new(statement)?;
+        let expected = placeholders(&sql.to_string());
+
+        if expected != params.len() {
+            return Err(SQLError::Err(format!(
+                "statement has {} placeholders but {} parameters were given", expected, params.len()
+            )));
+        }
+
+        if params.contains(&Value::Default) {
+            return Err(SQLError::Err(String::from("DEFAULT cannot be bound to a placeholder")));
+        }
+
+        Ok(Prepared { sql, params })
+    }
+}
+
+/// Returns the number of `?` placeholders in `statement`, leaving out those inside quoted strings and identifiers.
+fn placeholders(statement: &str) -> usize {
+    let mut count = 0;
+    let mut quote: Option<char> = None;
+    let mut chars = statement.chars();
+
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (Some(_), '\\') => {
+                chars.next();
+            },
+            (Some(end), _) if ch == end => quote = None,
+            (Some(_), _) => {},
+            (None, '\'' | '"' | '`') => quote = Some(ch),
+            (None, '?') => count += 1,
+            (None, _) => {},
+        }
+    }
+
+    count
+}
+
+/// Converts `value` into the driver's value. Text is sent as bytes, which the server reads in the connection's
+/// character set.
+fn param(value: &Value) -> mysql::Value {
+    match value {
+        Value::Null => mysql::Value::NULL,
+        Value::Bool(val) => mysql::Value::Int(*val as i64),
+        Value::Int(val) => mysql::Value::Int(*val),
+        Value::UInt(val) => mysql::Value::UInt(*val),
+        Value::Float(val) => mysql::Value::Double(*val),
+        Value::Text(val) => mysql::Value::Bytes(val.as_bytes().to_vec()),
+        Value::Bytes(val) => mysql::Value::Bytes(val.clone()),
+        Value::Date{year, month, day} => mysql::Value::Date(*year, *month, *day, 0, 0, 0, 0),
+        Value::DateTime{year, month, day, hour, minute, second, micro_second} => {
+            mysql::Value::Date(*year, *month, *day, *hour, *minute, *second, *micro_second)
+        },
+        Value::Default => unreachable!("DEFAULT is rejected by `SQL::prepared`"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mysql::Params;
+
+    use super::super::{SQL, SQLError, QML, value::Value};
+    use super::{placeholders, Prepared};
+
+    #[test]
+    fn placeholders_test() {
+        assert_eq!(placeholders("INSERT INTO t(a,b) VALUES (?,?)"), 2);
+        assert_eq!(placeholders("SELECT '?', \"?\", `?` FROM t WHERE a = ?"), 1);
+        assert_eq!(placeholders("SELECT 'it\\'s ?' FROM t WHERE a = ? AND b = 'O''Brien?'"), 1);
+        assert_eq!(placeholders("SELECT 1"), 0);
+    }
+
+    #[test]
+    fn prepared_test() {
+        let prepared = SQL::prepared(
+            "INSERT INTO t(a,b,c) VALUES (?,?,?)",
+            vec![Value::from("it's \"quoted\""), Value::from(vec![0x00, 0xff]), Value::Null],
+        ).unwrap();
+
+        assert_eq!(prepared.sql(), &SQL::Insert(QML(String::from("INSERT INTO t(a,b,c) VALUES (?,?,?)"))));
+        assert_eq!(prepared.to_string(), "INSERT INTO t(a,b,c) VALUES (?,?,?)");
+        assert_eq!(
+            prepared.bound(),
+            Params::Positional(vec![
+                mysql::Value::Bytes(b"it's \"quoted\"".to_vec()),
+                mysql::Value::Bytes(vec![0x00, 0xff]),
+                mysql::Value::NULL,
+            ])
+        );
+
+        assert_eq!(SQL::prepared("SELECT 1", Vec::new()).unwrap().bound(), Params::Empty);
+        assert_eq!(Prepared::new(SQL::new("SELECT 1").unwrap(), Vec::new()).params(), &[]);
+    }
+
+    #[test]
+    fn prepared_error_test() {
+        let error = |statement: &str, params: Vec<Value>| match SQL::prepared(statement, params) {
+            Err(SQLError::Err(err)) => err,
+            result => panic!("{:?}", result),
+        };
+
+        assert_eq!(
+            error("INSERT INTO t(a,b) VALUES (?,?)", vec![Value::Int(1)]),
+            "statement has 2 placeholders but 1 parameters were given"
+        );
+        assert_eq!(
+            error("INSERT INTO t(a) VALUES (?)", vec![Value::Default]),
+            "DEFAULT cannot be bound to a placeholder"
+        );
+        assert!(matches!(SQL::prepared("VALUES (?)", vec![Value::Int(1)]), Err(SQLError::NotValidCMD)));
+    }
+}
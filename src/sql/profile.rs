@@ -0,0 +1,282 @@
+use std::{fmt, time::Duration};
+
+use crate::fmt::{Grid, Align, duration};
+
+use super::{keywords::{ServerFlavor, ServerVersion}, value::Value};
+
+/// The mechanism used to collect the stage timings of a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileSource {
+    /// `SET profiling = 1` followed by `SHOW PROFILE`, deprecated since MySQL 8.0 but not on MariaDB.
+    ShowProfile,
+    /// The `performance_schema` statement and stage history tables.
+    PerformanceSchema,
+}
+
+impl ProfileSource {
+    /// Returns the mechanism to use on a `flavor` server running `version`.
+    pub fn for_version(flavor: ServerFlavor, version: ServerVersion) -> ProfileSource {
+        match flavor == ServerFlavor::MySql && version >= ServerVersion::new(8, 0, 0) {
+            true => ProfileSource::PerformanceSchema,
+            false => ProfileSource::ShowProfile,
+        }
+    }
+}
+
+/// The `performance_schema` consumers and instruments that record stage timings, which are off by default, and which
+/// of them were off before a profile turned them on.
+///
+/// Both are server wide, so other sessions are also timed while a profile runs.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StageSetup {
+    /// The consumers that were disabled.
+    consumers: Vec<String>,
+    /// The stage instruments that were disabled.
+    disabled: Vec<String>,
+    /// The stage instruments that were not timed.
+    untimed: Vec<String>,
+}
+
+impl StageSetup {
+    /// The consumers the stage and statement history of a profile are read from.
+    const CONSUMERS: &'static str = "NAME IN ('events_statements_current', 'events_statements_history', \
+        'events_stages_current', 'events_stages_history_long')";
+    /// The instruments of every stage.
+    const INSTRUMENTS: &'static str = "NAME LIKE 'stage/%'";
+
+    /// Returns the query reading the `(NAME, ENABLED)` of each consumer.
+    pub fn consumers_query() -> String {
+        format!("SELECT NAME, ENABLED FROM performance_schema.setup_consumers WHERE {}", StageSetup::CONSUMERS)
+    }
+
+    /// Returns the query reading the `(NAME, ENABLED, TIMED)` of each stage instrument.
+    pub fn instruments_query() -> String {
+        format!("SELECT NAME, ENABLED, TIMED FROM performance_schema.setup_instruments WHERE {}", StageSetup::INSTRUMENTS)
+    }
+
+    /// Builds the setup from the rows of `StageSetup::consumers_query` and `StageSetup::instruments_query`.
+    pub fn from_rows(consumers: Vec<(String, String)>, instruments: Vec<(String, String, String)>) -> StageSetup {
+        let off = |value: &str| !value.eq_ignore_ascii_case("YES");
+
+        StageSetup {
+            consumers: consumers.into_iter().filter(|(_, enabled)| off(enabled)).map(|(name, _)| name).collect(),
+            disabled: instruments.iter().filter(|(_, enabled, _)| off(enabled)).map(|(name, ..)| name.clone()).collect(),
+            untimed: instruments.iter().filter(|(.., timed)| off(timed)).map(|(name, ..)| name.clone()).collect(),
+        }
+    }
+
+    /// Returns the statements enabling every consumer and timing every stage instrument.
+    pub fn enable() -> Vec<String> {
+        vec![
+            format!("UPDATE performance_schema.setup_consumers SET ENABLED = 'YES' WHERE {}", StageSetup::CONSUMERS),
+            format!("UPDATE performance_schema.setup_instruments SET ENABLED = 'YES', TIMED = 'YES' WHERE {}", StageSetup::INSTRUMENTS),
+        ]
+    }
+
+    /// Returns the statements turning off again what was off before `StageSetup::enable`.
+    pub fn restore(&self) -> Vec<String> {
+        let names = |names: &[String]| names.iter().map(|name| Value::Text(name.clone()).to_string()).collect::<Vec<String>>().join(", ");
+
+        [
+            ("setup_consumers", "ENABLED", &self.consumers),
+            ("setup_instruments", "ENABLED", &self.disabled),
+            ("setup_instruments", "TIMED", &self.untimed),
+        ]
+            .into_iter()
+            .filter(|(.., names)| !names.is_empty())
+            .map(|(table, column, disabled)| {
+                format!("UPDATE performance_schema.{} SET {} = 'NO' WHERE NAME IN ({})", table, column, names(disabled))
+            })
+            .collect()
+    }
+}
+
+/// The time a query spent in one execution stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stage {
+    /// The name of the stage, such as `Sending data` or `Creating sort index`.
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// The stage timings of a query, as returned by `DataBase::profile`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryProfile {
+    pub source: ProfileSource,
+    /// The stages in the order they were executed.
+    pub stages: Vec<Stage>,
+}
+
+impl QueryProfile {
+    /// Builds a profile from the `(Status, Duration)` rows of `SHOW PROFILE`, where the duration is in seconds.
+    ///
+    /// Returns the first duration that could not be parsed as the error.
+    pub fn from_show_profile(rows: Vec<(String, String)>) -> Result<QueryProfile, String> {
+        let stages = rows.into_iter()
+            .map(|(name, duration)| match parse_seconds(&duration) {
+                Some(duration) => Ok(Stage { name, duration }),
+                None => Err(duration),
+            })
+            .collect::<Result<Vec<Stage>, String>>()?;
+
+        Ok(QueryProfile { source: ProfileSource::ShowProfile, stages })
+    }
+
+    /// Builds a profile from the `(EVENT_NAME, TIMER_WAIT)` rows of `performance_schema.events_stages_history_long`,
+    /// where the wait is in picoseconds.
+    pub fn from_stage_events(rows: Vec<(String, u64)>) -> QueryProfile {
+        let stages = rows.into_iter()
+            .map(|(name, wait)| Stage {
+                name: name.rsplit('/').next().unwrap_or_default().to_string(),
+                duration: Duration::from_nanos(wait / 1000),
+            })
+            .collect();
+
+        QueryProfile { source: ProfileSource::PerformanceSchema, stages }
+    }
+
+    /// Returns the sum of the stage durations.
+    pub fn total(&self) -> Duration {
+        self.stages.iter().map(|stage| stage.duration).sum()
+    }
+
+    /// Returns the slowest stage, if any.
+    pub fn slowest(&self) -> Option<&Stage> {
+        self.stages.iter().max_by_key(|stage| stage.duration)
+    }
+}
+
+/// Parses a decimal number of seconds (`0.000061`) without going through a float.
+fn parse_seconds(seconds: &str) -> Option<Duration> {
+    let (whole, fraction) = seconds.trim().split_once('.').unwrap_or((seconds.trim(), ""));
+
+    if fraction.len() > 9 || !fraction.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+
+    let whole = match whole {
+        "" => 0,
+        whole => whole.parse::<u64>().ok()?,
+    };
+    let nanos = format!("{:0<9}", fraction).parse::<u32>().ok()?;
+
+    Some(Duration::new(whole, nanos))
+}
+
+impl fmt::Display for QueryProfile {
+    /// Writes one row per stage with its duration, then the total, fitting in the formatter's width if one is given
+    /// (see `Grid::render`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut grid = Grid::new(&[("stage", Align::Left), ("duration", Align::Right)]);
+
+        for stage in &self.stages {
+            grid.push(vec![stage.name.clone(), duration(stage.duration)]);
+        }
+        grid.push(vec![String::from("total"), duration(self.total())]);
+
+        f.write_str(&grid.render(f.width()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::sql::keywords::{ServerFlavor, ServerVersion};
+
+    use super::{QueryProfile, ProfileSource, Stage, StageSetup, parse_seconds};
+
+    #[test]
+    fn parse_seconds_test() {
+        assert_eq!(parse_seconds("0.000061"), Some(Duration::from_micros(61)));
+        assert_eq!(parse_seconds("2.5"), Some(Duration::from_millis(2500)));
+        assert_eq!(parse_seconds("3"), Some(Duration::from_secs(3)));
+        assert_eq!(parse_seconds("0.0000000001"), None);
+        assert_eq!(parse_seconds("abc"), None);
+    }
+
+    #[test]
+    fn source_test() {
+        assert_eq!(ProfileSource::for_version(ServerFlavor::MySql, ServerVersion::new(5, 7, 40)), ProfileSource::ShowProfile);
+        assert_eq!(
+            ProfileSource::for_version(ServerFlavor::MySql, ServerVersion::new(8, 0, 31)),
+            ProfileSource::PerformanceSchema
+        );
+        assert_eq!(ProfileSource::for_version(ServerFlavor::MariaDb, ServerVersion::new(10, 6, 12)), ProfileSource::ShowProfile);
+    }
+
+    #[test]
+    fn stage_setup_test() {
+        let yes = || String::from("YES");
+        let no = || String::from("NO");
+
+        let setup = StageSetup::from_rows(
+            vec![(String::from("events_statements_history"), yes()), (String::from("events_stages_current"), no())],
+            vec![
+                (String::from("stage/sql/starting"), yes(), yes()),
+                (String::from("stage/sql/executing"), no(), no()),
+                (String::from("stage/innodb/alter table (end)"), yes(), no()),
+            ]
+        );
+
+        assert_eq!(
+            setup.restore(),
+            vec![
+                "UPDATE performance_schema.setup_consumers SET ENABLED = 'NO' WHERE NAME IN ('events_stages_current')",
+                "UPDATE performance_schema.setup_instruments SET ENABLED = 'NO' WHERE NAME IN ('stage/sql/executing')",
+                "UPDATE performance_schema.setup_instruments SET TIMED = 'NO' \
+                WHERE NAME IN ('stage/sql/executing', 'stage/innodb/alter table (end)')",
+            ]
+        );
+        assert!(StageSetup::enable()[0].ends_with("'events_stages_current', 'events_stages_history_long')"));
+
+        //nothing was off, so nothing is turned off again
+        assert!(StageSetup::from_rows(vec![(String::from("events_stages_current"), yes())], vec![]).restore().is_empty());
+    }
+
+    #[test]
+    fn show_profile_test() {
+        let profile = QueryProfile::from_show_profile(vec![
+            (String::from("starting"), String::from("0.000061")),
+            (String::from("Sending data"), String::from("0.001200")),
+            (String::from("Creating sort index"), String::from("0.000300")),
+        ]).unwrap();
+
+        assert_eq!(profile.total(), Duration::from_micros(1561));
+        assert_eq!(
+            profile.slowest(),
+            Some(&Stage{ name: String::from("Sending data"), duration: Duration::from_micros(1200) })
+        );
+        assert_eq!(
+            profile.to_string(),
+            "stage                duration\n\
+            starting                 61us\n\
+            Sending data            1.2ms\n\
+            Creating sort index     300us\n\
+            total                   1.6ms"
+        );
+        assert_eq!(format!("{:16}", profile).lines().nth(3), Some("Creat~     300us"));
+
+        assert_eq!(
+            QueryProfile::from_show_profile(vec![(String::from("starting"), String::from("NULL"))]),
+            Err(String::from("NULL"))
+        );
+    }
+
+    #[test]
+    fn stage_events_test() {
+        let profile = QueryProfile::from_stage_events(vec![
+            (String::from("stage/sql/starting"), 45_000_000),
+            (String::from("stage/sql/executing"), 2_000_000_000),
+        ]);
+
+        assert_eq!(profile.source, ProfileSource::PerformanceSchema);
+        assert_eq!(
+            profile.stages,
+            vec![
+                Stage{ name: String::from("starting"), duration: Duration::from_micros(45) },
+                Stage{ name: String::from("executing"), duration: Duration::from_millis(2) },
+            ]
+        );
+    }
+}
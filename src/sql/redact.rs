@@ -0,0 +1,51 @@
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+/// The text secrets are replaced with by `redact`.
+pub const REDACTED: &str = "'***'";
+
+lazy_static! {
+    static ref SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+}
+
+/// Registers `secret`, exactly as it appears in statement text, to be hidden by `redact`.
+pub fn register_secret(secret: &str) {
+    if secret.is_empty() {
+        return;
+    }
+
+    let mut secrets = SECRETS.write().unwrap_or_else(|err| err.into_inner());
+
+    if !secrets.iter().any(|registered| registered == secret) {
+        secrets.push(secret.to_string());
+        //longest first, so a secret containing another one is replaced whole
+        secrets.sort_by_key(|registered| std::cmp::Reverse(registered.len()));
+    }
+}
+
+/// Returns `statement` with every registered secret replaced by `REDACTED`, for use in logs.
+pub fn redact(statement: &str) -> String {
+    let secrets = SECRETS.read().unwrap_or_else(|err| err.into_inner());
+
+    secrets.iter()
+        .fold(statement.to_string(), |statement, secret| statement.replace(secret.as_str(), REDACTED))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{register_secret, redact};
+
+    #[test]
+    fn redact_test() {
+        register_secret("'redact_test_key'");
+        register_secret("'redact_test_key_2'");
+        register_secret("");
+
+        assert_eq!(
+            redact("SELECT AES_DECRYPT(a, 'redact_test_key'), AES_DECRYPT(b, 'redact_test_key_2') FROM t"),
+            "SELECT AES_DECRYPT(a, '***'), AES_DECRYPT(b, '***') FROM t"
+        );
+        assert_eq!(redact("SELECT 1"), "SELECT 1");
+    }
+}
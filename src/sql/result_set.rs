@@ -0,0 +1,600 @@
+use std::{fmt, collections::{HashMap, HashSet, VecDeque}};
+
+use mysql::{consts::ColumnType, Column, Row};
+
+use super::value::Value;
+
+/// Default tolerance used by `ResultSet::diff` when comparing floating point values.
+pub const DEFAULT_EPSILON: f64 = 1e-9;
+
+/// The columns and rows returned by a query, held in memory.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResultSet {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// The metadata of a column of a result set, as sent by the server ahead of the rows (see
+/// `DataBase::execute_with_columns`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnInfo {
+    /// The name of the column, or its alias.
+    pub name: String,
+    /// The table, or its alias, the column was read from. Empty for a column the query computed.
+    pub table: String,
+    /// The type the server sent the values of the column as.
+    pub column_type: ColumnType,
+}
+
+impl From<&Column> for ColumnInfo {
+    fn from(column: &Column) -> Self {
+        ColumnInfo {
+            name: column.name_str().to_string(),
+            table: column.table_str().to_string(),
+            column_type: column.column_type(),
+        }
+    }
+}
+
+/// A column whose value differs between two matched rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnChange {
+    pub column: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+/// A row present in both result sets whose values differ.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowChange {
+    /// The values of the key columns of the row.
+    pub key: Vec<Value>,
+    pub changes: Vec<ColumnChange>,
+}
+
+/// The differences between two result sets, as returned by `ResultSet::diff`.
+///
+/// Added and removed rows are given as `(column, value)` pairs so they do not depend on either set's column order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResultDiff {
+    pub key_columns: Vec<String>,
+    /// Columns only found in the other result set.
+    pub added_columns: Vec<String>,
+    /// Columns only found in this result set.
+    pub removed_columns: Vec<String>,
+    pub added: Vec<Vec<(String, Value)>>,
+    pub removed: Vec<Vec<(String, Value)>>,
+    pub changed: Vec<RowChange>,
+}
+
+impl ResultSet {
+    pub fn new(columns: Vec<String>, rows: Vec<Vec<Value>>) -> ResultSet {
+        ResultSet { columns, rows }
+    }
+
+    /// Builds a result set from rows returned by the driver.
+    ///
+    /// The columns are taken from the first row, so an empty `rows` gives a result set without columns.
+    pub fn from_rows(rows: Vec<Row>) -> ResultSet {
+        let columns = match rows.first() {
+            Some(row) => row.columns_ref()
+                .iter()
+                .map(|column| column.name_str().to_string())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let rows = rows.into_iter()
+            .map(|row| row.unwrap().into_iter().map(Value::from).collect())
+            .collect();
+
+        ResultSet { columns, rows }
+    }
+
+    /// Builds a result set from rows returned by the driver, under the `columns` of the result.
+    ///
+    /// Unlike `ResultSet::from_rows` the columns are known when there are no rows.
+    pub fn with_columns(columns: &[ColumnInfo], rows: Vec<Row>) -> ResultSet {
+        ResultSet {
+            columns: columns.iter().map(|column| column.name.clone()).collect(),
+            rows: ResultSet::from_rows(rows).rows,
+        }
+    }
+
+    /// Returns the index of `column`.
+    pub fn column_index(&self, column: &str) -> Option<usize> {
+        self.columns.iter().position(|name| name == column)
+    }
+
+    /// Returns the column names with duplicates made unique.
+    ///
+    /// Joins can return several columns with the same name. The first keeps its name and every later one is suffixed
+    /// with its occurrence, so `id, id` becomes `id, id_2`. A suffixed name that is already taken by another column is
+    /// skipped, so the names returned are always distinct.
+    pub fn unique_columns(&self) -> Vec<String> {
+        let taken: HashSet<&str> = self.columns.iter().map(|column| column.as_str()).collect();
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut unique = Vec::with_capacity(self.columns.len());
+
+        for column in &self.columns {
+            if seen.insert(column) {
+                unique.push(column.clone());
+                continue;
+            }
+
+            let name = (2..)
+                .map(|occurrence| format!("{}_{}", column, occurrence))
+                .find(|name| !taken.contains(name.as_str()) && !unique.contains(name))
+                .unwrap();
+
+            unique.push(name);
+        }
+
+        unique
+    }
+
+    /// Returns an iterator over the rows, giving access to their values by column name.
+    pub fn iter(&self) -> impl Iterator<Item = ResultRow<'_>> {
+        self.rows.iter().map(move |values| ResultRow { columns: &self.columns, values })
+    }
+
+    /// Returns every row as a map from column name to value.
+    ///
+    /// Duplicate column names are made unique as described in `ResultSet::unique_columns`. Use `ResultSet::iter` and
+    /// `ResultRow::columns` to read the rows in column order under their original names.
+    pub fn rows_as_maps(&self) -> Vec<HashMap<String, Value>> {
+        let columns = self.unique_columns();
+
+        self.rows.iter()
+            .map(|row| columns.iter().cloned().zip(row.iter().cloned()).collect())
+            .collect()
+    }
+
+    /// Compares the result set against `other`, matching rows by the values of `key_columns`.
+    ///
+    /// Floats are compared with `DEFAULT_EPSILON`; see `ResultSet::diff_with_epsilon`.
+    pub fn diff(&self, other: &ResultSet, key_columns: &[&str]) -> ResultDiff {
+        self.diff_with_epsilon(other, key_columns, DEFAULT_EPSILON)
+    }
+
+    /// Compares the result set against `other`, matching rows by the values of `key_columns`.
+    ///
+    /// Columns are matched by name, so the column order of the two sets does not matter. When `key_columns` is empty
+    /// every shared column is part of the key, which reduces the diff to added and removed rows. Rows sharing a key are
+    /// matched in the order they appear. Two floats are equal when they differ by at most `epsilon`.
+    pub fn diff_with_epsilon(&self, other: &ResultSet, key_columns: &[&str], epsilon: f64) -> ResultDiff {
+        let shared: Vec<(&String, usize, usize)> = self.columns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, column)| other.column_index(column).map(|j| (column, i, j)))
+            .collect();
+
+        let key_columns: Vec<String> = match key_columns.is_empty() {
+            true => shared.iter().map(|(column, _, _)| column.to_string()).collect(),
+            false => key_columns.iter().map(|column| column.to_string()).collect(),
+        };
+
+        let key_of = |set: &ResultSet, row: &Vec<Value>| -> Vec<Value> {
+            key_columns.iter()
+                .map(|column| match set.column_index(column) {
+                    Some(i) => row[i].clone(),
+                    None => Value::Null,
+                })
+                .collect()
+        };
+
+        //rows of other, grouped by their rendered key
+        let mut unmatched: HashMap<String, VecDeque<usize>> = HashMap::new();
+        for (j, row) in other.rows.iter().enumerate() {
+            unmatched.entry(render_key(&key_of(other, row)))
+                .or_default()
+                .push_back(j);
+        }
+
+        let mut diff = ResultDiff {
+            added_columns: other.columns.iter().filter(|column| self.column_index(column).is_none()).cloned().collect(),
+            removed_columns: self.columns.iter().filter(|column| other.column_index(column).is_none()).cloned().collect(),
+            key_columns: key_columns.clone(),
+            ..ResultDiff::default()
+        };
+
+        let mut matched = vec![false; other.rows.len()];
+
+        for row in &self.rows {
+            let key = key_of(self, row);
+
+            let j = match unmatched.get_mut(&render_key(&key)).and_then(|rows| rows.pop_front()) {
+                Some(j) => j,
+                None => {
+                    diff.removed.push(labelled(&self.columns, row));
+                    continue;
+                }
+            };
+            matched[j] = true;
+
+            let changes: Vec<ColumnChange> = shared.iter()
+                .filter(|(_, i, j_col)| !values_eq(&row[*i], &other.rows[j][*j_col], epsilon))
+                .map(|(column, i, j_col)| ColumnChange {
+                    column: column.to_string(),
+                    before: row[*i].clone(),
+                    after: other.rows[j][*j_col].clone(),
+                })
+                .collect();
+
+            if !changes.is_empty() {
+                diff.changed.push(RowChange { key, changes });
+            }
+        }
+
+        diff.added = other.rows.iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+            .map(|(row, _)| labelled(&other.columns, row))
+            .collect();
+
+        diff
+    }
+}
+
+/// A row of a `ResultSet`, as returned by `ResultSet::iter`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResultRow<'a> {
+    columns: &'a [String],
+    values: &'a [Value],
+}
+
+impl<'a> ResultRow<'a> {
+    /// Returns an iterator over the `(column, value)` pairs of the row, in column order.
+    ///
+    /// Duplicate column names are kept as returned by the server.
+    pub fn columns(&self) -> impl Iterator<Item = (&'a str, &'a Value)> {
+        self.columns.iter().map(|column| column.as_str()).zip(self.values.iter())
+    }
+
+    /// Returns the value of the first column named `column`.
+    pub fn get(&self, column: &str) -> Option<&'a Value> {
+        self.columns().find(|(name, _)| *name == column).map(|(_, value)| value)
+    }
+
+    /// Returns the value of the first column named `column` as bytes, such as the contents of a `BLOB` column.
+    ///
+    /// Binary values that are valid UTF-8 are read as `Value::Text`, and are returned as their UTF-8 bytes. Returns
+    /// `None` if there is no such column or its value is neither text nor bytes, such as `NULL`.
+    pub fn get_bytes(&self, column: &str) -> Option<Vec<u8>> {
+        self.get(column).cloned().and_then(|value| Vec::<u8>::try_from(value).ok())
+    }
+
+    /// Returns the values of the row, in column order.
+    pub fn values(&self) -> &'a [Value] {
+        self.values
+    }
+}
+
+impl ResultDiff {
+    /// Returns true if both result sets hold the same rows.
+    pub fn is_empty(&self) -> bool {
+        self.added_columns.is_empty() &&
+            self.removed_columns.is_empty() &&
+            self.added.is_empty() &&
+            self.removed.is_empty() &&
+            self.changed.is_empty()
+    }
+}
+
+/// Renders a key so it can be used for lookups, as `Value` is not hashable.
+fn render_key(key: &[Value]) -> String {
+    key.iter()
+        .map(|val| val.to_string())
+        .collect::<Vec<String>>()
+        .join("\u{1f}")
+}
+
+/// Pairs every value of `row` with its column name.
+fn labelled(columns: &[String], row: &[Value]) -> Vec<(String, Value)> {
+    columns.iter()
+        .cloned()
+        .zip(row.iter().cloned())
+        .collect()
+}
+
+/// Compares two values, treating numbers within `epsilon` of each other as equal.
+fn values_eq(a: &Value, b: &Value, epsilon: f64) -> bool {
+    let as_float = |val: &Value| match val {
+        Value::Float(val) => Some(*val),
+        Value::Int(val) => Some(*val as f64),
+        Value::UInt(val) => Some(*val as f64),
+        _ => None,
+    };
+
+    match (a, b) {
+        (Value::Float(_), _) | (_, Value::Float(_)) => match (as_float(a), as_float(b)) {
+            (Some(a), Some(b)) => a == b || (a - b).abs() <= epsilon,
+            _ => a == b,
+        },
+        _ => a == b,
+    }
+}
+
+/// Joins `(column, value)` pairs into `column=value` form.
+fn render_row(row: &[(String, Value)]) -> String {
+    row.iter()
+        .map(|(column, val)| format!("{}={}", column, val))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl fmt::Display for ResultDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )?;
+
+        for column in &self.added_columns {
+            writeln!(f, "+ column {}", column)?;
+        }
+        for column in &self.removed_columns {
+            writeln!(f, "- column {}", column)?;
+        }
+        for row in &self.removed {
+            writeln!(f, "- {}", render_row(row))?;
+        }
+        for row in &self.added {
+            writeln!(f, "+ {}", render_row(row))?;
+        }
+        for row in &self.changed {
+            let key = self.key_columns.iter()
+                .cloned()
+                .zip(row.key.iter().cloned())
+                .collect::<Vec<(String, Value)>>();
+
+            let changes = row.changes.iter()
+                .map(|change| format!("{}: {} -> {}", change.column, change.before, change.after))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            writeln!(f, "~ {} | {}", render_row(&key), changes)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sql::value::Value;
+
+    use std::collections::HashMap;
+
+    use super::{ResultSet, ResultDiff, RowChange, ColumnChange, ColumnInfo};
+
+    fn people() -> ResultSet {
+        ResultSet::new(
+            vec![String::from("id"), String::from("name"), String::from("score")],
+            vec![
+                vec![Value::Int(1), Value::Text(String::from("John")), Value::Float(1.5)],
+                vec![Value::Int(2), Value::Text(String::from("Jane")), Value::Float(2.0)],
+                vec![Value::Int(3), Value::Text(String::from("Joe")), Value::Null],
+            ]
+        )
+    }
+
+    #[test]
+    fn diff_test_1() {
+        //same rows, different column and row order
+        let other = ResultSet::new(
+            vec![String::from("score"), String::from("id"), String::from("name")],
+            vec![
+                vec![Value::Null, Value::Int(3), Value::Text(String::from("Joe"))],
+                vec![Value::Float(2.0 + 1e-12), Value::Int(2), Value::Text(String::from("Jane"))],
+                vec![Value::Float(1.5), Value::Int(1), Value::Text(String::from("John"))],
+            ]
+        );
+
+        let diff = people().diff(&other, &["id"]);
+
+        assert!(diff.is_empty());
+        assert!(!people().diff_with_epsilon(&other, &["id"], 0.0).is_empty());
+    }
+
+    #[test]
+    fn diff_test_2() {
+        let other = ResultSet::new(
+            vec![String::from("id"), String::from("name"), String::from("score")],
+            vec![
+                vec![Value::Int(1), Value::Text(String::from("Jon")), Value::Float(1.5)],
+                vec![Value::Int(3), Value::Text(String::from("Joe")), Value::Null],
+                vec![Value::Int(4), Value::Text(String::from("Jill")), Value::Float(4.0)],
+            ]
+        );
+
+        let diff = people().diff(&other, &["id"]);
+
+        assert_eq!(
+            diff,
+            ResultDiff {
+                key_columns: vec![String::from("id")],
+                added_columns: vec![],
+                removed_columns: vec![],
+                added: vec![vec![
+                    (String::from("id"), Value::Int(4)),
+                    (String::from("name"), Value::Text(String::from("Jill"))),
+                    (String::from("score"), Value::Float(4.0)),
+                ]],
+                removed: vec![vec![
+                    (String::from("id"), Value::Int(2)),
+                    (String::from("name"), Value::Text(String::from("Jane"))),
+                    (String::from("score"), Value::Float(2.0)),
+                ]],
+                changed: vec![RowChange {
+                    key: vec![Value::Int(1)],
+                    changes: vec![ColumnChange {
+                        column: String::from("name"),
+                        before: Value::Text(String::from("John")),
+                        after: Value::Text(String::from("Jon")),
+                    }],
+                }],
+            }
+        );
+
+        assert_eq!(
+            diff.to_string(),
+            "1 added, 1 removed, 1 changed\n\
+            - id=2, name='Jane', score=2\n\
+            + id=4, name='Jill', score=4\n\
+            ~ id=1 | name: 'John' -> 'Jon'\n"
+        );
+    }
+
+    #[test]
+    fn diff_test_3() {
+        //duplicate keys are matched in order and missing columns are reported
+        let this = ResultSet::new(
+            vec![String::from("name"), String::from("age")],
+            vec![
+                vec![Value::Text(String::from("John")), Value::Int(20)],
+                vec![Value::Text(String::from("John")), Value::Int(30)],
+            ]
+        );
+        let other = ResultSet::new(
+            vec![String::from("name")],
+            vec![
+                vec![Value::Text(String::from("John"))],
+            ]
+        );
+
+        let diff = this.diff(&other, &[]);
+
+        assert_eq!(diff.removed_columns, vec![String::from("age")]);
+        assert_eq!(diff.added, Vec::<Vec<(String, Value)>>::new());
+        assert_eq!(
+            diff.removed,
+            vec![vec![(String::from("name"), Value::Text(String::from("John"))), (String::from("age"), Value::Int(30))]]
+        );
+        assert!(diff.changed.is_empty());
+    }
+
+    //result of `SELECT * FROM user JOIN post ON post.user_id = user.id`
+    fn joined() -> ResultSet {
+        ResultSet::new(
+            vec![
+                String::from("id"),
+                String::from("name"),
+                String::from("id"),
+                String::from("id_2"),
+                String::from("name"),
+            ],
+            vec![
+                vec![
+                    Value::Int(1),
+                    Value::Text(String::from("John")),
+                    Value::Int(10),
+                    Value::Int(20),
+                    Value::Text(String::from("first post")),
+                ],
+            ]
+        )
+    }
+
+    #[test]
+    fn unique_columns_test() {
+        assert_eq!(
+            joined().unique_columns(),
+            vec![
+                String::from("id"),
+                String::from("name"),
+                String::from("id_3"),
+                String::from("id_2"),
+                String::from("name_2"),
+            ]
+        );
+        assert_eq!(people().unique_columns(), people().columns);
+    }
+
+    #[test]
+    fn rows_as_maps_test() {
+        assert_eq!(
+            joined().rows_as_maps(),
+            vec![HashMap::from([
+                (String::from("id"), Value::Int(1)),
+                (String::from("name"), Value::Text(String::from("John"))),
+                (String::from("id_3"), Value::Int(10)),
+                (String::from("id_2"), Value::Int(20)),
+                (String::from("name_2"), Value::Text(String::from("first post"))),
+            ])]
+        );
+    }
+
+    #[test]
+    fn columns_test() {
+        let set = joined();
+        let row = set.iter().next().unwrap();
+
+        assert_eq!(
+            row.columns().collect::<Vec<(&str, &Value)>>(),
+            vec![
+                ("id", &Value::Int(1)),
+                ("name", &Value::Text(String::from("John"))),
+                ("id", &Value::Int(10)),
+                ("id_2", &Value::Int(20)),
+                ("name", &Value::Text(String::from("first post"))),
+            ]
+        );
+        assert_eq!(row.get("id"), Some(&Value::Int(1)));
+        assert_eq!(row.get("missing"), None);
+        assert_eq!(set.iter().count(), 1);
+    }
+
+    #[test]
+    fn get_bytes_test() {
+        let set = ResultSet::new(
+            vec![String::from("id"), String::from("data"), String::from("name"), String::from("missing")],
+            vec![vec![Value::Int(1), Value::Bytes(vec![0x00, 0xff]), Value::Text(String::from("Doe")), Value::Null]]
+        );
+        let row = set.iter().next().unwrap();
+
+        assert_eq!(row.get_bytes("data"), Some(vec![0x00, 0xff]));
+        assert_eq!(row.get_bytes("name"), Some(b"Doe".to_vec()));
+        assert_eq!(row.get_bytes("id"), None);
+        assert_eq!(row.get_bytes("missing"), None);
+        assert_eq!(row.get_bytes("other"), None);
+    }
+
+    #[test]
+    fn with_columns_test() {
+        use std::sync::Arc;
+
+        use mysql::{consts::ColumnType, Column};
+
+        let columns: Arc<[Column]> = Arc::from(vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id").with_table(b"u"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"total"),
+        ]);
+        let columns: Vec<ColumnInfo> = columns.iter().map(ColumnInfo::from).collect();
+
+        assert_eq!(
+            columns,
+            vec![
+                ColumnInfo { name: String::from("id"), table: String::from("u"), column_type: ColumnType::MYSQL_TYPE_LONG },
+                ColumnInfo {
+                    name: String::from("total"),
+                    table: String::new(),
+                    column_type: ColumnType::MYSQL_TYPE_VAR_STRING,
+                },
+            ]
+        );
+
+        let empty = ResultSet::with_columns(&columns, Vec::new());
+        assert_eq!(empty, ResultSet::new(vec![String::from("id"), String::from("total")], Vec::new()));
+        assert_eq!(ResultSet::from_rows(Vec::new()).columns, Vec::<String>::new());
+
+        let row = mysql_common::row::new_row(
+            vec![mysql::Value::Int(1), mysql::Value::NULL],
+            Arc::from(vec![Column::new(ColumnType::MYSQL_TYPE_LONG), Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)]),
+        );
+        assert_eq!(ResultSet::with_columns(&columns, vec![row]).rows, vec![vec![Value::Int(1), Value::Null]]);
+    }
+}
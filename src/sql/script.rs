@@ -0,0 +1,141 @@
+/// Where `split_statements` is in a script.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Regular,
+    /// Inside a string or identifier ending with the quote.
+    Quoted(char),
+    /// Inside a `--` or `#` comment, which ends at the end of the line.
+    LineComment,
+    /// Inside a `/* */` comment.
+    BlockComment,
+}
+
+/// Splits `script` into its statements, in order, without their ending `;`.
+///
+/// A `;` only ends a statement outside of quoted strings, quoted identifiers and comments. Backslash escapes and
+/// doubled quotes inside strings are read as MySQL reads them. Comments are kept in the statement they are part of,
+/// so optimizer hints and `/*! */` version comments still apply, but text made only of comments and whitespace is
+/// dropped. The `DELIMITER` command of the `mysql` client is not supported.
+///
+/// ```ignore
+/// assert_eq!(
+///     split_statements("INSERT INTO note VALUES ('a;b'); -- done;\nSELECT 1"),
+///     vec!["INSERT INTO note VALUES ('a;b')", "-- done;\nSELECT 1"]
+/// );
+/// ```
+pub fn split_statements(script: &str) -> Vec<String> {
+    let chars: Vec<char> = script.chars().collect();
+
+    let mut statements: Vec<String> = Vec::new();
+    let mut statement = String::new();
+    let mut has_code = false;
+    let mut state = State::Regular;
+
+    let mut index = 0;
+
+    while index < chars.len() {
+        let ch = chars[index];
+        let next = chars.get(index + 1).copied();
+
+        match state {
+            State::Regular => match ch {
+                ';' => {
+                    if has_code {
+                        statements.push(statement.trim().to_string());
+                    }
+                    statement.clear();
+                    has_code = false;
+
+                    index += 1;
+                    continue;
+                },
+                '\'' | '"' | '`' => {
+                    state = State::Quoted(ch);
+                    has_code = true;
+                },
+                '#' => state = State::LineComment,
+                //MySQL only reads `--` as a comment when whitespace follows it
+                '-' if next == Some('-') && chars.get(index + 2).is_none_or(|ch| ch.is_whitespace()) => {
+                    state = State::LineComment;
+                },
+                '/' if next == Some('*') => {
+                    statement.push_str("/*");
+                    state = State::BlockComment;
+
+                    //optimizer hints and version comments are run by the server
+                    if matches!(chars.get(index + 2), Some('!' | '+')) {
+                        has_code = true;
+                    }
+
+                    index += 2;
+                    continue;
+                },
+                ch if ch.is_whitespace() => {},
+                _ => has_code = true,
+            },
+            State::Quoted(quote) => match ch {
+                '\\' if quote != '`' => {
+                    statement.push(ch);
+                    if let Some(next) = next {
+                        statement.push(next);
+                    }
+
+                    index += 2;
+                    continue;
+                },
+                ch if ch == quote => state = State::Regular,
+                _ => {},
+            },
+            State::LineComment => {
+                if ch == '\n' {
+                    state = State::Regular;
+                }
+            },
+            State::BlockComment => {
+                if ch == '*' && next == Some('/') {
+                    statement.push_str("*/");
+                    state = State::Regular;
+
+                    index += 2;
+                    continue;
+                }
+            },
+        }
+
+        statement.push(ch);
+        index += 1;
+    }
+
+    if has_code {
+        statements.push(statement.trim().to_string());
+    }
+
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_statements;
+
+    #[test]
+    fn split_statements_test() {
+        assert_eq!(
+            split_statements("CREATE TABLE a (id int);\nCREATE TABLE b (id int);\n"),
+            vec!["CREATE TABLE a (id int)", "CREATE TABLE b (id int)"]
+        );
+        assert_eq!(
+            split_statements("INSERT INTO note VALUES ('a;b', \"c;d\", 'it\\'s;', 'O''Brien;'); SELECT `x;y` FROM note"),
+            vec!["INSERT INTO note VALUES ('a;b', \"c;d\", 'it\\'s;', 'O''Brien;')", "SELECT `x;y` FROM note"]
+        );
+        assert_eq!(
+            split_statements("-- setup; of notes\nSELECT 1; # trailing; comment\n/* block; comment */ SELECT 2;"),
+            vec!["-- setup; of notes\nSELECT 1", "# trailing; comment\n/* block; comment */ SELECT 2"]
+        );
+        assert_eq!(
+            split_statements("SELECT 3--1; /*!40101 SET NAMES utf8 */;"),
+            vec!["SELECT 3--1", "/*!40101 SET NAMES utf8 */"]
+        );
+        assert_eq!(split_statements(";; -- only a comment;\n/* and; another */  "), Vec::<String>::new());
+        assert_eq!(split_statements("SELECT 'unterminated;"), vec!["SELECT 'unterminated;"]);
+    }
+}
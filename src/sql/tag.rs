@@ -0,0 +1,79 @@
+/// Returns `tag` made safe to place inside a `/* */` comment.
+///
+/// `*/` and `/*` are removed until none are left, so the tag can neither end the comment nor open another one, and
+/// control characters, line breaks included, become spaces.
+pub fn sanitize_tag(tag: &str) -> String {
+    let mut tag: String = tag.chars().map(|ch| if ch.is_control() { ' ' } else { ch }).collect();
+
+    //removing one sequence can join two halves into another, as in `**//`
+    while tag.contains("*/") || tag.contains("/*") {
+        tag = tag.replace("*/", "").replace("/*", "");
+    }
+
+    tag.trim().to_string()
+}
+
+/// Returns `statement` with the comment `/* tag */` before it, or unchanged if the sanitized tag is empty.
+///
+/// The tag is sanitized with `sanitize_tag`. The space after `/*` keeps a tag starting with `!` or `+` from turning
+/// the comment into a version comment or an optimizer hint.
+pub fn tag_statement(statement: &str, tag: &str) -> String {
+    let tag = sanitize_tag(tag);
+
+    match tag.is_empty() {
+        true => statement.to_string(),
+        false => format!("/* {} */ {}", tag, statement),
+    }
+}
+
+/// Returns `statement` without the comments and whitespace before its first keyword, for classifying it.
+pub(crate) fn skip_leading_comments(statement: &str) -> &str {
+    let mut statement = statement.trim_start();
+
+    loop {
+        if let Some(comment) = statement.strip_prefix("/*") {
+            statement = match comment.find("*/") {
+                Some(end) => comment[end + 2..].trim_start(),
+                None => return statement,
+            };
+        }
+        else if statement.starts_with("-- ") || statement.starts_with('#') {
+            statement = match statement.find('\n') {
+                Some(end) => statement[end + 1..].trim_start(),
+                None => "",
+            };
+        }
+        else {
+            return statement;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sanitize_tag, tag_statement, skip_leading_comments};
+
+    #[test]
+    fn sanitize_tag_test() {
+        assert_eq!(sanitize_tag("orders-service:sync_job"), "orders-service:sync_job");
+        assert_eq!(sanitize_tag("x */ DROP TABLE user; /* y"), "x  DROP TABLE user;  y");
+        assert_eq!(sanitize_tag("x**//y"), "xy");
+        assert_eq!(sanitize_tag("line\nbreak\r\t"), "line break");
+        assert_eq!(sanitize_tag(" */ "), "");
+    }
+
+    #[test]
+    fn tag_statement_test() {
+        assert_eq!(tag_statement("SELECT 1", "job"), "/* job */ SELECT 1");
+        assert_eq!(tag_statement("SELECT 1", "!50000 x"), "/* !50000 x */ SELECT 1");
+        assert_eq!(tag_statement("SELECT 1", "*/"), "SELECT 1");
+    }
+
+    #[test]
+    fn skip_leading_comments_test() {
+        assert_eq!(skip_leading_comments("/* job */ SELECT 1"), "SELECT 1");
+        assert_eq!(skip_leading_comments("  /* a */ /*b*/\n-- c\n# d\nINSERT INTO t VALUES (1)"), "INSERT INTO t VALUES (1)");
+        assert_eq!(skip_leading_comments("/* unterminated SELECT 1"), "/* unterminated SELECT 1");
+        assert_eq!(skip_leading_comments("SELECT 1 /* trailing */"), "SELECT 1 /* trailing */");
+    }
+}
@@ -0,0 +1,827 @@
+use std::{collections::HashMap, fmt};
+
+use crate::relation::table::AttributeType;
+
+/// Default upper bound, in bytes, of a single rendered literal.
+///
+/// Literals are spliced into the statement text, so a large blob would otherwise build an equally large `String`.
+pub const MAX_LITERAL_SIZE: usize = 16 * 1024 * 1024;
+
+/// Represents possible errors that can occur when rendering a `Value` into SQL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueError {
+    /// The rendered literal would exceed the configured size limit.
+    LiteralTooLarge{size: usize, limit: usize},
+    /// `Value::Null` is given to a `NOT NULL` column.
+    NullNotAllowed(String),
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueError::LiteralTooLarge{size, limit} => write!(f, "literal is {} bytes, the limit is {} bytes", size, limit),
+            ValueError::NullNotAllowed(column) => write!(f, "{} cannot be NULL", column),
+        }
+    }
+}
+
+/// Represents possible errors that can occur when converting a `Value` into a Rust type or another `Value`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The variant `from` has no conversion into `to`.
+    Type{from: &'static str, to: &'static str},
+    /// The value is outside the range of `to`.
+    Overflow{value: String, to: &'static str},
+    /// The conversion would lose part of the value, or the value is `NaN` or infinite.
+    Lossy{value: String, to: &'static str},
+    /// The text could not be parsed as `to`.
+    Parse{value: String, to: &'static str},
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::Type{from, to} => write!(f, "{} cannot be converted to {}", from, to),
+            ConversionError::Overflow{value, to} => write!(f, "{} is out of range for {}", value, to),
+            ConversionError::Lossy{value, to} => write!(f, "{} cannot be converted to {} without loss", value, to),
+            ConversionError::Parse{value, to} => write!(f, "{} is not a valid {}", value, to),
+        }
+    }
+}
+
+/// Represents a value that could not be coerced to a column's type by `Value::coerce_to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoerceError {
+    /// The column's type, as written in its definition.
+    pub data_type: String,
+    pub error: ConversionError,
+}
+
+impl fmt::Display for CoerceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot coerce to {}: {}", self.data_type, self.error)
+    }
+}
+
+/// A typed value that can be rendered as a MySQL literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// SQL `NULL`.
+    Null,
+    /// A boolean, rendered as `TRUE`/`FALSE`.
+    Bool(bool),
+    /// A signed integer.
+    Int(i64),
+    /// An unsigned integer.
+    UInt(u64),
+    /// A floating point number.
+    Float(f64),
+    /// A string, rendered as an escaped single quoted literal.
+    Text(String),
+    /// Binary data, rendered as a `x'...'` hex literal.
+    Bytes(Vec<u8>),
+    /// A calendar date, rendered as `'YYYY-MM-DD'`.
+    Date{year: u16, month: u8, day: u8},
+    /// A date and time, rendered as `'YYYY-MM-DD hh:mm:ss[.ffffff]'`.
+    DateTime{year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8, micro_second: u32},
+    /// The column's default, rendered as `DEFAULT`. Stands in for values assigned by the server, such as auto
+    /// incremented ids.
+    Default,
+}
+
+impl Value {
+    /// Returns the SQL literal of the value, or an error if the literal would be larger than `limit` bytes.
+    ///
+    /// The size is checked before the literal is built, so oversized blobs are rejected without allocating them.
+    pub fn to_bounded_literal(&self, limit: usize) -> Result<String, ValueError> {
+        let size = self.literal_size();
+
+        if size > limit {
+            return Err(ValueError::LiteralTooLarge{size, limit});
+        }
+
+        Ok(self.to_string())
+    }
+
+    /// Returns the length of the rendered literal, without building it for binary data.
+    fn literal_size(&self) -> usize {
+        match self {
+            Value::Bytes(val) => val.len() * 2 + 3,
+            _ => self.to_string().len(),
+        }
+    }
+
+    /// Returns the name of the value's variant, for error messages.
+    fn variant(&self) -> &'static str {
+        match self {
+            Value::Null => "NULL",
+            Value::Bool(_) => "Bool",
+            Value::Int(_) => "Int",
+            Value::UInt(_) => "UInt",
+            Value::Float(_) => "Float",
+            Value::Text(_) => "Text",
+            Value::Bytes(_) => "Bytes",
+            Value::Date{..} => "Date",
+            Value::DateTime{..} => "DateTime",
+            Value::Default => "DEFAULT",
+        }
+    }
+
+    /// Returns the value as an integer wide enough for every `i64` and `u64`, for the integer conversions.
+    ///
+    /// Booleans are `0` or `1`, floats must be whole numbers and text is parsed.
+    fn to_integer(&self, to: &'static str) -> Result<i128, ConversionError> {
+        match self {
+            Value::Bool(val) => Ok(*val as i128),
+            Value::Int(val) => Ok(*val as i128),
+            Value::UInt(val) => Ok(*val as i128),
+            Value::Float(val) if !val.is_finite() || val.fract() != 0.0 => {
+                Err(ConversionError::Lossy{value: self.to_string(), to})
+            },
+            Value::Float(val) if *val < i64::MIN as f64 || *val >= u64::MAX as f64 => {
+                Err(ConversionError::Overflow{value: self.to_string(), to})
+            },
+            Value::Float(val) => Ok(*val as i128),
+            Value::Text(val) => val.trim()
+                .parse::<i128>()
+                .map_err(|_| ConversionError::Parse{value: self.to_string(), to}),
+            _ => Err(ConversionError::Type{from: self.variant(), to}),
+        }
+    }
+
+    /// Returns the value as the exact text of a number of a `DECIMAL(digits, scale)` column, for `Value::coerce_to`.
+    ///
+    /// Floats are written as the shortest text that reads back as the same float, and text has to be a plain decimal
+    /// number such as `-12.50`. Zeros past `scale` are dropped; other digits past it, or more digits before the point
+    /// than the column holds, are errors rather than rounded.
+    fn to_decimal(&self, digits: u8, scale: u8, unsigned: bool, to: &'static str) -> Result<Value, ConversionError> {
+        let text = match self {
+            Value::Int(val) => val.to_string(),
+            Value::UInt(val) => val.to_string(),
+            Value::Float(val) if !val.is_finite() => return Err(ConversionError::Lossy{value: self.to_string(), to}),
+            Value::Float(val) => val.to_string(),
+            Value::Text(val) => val.trim().to_string(),
+            _ => return Err(ConversionError::Type{from: self.variant(), to}),
+        };
+
+        let (negative, number) = match text.strip_prefix('-') {
+            Some(number) => (true, number),
+            None => (false, text.strip_prefix('+').unwrap_or(&text)),
+        };
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+
+        if integer.is_empty() && fraction.is_empty() || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(ConversionError::Parse{value: self.to_string(), to});
+        }
+
+        let integer = integer.trim_start_matches('0');
+        let fraction = fraction.trim_end_matches('0');
+        let negative = negative && !(integer.is_empty() && fraction.is_empty());
+
+        if fraction.len() > scale as usize {
+            return Err(ConversionError::Lossy{value: self.to_string(), to});
+        }
+
+        if integer.len() > digits.saturating_sub(scale) as usize || negative && unsigned {
+            return Err(ConversionError::Overflow{value: self.to_string(), to});
+        }
+
+        let mut decimal = format!("{}{}", if negative { "-" } else { "" }, if integer.is_empty() { "0" } else { integer });
+
+        if !fraction.is_empty() {
+            decimal.push('.');
+            decimal.push_str(fraction);
+        }
+
+        Ok(Value::Text(decimal))
+    }
+
+    /// Returns the value converted to the `Value` a column of `data_type` stores, so it can be checked and inserted.
+    ///
+    /// The conversion is lenient: integers become booleans for `BOOL` columns, numeric text becomes numbers for numeric
+    /// columns and numbers become text for text columns. Numbers for `DECIMAL` columns are kept as exact text instead,
+    /// as a float cannot hold every value of a wide decimal. `NULL` and `DEFAULT` are kept as they are, as are values of
+    /// temporal and unmodelled types, which the server parses itself. Lengths are not checked (see
+    /// `Attribute::check_value`).
+    ///
+    /// # Errors
+    ///
+    /// Returns a `CoerceError` if the value has no conversion to the type or does not fit in it.
+    pub fn coerce_to(&self, data_type: &AttributeType) -> Result<Value, CoerceError> {
+        let to = "column";
+        let out_of_range = || ConversionError::Overflow{value: self.to_string(), to};
+
+        let integer = |min: i128, max: i128| -> Result<Value, ConversionError> {
+            let val = self.to_integer(to)?;
+
+            if val < min || val > max {
+                return Err(out_of_range());
+            }
+
+            Ok(match i64::try_from(val) {
+                Ok(val) => Value::Int(val),
+                Err(_) => Value::UInt(val as u64),
+            })
+        };
+        let ranged = || match data_type.integer_range() {
+            Some((min, max)) => integer(min, max),
+            None => Err(out_of_range()),
+        };
+        let float = || f64::try_from(self.clone()).and_then(|val| match val {
+            val if !val.is_finite() => Err(ConversionError::Lossy{value: self.to_string(), to}),
+            val => Ok(Value::Float(val)),
+        });
+
+        if let Value::Null | Value::Default = self {
+            return Ok(self.clone());
+        }
+
+        let coerced = match data_type {
+            AttributeType::Bool | AttributeType::Boolean => bool::try_from(self.clone()).map(Value::Bool),
+            AttributeType::TinyInt(_) |
+            AttributeType::SmallInt(_) |
+            AttributeType::MediumInt(_) |
+            AttributeType::Int(_) |
+            AttributeType::BigInt(_) => ranged(),
+            AttributeType::Unsigned{val, ..} => match val.as_ref() {
+                AttributeType::Decimal(digits, scale) => self.to_decimal(*digits, *scale, true, to),
+                AttributeType::Float(_) | AttributeType::Double(_) => float().and_then(|val| match val {
+                    Value::Float(number) if number < 0.0 => Err(out_of_range()),
+                    val => Ok(val),
+                }),
+                _ => ranged(),
+            },
+            AttributeType::Bit(bits) => integer(0, (1i128 << bits) - 1).map(|val| match val {
+                Value::Int(val) => Value::UInt(val as u64),
+                val => val,
+            }),
+            AttributeType::Year => self.to_integer(to).and_then(|val| match val {
+                0 | 1901..=2155 => Ok(Value::Int(val as i64)),
+                _ => Err(out_of_range()),
+            }),
+            AttributeType::Decimal(digits, scale) => self.to_decimal(*digits, *scale, false, to),
+            AttributeType::Float(_) | AttributeType::Double(_) => float(),
+
+            AttributeType::Char(_) |
+            AttributeType::VarChar(_) |
+            AttributeType::TinyText |
+            AttributeType::Text |
+            AttributeType::MediumText |
+            AttributeType::LongText |
+            AttributeType::Uuid |
+            AttributeType::Json |
+            AttributeType::Enum{..} |
+            AttributeType::Set{..} => match self {
+                Value::Int(_) | Value::UInt(_) | Value::Float(_) => Ok(Value::Text(self.to_string())),
+                _ => String::try_from(self.clone()).map(Value::Text),
+            },
+
+            AttributeType::Binary(_) |
+            AttributeType::VarBinary(_) |
+            AttributeType::TinyBlob |
+            AttributeType::Blob(_) |
+            AttributeType::MediumBlob |
+            AttributeType::LongBlob => Vec::<u8>::try_from(self.clone()).map(Value::Bytes),
+
+            AttributeType::Date |
+            AttributeType::DateTime(_) |
+            AttributeType::TimeStamp(_) |
+            AttributeType::Time(_) |
+            AttributeType::Unknown(_) => Ok(self.clone()),
+        };
+
+        coerced.map_err(|error| CoerceError{data_type: data_type.to_string(), error})
+    }
+
+    /// Converts a string into a `Value::Bytes` so that it is inserted as binary data.
+    pub(crate) fn into_bytes(self) -> Value {
+        match self {
+            Value::Text(val) => Value::Bytes(val.into_bytes()),
+            _ => self,
+        }
+    }
+}
+
+impl From<mysql::Value> for Value {
+    /// Converts a value returned by the driver.
+    ///
+    /// Queries sent over the text protocol return every value as bytes, which are read back as `Value::Text` when they
+    /// are valid UTF-8.
+    fn from(value: mysql::Value) -> Self {
+        match value {
+            mysql::Value::NULL => Value::Null,
+            mysql::Value::Bytes(bytes) => match String::from_utf8(bytes) {
+                Ok(text) => Value::Text(text),
+                Err(err) => Value::Bytes(err.into_bytes()),
+            },
+            mysql::Value::Int(val) => Value::Int(val),
+            mysql::Value::UInt(val) => Value::UInt(val),
+            mysql::Value::Float(val) => Value::Float(val as f64),
+            mysql::Value::Double(val) => Value::Float(val),
+            mysql::Value::Date(year, month, day, 0, 0, 0, 0) => Value::Date{year, month, day},
+            mysql::Value::Date(year, month, day, hour, minute, second, micro_second) => {
+                Value::DateTime{year, month, day, hour, minute, second, micro_second}
+            },
+            mysql::Value::Time(negative, days, hours, minutes, seconds, micro_seconds) => {
+                let sign = if negative { "-" } else { "" };
+                let hours = days * 24 + hours as u32;
+
+                match micro_seconds {
+                    0 => Value::Text(format!("{}{:02}:{:02}:{:02}", sign, hours, minutes, seconds)),
+                    _ => Value::Text(format!("{}{:02}:{:02}:{:02}.{:06}", sign, hours, minutes, seconds, micro_seconds)),
+                }
+            },
+        }
+    }
+}
+
+macro_rules! from_primitive {
+    ($variant:ident, $target:ty, $($source:ty),+) => {
+        $(
+            impl From<$source> for Value {
+                fn from(value: $source) -> Self {
+                    Value::$variant(value as $target)
+                }
+            }
+        )+
+    };
+}
+
+from_primitive!(Int, i64, i8, i16, i32, i64);
+from_primitive!(UInt, u64, u8, u16, u32, u64);
+from_primitive!(Float, f64, f32, f64);
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(value: &[u8]) -> Self {
+        Value::Bytes(value.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Value::Bytes(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    /// Converts `None` into `Value::Null`.
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl TryFrom<Value> for i64 {
+    type Error = ConversionError;
+
+    /// Converts integers in range, `0`/`1` for booleans, whole floats and integer text.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let val = value.to_integer("i64")?;
+
+        i64::try_from(val).map_err(|_| ConversionError::Overflow{value: value.to_string(), to: "i64"})
+    }
+}
+
+impl TryFrom<Value> for u64 {
+    type Error = ConversionError;
+
+    /// Converts integers in range, `0`/`1` for booleans, whole floats and integer text.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let val = value.to_integer("u64")?;
+
+        u64::try_from(val).map_err(|_| ConversionError::Overflow{value: value.to_string(), to: "u64"})
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = ConversionError;
+
+    /// Converts floats, integers that an `f64` represents exactly and numeric text. `NaN` floats are kept.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let lossy = || ConversionError::Lossy{value: value.to_string(), to: "f64"};
+
+        match &value {
+            Value::Float(val) => Ok(*val),
+            Value::Int(val) if (*val as f64) as i128 == *val as i128 => Ok(*val as f64),
+            Value::UInt(val) if (*val as f64) as i128 == *val as i128 => Ok(*val as f64),
+            Value::Int(_) | Value::UInt(_) => Err(lossy()),
+            Value::Text(val) => val.trim()
+                .parse::<f64>()
+                .map_err(|_| ConversionError::Parse{value: value.to_string(), to: "f64"}),
+            _ => Err(ConversionError::Type{from: value.variant(), to: "f64"}),
+        }
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = ConversionError;
+
+    /// Converts booleans, the numbers `0` and `1`, and the text `true`/`false` in any case or `0`/`1`.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        if let Value::Bool(val) = value {
+            return Ok(val);
+        }
+        if let Value::Text(val) = &value {
+            match val.trim().to_ascii_lowercase().as_str() {
+                "true" => return Ok(true),
+                "false" => return Ok(false),
+                _ => {},
+            }
+        }
+
+        match value.to_integer("bool")? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ConversionError::Overflow{value: value.to_string(), to: "bool"}),
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = ConversionError;
+
+    /// Converts text, UTF-8 bytes, and dates as `YYYY-MM-DD[ hh:mm:ss[.ffffff]]`.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Text(val) => Ok(val),
+            Value::Bytes(val) => String::from_utf8(val)
+                .map_err(|err| ConversionError::Parse{value: hex_literal(err.as_bytes()), to: "String"}),
+            Value::Date{..} | Value::DateTime{..} => Ok(value.to_string().trim_matches('\'').to_string()),
+            _ => Err(ConversionError::Type{from: value.variant(), to: "String"}),
+        }
+    }
+}
+
+impl TryFrom<Value> for Vec<u8> {
+    type Error = ConversionError;
+
+    /// Converts bytes, and text as its UTF-8 bytes.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Bytes(val) => Ok(val),
+            Value::Text(val) => Ok(val.into_bytes()),
+            _ => Err(ConversionError::Type{from: value.variant(), to: "Vec<u8>"}),
+        }
+    }
+}
+
+/// Returns the value of a column given as an unquoted string, as taken by `Table::insert`: `NULL` and `DEFAULT` in any
+/// case are `Value::Null` and `Value::Default`, anything else is `Value::Text`, left for `Value::coerce_to` to convert
+/// to the column's type.
+///
+/// Unlike `Table::insert`, which quotes every value of a text column, `NULL` becomes SQL `NULL` whatever the column.
+pub fn from_string(value: &str) -> Value {
+    match value.trim() {
+        val if val.eq_ignore_ascii_case("NULL") => Value::Null,
+        val if val.eq_ignore_ascii_case("DEFAULT") => Value::Default,
+        _ => Value::Text(value.to_string()),
+    }
+}
+
+/// Converts a map of unquoted strings, as taken by `Table::insert`, into typed values (see `from_string`).
+pub fn from_strings(values: &HashMap<String, String>) -> HashMap<String, Value> {
+    values.iter().map(|(column, value)| (column.clone(), from_string(value))).collect()
+}
+
+/// Returns `bytes` as a MySQL hex literal (`x'...'`).
+pub fn hex_literal(bytes: &[u8]) -> String {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    let mut literal = String::with_capacity(bytes.len() * 2 + 3);
+
+    literal.push_str("x'");
+    for byte in bytes {
+        literal.push(HEX[(byte >> 4) as usize] as char);
+        literal.push(HEX[(byte & 0x0f) as usize] as char);
+    }
+    literal.push('\'');
+
+    literal
+}
+
+/// Escapes `val` so it can be placed inside a single quoted MySQL string literal.
+///
+/// Only values are escaped. Identifiers, such as table and column names, are a separate concern: they are written
+/// into statements as they are, and must not come from untrusted input.
+pub fn escape_string(val: &str) -> String {
+    let mut escaped = String::with_capacity(val.len());
+
+    for ch in val.chars() {
+        match ch {
+            '\'' => escaped.push_str("''"),
+            '\\' => escaped.push_str("\\\\"),
+            '\0' => escaped.push_str("\\0"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\x1a' => escaped.push_str("\\Z"),
+            _ => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Bool(true) => write!(f, "TRUE"),
+            Value::Bool(false) => write!(f, "FALSE"),
+            Value::Int(val) => write!(f, "{}", val),
+            Value::UInt(val) => write!(f, "{}", val),
+            Value::Float(val) => write!(f, "{}", val),
+            Value::Text(val) => write!(f, "'{}'", escape_string(val)),
+            Value::Bytes(val) => write!(f, "{}", hex_literal(val)),
+            Value::Date{year, month, day} => write!(f, "'{:04}-{:02}-{:02}'", year, month, day),
+            Value::DateTime{year, month, day, hour, minute, second, micro_second: 0} => {
+                write!(f, "'{:04}-{:02}-{:02} {:02}:{:02}:{:02}'", year, month, day, hour, minute, second)
+            },
+            Value::DateTime{year, month, day, hour, minute, second, micro_second} => {
+                write!(f, "'{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}'", year, month, day, hour, minute, second, micro_second)
+            },
+            Value::Default => write!(f, "DEFAULT"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::relation::table::AttributeType;
+
+    use std::collections::HashMap;
+
+    use super::{Value, ValueError, ConversionError, hex_literal, from_strings};
+
+    //decodes a x'...' literal back into bytes
+    fn decode_hex_literal(literal: &str) -> Vec<u8> {
+        let hex = &literal[2..literal.len() - 1];
+
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn render_test_1() {
+        assert_eq!(Value::Null.to_string(), "NULL");
+        assert_eq!(Value::Bool(true).to_string(), "TRUE");
+        assert_eq!(Value::Int(-23).to_string(), "-23");
+        assert_eq!(Value::UInt(23).to_string(), "23");
+        assert_eq!(Value::Default.to_string(), "DEFAULT");
+        assert_eq!(Value::Text(String::from("O'Brien")).to_string(), "'O''Brien'");
+        assert_eq!(Value::Date{year: 2022, month: 12, day: 4}.to_string(), "'2022-12-04'");
+        assert_eq!(
+            Value::DateTime{year: 2022, month: 12, day: 4, hour: 9, minute: 0, second: 0, micro_second: 0}.to_string(),
+            "'2022-12-04 09:00:00'"
+        );
+    }
+
+    #[test]
+    fn bytes_test_1() {
+        assert_eq!(Value::Bytes(vec![0x00, 0xff, 0x10]).to_string(), "x'00ff10'");
+        assert_eq!(Value::Bytes(vec![]).to_string(), "x''");
+    }
+
+    #[test]
+    fn bytes_round_trip_test() {
+        //pseudo random bytes, including NUL bytes
+        let mut seed: u32 = 0x2545F491;
+        let bytes: Vec<u8> = (0..4096)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                (seed % 256) as u8
+            })
+            .chain([0u8, 0u8])
+            .collect();
+
+        let literal = hex_literal(&bytes);
+
+        assert_eq!(decode_hex_literal(&literal), bytes);
+    }
+
+    #[test]
+    fn from_driver_value_test() {
+        assert_eq!(Value::from(mysql::Value::NULL), Value::Null);
+        assert_eq!(Value::from(mysql::Value::Bytes(b"Doe".to_vec())), Value::Text(String::from("Doe")));
+        assert_eq!(Value::from(mysql::Value::Bytes(vec![0xff, 0x00])), Value::Bytes(vec![0xff, 0x00]));
+        assert_eq!(Value::from(mysql::Value::Int(-1)), Value::Int(-1));
+        assert_eq!(Value::from(mysql::Value::Date(2022, 12, 14, 0, 0, 0, 0)), Value::Date{year: 2022, month: 12, day: 14});
+        assert_eq!(Value::from(mysql::Value::Time(false, 1, 2, 3, 4, 0)), Value::Text(String::from("26:03:04")));
+    }
+
+    #[test]
+    fn bounded_literal_test() {
+        let value = Value::Bytes(vec![1; 10]);
+
+        assert_eq!(value.to_bounded_literal(23), Ok(String::from("x'01010101010101010101'")));
+        assert_eq!(value.to_bounded_literal(22), Err(ValueError::LiteralTooLarge{size: 23, limit: 22}));
+    }
+
+    fn overflow(value: &str, to: &'static str) -> ConversionError {
+        ConversionError::Overflow{value: String::from(value), to}
+    }
+
+    fn lossy(value: &str, to: &'static str) -> ConversionError {
+        ConversionError::Lossy{value: String::from(value), to}
+    }
+
+    #[test]
+    fn from_primitive_test() {
+        assert_eq!(Value::from(-3i8), Value::Int(-3));
+        assert_eq!(Value::from(i32::MIN), Value::Int(i32::MIN as i64));
+        assert_eq!(Value::from(7u16), Value::UInt(7));
+        assert_eq!(Value::from(u64::MAX), Value::UInt(u64::MAX));
+        assert_eq!(Value::from(0.5f32), Value::Float(0.5));
+        assert_eq!(Value::from(true), Value::Bool(true));
+        assert_eq!(Value::from("Doe"), Value::Text(String::from("Doe")));
+        assert_eq!(Value::from(String::from("Doe")), Value::Text(String::from("Doe")));
+        assert_eq!(Value::from(&[1u8, 2][..]), Value::Bytes(vec![1, 2]));
+        assert_eq!(Value::from(vec![1u8, 2]), Value::Bytes(vec![1, 2]));
+        assert_eq!(Value::from(Some(3i64)), Value::Int(3));
+        assert_eq!(Value::from(None::<&str>), Value::Null);
+    }
+
+    #[test]
+    fn try_from_integer_test() {
+        assert_eq!(i64::try_from(Value::Int(i64::MIN)), Ok(i64::MIN));
+        assert_eq!(i64::try_from(Value::UInt(i64::MAX as u64)), Ok(i64::MAX));
+        assert_eq!(i64::try_from(Value::UInt(i64::MAX as u64 + 1)), Err(overflow("9223372036854775808", "i64")));
+        assert_eq!(i64::try_from(Value::Bool(true)), Ok(1));
+        assert_eq!(i64::try_from(Value::Float(-3.0)), Ok(-3));
+        assert_eq!(i64::try_from(Value::Float(1.5)), Err(lossy("1.5", "i64")));
+        assert_eq!(i64::try_from(Value::Float(f64::NAN)), Err(lossy("NaN", "i64")));
+        assert_eq!(i64::try_from(Value::Float(f64::INFINITY)), Err(lossy("inf", "i64")));
+        assert_eq!(i64::try_from(Value::Float(1e30)), Err(overflow("1000000000000000000000000000000", "i64")));
+        assert_eq!(i64::try_from(Value::Text(String::from(" -42 "))), Ok(-42));
+        assert_eq!(
+            i64::try_from(Value::Text(String::from("4.2"))),
+            Err(ConversionError::Parse{value: String::from("'4.2'"), to: "i64"})
+        );
+        assert_eq!(i64::try_from(Value::Null), Err(ConversionError::Type{from: "NULL", to: "i64"}));
+        assert_eq!(i64::try_from(Value::Bytes(vec![1])), Err(ConversionError::Type{from: "Bytes", to: "i64"}));
+
+        assert_eq!(u64::try_from(Value::UInt(u64::MAX)), Ok(u64::MAX));
+        assert_eq!(u64::try_from(Value::Int(0)), Ok(0));
+        assert_eq!(u64::try_from(Value::Int(i64::MIN)), Err(overflow("-9223372036854775808", "u64")));
+        assert_eq!(u64::try_from(Value::Int(-1)), Err(overflow("-1", "u64")));
+        assert_eq!(u64::try_from(Value::Text(String::from("18446744073709551615"))), Ok(u64::MAX));
+        assert!(u64::try_from(Value::Text(String::from("18446744073709551616"))).is_err());
+        assert_eq!(u64::try_from(Value::Default), Err(ConversionError::Type{from: "DEFAULT", to: "u64"}));
+    }
+
+    #[test]
+    fn try_from_float_test() {
+        assert_eq!(f64::try_from(Value::Float(0.25)), Ok(0.25));
+        assert!(f64::try_from(Value::Float(f64::NAN)).unwrap().is_nan());
+        assert_eq!(f64::try_from(Value::Int(1 << 53)), Ok(9007199254740992.0));
+        assert_eq!(f64::try_from(Value::Int((1 << 53) + 1)), Err(lossy("9007199254740993", "f64")));
+        assert_eq!(f64::try_from(Value::Int(i64::MIN)), Ok(-9223372036854775808.0));
+        assert_eq!(f64::try_from(Value::Int(i64::MAX)), Err(lossy("9223372036854775807", "f64")));
+        assert_eq!(f64::try_from(Value::UInt(u64::MAX)), Err(lossy("18446744073709551615", "f64")));
+        assert_eq!(f64::try_from(Value::Text(String::from("1.5e3"))), Ok(1500.0));
+        assert_eq!(f64::try_from(Value::Bool(true)), Err(ConversionError::Type{from: "Bool", to: "f64"}));
+    }
+
+    #[test]
+    fn try_from_other_test() {
+        assert_eq!(bool::try_from(Value::Bool(false)), Ok(false));
+        assert_eq!(bool::try_from(Value::Int(1)), Ok(true));
+        assert_eq!(bool::try_from(Value::UInt(0)), Ok(false));
+        assert_eq!(bool::try_from(Value::Int(2)), Err(overflow("2", "bool")));
+        assert_eq!(bool::try_from(Value::Text(String::from("TRUE"))), Ok(true));
+        assert_eq!(bool::try_from(Value::Text(String::from("0"))), Ok(false));
+        assert!(bool::try_from(Value::Text(String::from("yes"))).is_err());
+        assert_eq!(bool::try_from(Value::Float(f64::NAN)), Err(lossy("NaN", "bool")));
+
+        assert_eq!(String::try_from(Value::Text(String::from("Doe"))), Ok(String::from("Doe")));
+        assert_eq!(String::try_from(Value::Bytes(b"Doe".to_vec())), Ok(String::from("Doe")));
+        assert_eq!(
+            String::try_from(Value::Bytes(vec![0xff])),
+            Err(ConversionError::Parse{value: String::from("x'ff'"), to: "String"})
+        );
+        assert_eq!(String::try_from(Value::Date{year: 2022, month: 12, day: 4}), Ok(String::from("2022-12-04")));
+        assert_eq!(String::try_from(Value::Int(1)), Err(ConversionError::Type{from: "Int", to: "String"}));
+
+        assert_eq!(Vec::<u8>::try_from(Value::Bytes(vec![0, 1])), Ok(vec![0, 1]));
+        assert_eq!(Vec::<u8>::try_from(Value::Text(String::from("ab"))), Ok(b"ab".to_vec()));
+        assert_eq!(Vec::<u8>::try_from(Value::Float(1.0)), Err(ConversionError::Type{from: "Float", to: "Vec<u8>"}));
+    }
+
+    #[test]
+    fn coerce_to_test() {
+        let coerce = |value: Value, data_type: AttributeType| value.coerce_to(&data_type).map_err(|err| err.error);
+        let column = "column";
+
+        assert_eq!(coerce(Value::Int(1), AttributeType::Bool), Ok(Value::Bool(true)));
+        assert_eq!(coerce(Value::Text(String::from("false")), AttributeType::Boolean), Ok(Value::Bool(false)));
+        assert_eq!(coerce(Value::Int(2), AttributeType::Bool), Err(overflow("2", "bool")));
+
+        assert_eq!(coerce(Value::Text(String::from("42")), AttributeType::Int(11)), Ok(Value::Int(42)));
+        assert_eq!(coerce(Value::Bool(true), AttributeType::TinyInt(1)), Ok(Value::Int(1)));
+        assert_eq!(coerce(Value::Int(255), AttributeType::TinyInt(4)), Err(overflow("255", column)));
+        assert_eq!(coerce(Value::Int(-129), AttributeType::TinyInt(4)), Err(overflow("-129", column)));
+        assert_eq!(coerce(Value::UInt(u64::MAX), AttributeType::BigInt(20)), Err(overflow("18446744073709551615", column)));
+
+        let unsigned = |val: AttributeType| AttributeType::Unsigned{val: Box::new(val), zerofill: false};
+        assert_eq!(coerce(Value::Int(255), unsigned(AttributeType::TinyInt(3))), Ok(Value::Int(255)));
+        assert_eq!(coerce(Value::Int(-1), unsigned(AttributeType::TinyInt(3))), Err(overflow("-1", column)));
+        assert_eq!(coerce(Value::UInt(u64::MAX), unsigned(AttributeType::BigInt(20))), Ok(Value::UInt(u64::MAX)));
+        assert_eq!(coerce(Value::Float(-0.5), unsigned(AttributeType::Decimal(4, 2))), Err(overflow("-0.5", column)));
+        assert_eq!(coerce(Value::Int(12), unsigned(AttributeType::Decimal(4, 2))), Ok(Value::Text(String::from("12"))));
+        assert_eq!(coerce(Value::Float(2.5), AttributeType::Int(11)), Err(lossy("2.5", column)));
+
+        assert_eq!(coerce(Value::Int(5), AttributeType::Bit(8)), Ok(Value::UInt(5)));
+        assert_eq!(coerce(Value::Int(i64::MIN), AttributeType::Bit(64)), Err(overflow("-9223372036854775808", column)));
+        assert_eq!(coerce(Value::Int(256), AttributeType::Bit(8)), Err(overflow("256", column)));
+
+        assert_eq!(coerce(Value::Text(String::from("2023")), AttributeType::Year), Ok(Value::Int(2023)));
+        assert_eq!(coerce(Value::Int(1900), AttributeType::Year), Err(overflow("1900", column)));
+
+        assert_eq!(coerce(Value::Text(String::from("0.5")), AttributeType::Decimal(4, 2)), Ok(Value::Text(String::from("0.5"))));
+        assert_eq!(coerce(Value::Int(3), AttributeType::Float(8)), Ok(Value::Float(3.0)));
+        assert_eq!(coerce(Value::Float(f64::NAN), AttributeType::Float(8)), Err(lossy("NaN", column)));
+        assert_eq!(coerce(Value::Text(String::from("inf")), AttributeType::Float(8)), Err(lossy("'inf'", column)));
+
+        assert_eq!(coerce(Value::Int(12), AttributeType::VarChar(4)), Ok(Value::Text(String::from("12"))));
+        assert_eq!(coerce(Value::Bytes(b"ab".to_vec()), AttributeType::Text), Ok(Value::Text(String::from("ab"))));
+        assert_eq!(coerce(Value::Bool(true), AttributeType::Char(1)), Err(ConversionError::Type{from: "Bool", to: "String"}));
+
+        assert_eq!(coerce(Value::Text(String::from("ab")), AttributeType::Blob(16)), Ok(Value::Bytes(b"ab".to_vec())));
+        assert_eq!(coerce(Value::Int(1), AttributeType::VarBinary(4)), Err(ConversionError::Type{from: "Int", to: "Vec<u8>"}));
+
+        assert_eq!(coerce(Value::Null, AttributeType::Int(11)), Ok(Value::Null));
+        assert_eq!(coerce(Value::Default, AttributeType::Bool), Ok(Value::Default));
+        assert_eq!(coerce(Value::Text(String::from("2022-12-04")), AttributeType::Date), Ok(Value::Text(String::from("2022-12-04"))));
+        assert_eq!(coerce(Value::Int(1), AttributeType::Unknown(String::from("point"))), Ok(Value::Int(1)));
+
+        let err = Value::Int(300).coerce_to(&AttributeType::TinyInt(4)).unwrap_err();
+        assert_eq!(err.data_type, AttributeType::TinyInt(4).to_string());
+        assert_eq!(err.to_string(), format!("cannot coerce to {}: 300 is out of range for column", AttributeType::TinyInt(4)));
+    }
+
+    #[test]
+    fn coerce_to_decimal_test() {
+        let coerce = |value: Value, data_type: AttributeType| value.coerce_to(&data_type).map_err(|err| err.error);
+        let text = |val: &str| Ok(Value::Text(String::from(val)));
+        let column = "column";
+
+        //20 significant digits, more than a float holds
+        assert_eq!(coerce(Value::from("1234567890123456.7891"), AttributeType::Decimal(20, 4)), text("1234567890123456.7891"));
+        assert_eq!(coerce(Value::from(" -0012.3400 "), AttributeType::Decimal(20, 4)), text("-12.34"));
+        assert_eq!(coerce(Value::from("+.5"), AttributeType::Decimal(4, 2)), text("0.5"));
+        assert_eq!(coerce(Value::from("-0.00"), AttributeType::Decimal(4, 2)), text("0"));
+        assert_eq!(coerce(Value::Int(-12), AttributeType::Decimal(4, 2)), text("-12"));
+        assert_eq!(coerce(Value::UInt(u64::MAX), AttributeType::Decimal(20, 0)), text("18446744073709551615"));
+        assert_eq!(coerce(Value::Float(2.25), AttributeType::Decimal(4, 2)), text("2.25"));
+
+        assert_eq!(coerce(Value::from("123.4"), AttributeType::Decimal(4, 2)), Err(overflow("'123.4'", column)));
+        assert_eq!(coerce(Value::from("0.125"), AttributeType::Decimal(4, 2)), Err(lossy("'0.125'", column)));
+        assert_eq!(coerce(Value::Float(f64::INFINITY), AttributeType::Decimal(4, 2)), Err(lossy("inf", column)));
+        assert_eq!(
+            coerce(Value::from("1e3"), AttributeType::Decimal(4, 2)),
+            Err(ConversionError::Parse{value: String::from("'1e3'"), to: column})
+        );
+        assert_eq!(
+            coerce(Value::from("."), AttributeType::Decimal(4, 2)),
+            Err(ConversionError::Parse{value: String::from("'.'"), to: column})
+        );
+        assert_eq!(coerce(Value::Bool(true), AttributeType::Decimal(4, 2)), Err(ConversionError::Type{from: "Bool", to: column}));
+
+        let unsigned = AttributeType::Unsigned{val: Box::new(AttributeType::Decimal(20, 4)), zerofill: false};
+        assert_eq!(coerce(Value::from("9876543210987654.3210"), unsigned.clone()), text("9876543210987654.321"));
+        assert_eq!(coerce(Value::from("-1"), unsigned), Err(overflow("'-1'", column)));
+    }
+
+    #[test]
+    fn from_strings_test() {
+        let values = from_strings(&HashMap::from([
+            (String::from("a"), String::from("null")),
+            (String::from("b"), String::from("DEFAULT")),
+            (String::from("c"), String::from("O'Brien")),
+        ]));
+
+        assert_eq!(values["a"], Value::Null);
+        assert_eq!(values["b"], Value::Default);
+        assert_eq!(values["c"], Value::Text(String::from("O'Brien")));
+    }
+}
@@ -0,0 +1,131 @@
+use std::{iter::Peekable, str::Chars};
+
+/// Fragments that are likely to break out of a rendered literal, or confuse the statement splitter.
+const FRAGMENTS: [&str; 26] = [
+    "'", "''", "\\", "\\'", "\\\\", "\0", "\n", "\r", "\t", "\x1a", "\"", "`", ";", "--", "#", "/*", "*/", ",", ")",
+    "#file:(Cargo.toml as S)", "😀", "é", "\u{2028}", "\u{feff}", " ", "DROP TABLE t; --",
+];
+
+/// A deterministic xorshift generator of hostile strings and bytes.
+pub struct Fuzzer {
+    seed: u32,
+}
+
+impl Fuzzer {
+    pub fn new(seed: u32) -> Fuzzer {
+        Fuzzer{seed: seed.max(1)}
+    }
+
+    /// Returns the next pseudo random number.
+    pub fn next(&mut self) -> u32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.seed
+    }
+
+    /// Returns a string of up to `max_parts` fragments and random characters.
+    pub fn string(&mut self, max_parts: u32) -> String {
+        let parts = self.next() % (max_parts + 1);
+
+        (0..parts)
+            .map(|_| match self.next() % 3 {
+                0 => char::from_u32(self.next() % 0x11_0000).unwrap_or('\u{fffd}').to_string(),
+                1 => char::from(b'a' + (self.next() % 26) as u8).to_string(),
+                _ => FRAGMENTS[(self.next() as usize) % FRAGMENTS.len()].to_string(),
+            })
+            .collect()
+    }
+
+    /// Returns up to `max_len` random bytes.
+    pub fn bytes(&mut self, max_len: u32) -> Vec<u8> {
+        let len = self.next() % (max_len + 1);
+
+        (0..len).map(|_| (self.next() % 256) as u8).collect()
+    }
+}
+
+/// A literal read back from a statement by `values`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Literal {
+    /// A single quoted string, unescaped as MySQL does.
+    Text(String),
+    /// A `x'...'` hex literal.
+    Bytes(Vec<u8>),
+    /// Anything else, as written.
+    Other(String),
+}
+
+/// Reads the literals of the single `VALUES (...)` tuple ending `statement`, the way MySQL tokenizes them.
+///
+/// Returns `None` if anything but the tuple follows `VALUES`, or a literal is not terminated.
+pub fn values(statement: &str) -> Option<Vec<Literal>> {
+    let start = statement.find(" VALUES (")? + " VALUES (".len();
+    let mut chars = statement[start..].chars().peekable();
+    let mut literals: Vec<Literal> = Vec::new();
+
+    loop {
+        let literal = match chars.peek()? {
+            '\'' => {
+                chars.next();
+                Literal::Text(quoted(&mut chars)?)
+            },
+            'x' => {
+                chars.next();
+                if chars.next()? != '\'' {
+                    return None;
+                }
+                let hex: String = chars.by_ref().take_while(|ch| *ch != '\'').collect();
+                let bytes = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                    .collect::<Option<Vec<u8>>>()?;
+                Literal::Bytes(bytes)
+            },
+            _ => {
+                let mut other = String::new();
+                while !matches!(chars.peek()?, ',' | ')') {
+                    other.push(chars.next()?);
+                }
+                Literal::Other(other)
+            },
+        };
+
+        literals.push(literal);
+
+        match chars.next()? {
+            ',' => continue,
+            ')' => break,
+            _ => return None,
+        }
+    }
+
+    match chars.next() {
+        None => Some(literals),
+        Some(_) => None,
+    }
+}
+
+/// Reads the rest of a single quoted string, after its opening quote.
+fn quoted(chars: &mut Peekable<Chars<'_>>) -> Option<String> {
+    let mut text = String::new();
+
+    loop {
+        match chars.next()? {
+            '\'' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                text.push('\'');
+            },
+            '\'' => return Some(text),
+            '\\' => text.push(match chars.next()? {
+                '0' => '\0',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'Z' => '\x1a',
+                ch => ch,
+            }),
+            ch => text.push(ch),
+        }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod file_env;
-pub mod db_env;
\ No newline at end of file
+pub mod db_env;
+#[cfg(test)]
+pub mod fuzz;